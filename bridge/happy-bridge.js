@@ -678,6 +678,16 @@ const start = async () => {
     } else if (payload.type === "thread-archive") {
       delete state.sessions[payload.threadId];
       await saveState();
+    } else if (payload.type === "app-server-event") {
+      const session = sessions.get(payload.threadId);
+      if (!session) return;
+      if (payload.method === "turn/started" || payload.method === "turn/completed") {
+        session.socket.emit("session-alive", {
+          sid: session.sessionId,
+          time: Date.now(),
+          thinking: payload.method === "turn/started",
+        });
+      }
     }
   });
 
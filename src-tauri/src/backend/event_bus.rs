@@ -0,0 +1,485 @@
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+const DEFAULT_SUBSCRIBER_CAPACITY: usize = 256;
+/// Turn lifecycle events are mirrored to bridges; deltas are left to the UI.
+const BRIDGE_METHOD_PREFIX: &str = "turn/";
+const BRIDGE_OBSERVER_CAPACITY: usize = 64;
+
+/// Selects which app-server messages a subscriber receives. Empty fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct EventFilter {
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) method_prefix: Option<String>,
+}
+
+impl EventFilter {
+    pub(crate) fn workspace(workspace_id: &str) -> Self {
+        Self {
+            workspace_id: Some(workspace_id.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn thread(workspace_id: &str, thread_id: &str) -> Self {
+        Self {
+            workspace_id: Some(workspace_id.to_string()),
+            thread_id: Some(thread_id.to_string()),
+            method_prefix: None,
+        }
+    }
+
+    pub(crate) fn with_method_prefix(mut self, prefix: &str) -> Self {
+        self.method_prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub(crate) fn matches(
+        &self,
+        workspace_id: &str,
+        thread_id: Option<&str>,
+        method: &str,
+    ) -> bool {
+        if let Some(expected) = self.workspace_id.as_deref() {
+            if expected != workspace_id {
+                return false;
+            }
+        }
+        if let Some(expected) = self.thread_id.as_deref() {
+            if thread_id != Some(expected) {
+                return false;
+            }
+        }
+        if let Some(prefix) = self.method_prefix.as_deref() {
+            if !method.starts_with(prefix) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// What happens when a subscriber's queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LagPolicy {
+    /// Discard the oldest queued event to make room.
+    DropOldest,
+    /// Discard the incoming event.
+    DropNewest,
+    /// Close the subscription; the receiver drains what is queued and then sees the end.
+    Disconnect,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SubscribeOptions {
+    pub(crate) filter: EventFilter,
+    pub(crate) capacity: usize,
+    pub(crate) lag_policy: LagPolicy,
+    /// Exclusive subscribers claim the events they match, hiding them from the UI sink.
+    pub(crate) exclusive: bool,
+}
+
+impl SubscribeOptions {
+    pub(crate) fn new(filter: EventFilter) -> Self {
+        Self {
+            filter,
+            capacity: DEFAULT_SUBSCRIBER_CAPACITY,
+            lag_policy: LagPolicy::DropOldest,
+            exclusive: false,
+        }
+    }
+
+    pub(crate) fn exclusive(mut self) -> Self {
+        self.exclusive = true;
+        self
+    }
+
+    pub(crate) fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub(crate) fn lag_policy(mut self, lag_policy: LagPolicy) -> Self {
+        self.lag_policy = lag_policy;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BusEvent {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) message: Value,
+}
+
+struct SubscriberQueue {
+    events: VecDeque<BusEvent>,
+    dropped: u64,
+    closed: bool,
+}
+
+struct Subscriber {
+    id: u64,
+    options: SubscribeOptions,
+    queue: Mutex<SubscriberQueue>,
+    notify: Notify,
+}
+
+impl Subscriber {
+    /// Queues an event, returning false once the subscriber is closed.
+    fn push(&self, event: BusEvent) -> bool {
+        let Ok(mut queue) = self.queue.lock() else {
+            return false;
+        };
+        if queue.closed {
+            return false;
+        }
+        if queue.events.len() >= self.options.capacity {
+            queue.dropped += 1;
+            match self.options.lag_policy {
+                LagPolicy::DropOldest => {
+                    queue.events.pop_front();
+                }
+                LagPolicy::DropNewest => return true,
+                LagPolicy::Disconnect => {
+                    queue.closed = true;
+                    drop(queue);
+                    self.notify.notify_one();
+                    return false;
+                }
+            }
+        }
+        queue.events.push_back(event);
+        drop(queue);
+        self.notify.notify_one();
+        true
+    }
+
+    fn close(&self) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.closed = true;
+        }
+        self.notify.notify_one();
+    }
+}
+
+/// Fans app-server messages out to any number of filtered subscribers.
+///
+/// The UI sink is fed directly by the session and only misses events that an
+/// exclusive subscriber (a headless runner, a provider thread) has claimed.
+#[derive(Default)]
+pub(crate) struct EventBus {
+    next_id: AtomicU64,
+    subscribers: Mutex<Vec<Arc<Subscriber>>>,
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub(crate) fn subscribe(self: &Arc<Self>, options: SubscribeOptions) -> EventSubscription {
+        let subscriber = Arc::new(Subscriber {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            options,
+            queue: Mutex::new(SubscriberQueue {
+                events: VecDeque::new(),
+                dropped: 0,
+                closed: false,
+            }),
+            notify: Notify::new(),
+        });
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(Arc::clone(&subscriber));
+        }
+        EventSubscription {
+            bus: Arc::downgrade(self),
+            subscriber,
+        }
+    }
+
+    /// Delivers a message to every matching subscriber.
+    /// Returns true when an exclusive subscriber claimed it.
+    pub(crate) fn publish(
+        &self,
+        workspace_id: &str,
+        thread_id: Option<&str>,
+        message: &Value,
+    ) -> bool {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return false;
+        };
+        let mut claimed = false;
+        subscribers.retain(|subscriber| {
+            if !subscriber
+                .options
+                .filter
+                .matches(workspace_id, thread_id, method)
+            {
+                return true;
+            }
+            claimed |= subscriber.options.exclusive;
+            subscriber.push(BusEvent {
+                workspace_id: workspace_id.to_string(),
+                thread_id: thread_id.map(|value| value.to_string()),
+                message: message.clone(),
+            })
+        });
+        claimed
+    }
+
+    /// True while an exclusive subscriber is scoped to this workspace.
+    pub(crate) fn has_exclusive_subscribers(&self, workspace_id: &str) -> bool {
+        self.subscribers
+            .lock()
+            .map(|subscribers| {
+                subscribers.iter().any(|subscriber| {
+                    subscriber.options.exclusive
+                        && subscriber.options.filter.workspace_id.as_deref() == Some(workspace_id)
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    fn unsubscribe(&self, id: u64) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.id != id);
+        }
+    }
+}
+
+/// Receiving end of a bus subscription; unsubscribes when dropped.
+pub(crate) struct EventSubscription {
+    bus: Weak<EventBus>,
+    subscriber: Arc<Subscriber>,
+}
+
+impl EventSubscription {
+    /// Waits for the next event, or `None` once the subscription was closed and drained.
+    pub(crate) async fn recv(&mut self) -> Option<BusEvent> {
+        loop {
+            if let Some(event) = self.try_recv() {
+                return Some(event);
+            }
+            if self.is_closed() {
+                return None;
+            }
+            self.subscriber.notify.notified().await;
+        }
+    }
+
+    pub(crate) fn try_recv(&mut self) -> Option<BusEvent> {
+        self.subscriber
+            .queue
+            .lock()
+            .ok()
+            .and_then(|mut queue| queue.events.pop_front())
+    }
+
+    /// Number of events lost to the lag policy so far.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.subscriber
+            .queue
+            .lock()
+            .map(|queue| queue.dropped)
+            .unwrap_or(0)
+    }
+
+    fn is_closed(&self) -> bool {
+        self.subscriber
+            .queue
+            .lock()
+            .map(|queue| queue.closed)
+            .unwrap_or(true)
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        self.subscriber.close();
+        if let Some(bus) = self.bus.upgrade() {
+            bus.unsubscribe(self.subscriber.id);
+        }
+    }
+}
+
+/// The `app-server-event` command a bridge receives for a thread event.
+fn bridge_event_command(event: &BusEvent) -> Option<Value> {
+    let thread_id = event.thread_id.as_ref()?;
+    Some(json!({
+        "type": "app-server-event",
+        "workspaceId": event.workspace_id,
+        "threadId": thread_id,
+        "method": event.message.get("method").cloned().unwrap_or(Value::Null),
+        "params": event.message.get("params").cloned().unwrap_or(Value::Null),
+    }))
+}
+
+/// Lets a bridge observe turn events of the threads the UI shows. Each event is handed
+/// to `forward` as an `app-server-event` command until `forward` fails.
+pub(crate) fn spawn_bridge_observer<F, Fut>(bus: &Arc<EventBus>, mut forward: F) -> JoinHandle<()>
+where
+    F: FnMut(Value) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), String>> + Send,
+{
+    let mut events = bus.subscribe(
+        SubscribeOptions::new(EventFilter::default().with_method_prefix(BRIDGE_METHOD_PREFIX))
+            .capacity(BRIDGE_OBSERVER_CAPACITY),
+    );
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            let Some(command) = bridge_event_command(&event) else {
+                continue;
+            };
+            if forward(command).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{spawn_bridge_observer, EventBus, EventFilter, LagPolicy, SubscribeOptions};
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn filter_matches_workspace_thread_and_method_prefix() {
+        let filter = EventFilter::thread("ws-1", "thread-1").with_method_prefix("item/");
+        assert!(filter.matches("ws-1", Some("thread-1"), "item/agentMessage/delta"));
+        assert!(!filter.matches("ws-2", Some("thread-1"), "item/agentMessage/delta"));
+        assert!(!filter.matches("ws-1", Some("thread-2"), "item/agentMessage/delta"));
+        assert!(!filter.matches("ws-1", None, "item/agentMessage/delta"));
+        assert!(!filter.matches("ws-1", Some("thread-1"), "turn/completed"));
+        assert!(EventFilter::default().matches("any", None, ""));
+    }
+
+    #[test]
+    fn every_matching_subscriber_receives_the_event() {
+        let bus = EventBus::new();
+        let mut ui_mirror = bus.subscribe(SubscribeOptions::new(EventFilter::default()));
+        let mut runner = bus
+            .subscribe(SubscribeOptions::new(EventFilter::thread("ws-1", "thread-1")).exclusive());
+        let mut other = bus.subscribe(SubscribeOptions::new(EventFilter::workspace("ws-2")));
+
+        let message = json!({ "method": "turn/started", "params": { "threadId": "thread-1" } });
+        assert!(bus.publish("ws-1", Some("thread-1"), &message));
+        assert!(!bus.publish("ws-1", Some("thread-9"), &message));
+
+        assert_eq!(ui_mirror.try_recv().unwrap().message, message);
+        assert_eq!(
+            ui_mirror.try_recv().unwrap().thread_id.as_deref(),
+            Some("thread-9")
+        );
+        assert_eq!(runner.try_recv().unwrap().workspace_id, "ws-1");
+        assert!(runner.try_recv().is_none());
+        assert!(other.try_recv().is_none());
+    }
+
+    #[test]
+    fn lag_policies_bound_the_queue() {
+        let bus = EventBus::new();
+        let mut oldest = bus.subscribe(
+            SubscribeOptions::new(EventFilter::default())
+                .capacity(2)
+                .lag_policy(LagPolicy::DropOldest),
+        );
+        let mut newest = bus.subscribe(
+            SubscribeOptions::new(EventFilter::default())
+                .capacity(2)
+                .lag_policy(LagPolicy::DropNewest),
+        );
+        for index in 0..3 {
+            bus.publish(
+                "ws",
+                None,
+                &json!({ "method": "m", "params": { "n": index } }),
+            );
+        }
+        assert_eq!(oldest.dropped(), 1);
+        assert_eq!(oldest.try_recv().unwrap().message["params"]["n"], 1);
+        assert_eq!(newest.dropped(), 1);
+        assert_eq!(newest.try_recv().unwrap().message["params"]["n"], 0);
+        assert_eq!(newest.try_recv().unwrap().message["params"]["n"], 1);
+        assert!(newest.try_recv().is_none());
+    }
+
+    #[test]
+    fn disconnect_policy_closes_lagging_subscribers() {
+        let bus = EventBus::new();
+        let mut subscription = bus.subscribe(
+            SubscribeOptions::new(EventFilter::default())
+                .capacity(1)
+                .lag_policy(LagPolicy::Disconnect),
+        );
+        bus.publish("ws", None, &json!({ "method": "a" }));
+        bus.publish("ws", None, &json!({ "method": "b" }));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            assert_eq!(subscription.recv().await.unwrap().message["method"], "a");
+            assert!(subscription.recv().await.is_none());
+        });
+        assert!(bus.subscribers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn dropping_a_subscription_unsubscribes() {
+        let bus = EventBus::new();
+        let subscription =
+            bus.subscribe(SubscribeOptions::new(EventFilter::workspace("ws-1")).exclusive());
+        assert!(bus.has_exclusive_subscribers("ws-1"));
+        drop(subscription);
+        assert!(!bus.has_exclusive_subscribers("ws-1"));
+        assert!(!bus.publish("ws-1", None, &json!({ "method": "turn/started" })));
+    }
+
+    #[test]
+    fn bridge_observer_forwards_thread_turn_events() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let bus = EventBus::new();
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let observer = spawn_bridge_observer(&bus, move |command| {
+                let tx = tx.clone();
+                async move { tx.send(command).map_err(|err| err.to_string()) }
+            });
+
+            let started = json!({ "method": "turn/started", "params": { "threadId": "thread-1" } });
+            bus.publish("ws-1", None, &started);
+            bus.publish(
+                "ws-1",
+                Some("thread-1"),
+                &json!({ "method": "item/agentMessage/delta", "params": {} }),
+            );
+            bus.publish("ws-1", Some("thread-1"), &started);
+
+            let command = rx.recv().await.expect("forwarded command");
+            assert_eq!(
+                command,
+                json!({
+                    "type": "app-server-event",
+                    "workspaceId": "ws-1",
+                    "threadId": "thread-1",
+                    "method": "turn/started",
+                    "params": { "threadId": "thread-1" },
+                })
+            );
+            assert!(rx.try_recv().is_err());
+            observer.abort();
+        });
+    }
+}
@@ -0,0 +1,217 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::events::AppServerEvent;
+
+/// Upper bound for a merged delta before it is emitted regardless of the window.
+const MAX_COALESCED_DELTA_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CoalescerStats {
+    /// App-server events handed to the sink.
+    pub(crate) received: u64,
+    /// Events actually emitted to the webview.
+    pub(crate) emitted: u64,
+    /// Delta events merged into a previous one instead of being emitted.
+    pub(crate) coalesced: u64,
+}
+
+/// Streaming notifications such as `item/agentMessage/delta` or
+/// `item/commandExecution/outputDelta` that carry a string `delta` for one item.
+pub(crate) fn is_delta_notification(message: &Value) -> bool {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return false;
+    };
+    if message.get("id").is_some() || !(method.ends_with("/delta") || method.ends_with("Delta")) {
+        return false;
+    }
+    message
+        .get("params")
+        .and_then(|params| params.get("delta"))
+        .is_some_and(Value::is_string)
+}
+
+/// Everything except the delta text; deltas are only merged when this is identical.
+fn delta_key(event: &AppServerEvent) -> Option<(String, Value)> {
+    let mut params = event.message.get("params")?.clone();
+    params.as_object_mut()?.remove("delta");
+    let method = event.message.get("method")?.clone();
+    Some((
+        event.workspace_id.clone(),
+        Value::Array(vec![method, params]),
+    ))
+}
+
+struct PendingDelta {
+    key: (String, Value),
+    event: AppServerEvent,
+    text: String,
+}
+
+impl PendingDelta {
+    fn into_event(self) -> AppServerEvent {
+        let mut event = self.event;
+        if let Some(params) = event
+            .message
+            .get_mut("params")
+            .and_then(Value::as_object_mut)
+        {
+            params.insert("delta".to_string(), Value::String(self.text));
+        }
+        event
+    }
+}
+
+/// Merges consecutive delta notifications for the same item. Any other event
+/// flushes the buffered delta first so the webview sees messages in order.
+#[derive(Default)]
+pub(crate) struct DeltaCoalescer {
+    pending: Option<PendingDelta>,
+    stats: CoalescerStats,
+}
+
+impl DeltaCoalescer {
+    /// Returns the events to emit right away. With `coalesce` off every event passes through.
+    pub(crate) fn push(&mut self, event: AppServerEvent, coalesce: bool) -> Vec<AppServerEvent> {
+        self.stats.received += 1;
+        let mut ready = Vec::new();
+        let key = if coalesce && is_delta_notification(&event.message) {
+            delta_key(&event)
+        } else {
+            None
+        };
+        let Some(key) = key else {
+            ready.extend(self.flush());
+            ready.push(event);
+            self.stats.emitted += 1;
+            return ready;
+        };
+        let delta = event
+            .message
+            .get("params")
+            .and_then(|params| params.get("delta"))
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        match self.pending.as_mut() {
+            Some(pending) if pending.key == key => {
+                pending.text.push_str(&delta);
+                self.stats.coalesced += 1;
+            }
+            _ => {
+                ready.extend(self.flush());
+                self.pending = Some(PendingDelta {
+                    key,
+                    event,
+                    text: delta,
+                });
+            }
+        }
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| pending.text.len() >= MAX_COALESCED_DELTA_BYTES)
+        {
+            ready.extend(self.flush());
+        }
+        ready
+    }
+
+    /// Takes the buffered delta, if any, once its window has elapsed.
+    pub(crate) fn flush(&mut self) -> Option<AppServerEvent> {
+        let pending = self.pending.take()?;
+        self.stats.emitted += 1;
+        Some(pending.into_event())
+    }
+
+    pub(crate) fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub(crate) fn stats(&self) -> CoalescerStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_delta_notification, CoalescerStats, DeltaCoalescer};
+    use crate::backend::events::AppServerEvent;
+    use serde_json::{json, Value};
+
+    fn event(message: Value) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message,
+        }
+    }
+
+    fn delta(item_id: &str, text: &str) -> AppServerEvent {
+        event(json!({
+            "method": "item/agentMessage/delta",
+            "params": { "threadId": "thread-1", "itemId": item_id, "delta": text }
+        }))
+    }
+
+    #[test]
+    fn detects_delta_notifications() {
+        assert!(is_delta_notification(&delta("item-1", "hi").message));
+        assert!(is_delta_notification(&json!({
+            "method": "item/commandExecution/outputDelta",
+            "params": { "itemId": "item-1", "delta": "out" }
+        })));
+        assert!(!is_delta_notification(&json!({
+            "method": "turn/completed",
+            "params": {}
+        })));
+        assert!(!is_delta_notification(&json!({
+            "id": 3,
+            "method": "item/agentMessage/delta",
+            "params": { "delta": "x" }
+        })));
+    }
+
+    #[test]
+    fn merges_consecutive_deltas_for_the_same_item() {
+        let mut coalescer = DeltaCoalescer::default();
+        assert!(coalescer.push(delta("item-1", "Hel"), true).is_empty());
+        assert!(coalescer.push(delta("item-1", "lo"), true).is_empty());
+        let ready = coalescer.push(delta("item-2", "!"), true);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].message["params"]["delta"], "Hello");
+        assert_eq!(ready[0].message["params"]["itemId"], "item-1");
+        let flushed = coalescer.flush().expect("pending delta");
+        assert_eq!(flushed.message["params"]["delta"], "!");
+        assert_eq!(
+            coalescer.stats(),
+            CoalescerStats {
+                received: 3,
+                emitted: 2,
+                coalesced: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn other_events_flush_pending_delta_first() {
+        let mut coalescer = DeltaCoalescer::default();
+        coalescer.push(delta("item-1", "a"), true);
+        let completed = event(json!({ "method": "turn/completed", "params": {} }));
+        let ready = coalescer.push(completed, true);
+        let methods: Vec<&str> = ready
+            .iter()
+            .map(|event| event.message["method"].as_str().unwrap())
+            .collect();
+        assert_eq!(methods, vec!["item/agentMessage/delta", "turn/completed"]);
+        assert!(!coalescer.has_pending());
+    }
+
+    #[test]
+    fn disabled_coalescing_passes_everything_through() {
+        let mut coalescer = DeltaCoalescer::default();
+        assert_eq!(coalescer.push(delta("item-1", "a"), false).len(), 1);
+        assert_eq!(coalescer.push(delta("item-1", "b"), false).len(), 1);
+        assert_eq!(coalescer.stats().coalesced, 0);
+    }
+}
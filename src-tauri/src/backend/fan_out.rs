@@ -0,0 +1,623 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::event_bus::{EventFilter, SubscribeOptions};
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::git_ops::git_diffs;
+use crate::backend::message_queue::{start_user_turn, OutgoingMessage};
+use crate::backend::protocol::{
+    parse_notification, parse_response, ServerNotification, ThreadStartParams,
+};
+use crate::backend::sandbox_profiles::turn_policies;
+use crate::backend::thread_fork::ForkHost;
+use crate::types::GitFileDiff;
+
+/// Emitted as each lane finishes, with the lane's report.
+pub(crate) const FAN_OUT_LANE_METHOD: &str = "openvibe/fan_out/lane";
+/// Emitted once every lane finished, with the whole [`FanOutReport`].
+pub(crate) const FAN_OUT_COMPLETED_METHOD: &str = "openvibe/fan_out/completed";
+pub(crate) const MAX_FAN_OUT_LANES: usize = 8;
+pub(crate) const DEFAULT_FAN_OUT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const LANE_EVENT_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FanOutLane {
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FanOutRequest {
+    pub(crate) prompt: String,
+    /// Existing workspaces to run in. When empty, `count` worktrees of `parent_id` are created.
+    #[serde(default)]
+    pub(crate) workspace_ids: Vec<String>,
+    pub(crate) count: Option<u32>,
+    pub(crate) parent_id: Option<String>,
+    /// Worktree branches are named `<prefix>-<n>`.
+    pub(crate) branch_prefix: Option<String>,
+    /// Per-lane overrides, matched to lanes by position.
+    #[serde(default)]
+    pub(crate) lanes: Vec<FanOutLane>,
+    pub(crate) access_mode: Option<String>,
+    pub(crate) sandbox_profile: Option<String>,
+    pub(crate) timeout_secs: Option<u64>,
+}
+
+impl FanOutRequest {
+    pub(crate) fn lane(&self, index: usize) -> FanOutLane {
+        self.lanes.get(index).cloned().unwrap_or_default()
+    }
+
+    pub(crate) fn timeout(&self) -> Duration {
+        self.timeout_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_FAN_OUT_TIMEOUT)
+    }
+
+    /// Number of worktrees to create, or `None` when explicit workspaces were given.
+    pub(crate) fn worktree_count(&self) -> Result<Option<usize>, String> {
+        if self.prompt.trim().is_empty() {
+            return Err("Prompt is required.".to_string());
+        }
+        if !self.workspace_ids.is_empty() {
+            if self.workspace_ids.len() > MAX_FAN_OUT_LANES {
+                return Err(format!("At most {MAX_FAN_OUT_LANES} lanes are supported."));
+            }
+            return Ok(None);
+        }
+        let count = self.count.unwrap_or(0) as usize;
+        if count == 0 || count > MAX_FAN_OUT_LANES {
+            return Err(format!(
+                "Provide workspace ids or a lane count between 1 and {MAX_FAN_OUT_LANES}."
+            ));
+        }
+        if self
+            .parent_id
+            .as_deref()
+            .is_none_or(|id| id.trim().is_empty())
+        {
+            return Err("A parent workspace is required to create worktrees.".to_string());
+        }
+        Ok(Some(count))
+    }
+
+    pub(crate) fn branch_name(&self, prefix_fallback: &str, index: usize) -> String {
+        let prefix = self
+            .branch_prefix
+            .as_deref()
+            .map(str::trim)
+            .filter(|prefix| !prefix.is_empty())
+            .unwrap_or(prefix_fallback);
+        format!("{prefix}-{}", index + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum LaneStatus {
+    Completed,
+    Failed,
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FanOutLaneReport {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+    pub(crate) status: LaneStatus,
+    pub(crate) final_message: Option<String>,
+    pub(crate) error: Option<String>,
+    pub(crate) diffs: Vec<GitFileDiff>,
+    pub(crate) token_usage: Option<Value>,
+    pub(crate) duration_ms: u64,
+}
+
+impl FanOutLaneReport {
+    fn failed(workspace_id: String, lane: FanOutLane, error: Option<String>) -> Self {
+        Self {
+            workspace_id,
+            thread_id: None,
+            model: lane.model,
+            effort: lane.effort,
+            status: LaneStatus::Failed,
+            final_message: None,
+            error,
+            diffs: Vec::new(),
+            token_usage: None,
+            duration_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FanOutReport {
+    pub(crate) fan_out_id: String,
+    pub(crate) prompt: String,
+    pub(crate) lanes: Vec<FanOutLaneReport>,
+}
+
+/// What `start_fan_out` returns once every lane is running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FanOutStarted {
+    pub(crate) fan_out_id: String,
+    pub(crate) prompt: String,
+    /// Lane workspaces in lane order; lane events carry the same index.
+    pub(crate) workspace_ids: Vec<String>,
+}
+
+/// Follows one lane's thread events until its turn ends.
+#[derive(Debug, Default)]
+struct LaneTracker {
+    deltas: String,
+    final_message: Option<String>,
+    token_usage: Option<Value>,
+}
+
+impl LaneTracker {
+    /// Returns the turn's result once it completed or failed.
+    fn observe(&mut self, message: &Value) -> Option<Result<(), String>> {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params");
+        if method == "thread/tokenUsage/updated" {
+            if let Some(usage) = params.and_then(|params| {
+                params
+                    .get("tokenUsage")
+                    .or_else(|| params.get("token_usage"))
+            }) {
+                self.token_usage = Some(usage.clone());
+            }
+            return None;
+        }
+        if method == "item/completed" {
+            let item = params.and_then(|params| params.get("item"));
+            if item
+                .and_then(|item| item.get("type"))
+                .and_then(Value::as_str)
+                == Some("agentMessage")
+            {
+                if let Some(text) = item
+                    .and_then(|item| item.get("text"))
+                    .and_then(Value::as_str)
+                {
+                    self.final_message = Some(text.to_string());
+                    self.deltas.clear();
+                }
+            }
+            return None;
+        }
+        match parse_notification(message)? {
+            ServerNotification::AgentMessageDelta(delta) => {
+                self.deltas.push_str(&delta.delta);
+                None
+            }
+            ServerNotification::TurnCompleted(_) => Some(Ok(())),
+            ServerNotification::TurnError(error) => {
+                Some(Err(error.unwrap_or_else(|| "Turn failed".to_string())))
+            }
+            ServerNotification::TurnStarted(_) => None,
+        }
+    }
+
+    fn final_message(&self) -> Option<String> {
+        self.final_message
+            .clone()
+            .or_else(|| (!self.deltas.trim().is_empty()).then(|| self.deltas.clone()))
+            .map(|text| text.trim().to_string())
+    }
+}
+
+/// Starts a visible thread in the lane's workspace, sends the prompt and waits for the
+/// turn to end. Diffs are left to the caller, which knows how to read the worktree.
+pub(crate) async fn run_lane(
+    session: Arc<WorkspaceSession>,
+    prompt: String,
+    lane: FanOutLane,
+    access_mode: Option<String>,
+    sandbox_profile: Option<String>,
+    turn_timeout: Duration,
+) -> FanOutLaneReport {
+    let started = Instant::now();
+    let mut report = FanOutLaneReport::failed(session.entry.id.clone(), lane.clone(), None);
+    let policies = turn_policies(
+        &session.sandbox_profiles,
+        &session.entry.path,
+        access_mode.as_deref(),
+        sandbox_profile.as_deref(),
+    );
+    let thread = match policies {
+        Ok((approval_policy, _)) => session
+            .request(&ThreadStartParams {
+                cwd: session.entry.path.clone(),
+                approval_policy,
+            })
+            .await
+            .and_then(|response| parse_response::<ThreadStartParams>(&response)),
+        Err(error) => Err(error),
+    };
+    let thread_id = match thread {
+        Ok(thread) => thread.thread.id,
+        Err(error) => {
+            report.error = Some(error);
+            report.duration_ms = started.elapsed().as_millis() as u64;
+            return report;
+        }
+    };
+    report.thread_id = Some(thread_id.clone());
+    let mut events = session.event_bus.subscribe(
+        SubscribeOptions::new(EventFilter::thread(&session.entry.id, &thread_id))
+            .capacity(LANE_EVENT_CAPACITY),
+    );
+    let message = OutgoingMessage {
+        text: prompt,
+        model: lane.model,
+        effort: lane.effort,
+        access_mode,
+        sandbox_profile,
+        ..OutgoingMessage::default()
+    };
+    if let Err(error) = start_user_turn(&session, thread_id, message).await {
+        report.error = Some(error);
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        return report;
+    }
+
+    let mut tracker = LaneTracker::default();
+    let outcome = timeout(turn_timeout, async {
+        while let Some(event) = events.recv().await {
+            if let Some(result) = tracker.observe(&event.message) {
+                return result;
+            }
+        }
+        Err("Lane event stream closed".to_string())
+    })
+    .await;
+    match outcome {
+        Ok(Ok(())) => report.status = LaneStatus::Completed,
+        Ok(Err(error)) => report.error = Some(error),
+        Err(_) => {
+            report.status = LaneStatus::TimedOut;
+            report.error = Some(format!(
+                "Timed out after {}s waiting for the turn",
+                turn_timeout.as_secs()
+            ));
+        }
+    }
+    report.final_message = tracker.final_message();
+    report.token_usage = tracker.token_usage;
+    report.duration_ms = started.elapsed().as_millis() as u64;
+    report
+}
+
+/// Creates the worktrees (when only a count is given), connects every lane and starts
+/// them, then returns. Each lane reports through a [`FAN_OUT_LANE_METHOD`] event and the
+/// run through [`FAN_OUT_COMPLETED_METHOD`]. Worktrees created for a fan-out that fails to
+/// start are removed again.
+pub(crate) async fn start_fan_out<H: ForkHost, E: EventSink>(
+    host: &H,
+    request: FanOutRequest,
+    event_sink: E,
+) -> Result<FanOutStarted, String> {
+    let worktree_count = request.worktree_count()?;
+    let mut created = Vec::new();
+    let sessions = match connect_lanes(host, &request, worktree_count, &mut created).await {
+        Ok(sessions) => sessions,
+        Err(error) => {
+            for id in created {
+                if let Err(err) = host.remove_worktree(&id).await {
+                    eprintln!("[fan-out] failed to remove worktree {id}: {err}");
+                }
+            }
+            return Err(error);
+        }
+    };
+    let started = FanOutStarted {
+        fan_out_id: Uuid::new_v4().to_string(),
+        prompt: request.prompt.clone(),
+        workspace_ids: sessions
+            .iter()
+            .map(|session| session.entry.id.clone())
+            .collect(),
+    };
+    tokio::spawn(run_fan_out(
+        started.fan_out_id.clone(),
+        request,
+        sessions,
+        event_sink,
+    ));
+    Ok(started)
+}
+
+/// Resolves a session per lane, recording every worktree it creates in `created`.
+async fn connect_lanes<H: ForkHost>(
+    host: &H,
+    request: &FanOutRequest,
+    worktree_count: Option<usize>,
+    created: &mut Vec<String>,
+) -> Result<Vec<Arc<WorkspaceSession>>, String> {
+    let workspace_ids = match worktree_count {
+        None => request.workspace_ids.clone(),
+        Some(count) => {
+            let parent_id = request.parent_id.clone().unwrap_or_default();
+            let fallback = format!("fanout-{}", &Uuid::new_v4().simple().to_string()[..8]);
+            for index in 0..count {
+                let branch = request.branch_name(&fallback, index);
+                let worktree = host.add_worktree(&parent_id, &branch).await?;
+                created.push(worktree.id);
+            }
+            created.clone()
+        }
+    };
+    let mut sessions = Vec::with_capacity(workspace_ids.len());
+    for workspace_id in &workspace_ids {
+        sessions.push(host.session(workspace_id).await?);
+    }
+    Ok(sessions)
+}
+
+fn fan_out_event(workspace_id: &str, method: &str, params: Value) -> AppServerEvent {
+    AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({ "method": method, "params": params }),
+    }
+}
+
+/// Runs every lane to the end, reporting each one as soon as it finishes. A lane that
+/// panics is reported as failed instead of taking the other lanes' reports with it.
+async fn run_fan_out<E: EventSink>(
+    fan_out_id: String,
+    request: FanOutRequest,
+    sessions: Vec<Arc<WorkspaceSession>>,
+    event_sink: E,
+) {
+    let report_workspace_id = request
+        .parent_id
+        .clone()
+        .or_else(|| sessions.first().map(|session| session.entry.id.clone()))
+        .unwrap_or_default();
+    let mut lanes = JoinSet::new();
+    let mut lane_ids = HashMap::new();
+    for (index, session) in sessions.into_iter().enumerate() {
+        let entry = session.entry.clone();
+        let workspace_id = entry.id.clone();
+        let lane_run = run_lane(
+            session,
+            request.prompt.clone(),
+            request.lane(index),
+            request.access_mode.clone(),
+            request.sandbox_profile.clone(),
+            request.timeout(),
+        );
+        let handle = lanes.spawn(async move {
+            let mut report = lane_run.await;
+            match git_diffs(&entry).await {
+                Ok(diffs) => report.diffs = diffs,
+                Err(error) => {
+                    report.error.get_or_insert(error);
+                }
+            }
+            report
+        });
+        lane_ids.insert(handle.id(), (index, workspace_id));
+    }
+    let mut reports: Vec<Option<FanOutLaneReport>> = vec![None; lane_ids.len()];
+    while let Some(joined) = lanes.join_next_with_id().await {
+        let (id, report) = match joined {
+            Ok((id, report)) => (id, Ok(report)),
+            Err(err) => (err.id(), Err(err)),
+        };
+        let Some((index, workspace_id)) = lane_ids.remove(&id) else {
+            continue;
+        };
+        let report = report.unwrap_or_else(|err| {
+            FanOutLaneReport::failed(
+                workspace_id.clone(),
+                request.lane(index),
+                Some(format!("lane crashed: {err}")),
+            )
+        });
+        event_sink.emit_app_server_event(fan_out_event(
+            &workspace_id,
+            FAN_OUT_LANE_METHOD,
+            json!({ "fanOutId": fan_out_id, "index": index, "lane": report }),
+        ));
+        reports[index] = Some(report);
+    }
+    let report = FanOutReport {
+        fan_out_id,
+        prompt: request.prompt,
+        lanes: reports.into_iter().flatten().collect(),
+    };
+    event_sink.emit_app_server_event(fan_out_event(
+        &report_workspace_id,
+        FAN_OUT_COMPLETED_METHOD,
+        serde_json::to_value(&report).unwrap_or(Value::Null),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{start_fan_out, FanOutRequest, LaneTracker};
+    use crate::backend::app_server::WorkspaceSession;
+    use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+    use crate::backend::thread_fork::ForkHost;
+    use crate::types::WorkspaceInfo;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    struct NoopSink;
+
+    impl EventSink for NoopSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exit(&self, _event: TerminalExit) {}
+    }
+
+    /// Creates worktrees until `fail_after` of them exist and never connects a session.
+    #[derive(Default)]
+    struct FailingHost {
+        fail_after: Option<usize>,
+        added: Mutex<usize>,
+        removed: Mutex<Vec<String>>,
+    }
+
+    impl ForkHost for FailingHost {
+        async fn add_worktree(
+            &self,
+            parent_id: &str,
+            branch: &str,
+        ) -> Result<WorkspaceInfo, String> {
+            let mut added = self.added.lock().unwrap();
+            if self.fail_after == Some(*added) {
+                return Err("branch already exists".to_string());
+            }
+            *added += 1;
+            serde_json::from_value(json!({
+                "id": format!("{parent_id}-{branch}"),
+                "name": branch,
+                "path": "/tmp/worktree",
+                "connected": false,
+                "kind": "worktree",
+                "parentId": parent_id,
+                "settings": {},
+            }))
+            .map_err(|err| err.to_string())
+        }
+
+        async fn remove_worktree(&self, id: &str) -> Result<(), String> {
+            self.removed.lock().unwrap().push(id.to_string());
+            Ok(())
+        }
+
+        async fn session(&self, _workspace_id: &str) -> Result<Arc<WorkspaceSession>, String> {
+            Err("workspace not connected".to_string())
+        }
+    }
+
+    #[test]
+    fn tracker_collects_final_message_and_usage() {
+        let mut tracker = LaneTracker::default();
+        let delta = json!({
+            "method": "item/agentMessage/delta",
+            "params": { "threadId": "t", "itemId": "i", "delta": "partial" }
+        });
+        assert!(tracker.observe(&delta).is_none());
+        assert_eq!(tracker.final_message().as_deref(), Some("partial"));
+        tracker.observe(&json!({
+            "method": "item/completed",
+            "params": { "item": { "type": "agentMessage", "text": " Done. " } }
+        }));
+        tracker.observe(&json!({
+            "method": "thread/tokenUsage/updated",
+            "params": { "threadId": "t", "tokenUsage": { "total": { "totalTokens": 42 } } }
+        }));
+        let completed = json!({
+            "method": "turn/completed",
+            "params": { "threadId": "t", "turn": { "id": "turn-1" } }
+        });
+        assert_eq!(tracker.observe(&completed), Some(Ok(())));
+        assert_eq!(tracker.final_message().as_deref(), Some("Done."));
+        assert_eq!(tracker.token_usage.unwrap()["total"]["totalTokens"], 42);
+    }
+
+    #[test]
+    fn tracker_reports_turn_errors() {
+        let mut tracker = LaneTracker::default();
+        let error = json!({ "method": "turn/error", "params": { "error": "rate limited" } });
+        assert_eq!(
+            tracker.observe(&error),
+            Some(Err("rate limited".to_string()))
+        );
+    }
+
+    #[test]
+    fn request_validates_lanes() {
+        let request = FanOutRequest {
+            prompt: "Fix it".to_string(),
+            count: Some(3),
+            parent_id: Some("ws-1".to_string()),
+            ..FanOutRequest::default()
+        };
+        assert_eq!(request.worktree_count(), Ok(Some(3)));
+        assert_eq!(request.branch_name("fanout-ab12", 0), "fanout-ab12-1");
+        assert!(request.lane(2).model.is_none());
+
+        let explicit = FanOutRequest {
+            prompt: "Fix it".to_string(),
+            workspace_ids: vec!["ws-1".to_string(), "ws-2".to_string()],
+            ..FanOutRequest::default()
+        };
+        assert_eq!(explicit.worktree_count(), Ok(None));
+
+        let too_many = FanOutRequest {
+            count: Some(20),
+            ..request.clone()
+        };
+        assert!(too_many.worktree_count().is_err());
+        let orphan = FanOutRequest {
+            parent_id: None,
+            ..request
+        };
+        assert!(orphan.worktree_count().is_err());
+    }
+
+    #[test]
+    fn failed_start_removes_created_worktrees() {
+        let request = FanOutRequest {
+            prompt: "Fix it".to_string(),
+            count: Some(3),
+            parent_id: Some("ws-1".to_string()),
+            branch_prefix: Some("try".to_string()),
+            ..FanOutRequest::default()
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let host = FailingHost {
+                fail_after: Some(2),
+                ..FailingHost::default()
+            };
+            let error = start_fan_out(&host, request.clone(), NoopSink)
+                .await
+                .unwrap_err();
+            assert_eq!(error, "branch already exists");
+            assert_eq!(
+                *host.removed.lock().unwrap(),
+                vec!["ws-1-try-1".to_string(), "ws-1-try-2".to_string()]
+            );
+
+            let host = FailingHost::default();
+            let error = start_fan_out(&host, request, NoopSink).await.unwrap_err();
+            assert_eq!(error, "workspace not connected");
+            assert_eq!(host.removed.lock().unwrap().len(), 3);
+
+            let host = FailingHost::default();
+            let explicit = FanOutRequest {
+                prompt: "Fix it".to_string(),
+                workspace_ids: vec!["ws-1".to_string()],
+                ..FanOutRequest::default()
+            };
+            assert!(start_fan_out(&host, explicit, NoopSink).await.is_err());
+            assert!(host.removed.lock().unwrap().is_empty());
+        });
+    }
+}
@@ -0,0 +1,951 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use git2::{BranchType, DiffOptions, Repository, Sort, Status, StatusOptions};
+use serde_json::json;
+use tokio::process::Command;
+
+use crate::git_utils::{
+    checkout_branch, commit_to_entry, diff_patch_to_string, diff_stats_for_path, image_mime_type,
+    list_git_roots as scan_git_roots, resolve_git_root,
+};
+use crate::types::{
+    BranchInfo, GitCommitDiff, GitFileDiff, GitFileStatus, GitLogResponse, WorkspaceEntry,
+};
+use crate::utils::{git_env_path, normalize_git_path, resolve_git_binary};
+
+const INDEX_SKIP_WORKTREE_FLAG: u16 = 0x4000;
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+fn encode_image_base64(data: &[u8]) -> Option<String> {
+    if data.len() > MAX_IMAGE_BYTES {
+        return None;
+    }
+    Some(STANDARD.encode(data))
+}
+
+fn blob_to_base64(blob: git2::Blob) -> Option<String> {
+    if blob.size() > MAX_IMAGE_BYTES {
+        return None;
+    }
+    encode_image_base64(blob.content())
+}
+
+fn read_image_base64(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_IMAGE_BYTES as u64 {
+        return None;
+    }
+    let data = fs::read(path).ok()?;
+    encode_image_base64(&data)
+}
+
+async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String> {
+    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
+    let mut command = Command::new(git_bin);
+    crate::utils::apply_background_command_flags_tokio(&mut command);
+    let output = command
+        .args(args)
+        .current_dir(repo_root)
+        .env("PATH", git_env_path())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if detail.is_empty() {
+        return Err("Git command failed.".to_string());
+    }
+    Err(detail.to_string())
+}
+
+fn action_paths_for_file(repo_root: &Path, path: &str) -> Vec<String> {
+    let target = normalize_git_path(path).trim().to_string();
+    if target.is_empty() {
+        return Vec::new();
+    }
+
+    let repo = match Repository::open(repo_root) {
+        Ok(repo) => repo,
+        Err(_) => return vec![target],
+    };
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .include_ignored(false);
+
+    let statuses = match repo.statuses(Some(&mut status_options)) {
+        Ok(statuses) => statuses,
+        Err(_) => return vec![target],
+    };
+
+    for entry in statuses.iter() {
+        let status = entry.status();
+        if !(status.contains(Status::WT_RENAMED) || status.contains(Status::INDEX_RENAMED)) {
+            continue;
+        }
+        let delta = entry.index_to_workdir().or_else(|| entry.head_to_index());
+        let Some(delta) = delta else {
+            continue;
+        };
+        let (Some(old_path), Some(new_path)) = (delta.old_file().path(), delta.new_file().path())
+        else {
+            continue;
+        };
+        let old_path = normalize_git_path(old_path.to_string_lossy().as_ref());
+        let new_path = normalize_git_path(new_path.to_string_lossy().as_ref());
+        if old_path != target && new_path != target {
+            continue;
+        }
+        if old_path == new_path || new_path.is_empty() {
+            return vec![target];
+        }
+        let mut result = Vec::new();
+        if !old_path.is_empty() {
+            result.push(old_path);
+        }
+        if !new_path.is_empty() && !result.contains(&new_path) {
+            result.push(new_path);
+        }
+        return if result.is_empty() {
+            vec![target]
+        } else {
+            result
+        };
+    }
+
+    vec![target]
+}
+
+fn parse_upstream_ref(name: &str) -> Option<(String, String)> {
+    let trimmed = name.strip_prefix("refs/remotes/").unwrap_or(name);
+    let mut parts = trimmed.splitn(2, '/');
+    let remote = parts.next()?;
+    let branch = parts.next()?;
+    if remote.is_empty() || branch.is_empty() {
+        return None;
+    }
+    Some((remote.to_string(), branch.to_string()))
+}
+
+fn upstream_remote_and_branch(repo_root: &Path) -> Result<Option<(String, String)>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(_) => return Ok(None),
+    };
+    if !head.is_branch() {
+        return Ok(None);
+    }
+    let branch_name = match head.shorthand() {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let branch = repo
+        .find_branch(branch_name, BranchType::Local)
+        .map_err(|e| e.to_string())?;
+    let upstream_branch = match branch.upstream() {
+        Ok(upstream) => upstream,
+        Err(_) => return Ok(None),
+    };
+    let upstream_ref = upstream_branch.get();
+    let upstream_name = upstream_ref.name().or_else(|| upstream_ref.shorthand());
+    Ok(upstream_name.and_then(parse_upstream_ref))
+}
+
+async fn push_with_upstream(repo_root: &Path) -> Result<(), String> {
+    let upstream = upstream_remote_and_branch(repo_root)?;
+    if let Some((remote, branch)) = upstream {
+        // Refresh remote-tracking refs before push so ahead/behind state is current
+        // and we can surface pull/sync requirements before attempting the push.
+        // This is best-effort because some setups intentionally allow push but not fetch.
+        let _ = run_git_command(repo_root, &["fetch", "--prune", remote.as_str()]).await;
+        let refspec = format!("HEAD:{branch}");
+        return run_git_command(repo_root, &["push", remote.as_str(), refspec.as_str()]).await;
+    }
+    run_git_command(repo_root, &["push"]).await
+}
+
+async fn pull_with_default_strategy(repo_root: &Path) -> Result<(), String> {
+    fn autostash_unsupported(lower: &str) -> bool {
+        lower.contains("unknown option") && lower.contains("autostash")
+    }
+
+    fn needs_reconcile_strategy(lower: &str) -> bool {
+        lower.contains("need to specify how to reconcile divergent branches")
+            || lower.contains("you have divergent branches")
+    }
+
+    // Respect user/repo git config first, while still allowing dirty worktrees.
+    match run_git_command(repo_root, &["pull", "--autostash"]).await {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            let lower = err.to_lowercase();
+            if autostash_unsupported(&lower) {
+                match run_git_command(repo_root, &["pull"]).await {
+                    Ok(()) => Ok(()),
+                    Err(no_autostash_err) => {
+                        let no_autostash_lower = no_autostash_err.to_lowercase();
+                        if needs_reconcile_strategy(&no_autostash_lower) {
+                            return run_git_command(repo_root, &["pull", "--no-rebase"]).await;
+                        }
+                        Err(no_autostash_err)
+                    }
+                }
+            } else if needs_reconcile_strategy(&lower) {
+                match run_git_command(repo_root, &["pull", "--no-rebase", "--autostash"]).await {
+                    Ok(()) => Ok(()),
+                    Err(merge_err) => {
+                        let merge_lower = merge_err.to_lowercase();
+                        if autostash_unsupported(&merge_lower) {
+                            return run_git_command(repo_root, &["pull", "--no-rebase"]).await;
+                        }
+                        Err(merge_err)
+                    }
+                }
+            } else {
+                Err(err)
+            }
+        }
+    }
+}
+fn status_for_index(status: Status) -> Option<&'static str> {
+    if status.contains(Status::INDEX_NEW) {
+        Some("A")
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        Some("M")
+    } else if status.contains(Status::INDEX_DELETED) {
+        Some("D")
+    } else if status.contains(Status::INDEX_RENAMED) {
+        Some("R")
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        Some("T")
+    } else {
+        None
+    }
+}
+
+fn status_for_workdir(status: Status) -> Option<&'static str> {
+    if status.contains(Status::WT_NEW) {
+        Some("A")
+    } else if status.contains(Status::WT_MODIFIED) {
+        Some("M")
+    } else if status.contains(Status::WT_DELETED) {
+        Some("D")
+    } else if status.contains(Status::WT_RENAMED) {
+        Some("R")
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        Some("T")
+    } else {
+        None
+    }
+}
+
+fn status_for_delta(status: git2::Delta) -> &'static str {
+    match status {
+        git2::Delta::Added => "A",
+        git2::Delta::Modified => "M",
+        git2::Delta::Deleted => "D",
+        git2::Delta::Renamed => "R",
+        git2::Delta::Typechange => "T",
+        _ => "M",
+    }
+}
+
+pub(crate) fn git_status(entry: &WorkspaceEntry) -> Result<serde_json::Value, String> {
+    let repo_root = resolve_git_root(entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+
+    let branch_name = repo
+        .head()
+        .ok()
+        .and_then(|head| head.shorthand().map(|s| s.to_string()))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .include_ignored(false);
+
+    let statuses = repo
+        .statuses(Some(&mut status_options))
+        .map_err(|e| e.to_string())?;
+
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let index = repo.index().ok();
+
+    let mut files = Vec::new();
+    let mut staged_files = Vec::new();
+    let mut unstaged_files = Vec::new();
+    let mut total_additions = 0i64;
+    let mut total_deletions = 0i64;
+    for entry in statuses.iter() {
+        let path = entry.path().unwrap_or("");
+        if path.is_empty() {
+            continue;
+        }
+        if let Some(index) = index.as_ref() {
+            if let Some(entry) = index.get_path(Path::new(path), 0) {
+                if entry.flags_extended & INDEX_SKIP_WORKTREE_FLAG != 0 {
+                    continue;
+                }
+            }
+        }
+        let status = entry.status();
+        let normalized_path = normalize_git_path(path);
+        let include_index = status.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        );
+        let include_workdir = status.intersects(
+            Status::WT_NEW
+                | Status::WT_MODIFIED
+                | Status::WT_DELETED
+                | Status::WT_RENAMED
+                | Status::WT_TYPECHANGE,
+        );
+        let mut combined_additions = 0i64;
+        let mut combined_deletions = 0i64;
+
+        if include_index {
+            let (additions, deletions) =
+                diff_stats_for_path(&repo, head_tree.as_ref(), path, true, false).unwrap_or((0, 0));
+            if let Some(status_str) = status_for_index(status) {
+                staged_files.push(GitFileStatus {
+                    path: normalized_path.clone(),
+                    status: status_str.to_string(),
+                    additions,
+                    deletions,
+                });
+            }
+            combined_additions += additions;
+            combined_deletions += deletions;
+            total_additions += additions;
+            total_deletions += deletions;
+        }
+
+        if include_workdir {
+            let (additions, deletions) =
+                diff_stats_for_path(&repo, head_tree.as_ref(), path, false, true).unwrap_or((0, 0));
+            if let Some(status_str) = status_for_workdir(status) {
+                unstaged_files.push(GitFileStatus {
+                    path: normalized_path.clone(),
+                    status: status_str.to_string(),
+                    additions,
+                    deletions,
+                });
+            }
+            combined_additions += additions;
+            combined_deletions += deletions;
+            total_additions += additions;
+            total_deletions += deletions;
+        }
+
+        if include_index || include_workdir {
+            let status_str = status_for_workdir(status)
+                .or_else(|| status_for_index(status))
+                .unwrap_or("--");
+            files.push(GitFileStatus {
+                path: normalized_path,
+                status: status_str.to_string(),
+                additions: combined_additions,
+                deletions: combined_deletions,
+            });
+        }
+    }
+
+    Ok(json!({
+        "branchName": branch_name,
+        "files": files,
+        "stagedFiles": staged_files,
+        "unstagedFiles": unstaged_files,
+        "totalAdditions": total_additions,
+        "totalDeletions": total_deletions,
+    }))
+}
+
+pub(crate) async fn stage_file(entry: &WorkspaceEntry, path: &str) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    // If libgit2 reports a rename, we want a single UI action to stage both the
+    // old + new paths so the change actually moves to the staged section.
+    for path in action_paths_for_file(&repo_root, path) {
+        run_git_command(&repo_root, &["add", "-A", "--", &path]).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn stage_all(entry: &WorkspaceEntry) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    run_git_command(&repo_root, &["add", "-A"]).await
+}
+
+pub(crate) async fn unstage_file(entry: &WorkspaceEntry, path: &str) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    for path in action_paths_for_file(&repo_root, path) {
+        run_git_command(&repo_root, &["restore", "--staged", "--", &path]).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn revert_file(entry: &WorkspaceEntry, path: &str) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    for path in action_paths_for_file(&repo_root, path) {
+        if run_git_command(
+            &repo_root,
+            &["restore", "--staged", "--worktree", "--", &path],
+        )
+        .await
+        .is_ok()
+        {
+            continue;
+        }
+        run_git_command(&repo_root, &["clean", "-f", "--", &path]).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn revert_all(entry: &WorkspaceEntry) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    run_git_command(
+        &repo_root,
+        &["restore", "--staged", "--worktree", "--", "."],
+    )
+    .await?;
+    run_git_command(&repo_root, &["clean", "-f", "-d"]).await
+}
+
+pub(crate) async fn commit(entry: &WorkspaceEntry, message: &str) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    run_git_command(&repo_root, &["commit", "-m", message]).await
+}
+
+pub(crate) async fn push(entry: &WorkspaceEntry) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    push_with_upstream(&repo_root).await
+}
+
+pub(crate) async fn pull(entry: &WorkspaceEntry) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    pull_with_default_strategy(&repo_root).await
+}
+
+pub(crate) async fn sync(entry: &WorkspaceEntry) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    // Pull first, then push (like VSCode sync)
+    pull_with_default_strategy(&repo_root).await?;
+    push_with_upstream(&repo_root).await
+}
+
+pub(crate) fn git_roots(entry: &WorkspaceEntry, depth: Option<usize>) -> Vec<String> {
+    let root = PathBuf::from(&entry.path);
+    let depth = depth.unwrap_or(2).clamp(1, 6);
+    scan_git_roots(&root, depth, 200)
+}
+
+fn build_combined_diff(diff: &git2::Diff) -> String {
+    let mut combined_diff = String::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let path = delta.new_file().path().or_else(|| delta.old_file().path());
+        let Some(path) = path else {
+            continue;
+        };
+        let patch = match git2::Patch::from_diff(diff, index) {
+            Ok(patch) => patch,
+            Err(_) => continue,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        let content = match diff_patch_to_string(&mut patch) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        if !combined_diff.is_empty() {
+            combined_diff.push_str("\n\n");
+        }
+        combined_diff.push_str(&format!("=== {} ===\n", path.display()));
+        combined_diff.push_str(&content);
+    }
+    combined_diff
+}
+
+fn collect_workspace_diff(repo_root: &Path) -> Result<String, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let mut options = DiffOptions::new();
+    let index = repo.index().map_err(|e| e.to_string())?;
+    let diff = match head_tree.as_ref() {
+        Some(tree) => repo
+            .diff_tree_to_index(Some(tree), Some(&index), Some(&mut options))
+            .map_err(|e| e.to_string())?,
+        None => repo
+            .diff_tree_to_index(None, Some(&index), Some(&mut options))
+            .map_err(|e| e.to_string())?,
+    };
+    let combined_diff = build_combined_diff(&diff);
+    if !combined_diff.trim().is_empty() {
+        return Ok(combined_diff);
+    }
+
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = match head_tree.as_ref() {
+        Some(tree) => repo
+            .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
+            .map_err(|e| e.to_string())?,
+        None => repo
+            .diff_tree_to_workdir_with_index(None, Some(&mut options))
+            .map_err(|e| e.to_string())?,
+    };
+    Ok(build_combined_diff(&diff))
+}
+
+/// Staged changes, or every working-tree change when nothing is staged.
+pub(crate) fn workspace_diff(entry: &WorkspaceEntry) -> Result<String, String> {
+    let repo_root = resolve_git_root(entry)?;
+    collect_workspace_diff(&repo_root)
+}
+
+/// The prompt used to generate a commit message for the workspace's changes.
+pub(crate) fn commit_message_prompt(entry: &WorkspaceEntry) -> Result<String, String> {
+    let diff = workspace_diff(entry)?;
+    if diff.trim().is_empty() {
+        return Err("No changes to generate commit message for".to_string());
+    }
+    Ok(format!(
+        "Generate a concise git commit message for the following changes. \
+Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
+Keep the summary line under 72 characters. \
+Only output the commit message, nothing else.\n\n\
+Changes:\n{diff}"
+    ))
+}
+
+pub(crate) async fn git_diffs(entry: &WorkspaceEntry) -> Result<Vec<GitFileDiff>, String> {
+    let repo_root = resolve_git_root(entry)?;
+    tokio::task::spawn_blocking(move || {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+        let mut options = DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+
+        let diff = match head_tree.as_ref() {
+            Some(tree) => repo
+                .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
+                .map_err(|e| e.to_string())?,
+            None => repo
+                .diff_tree_to_workdir_with_index(None, Some(&mut options))
+                .map_err(|e| e.to_string())?,
+        };
+
+        let mut results = Vec::new();
+        for (index, delta) in diff.deltas().enumerate() {
+            let old_path = delta.old_file().path();
+            let new_path = delta.new_file().path();
+            let display_path = new_path.or(old_path);
+            let Some(display_path) = display_path else {
+                continue;
+            };
+            let old_path_str = old_path.map(|path| path.to_string_lossy());
+            let new_path_str = new_path.map(|path| path.to_string_lossy());
+            let display_path_str = display_path.to_string_lossy();
+            let normalized_path = normalize_git_path(&display_path_str);
+            let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
+            let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
+            let is_image = old_image_mime.is_some() || new_image_mime.is_some();
+
+            if is_image {
+                let is_deleted = delta.status() == git2::Delta::Deleted;
+                let is_added = delta.status() == git2::Delta::Added;
+
+                let old_image_data = if !is_added && old_image_mime.is_some() {
+                    head_tree
+                        .as_ref()
+                        .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
+                        .and_then(|entry| repo.find_blob(entry.id()).ok())
+                        .and_then(blob_to_base64)
+                } else {
+                    None
+                };
+
+                let new_image_data = if !is_deleted && new_image_mime.is_some() {
+                    match new_path {
+                        Some(path) => {
+                            let full_path = repo_root.join(path);
+                            read_image_base64(&full_path)
+                        }
+                        None => None,
+                    }
+                } else {
+                    None
+                };
+
+                results.push(GitFileDiff {
+                    path: normalized_path,
+                    diff: String::new(),
+                    is_binary: true,
+                    is_image: true,
+                    old_image_data,
+                    new_image_data,
+                    old_image_mime: old_image_mime.map(str::to_string),
+                    new_image_mime: new_image_mime.map(str::to_string),
+                });
+                continue;
+            }
+
+            let patch = match git2::Patch::from_diff(&diff, index) {
+                Ok(patch) => patch,
+                Err(_) => continue,
+            };
+            let Some(mut patch) = patch else {
+                continue;
+            };
+            let content = match diff_patch_to_string(&mut patch) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if content.trim().is_empty() {
+                continue;
+            }
+            results.push(GitFileDiff {
+                path: normalized_path,
+                diff: content,
+                is_binary: false,
+                is_image: false,
+                old_image_data: None,
+                new_image_data: None,
+                old_image_mime: None,
+                new_image_mime: None,
+            });
+        }
+
+        Ok(results)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub(crate) fn git_log(
+    entry: &WorkspaceEntry,
+    limit: Option<usize>,
+) -> Result<GitLogResponse, String> {
+    let repo_root = resolve_git_root(entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let max_items = limit.unwrap_or(40);
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push_head().map_err(|e| e.to_string())?;
+    revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+
+    let mut total = 0usize;
+    for oid_result in revwalk {
+        oid_result.map_err(|e| e.to_string())?;
+        total += 1;
+    }
+
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push_head().map_err(|e| e.to_string())?;
+    revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for oid_result in revwalk.take(max_items) {
+        let oid = oid_result.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        entries.push(commit_to_entry(commit));
+    }
+
+    let mut ahead = 0usize;
+    let mut behind = 0usize;
+    let mut ahead_entries = Vec::new();
+    let mut behind_entries = Vec::new();
+    let mut upstream = None;
+
+    if let Ok(head) = repo.head() {
+        if head.is_branch() {
+            if let Some(branch_name) = head.shorthand() {
+                if let Ok(branch) = repo.find_branch(branch_name, BranchType::Local) {
+                    if let Ok(upstream_branch) = branch.upstream() {
+                        let upstream_ref = upstream_branch.get();
+                        upstream = upstream_ref
+                            .shorthand()
+                            .map(|name| name.to_string())
+                            .or_else(|| upstream_ref.name().map(|name| name.to_string()));
+                        if let (Some(head_oid), Some(upstream_oid)) =
+                            (head.target(), upstream_ref.target())
+                        {
+                            let (ahead_count, behind_count) = repo
+                                .graph_ahead_behind(head_oid, upstream_oid)
+                                .map_err(|e| e.to_string())?;
+                            ahead = ahead_count;
+                            behind = behind_count;
+
+                            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                            revwalk.push(head_oid).map_err(|e| e.to_string())?;
+                            revwalk.hide(upstream_oid).map_err(|e| e.to_string())?;
+                            revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+                            for oid_result in revwalk.take(max_items) {
+                                let oid = oid_result.map_err(|e| e.to_string())?;
+                                let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                ahead_entries.push(commit_to_entry(commit));
+                            }
+
+                            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                            revwalk.push(upstream_oid).map_err(|e| e.to_string())?;
+                            revwalk.hide(head_oid).map_err(|e| e.to_string())?;
+                            revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+                            for oid_result in revwalk.take(max_items) {
+                                let oid = oid_result.map_err(|e| e.to_string())?;
+                                let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                behind_entries.push(commit_to_entry(commit));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(GitLogResponse {
+        total,
+        entries,
+        ahead,
+        behind,
+        ahead_entries,
+        behind_entries,
+        upstream,
+    })
+}
+
+pub(crate) fn commit_diff(entry: &WorkspaceEntry, sha: &str) -> Result<Vec<GitCommitDiff>, String> {
+    let repo_root = resolve_git_root(entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let oid = git2::Oid::from_str(sha).map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    let commit_tree = commit.tree().map_err(|e| e.to_string())?;
+    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+
+    let mut options = DiffOptions::new();
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut options))
+        .map_err(|e| e.to_string())?;
+
+    let mut results = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let old_path = delta.old_file().path();
+        let new_path = delta.new_file().path();
+        let display_path = new_path.or(old_path);
+        let Some(display_path) = display_path else {
+            continue;
+        };
+        let old_path_str = old_path.map(|path| path.to_string_lossy());
+        let new_path_str = new_path.map(|path| path.to_string_lossy());
+        let display_path_str = display_path.to_string_lossy();
+        let normalized_path = normalize_git_path(&display_path_str);
+        let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
+        let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
+        let is_image = old_image_mime.is_some() || new_image_mime.is_some();
+
+        if is_image {
+            let is_deleted = delta.status() == git2::Delta::Deleted;
+            let is_added = delta.status() == git2::Delta::Added;
+
+            let old_image_data = if !is_added && old_image_mime.is_some() {
+                parent_tree
+                    .as_ref()
+                    .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
+                    .and_then(|entry| repo.find_blob(entry.id()).ok())
+                    .and_then(blob_to_base64)
+            } else {
+                None
+            };
+
+            let new_image_data = if !is_deleted && new_image_mime.is_some() {
+                new_path
+                    .and_then(|path| commit_tree.get_path(path).ok())
+                    .and_then(|entry| repo.find_blob(entry.id()).ok())
+                    .and_then(blob_to_base64)
+            } else {
+                None
+            };
+
+            results.push(GitCommitDiff {
+                path: normalized_path,
+                status: status_for_delta(delta.status()).to_string(),
+                diff: String::new(),
+                is_binary: true,
+                is_image: true,
+                old_image_data,
+                new_image_data,
+                old_image_mime: old_image_mime.map(str::to_string),
+                new_image_mime: new_image_mime.map(str::to_string),
+            });
+            continue;
+        }
+
+        let patch = match git2::Patch::from_diff(&diff, index) {
+            Ok(patch) => patch,
+            Err(_) => continue,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        let content = match diff_patch_to_string(&mut patch) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        results.push(GitCommitDiff {
+            path: normalized_path,
+            status: status_for_delta(delta.status()).to_string(),
+            diff: content,
+            is_binary: false,
+            is_image: false,
+            old_image_data: None,
+            new_image_data: None,
+            old_image_mime: None,
+            new_image_mime: None,
+        });
+    }
+
+    Ok(results)
+}
+
+pub(crate) fn list_branches(entry: &WorkspaceEntry) -> Result<serde_json::Value, String> {
+    let repo_root = resolve_git_root(entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let mut branches = Vec::new();
+    let refs = repo
+        .branches(Some(BranchType::Local))
+        .map_err(|e| e.to_string())?;
+    for branch_result in refs {
+        let (branch, _) = branch_result.map_err(|e| e.to_string())?;
+        let name = branch.name().ok().flatten().unwrap_or("").to_string();
+        if name.is_empty() {
+            continue;
+        }
+        let last_commit = branch
+            .get()
+            .target()
+            .and_then(|oid| repo.find_commit(oid).ok())
+            .map(|commit| commit.time().seconds())
+            .unwrap_or(0);
+        branches.push(BranchInfo { name, last_commit });
+    }
+    branches.sort_by(|a, b| b.last_commit.cmp(&a.last_commit));
+    Ok(json!({ "branches": branches }))
+}
+
+pub(crate) fn checkout(entry: &WorkspaceEntry, name: &str) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    checkout_branch(&repo, name).map_err(|e| e.to_string())
+}
+
+pub(crate) fn create_branch(entry: &WorkspaceEntry, name: &str) -> Result<(), String> {
+    let repo_root = resolve_git_root(entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let head = repo.head().map_err(|e| e.to_string())?;
+    let target = head.peel_to_commit().map_err(|e| e.to_string())?;
+    repo.branch(name, &target, false)
+        .map_err(|e| e.to_string())?;
+    checkout_branch(&repo, name).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn create_temp_repo() -> (PathBuf, Repository) {
+        let root = std::env::temp_dir().join(format!("open-vibe-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create temp repo root");
+        let repo = Repository::init(&root).expect("init repo");
+        (root, repo)
+    }
+
+    #[test]
+    fn action_paths_for_file_expands_renames() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("a.txt"), "hello\n").expect("write file");
+
+        let mut index = repo.index().expect("repo index");
+        index.add_path(Path::new("a.txt")).expect("add path");
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .expect("commit");
+
+        fs::rename(root.join("a.txt"), root.join("b.txt")).expect("rename file");
+
+        // Stage the rename so libgit2 reports it as an INDEX_RENAMED entry.
+        let mut index = repo.index().expect("repo index");
+        index
+            .remove_path(Path::new("a.txt"))
+            .expect("remove old path");
+        index.add_path(Path::new("b.txt")).expect("add new path");
+        index.write().expect("write index");
+
+        let paths = action_paths_for_file(&root, "b.txt");
+        assert_eq!(paths, vec!["a.txt".to_string(), "b.txt".to_string()]);
+    }
+
+    #[test]
+    fn collect_workspace_diff_prefers_staged_changes() {
+        let (root, repo) = create_temp_repo();
+        let file_path = root.join("staged.txt");
+        fs::write(&file_path, "staged\n").expect("write staged file");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new("staged.txt")).expect("add path");
+        index.write().expect("write index");
+
+        let diff = collect_workspace_diff(&root).expect("collect diff");
+        assert!(diff.contains("staged.txt"));
+        assert!(diff.contains("staged"));
+    }
+
+    #[test]
+    fn collect_workspace_diff_falls_back_to_workdir() {
+        let (root, _repo) = create_temp_repo();
+        let file_path = root.join("unstaged.txt");
+        fs::write(&file_path, "unstaged\n").expect("write unstaged file");
+
+        let diff = collect_workspace_diff(&root).expect("collect diff");
+        assert!(diff.contains("unstaged.txt"));
+        assert!(diff.contains("unstaged"));
+    }
+}
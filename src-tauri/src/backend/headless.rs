@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::time::timeout;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::AppServerEvent;
+use crate::backend::protocol::{
+    parse_notification, parse_response, ApprovalPolicy, SandboxPolicy, ServerNotification,
+    ThreadArchiveParams, ThreadStartParams, TurnInterruptParams, TurnStartParams, UserInput,
+};
+
+pub(crate) const DEFAULT_HEADLESS_TIMEOUT: Duration = Duration::from_secs(60);
+/// Method used for progress events so the UI can tell them apart from thread events.
+pub(crate) const HEADLESS_PROGRESS_METHOD: &str = "headless/progress";
+
+/// A one-shot prompt run on a hidden, read-only thread.
+#[derive(Debug, Clone)]
+pub(crate) struct HeadlessRequest {
+    pub(crate) prompt: String,
+    /// When set, the agent is asked for JSON matching this schema and the reply is parsed.
+    pub(crate) output_schema: Option<Value>,
+    pub(crate) timeout: Duration,
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+}
+
+impl HeadlessRequest {
+    pub(crate) fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            output_schema: None,
+            timeout: DEFAULT_HEADLESS_TIMEOUT,
+            model: None,
+            effort: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HeadlessResult {
+    pub(crate) thread_id: String,
+    pub(crate) text: String,
+    /// Parsed JSON reply, present when an output schema was requested.
+    pub(crate) output: Option<Value>,
+    pub(crate) duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "stage", rename_all = "camelCase")]
+pub(crate) enum HeadlessProgress {
+    ThreadStarted {
+        #[serde(rename = "threadId")]
+        thread_id: String,
+    },
+    TurnStarted {
+        #[serde(rename = "turnId")]
+        turn_id: String,
+    },
+    Delta {
+        delta: String,
+    },
+    Completed,
+}
+
+/// Wraps a progress update as a `headless/progress` event tagged with the caller's run id.
+pub(crate) fn progress_event(
+    workspace_id: &str,
+    run_id: &str,
+    progress: &HeadlessProgress,
+) -> AppServerEvent {
+    let mut params = serde_json::to_value(progress).unwrap_or_else(|_| json!({}));
+    if let Some(object) = params.as_object_mut() {
+        object.insert("runId".to_string(), json!(run_id));
+    }
+    AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({ "method": HEADLESS_PROGRESS_METHOD, "params": params }),
+    }
+}
+
+/// Interrupts and archives the hidden thread however the run ends, including when
+/// the caller drops the future mid-flight.
+struct ThreadCleanup {
+    session: Arc<WorkspaceSession>,
+    thread_id: String,
+    turn_id: Option<String>,
+    armed: bool,
+}
+
+impl ThreadCleanup {
+    async fn run(session: &WorkspaceSession, thread_id: String, turn_id: Option<String>) {
+        if let Some(turn_id) = turn_id {
+            let _ = session
+                .request(&TurnInterruptParams {
+                    thread_id: thread_id.clone(),
+                    turn_id,
+                })
+                .await;
+        }
+        let _ = session.request(&ThreadArchiveParams { thread_id }).await;
+    }
+
+    /// Archives the thread; the turn is only interrupted when it did not finish.
+    async fn finish(mut self, turn_finished: bool) {
+        self.armed = false;
+        let turn_id = if turn_finished {
+            None
+        } else {
+            self.turn_id.take()
+        };
+        Self::run(&self.session, self.thread_id.clone(), turn_id).await;
+    }
+}
+
+impl Drop for ThreadCleanup {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let session = Arc::clone(&self.session);
+        let thread_id = self.thread_id.clone();
+        let turn_id = self.turn_id.take();
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                ThreadCleanup::run(&session, thread_id, turn_id).await;
+            });
+        }
+    }
+}
+
+fn agent_message_text(value: &Value) -> Option<String> {
+    if value.get("method")?.as_str()? != "item/completed" {
+        return None;
+    }
+    let item = value.get("params")?.get("item")?;
+    if item.get("type")?.as_str()? != "agentMessage" {
+        return None;
+    }
+    item.get("text")
+        .and_then(Value::as_str)
+        .map(|text| text.to_string())
+}
+
+/// Pulls the first JSON object or array out of an agent reply, tolerating code fences
+/// and surrounding prose.
+pub(crate) fn extract_json_output(raw: &str) -> Option<Value> {
+    let trimmed = raw.trim();
+    if let Ok(value) = serde_json::from_str::<Value>(trimmed) {
+        return Some(value);
+    }
+    for (open, close) in [('{', '}'), ('[', ']')] {
+        let (Some(start), Some(end)) = (trimmed.find(open), trimmed.rfind(close)) else {
+            continue;
+        };
+        if end > start {
+            if let Ok(value) = serde_json::from_str::<Value>(&trimmed[start..=end]) {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+/// Checks the parts of a JSON schema that matter for one-shot results: the top-level
+/// type and the required object keys.
+pub(crate) fn check_output_schema(output: &Value, schema: &Value) -> Result<(), String> {
+    let type_matches = match schema.get("type").and_then(Value::as_str) {
+        Some("object") => output.is_object(),
+        Some("array") => output.is_array(),
+        Some("string") => output.is_string(),
+        Some("number") => output.is_number(),
+        Some("integer") => output.is_i64() || output.is_u64(),
+        Some("boolean") => output.is_boolean(),
+        _ => true,
+    };
+    if !type_matches {
+        return Err("Result does not match the output schema type".to_string());
+    }
+    let required = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str);
+    for key in required {
+        if output.get(key).is_none() {
+            return Err(format!("Result is missing required field `{key}`"));
+        }
+    }
+    Ok(())
+}
+
+/// Runs a prompt on a hidden thread and returns the agent's reply.
+///
+/// The thread never asks for approval, runs read-only, is invisible to the UI and
+/// is archived afterwards whether the run succeeds, fails, times out or is dropped.
+pub(crate) async fn run_headless_prompt<F>(
+    session: &Arc<WorkspaceSession>,
+    request: HeadlessRequest,
+    mut on_progress: F,
+) -> Result<HeadlessResult, String>
+where
+    F: FnMut(HeadlessProgress),
+{
+    let started = Instant::now();
+    if request.prompt.trim().is_empty() {
+        return Err("Prompt is required.".to_string());
+    }
+
+    let thread_response = session
+        .request(&ThreadStartParams {
+            cwd: session.entry.path.clone(),
+            approval_policy: ApprovalPolicy::Never,
+        })
+        .await?;
+    let thread_id = parse_response::<ThreadStartParams>(&thread_response)?
+        .thread
+        .id;
+    let mut cleanup = ThreadCleanup {
+        session: Arc::clone(session),
+        thread_id: thread_id.clone(),
+        turn_id: None,
+        armed: true,
+    };
+    let mut events = session.claim_thread_events(&thread_id);
+    on_progress(HeadlessProgress::ThreadStarted {
+        thread_id: thread_id.clone(),
+    });
+
+    let turn_params = TurnStartParams {
+        thread_id: thread_id.clone(),
+        input: vec![UserInput::Text {
+            text: request.prompt.clone(),
+        }],
+        cwd: session.entry.path.clone(),
+        approval_policy: ApprovalPolicy::Never,
+        sandbox_policy: SandboxPolicy::ReadOnly,
+        model: request.model.clone(),
+        effort: request.effort.clone(),
+        collaboration_mode: None,
+        output_schema: request.output_schema.clone(),
+    };
+    let turn = match session.request(&turn_params).await {
+        Ok(response) => parse_response::<TurnStartParams>(&response),
+        Err(error) => Err(error),
+    };
+    let turn = match turn {
+        Ok(turn) => turn,
+        Err(error) => {
+            cleanup.finish(false).await;
+            return Err(error);
+        }
+    };
+    cleanup.turn_id = Some(turn.turn.id.clone());
+    on_progress(HeadlessProgress::TurnStarted {
+        turn_id: turn.turn.id,
+    });
+
+    let mut text = String::new();
+    let collected = timeout(request.timeout, async {
+        while let Some(event) = events.recv().await {
+            if let Some(completed) = agent_message_text(&event.message) {
+                if text.trim().is_empty() {
+                    text = completed;
+                }
+                continue;
+            }
+            match parse_notification(&event.message) {
+                Some(ServerNotification::AgentMessageDelta(delta)) => {
+                    text.push_str(&delta.delta);
+                    on_progress(HeadlessProgress::Delta { delta: delta.delta });
+                }
+                Some(ServerNotification::TurnCompleted(_)) => return Ok(()),
+                Some(ServerNotification::TurnError(message)) => {
+                    return Err(message.unwrap_or_else(|| "Headless turn failed".to_string()));
+                }
+                _ => {}
+            }
+        }
+        Err("Headless turn event stream closed".to_string())
+    })
+    .await;
+    drop(events);
+
+    let outcome = match collected {
+        Ok(result) => result,
+        Err(_) => Err(format!(
+            "Timed out after {}s waiting for the headless turn",
+            request.timeout.as_secs()
+        )),
+    };
+    cleanup.finish(outcome.is_ok()).await;
+    outcome?;
+    on_progress(HeadlessProgress::Completed);
+
+    let text = text.trim().to_string();
+    let output = match request.output_schema.as_ref() {
+        Some(schema) => {
+            let output = extract_json_output(&text)
+                .ok_or_else(|| "The agent did not return valid JSON".to_string())?;
+            check_output_schema(&output, schema)?;
+            Some(output)
+        }
+        None => None,
+    };
+    Ok(HeadlessResult {
+        thread_id,
+        text,
+        output,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        agent_message_text, check_output_schema, extract_json_output, progress_event,
+        HeadlessProgress,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn extract_json_output_handles_fences_and_prose() {
+        assert_eq!(
+            extract_json_output("```json\n{\"title\":\"A\"}\n```"),
+            Some(json!({ "title": "A" }))
+        );
+        assert_eq!(
+            extract_json_output("Here you go: [1, 2]"),
+            Some(json!([1, 2]))
+        );
+        assert_eq!(extract_json_output("no json here"), None);
+    }
+
+    #[test]
+    fn check_output_schema_requires_type_and_keys() {
+        let schema = json!({
+            "type": "object",
+            "required": ["title", "worktreeName"],
+        });
+        assert!(
+            check_output_schema(&json!({ "title": "A", "worktreeName": "feat/a" }), &schema)
+                .is_ok()
+        );
+        assert_eq!(
+            check_output_schema(&json!({ "title": "A" }), &schema).unwrap_err(),
+            "Result is missing required field `worktreeName`"
+        );
+        assert!(check_output_schema(&json!([1]), &schema).is_err());
+        assert!(check_output_schema(&json!("text"), &json!({})).is_ok());
+    }
+
+    #[test]
+    fn agent_message_text_reads_completed_agent_items() {
+        let event = json!({
+            "method": "item/completed",
+            "params": { "item": { "type": "agentMessage", "text": "done" } }
+        });
+        assert_eq!(agent_message_text(&event), Some("done".to_string()));
+        let event = json!({
+            "method": "item/completed",
+            "params": { "item": { "type": "reasoning", "text": "thinking" } }
+        });
+        assert_eq!(agent_message_text(&event), None);
+    }
+
+    #[test]
+    fn progress_event_tags_stage_and_run_id() {
+        let event = progress_event(
+            "ws-1",
+            "run-1",
+            &HeadlessProgress::ThreadStarted {
+                thread_id: "thread-1".to_string(),
+            },
+        );
+        assert_eq!(event.workspace_id, "ws-1");
+        assert_eq!(
+            event.message,
+            json!({
+                "method": "headless/progress",
+                "params": { "stage": "threadStarted", "threadId": "thread-1", "runId": "run-1" }
+            })
+        );
+    }
+}
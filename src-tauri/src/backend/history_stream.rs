@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// How often a followed rollout is checked for new lines once the reader hits the end.
+pub(crate) const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Deserialize)]
+struct RolloutLine {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    payload: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct StreamMessage {
+    id: String,
+    /// The `N` in `id`.
+    #[serde(skip)]
+    pub(crate) index: u64,
+    kind: String,
+    pub(crate) role: String,
+    pub(crate) text: String,
+}
+
+fn extract_response_item_text(payload: &Value) -> Option<String> {
+    if let Some(content) = payload.get("content") {
+        if let Some(arr) = content.as_array() {
+            let parts: Vec<String> = arr
+                .iter()
+                .filter_map(|entry| {
+                    entry
+                        .get("text")
+                        .and_then(|text| text.as_str())
+                        .map(|text| text.to_string())
+                })
+                .filter(|text| !text.trim().is_empty())
+                .collect();
+            if !parts.is_empty() {
+                return Some(if parts.len() == 1 {
+                    parts[0].clone()
+                } else {
+                    parts.join("\n")
+                });
+            }
+        } else if let Some(text) = content.as_str() {
+            if !text.trim().is_empty() {
+                return Some(text.to_string());
+            }
+        }
+    }
+    payload
+        .get("text")
+        .and_then(|text| text.as_str())
+        .map(|text| text.to_string())
+}
+
+fn build_stream_message(
+    thread_id: &str,
+    message_index: &mut u64,
+    role: &str,
+    text: &str,
+) -> Option<StreamMessage> {
+    if role != "user" && role != "assistant" {
+        return None;
+    }
+    if text.trim().is_empty() {
+        return None;
+    }
+    *message_index = message_index.saturating_add(1);
+    Some(StreamMessage {
+        id: format!("history-{thread_id}-{message_index}"),
+        index: *message_index,
+        kind: "message".to_string(),
+        role: role.to_string(),
+        text: text.to_string(),
+    })
+}
+
+fn extract_event_stream_message(
+    payload: &Value,
+    thread_id: &str,
+    message_index: &mut u64,
+) -> Option<StreamMessage> {
+    let event_type = payload.get("type")?.as_str()?;
+    let (role, text) = match event_type {
+        "user_message" => ("user", payload.get("message")?.as_str()?),
+        "agent_message" => ("assistant", payload.get("message")?.as_str()?),
+        _ => return None,
+    };
+    build_stream_message(thread_id, message_index, role, text)
+}
+
+fn extract_response_item_stream_message(
+    payload: &Value,
+    thread_id: &str,
+    message_index: &mut u64,
+) -> Option<StreamMessage> {
+    if payload.get("type")?.as_str()? != "message" {
+        return None;
+    }
+    let role = payload.get("role")?.as_str()?;
+    let text = extract_response_item_text(payload)?;
+    build_stream_message(thread_id, message_index, role, &text)
+}
+
+/// Turns rollout lines into the history items `stream_thread_history` emits.
+///
+/// `response_item` messages only count while no `event_msg` message has been seen and are
+/// held back until the initial read finishes, since event messages replace them.
+pub(crate) struct HistoryStreamParser {
+    thread_id: String,
+    message_index: u64,
+    seen_event_message: bool,
+    pending_response_items: Vec<StreamMessage>,
+    /// Set once the initial read is done; later items are emitted as they arrive.
+    live: bool,
+}
+
+impl HistoryStreamParser {
+    pub(crate) fn new(thread_id: &str) -> Self {
+        Self {
+            thread_id: thread_id.to_string(),
+            message_index: 0,
+            seen_event_message: false,
+            pending_response_items: Vec::new(),
+            live: false,
+        }
+    }
+
+    /// Items ready to emit after reading `line`.
+    pub(crate) fn push_line(&mut self, line: &str) -> Vec<StreamMessage> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return Vec::new();
+        }
+        let Ok(line) = serde_json::from_str::<RolloutLine>(trimmed) else {
+            return Vec::new();
+        };
+        let (Some(kind), Some(payload)) = (line.kind.as_deref(), line.payload.as_ref()) else {
+            return Vec::new();
+        };
+        match kind {
+            "event_msg" => {
+                let Some(item) =
+                    extract_event_stream_message(payload, &self.thread_id, &mut self.message_index)
+                else {
+                    return Vec::new();
+                };
+                if !self.seen_event_message {
+                    self.seen_event_message = true;
+                    self.pending_response_items.clear();
+                }
+                vec![item]
+            }
+            "response_item" if !self.seen_event_message => {
+                let Some(item) = extract_response_item_stream_message(
+                    payload,
+                    &self.thread_id,
+                    &mut self.message_index,
+                ) else {
+                    return Vec::new();
+                };
+                if self.live {
+                    vec![item]
+                } else {
+                    self.pending_response_items.push(item);
+                    Vec::new()
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Ends the initial read, releasing held `response_item` messages when the rollout
+    /// has no event messages.
+    pub(crate) fn finish_initial(&mut self) -> Vec<StreamMessage> {
+        self.live = true;
+        if self.seen_event_message {
+            self.pending_response_items.clear();
+            return Vec::new();
+        }
+        std::mem::take(&mut self.pending_response_items)
+    }
+}
+
+/// Every history item of a complete rollout, numbered as `stream_thread_history` numbers them.
+pub(crate) fn parse_history(thread_id: &str, contents: &str) -> Vec<StreamMessage> {
+    let mut parser = HistoryStreamParser::new(thread_id);
+    let mut messages: Vec<StreamMessage> = contents
+        .lines()
+        .flat_map(|line| parser.push_line(line))
+        .collect();
+    messages.extend(parser.finish_initial());
+    messages
+}
+
+/// Reads complete lines from a rollout that may still be growing. A trailing line
+/// without a newline is kept until the writer finishes it.
+pub(crate) struct RolloutTail {
+    reader: BufReader<File>,
+    partial: String,
+}
+
+impl RolloutTail {
+    pub(crate) async fn open(path: &Path) -> std::io::Result<Self> {
+        let file = File::open(path).await?;
+        Ok(Self {
+            reader: BufReader::new(file),
+            partial: String::new(),
+        })
+    }
+
+    /// The next complete line, or `None` at the current end of the file.
+    pub(crate) async fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        let read = self.reader.read_line(&mut self.partial).await?;
+        if read == 0 || !self.partial.ends_with('\n') {
+            return Ok(None);
+        }
+        let line = std::mem::take(&mut self.partial);
+        Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+    }
+
+    /// Hands out an unfinished last line, for readers that stop at the end of the file.
+    pub(crate) fn take_partial(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.partial);
+        (!line.trim().is_empty()).then_some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryStreamParser, RolloutTail};
+    use serde_json::json;
+    use std::io::Write;
+    use uuid::Uuid;
+
+    fn event(kind: &str, message: &str) -> String {
+        json!({ "type": "event_msg", "payload": { "type": kind, "message": message } }).to_string()
+    }
+
+    fn response(role: &str, text: &str) -> String {
+        json!({ "type": "response_item", "payload": {
+            "type": "message", "role": role, "content": [{ "text": text }]
+        } })
+        .to_string()
+    }
+
+    #[test]
+    fn parser_prefers_event_messages_and_keeps_numbering() {
+        let mut parser = HistoryStreamParser::new("t");
+        assert!(parser.push_line(&response("user", "<env>")).is_empty());
+        let items = parser.push_line(&event("user_message", "hi"));
+        assert_eq!(items[0].id, "history-t-2");
+        assert!(parser.push_line(&response("assistant", "dup")).is_empty());
+        assert!(parser.finish_initial().is_empty());
+        let live = parser.push_line(&event("agent_message", "hello"));
+        assert_eq!(live[0].id, "history-t-3");
+        assert_eq!(live[0].role, "assistant");
+    }
+
+    #[test]
+    fn parser_releases_response_items_without_event_messages() {
+        let mut parser = HistoryStreamParser::new("t");
+        assert!(parser.push_line(&response("user", "hello")).is_empty());
+        assert!(parser.push_line("not json").is_empty());
+        assert_eq!(parser.finish_initial().len(), 1);
+        assert_eq!(parser.push_line(&response("assistant", "hi")).len(), 1);
+    }
+
+    #[test]
+    fn tail_waits_for_complete_lines() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rollout.jsonl");
+        std::fs::write(&path, "first\nsec").unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut tail = RolloutTail::open(&path).await.unwrap();
+            assert_eq!(tail.next_line().await.unwrap().as_deref(), Some("first"));
+            assert_eq!(tail.next_line().await.unwrap(), None);
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .unwrap();
+            file.write_all(b"ond\r\nthird").unwrap();
+            assert_eq!(tail.next_line().await.unwrap().as_deref(), Some("second"));
+            assert_eq!(tail.next_line().await.unwrap(), None);
+            assert_eq!(tail.take_partial().as_deref(), Some("third"));
+        });
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
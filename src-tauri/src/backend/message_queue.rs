@@ -0,0 +1,797 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use tokio::sync::{mpsc, Mutex};
+use uuid::Uuid;
+
+use crate::backend::app_server::{WorkspaceSession, SESSION_INSTALLED_METHOD};
+use crate::backend::checkpoints::record_checkpoint;
+use crate::backend::event_bus::{BusEvent, EventBus, EventFilter, SubscribeOptions};
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::protocol::{build_user_input, TurnStartParams};
+use crate::backend::sandbox_profiles::turn_policies;
+use crate::storage::{now_ms, read_json_store, write_json_atomic};
+
+/// Method of the event emitted whenever a thread's queue changes.
+pub(crate) const QUEUE_CHANGED_METHOD: &str = "openvibe/queue/changed";
+const QUEUE_EVENT_CAPACITY: usize = 1024;
+
+/// A user message as `send_user_message` takes it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OutgoingMessage {
+    pub(crate) text: String,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) effort: Option<String>,
+    #[serde(default)]
+    pub(crate) access_mode: Option<String>,
+    /// Named sandbox profile; takes precedence over `access_mode` when set.
+    #[serde(default)]
+    pub(crate) sandbox_profile: Option<String>,
+    #[serde(default)]
+    pub(crate) images: Option<Vec<String>>,
+    #[serde(default)]
+    pub(crate) collaboration_mode: Option<Value>,
+}
+
+/// Starts a turn for a user message on an existing thread.
+pub(crate) async fn start_user_turn(
+    session: &WorkspaceSession,
+    thread_id: String,
+    message: OutgoingMessage,
+) -> Result<Value, String> {
+    let (approval_policy, sandbox_policy) = turn_policies(
+        &session.sandbox_profiles,
+        &session.entry.path,
+        message.access_mode.as_deref(),
+        message.sandbox_profile.as_deref(),
+    )?;
+    let input = build_user_input(&message.text, message.images);
+    if input.is_empty() {
+        return Err("empty user message".to_string());
+    }
+    // A checkpoint that cannot be written should not block the turn.
+    if session.checkpoints_enabled.load(Ordering::SeqCst) {
+        if let Err(err) = record_checkpoint(&session.entry, &thread_id, &message.text).await {
+            eprintln!("failed to record checkpoint for {thread_id}: {err}");
+        }
+    }
+    let params = TurnStartParams {
+        thread_id,
+        input,
+        cwd: session.entry.path.clone(),
+        approval_policy,
+        sandbox_policy,
+        model: message.model,
+        effort: message.effort,
+        collaboration_mode: message.collaboration_mode.filter(|mode| !mode.is_null()),
+        output_schema: None,
+    };
+    session.request(&params).await
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QueuedMessage {
+    pub(crate) id: String,
+    pub(crate) created_at: u64,
+    #[serde(flatten)]
+    pub(crate) message: OutgoingMessage,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadQueue {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    #[serde(default)]
+    pub(crate) paused: bool,
+    #[serde(default)]
+    pub(crate) items: Vec<QueuedMessage>,
+    /// Why the queue paused itself after a failed dispatch.
+    #[serde(default)]
+    pub(crate) last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum QueueAction {
+    Enqueue {
+        message: OutgoingMessage,
+    },
+    Edit {
+        id: String,
+        text: String,
+    },
+    Remove {
+        id: String,
+    },
+    /// New order by message id; ids left out keep their relative order at the end.
+    Reorder {
+        ids: Vec<String>,
+    },
+    Pause,
+    Resume,
+    Clear,
+}
+
+#[derive(Debug, PartialEq)]
+enum QueueSignal {
+    TurnStarted(String, String),
+    /// `turn/completed`, which also follows a turn that failed for good. Errors are not
+    /// signals of their own, so one failed turn dispatches a single message.
+    TurnEnded(String, String),
+    /// The workspace's session is installed or its app-server respawned; its queues may
+    /// dispatch again.
+    SessionStarted(String),
+    /// The workspace's app-server went away, taking any running turns with it.
+    SessionEnded(String),
+    /// The queue changed in a way that may allow a dispatch.
+    Kick(String, String),
+}
+
+/// Methods the dispatcher listens to, as event bus prefixes.
+const QUEUE_EVENT_PREFIXES: [&str; 3] = ["turn/", "codex/", SESSION_INSTALLED_METHOD];
+
+fn queue_signal(event: BusEvent) -> Option<QueueSignal> {
+    let method = event.message.get("method").and_then(Value::as_str)?;
+    match method {
+        SESSION_INSTALLED_METHOD | "codex/connected" => {
+            return Some(QueueSignal::SessionStarted(event.workspace_id))
+        }
+        "codex/disconnected" | "codex/respawnFailed" | "codex/idleShutdown" => {
+            return Some(QueueSignal::SessionEnded(event.workspace_id))
+        }
+        _ => {}
+    }
+    let thread_id = event.thread_id?;
+    match method {
+        "turn/started" => Some(QueueSignal::TurnStarted(event.workspace_id, thread_id)),
+        "turn/completed" => Some(QueueSignal::TurnEnded(event.workspace_id, thread_id)),
+        _ => None,
+    }
+}
+
+type QueueKey = (String, String);
+
+#[derive(Default)]
+struct QueueState {
+    queues: HashMap<QueueKey, ThreadQueue>,
+    /// Threads with a turn in flight, as seen on the event bus.
+    busy: HashSet<QueueKey>,
+}
+
+/// Per-thread outgoing message queues, persisted to disk and drained one message per
+/// completed turn by [`run_queue_dispatcher`].
+pub(crate) struct MessageQueue {
+    path: Option<PathBuf>,
+    /// Why the file on disk could not be loaded; saving would overwrite it, so it is refused.
+    load_error: Option<String>,
+    state: StdMutex<QueueState>,
+    signals: mpsc::UnboundedSender<QueueSignal>,
+    receiver: StdMutex<Option<mpsc::UnboundedReceiver<QueueSignal>>>,
+}
+
+impl MessageQueue {
+    /// Loads persisted queues from `path`; without a path the queue lives in memory only.
+    pub(crate) fn load(path: Option<PathBuf>) -> Arc<Self> {
+        let loaded: Result<Vec<ThreadQueue>, String> = match path.as_ref() {
+            Some(path) => read_json_store(path, "message queue"),
+            None => Ok(Vec::new()),
+        };
+        let (queues, load_error) = match loaded {
+            Ok(queues) => (queues, None),
+            Err(err) => {
+                eprintln!("{err}");
+                (Vec::new(), Some(err))
+            }
+        };
+        let (signals, receiver) = mpsc::unbounded_channel();
+        Arc::new(Self {
+            path,
+            load_error,
+            state: StdMutex::new(QueueState {
+                queues: queues
+                    .into_iter()
+                    .filter(|queue| !queue.items.is_empty() || queue.paused)
+                    .map(|queue| ((queue.workspace_id.clone(), queue.thread_id.clone()), queue))
+                    .collect(),
+                busy: HashSet::new(),
+            }),
+            signals,
+            receiver: StdMutex::new(Some(receiver)),
+        })
+    }
+
+    fn persist(&self, state: &QueueState) -> Result<(), String> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if let Some(err) = &self.load_error {
+            return Err(err.clone());
+        }
+        let mut queues: Vec<&ThreadQueue> = state
+            .queues
+            .values()
+            .filter(|queue| !queue.items.is_empty() || queue.paused)
+            .collect();
+        queues
+            .sort_by(|a, b| (&a.workspace_id, &a.thread_id).cmp(&(&b.workspace_id, &b.thread_id)));
+        write_json_atomic(path, &queues)
+            .map_err(|err| format!("failed to write message queue: {err}"))
+    }
+
+    /// Persists from the dispatcher, where there is no caller to hand the error to.
+    fn persist_or_log(&self, state: &QueueState) {
+        if let Err(err) = self.persist(state) {
+            eprintln!("{err}");
+        }
+    }
+
+    pub(crate) fn snapshot(&self, workspace_id: &str, thread_id: &str) -> ThreadQueue {
+        let key = (workspace_id.to_string(), thread_id.to_string());
+        self.state
+            .lock()
+            .ok()
+            .and_then(|state| state.queues.get(&key).cloned())
+            .unwrap_or_else(|| ThreadQueue {
+                workspace_id: workspace_id.to_string(),
+                thread_id: thread_id.to_string(),
+                ..ThreadQueue::default()
+            })
+    }
+
+    /// Applies a user edit to a thread's queue and returns the updated queue. The edit is
+    /// undone when it cannot be saved.
+    pub(crate) fn apply(
+        &self,
+        workspace_id: &str,
+        thread_id: &str,
+        action: QueueAction,
+    ) -> Result<ThreadQueue, String> {
+        let key = (workspace_id.to_string(), thread_id.to_string());
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| "message queue unavailable".to_string())?;
+        let previous = state.queues.get(&key).cloned();
+        let queue = state
+            .queues
+            .entry(key.clone())
+            .or_insert_with(|| ThreadQueue {
+                workspace_id: workspace_id.to_string(),
+                thread_id: thread_id.to_string(),
+                ..ThreadQueue::default()
+            });
+        let find = |items: &[QueuedMessage], id: &str| {
+            items
+                .iter()
+                .position(|item| item.id == id)
+                .ok_or_else(|| format!("queued message not found: {id}"))
+        };
+        let kick = match action {
+            QueueAction::Enqueue { message } => {
+                if message.text.trim().is_empty()
+                    && message.images.as_ref().is_none_or(Vec::is_empty)
+                {
+                    return Err("empty user message".to_string());
+                }
+                queue.items.push(QueuedMessage {
+                    id: Uuid::new_v4().to_string(),
+                    created_at: now_ms(),
+                    message,
+                });
+                true
+            }
+            QueueAction::Edit { id, text } => {
+                let index = find(&queue.items, &id)?;
+                queue.items[index].message.text = text;
+                false
+            }
+            QueueAction::Remove { id } => {
+                let index = find(&queue.items, &id)?;
+                queue.items.remove(index);
+                false
+            }
+            QueueAction::Reorder { ids } => {
+                let mut remaining = std::mem::take(&mut queue.items);
+                for id in ids {
+                    if let Some(index) = remaining.iter().position(|item| item.id == id) {
+                        queue.items.push(remaining.remove(index));
+                    }
+                }
+                queue.items.extend(remaining);
+                false
+            }
+            QueueAction::Pause => {
+                queue.paused = true;
+                false
+            }
+            QueueAction::Resume => {
+                queue.paused = false;
+                queue.last_error = None;
+                true
+            }
+            QueueAction::Clear => {
+                queue.items.clear();
+                queue.last_error = None;
+                false
+            }
+        };
+        let snapshot = queue.clone();
+        if let Err(err) = self.persist(&state) {
+            match previous {
+                Some(previous) => state.queues.insert(key, previous),
+                None => state.queues.remove(&key),
+            };
+            return Err(err);
+        }
+        drop(state);
+        if kick {
+            let _ = self.signals.send(QueueSignal::Kick(key.0, key.1));
+        }
+        Ok(snapshot)
+    }
+
+    /// Forgets running turns of a workspace whose app-server went away.
+    fn clear_busy(&self, workspace_id: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state
+                .busy
+                .retain(|(busy_workspace, _)| busy_workspace != workspace_id);
+        }
+    }
+
+    /// Queues of `workspace_id` that have messages waiting and are not paused.
+    fn ready_keys(&self, workspace_id: &str) -> Vec<QueueKey> {
+        self.state
+            .lock()
+            .map(|state| {
+                state
+                    .queues
+                    .iter()
+                    .filter(|(key, queue)| {
+                        key.0 == workspace_id && !queue.paused && !queue.items.is_empty()
+                    })
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_busy(&self, key: &QueueKey, busy: bool) {
+        if let Ok(mut state) = self.state.lock() {
+            if busy {
+                state.busy.insert(key.clone());
+            } else {
+                state.busy.remove(key);
+            }
+        }
+    }
+
+    /// Pops the next message when the thread is idle and its queue is not paused. The
+    /// thread counts as busy from here on so a second signal cannot double-send.
+    fn take_next(&self, key: &QueueKey) -> Option<(QueuedMessage, ThreadQueue)> {
+        let mut state = self.state.lock().ok()?;
+        if state.busy.contains(key) {
+            return None;
+        }
+        let queue = state.queues.get_mut(key)?;
+        if queue.paused || queue.items.is_empty() {
+            return None;
+        }
+        let next = queue.items.remove(0);
+        let snapshot = queue.clone();
+        state.busy.insert(key.clone());
+        self.persist_or_log(&state);
+        Some((next, snapshot))
+    }
+
+    /// Puts a message that failed to send back at the front and pauses the queue.
+    fn restore_failed(&self, key: &QueueKey, message: QueuedMessage, error: String) -> ThreadQueue {
+        let Ok(mut state) = self.state.lock() else {
+            return ThreadQueue::default();
+        };
+        state.busy.remove(key);
+        let queue = state
+            .queues
+            .entry(key.clone())
+            .or_insert_with(|| ThreadQueue {
+                workspace_id: key.0.clone(),
+                thread_id: key.1.clone(),
+                ..ThreadQueue::default()
+            });
+        queue.items.insert(0, message);
+        queue.paused = true;
+        queue.last_error = Some(error);
+        let snapshot = queue.clone();
+        self.persist_or_log(&state);
+        snapshot
+    }
+}
+
+pub(crate) fn queue_changed_event(queue: &ThreadQueue) -> AppServerEvent {
+    AppServerEvent {
+        workspace_id: queue.workspace_id.clone(),
+        message: json!({
+            "method": QUEUE_CHANGED_METHOD,
+            "params": queue,
+        }),
+    }
+}
+
+async fn dispatch_next<E: EventSink>(
+    queue: &MessageQueue,
+    key: &QueueKey,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    event_sink: &E,
+) {
+    // Messages wait for the workspace to connect; installing the session kicks them again.
+    let Some(session) = sessions
+        .lock()
+        .await
+        .get(&key.0)
+        .cloned()
+        .filter(|session| session.is_alive())
+    else {
+        return;
+    };
+    let Some((next, snapshot)) = queue.take_next(key) else {
+        return;
+    };
+    event_sink.emit_app_server_event(queue_changed_event(&snapshot));
+    let result = match session.ensure_thread_open(&key.1).await {
+        Ok(()) => start_user_turn(&session, key.1.clone(), next.message.clone()).await,
+        Err(error) => Err(error),
+    };
+    if let Err(error) = result {
+        let snapshot = queue.restore_failed(key, next, error);
+        event_sink.emit_app_server_event(queue_changed_event(&snapshot));
+    }
+}
+
+/// Watches turn and session lifecycle events and sends the next queued message after each
+/// finished turn. Queues loaded from disk start once their workspace is connected. Each
+/// dispatch runs on its own task so a slow workspace does not hold up other threads.
+/// Runs until the event bus goes away; only one dispatcher per queue.
+pub(crate) async fn run_queue_dispatcher<E: EventSink + Clone + Send + Sync + 'static>(
+    queue: Arc<MessageQueue>,
+    event_bus: Arc<EventBus>,
+    sessions: Arc<Mutex<HashMap<String, Arc<WorkspaceSession>>>>,
+    event_sink: E,
+) {
+    let Some(mut signals) = queue.receiver.lock().ok().and_then(|mut slot| slot.take()) else {
+        return;
+    };
+    for prefix in QUEUE_EVENT_PREFIXES {
+        let mut events = event_bus.subscribe(
+            SubscribeOptions::new(EventFilter::default().with_method_prefix(prefix))
+                .capacity(QUEUE_EVENT_CAPACITY),
+        );
+        let forward = queue.signals.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let Some(signal) = queue_signal(event) else {
+                    continue;
+                };
+                if forward.send(signal).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    let connected: Vec<String> = sessions.lock().await.keys().cloned().collect();
+    for workspace_id in connected {
+        let _ = queue
+            .signals
+            .send(QueueSignal::SessionStarted(workspace_id));
+    }
+    // `take_next` marks the thread busy, so overlapping tasks for one thread send at most
+    // one message.
+    let spawn_dispatch = |key: QueueKey| {
+        let queue = Arc::clone(&queue);
+        let sessions = Arc::clone(&sessions);
+        let event_sink = event_sink.clone();
+        tokio::spawn(async move {
+            dispatch_next(&queue, &key, &sessions, &event_sink).await;
+        });
+    };
+    while let Some(signal) = signals.recv().await {
+        match signal {
+            QueueSignal::TurnStarted(workspace_id, thread_id) => {
+                queue.set_busy(&(workspace_id, thread_id), true);
+            }
+            QueueSignal::TurnEnded(workspace_id, thread_id) => {
+                let key = (workspace_id, thread_id);
+                queue.set_busy(&key, false);
+                spawn_dispatch(key);
+            }
+            QueueSignal::SessionStarted(workspace_id) => {
+                for key in queue.ready_keys(&workspace_id) {
+                    spawn_dispatch(key);
+                }
+            }
+            QueueSignal::SessionEnded(workspace_id) => {
+                queue.clear_busy(&workspace_id);
+            }
+            QueueSignal::Kick(workspace_id, thread_id) => {
+                spawn_dispatch((workspace_id, thread_id));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{queue_signal, MessageQueue, OutgoingMessage, QueueAction, QueueSignal};
+    use crate::backend::app_server::SESSION_INSTALLED_METHOD;
+    use crate::backend::event_bus::BusEvent;
+    use crate::storage::block_store_dir;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    fn message(text: &str) -> OutgoingMessage {
+        OutgoingMessage {
+            text: text.to_string(),
+            ..OutgoingMessage::default()
+        }
+    }
+
+    fn key() -> (String, String) {
+        ("ws-1".to_string(), "thread-1".to_string())
+    }
+
+    #[test]
+    fn edits_reorders_and_removes_queued_messages() {
+        let queue = MessageQueue::load(None);
+        for text in ["a", "b", "c"] {
+            queue
+                .apply(
+                    "ws-1",
+                    "thread-1",
+                    QueueAction::Enqueue {
+                        message: message(text),
+                    },
+                )
+                .unwrap();
+        }
+        let ids: Vec<String> = queue
+            .snapshot("ws-1", "thread-1")
+            .items
+            .iter()
+            .map(|item| item.id.clone())
+            .collect();
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Reorder {
+                    ids: vec![ids[2].clone()],
+                },
+            )
+            .unwrap();
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Edit {
+                    id: ids[0].clone(),
+                    text: "a2".to_string(),
+                },
+            )
+            .unwrap();
+        let snapshot = queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Remove { id: ids[1].clone() },
+            )
+            .unwrap();
+        let texts: Vec<&str> = snapshot
+            .items
+            .iter()
+            .map(|item| item.message.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["c", "a2"]);
+        assert!(queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message(" ")
+                }
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn take_next_respects_pause_and_busy_threads() {
+        let queue = MessageQueue::load(None);
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message("a"),
+                },
+            )
+            .unwrap();
+        queue.apply("ws-1", "thread-1", QueueAction::Pause).unwrap();
+        assert!(queue.take_next(&key()).is_none());
+        queue
+            .apply("ws-1", "thread-1", QueueAction::Resume)
+            .unwrap();
+        queue.set_busy(&key(), true);
+        assert!(queue.take_next(&key()).is_none());
+        queue.set_busy(&key(), false);
+        let (next, snapshot) = queue.take_next(&key()).unwrap();
+        assert_eq!(next.message.text, "a");
+        assert!(snapshot.items.is_empty());
+        // Dispatching marks the thread busy until the turn completes.
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message("b"),
+                },
+            )
+            .unwrap();
+        assert!(queue.take_next(&key()).is_none());
+    }
+
+    #[test]
+    fn failed_dispatch_is_restored_and_pauses_the_queue() {
+        let queue = MessageQueue::load(None);
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message("a"),
+                },
+            )
+            .unwrap();
+        let (next, _) = queue.take_next(&key()).unwrap();
+        let snapshot = queue.restore_failed(&key(), next, "boom".to_string());
+        assert!(snapshot.paused);
+        assert_eq!(snapshot.last_error.as_deref(), Some("boom"));
+        assert_eq!(snapshot.items.len(), 1);
+    }
+
+    #[test]
+    fn queues_persist_across_loads() {
+        let path = std::env::temp_dir().join(format!("open-vibe-test-{}.json", Uuid::new_v4()));
+        let queue = MessageQueue::load(Some(path.clone()));
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message("a"),
+                },
+            )
+            .unwrap();
+        queue.apply("ws-1", "thread-2", QueueAction::Clear).unwrap();
+        let reloaded = MessageQueue::load(Some(path.clone()));
+        assert_eq!(reloaded.snapshot("ws-1", "thread-1").items.len(), 1);
+        assert!(reloaded.state.lock().unwrap().queues.len() == 1);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn corrupt_queue_files_are_kept_aside() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("queue.json");
+        std::fs::write(&path, "[{").unwrap();
+        let queue = MessageQueue::load(Some(path.clone()));
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message("a"),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("queue.json.bak")).unwrap(),
+            "[{"
+        );
+        let reloaded = MessageQueue::load(Some(path));
+        assert_eq!(reloaded.snapshot("ws-1", "thread-1").items.len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn failed_save_rolls_the_edit_back() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        block_store_dir(&dir);
+        let queue = MessageQueue::load(Some(dir.join("queue.json")));
+        let error = queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message("a"),
+                },
+            )
+            .unwrap_err();
+        assert!(
+            error.starts_with("failed to write message queue"),
+            "{error}"
+        );
+        assert!(queue.snapshot("ws-1", "thread-1").items.is_empty());
+        assert!(queue.take_next(&key()).is_none());
+        let _ = std::fs::remove_file(dir);
+    }
+
+    #[test]
+    fn ended_sessions_clear_busy_threads() {
+        let queue = MessageQueue::load(None);
+        queue
+            .apply(
+                "ws-1",
+                "thread-1",
+                QueueAction::Enqueue {
+                    message: message("a"),
+                },
+            )
+            .unwrap();
+        queue.set_busy(&key(), true);
+        queue.set_busy(&("ws-2".to_string(), "thread-1".to_string()), true);
+        assert!(queue.take_next(&key()).is_none());
+        queue.clear_busy("ws-1");
+        assert_eq!(queue.ready_keys("ws-1"), vec![key()]);
+        assert!(queue.take_next(&key()).is_some());
+        assert!(queue
+            .state
+            .lock()
+            .unwrap()
+            .busy
+            .contains(&("ws-2".to_string(), "thread-1".to_string())));
+    }
+
+    #[test]
+    fn maps_bus_events_to_queue_signals() {
+        let event = |thread_id: Option<&str>, message: Value| BusEvent {
+            workspace_id: "ws-1".to_string(),
+            thread_id: thread_id.map(str::to_string),
+            message,
+        };
+        let (ws, thread) = key();
+        assert_eq!(
+            queue_signal(event(
+                Some("thread-1"),
+                json!({ "method": "turn/completed" })
+            )),
+            Some(QueueSignal::TurnEnded(ws.clone(), thread.clone()))
+        );
+        assert_eq!(
+            queue_signal(event(
+                Some("thread-1"),
+                json!({ "method": "error", "params": { "willRetry": false } })
+            )),
+            None
+        );
+        assert_eq!(
+            queue_signal(event(None, json!({ "method": "codex/disconnected" }))),
+            Some(QueueSignal::SessionEnded(ws.clone()))
+        );
+        assert_eq!(
+            queue_signal(event(None, json!({ "method": SESSION_INSTALLED_METHOD }))),
+            Some(QueueSignal::SessionStarted(ws))
+        );
+        assert_eq!(
+            queue_signal(event(None, json!({ "method": "turn/completed" }))),
+            None
+        );
+    }
+}
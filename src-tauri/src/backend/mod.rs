@@ -1,2 +1,22 @@
 pub(crate) mod app_server;
+pub(crate) mod approvals;
+pub(crate) mod checkpoints;
+pub(crate) mod event_bus;
+pub(crate) mod event_coalescer;
 pub(crate) mod events;
+pub(crate) mod fan_out;
+pub(crate) mod git_ops;
+pub(crate) mod headless;
+pub(crate) mod history_stream;
+pub(crate) mod message_queue;
+pub(crate) mod orphaned_sessions;
+pub(crate) mod protocol;
+pub(crate) mod sandbox_profiles;
+pub(crate) mod session_log;
+pub(crate) mod terminal;
+pub(crate) mod thread_export;
+pub(crate) mod thread_fork;
+pub(crate) mod thread_metadata;
+pub(crate) mod thread_search;
+pub(crate) mod tls;
+pub(crate) mod transcript;
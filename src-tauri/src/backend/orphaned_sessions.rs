@@ -0,0 +1,280 @@
+use ignore::WalkBuilder;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::protocol::{parse_response, ThreadListParams};
+use crate::codex_home::{resolve_default_codex_home, resolve_workspace_codex_home};
+use crate::types::{OrphanedSession, OrphanedSessionKind, WorkspaceEntry};
+
+/// Every page of `thread/list`, with the number of pages fetched.
+pub(crate) async fn list_all_threads(
+    session: &WorkspaceSession,
+) -> Result<(Vec<Value>, usize), String> {
+    let mut all_threads: Vec<Value> = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        pages += 1;
+        let params = ThreadListParams {
+            cursor: cursor.take(),
+            limit: Some(2000),
+        };
+        let response = session.request(&params).await?;
+        let page = parse_response::<ThreadListParams>(&response)?;
+        all_threads.extend(page.data);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok((all_threads, pages))
+}
+
+/// Finds CLI sessions that no workspace shows. A failed global `thread/list` is an
+/// error; a workspace whose own listing failed, or was never run, reports its unlisted
+/// sessions as `Unknown` instead of guessing.
+pub(crate) async fn find_orphaned_sessions(
+    global: &WorkspaceSession,
+    workspace_sessions: &[Arc<WorkspaceSession>],
+    workspaces: HashMap<String, WorkspaceEntry>,
+) -> Result<Vec<OrphanedSession>, String> {
+    let (threads, _) = list_all_threads(global)
+        .await
+        .map_err(|err| format!("thread/list failed: {err}"))?;
+    let mut listed_thread_ids = thread_ids(&threads);
+    // Workspaces with their own CODEX_HOME list threads the global session cannot see.
+    let mut unknown_workspace_ids: HashSet<String> = workspaces
+        .values()
+        .filter(|entry| workspace_codex_home(entry, &workspaces).is_some())
+        .map(|entry| entry.id.clone())
+        .collect();
+    for session in workspace_sessions {
+        match list_all_threads(session).await {
+            Ok((threads, _)) => {
+                listed_thread_ids.extend(thread_ids(&threads));
+                unknown_workspace_ids.remove(&session.entry.id);
+            }
+            Err(_) => {
+                unknown_workspace_ids.insert(session.entry.id.clone());
+            }
+        }
+    }
+    tokio::task::spawn_blocking(move || {
+        scan_orphaned_sessions(
+            &sessions_roots(&workspaces),
+            &workspaces,
+            &listed_thread_ids,
+            &unknown_workspace_ids,
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// The default `sessions` directory plus one per workspace CODEX_HOME override.
+pub(crate) fn sessions_roots(workspaces: &HashMap<String, WorkspaceEntry>) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    let mut seen = HashSet::new();
+    let homes = resolve_default_codex_home().into_iter().chain(
+        workspaces
+            .values()
+            .filter_map(|entry| workspace_codex_home(entry, workspaces)),
+    );
+    for home in homes {
+        let root = home.join("sessions");
+        if seen.insert(root.clone()) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+pub(crate) fn path_matches_workspace(cwd: &str, workspace_path: &Path) -> bool {
+    let cwd_path = Path::new(cwd);
+    cwd_path == workspace_path || cwd_path.starts_with(workspace_path)
+}
+
+fn workspace_codex_home(
+    entry: &WorkspaceEntry,
+    workspaces: &HashMap<String, WorkspaceEntry>,
+) -> Option<PathBuf> {
+    let parent_entry = entry
+        .parent_id
+        .as_ref()
+        .and_then(|parent_id| workspaces.get(parent_id));
+    resolve_workspace_codex_home(entry, parent_entry)
+}
+
+fn thread_ids(threads: &[Value]) -> HashSet<String> {
+    threads
+        .iter()
+        .filter_map(|thread| thread.get("id").and_then(Value::as_str))
+        .map(|id| id.to_string())
+        .collect()
+}
+
+fn scan_orphaned_sessions(
+    sessions_roots: &[PathBuf],
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    listed_thread_ids: &HashSet<String>,
+    unknown_workspace_ids: &HashSet<String>,
+) -> Vec<OrphanedSession> {
+    let mut orphans = Vec::new();
+    let mut seen = HashSet::new();
+    for root in sessions_roots {
+        if !root.exists() {
+            continue;
+        }
+        for entry in WalkBuilder::new(root).standard_filters(false).build() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Some((thread_id, cwd, started_at)) = read_session_meta(path) else {
+                continue;
+            };
+            if !seen.insert(thread_id.clone()) {
+                continue;
+            }
+            let workspace = workspaces
+                .values()
+                .filter(|entry| path_matches_workspace(&cwd, Path::new(&entry.path)))
+                .max_by_key(|entry| entry.path.len());
+            let kind = match workspace {
+                None => OrphanedSessionKind::Unregistered,
+                Some(_) if listed_thread_ids.contains(&thread_id) => continue,
+                Some(entry) if unknown_workspace_ids.contains(&entry.id) => {
+                    OrphanedSessionKind::Unknown
+                }
+                Some(_) => OrphanedSessionKind::Unlisted,
+            };
+            let updated_at = entry
+                .metadata()
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_millis() as i64)
+                .unwrap_or(0);
+            orphans.push(OrphanedSession {
+                thread_id,
+                path: path.to_string_lossy().to_string(),
+                cwd,
+                kind,
+                workspace_id: workspace.map(|entry| entry.id.clone()),
+                started_at,
+                updated_at,
+            });
+        }
+    }
+    orphans.sort_by_key(|orphan| std::cmp::Reverse(orphan.updated_at));
+    orphans
+}
+
+/// Thread id, `cwd` and start time from a rollout's `session_meta` line.
+fn read_session_meta(path: &Path) -> Option<(String, String, Option<String>)> {
+    let file = File::open(path).ok()?;
+    let reader = BufReader::new(file);
+    for line in reader.lines().take(6) {
+        let Ok(line) = line else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if value.get("type").and_then(|value| value.as_str()) != Some("session_meta") {
+            continue;
+        }
+        let payload = value.get("payload")?;
+        let id = payload.get("id").and_then(|value| value.as_str())?;
+        let cwd = payload
+            .get("cwd")
+            .and_then(|value| value.as_str())
+            .filter(|cwd| !cwd.trim().is_empty())?;
+        let started_at = payload
+            .get("timestamp")
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string());
+        return Some((id.to_string(), cwd.to_string(), started_at));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+    use std::fs;
+    use uuid::Uuid;
+
+    fn workspace(id: &str, path: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            path: path.to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    fn write_rollout(root: &Path, id: &str, cwd: &str) {
+        let day_dir = root.join("2026").join("01").join("19");
+        fs::create_dir_all(&day_dir).expect("create day dir");
+        let line = format!(
+            r#"{{"timestamp":"2026-01-19T12:00:00.000Z","type":"session_meta","payload":{{"id":"{id}","timestamp":"2026-01-19T12:00:00.000Z","cwd":"{cwd}"}}}}"#
+        );
+        fs::write(
+            day_dir.join(format!("rollout-{id}.jsonl")),
+            format!("{line}\n"),
+        )
+        .expect("write rollout");
+    }
+
+    #[test]
+    fn classifies_unknown_cwds_and_unlisted_threads() {
+        let root = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        write_rollout(&root, "listed", "/tmp/project-a");
+        write_rollout(&root, "unlisted", "/tmp/project-a/sub");
+        write_rollout(&root, "stray", "/tmp/elsewhere");
+        write_rollout(&root, "unchecked", "/tmp/project-b");
+
+        let workspaces = HashMap::from([
+            ("a".to_string(), workspace("a", "/tmp/project-a")),
+            ("b".to_string(), workspace("b", "/tmp/project-b")),
+        ]);
+        let listed = HashSet::from(["listed".to_string()]);
+        let unknown = HashSet::from(["b".to_string()]);
+
+        let mut orphans =
+            scan_orphaned_sessions(std::slice::from_ref(&root), &workspaces, &listed, &unknown);
+        orphans.sort_by(|a, b| a.thread_id.cmp(&b.thread_id));
+        assert_eq!(orphans.len(), 3);
+        assert_eq!(orphans[0].thread_id, "stray");
+        assert_eq!(orphans[0].kind, OrphanedSessionKind::Unregistered);
+        assert!(orphans[0].workspace_id.is_none());
+        assert_eq!(orphans[1].thread_id, "unchecked");
+        assert_eq!(orphans[1].kind, OrphanedSessionKind::Unknown);
+        assert_eq!(orphans[1].workspace_id.as_deref(), Some("b"));
+        assert_eq!(orphans[2].thread_id, "unlisted");
+        assert_eq!(orphans[2].kind, OrphanedSessionKind::Unlisted);
+        assert_eq!(orphans[2].workspace_id.as_deref(), Some("a"));
+        assert_eq!(orphans[2].cwd, "/tmp/project-a/sub");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,562 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Oldest Codex release whose app-server speaks the v2 thread/turn API used here.
+pub(crate) const MIN_CODEX_VERSION: (u64, u64, u64) = (0, 50, 0);
+
+/// JSON-RPC error code returned for methods the app-server does not implement.
+pub(crate) const METHOD_NOT_FOUND_CODE: i64 = -32601;
+
+/// A request Open Vibe sends to `codex app-server`, with its method name and result type.
+pub(crate) trait ClientRequest: Serialize {
+    const METHOD: &'static str;
+    type Response: DeserializeOwned;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ApprovalPolicy {
+    Untrusted,
+    OnFailure,
+    OnRequest,
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum SandboxPolicy {
+    DangerFullAccess,
+    ReadOnly,
+    #[serde(rename_all = "camelCase")]
+    WorkspaceWrite {
+        writable_roots: Vec<String>,
+        network_access: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum UserInput {
+    Text { text: String },
+    Image { url: String },
+    LocalImage { path: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ClientInfo {
+    pub(crate) name: String,
+    pub(crate) title: String,
+    pub(crate) version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InitializeParams {
+    pub(crate) client_info: ClientInfo,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InitializeResponse {
+    #[serde(default)]
+    pub(crate) user_agent: Option<String>,
+}
+
+impl ClientRequest for InitializeParams {
+    const METHOD: &'static str = "initialize";
+    type Response = InitializeResponse;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ThreadRef {
+    pub(crate) id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TurnRef {
+    pub(crate) id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadStartParams {
+    pub(crate) cwd: String,
+    pub(crate) approval_policy: ApprovalPolicy,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ThreadStartResponse {
+    pub(crate) thread: ThreadRef,
+}
+
+impl ClientRequest for ThreadStartParams {
+    const METHOD: &'static str = "thread/start";
+    type Response = ThreadStartResponse;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadResumeParams {
+    pub(crate) thread_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ThreadResumeResponse {
+    pub(crate) thread: ThreadRef,
+}
+
+impl ClientRequest for ThreadResumeParams {
+    const METHOD: &'static str = "thread/resume";
+    type Response = ThreadResumeResponse;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadForkParams {
+    pub(crate) thread_id: String,
+    /// Rollout to fork from, for app-servers that do not know the thread id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ThreadForkResponse {
+    pub(crate) thread: ThreadRef,
+}
+
+impl ClientRequest for ThreadForkParams {
+    const METHOD: &'static str = "thread/fork";
+    type Response = ThreadForkResponse;
+}
+
+/// Drops the last `num_turns` user turns from a thread's history.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadRollbackParams {
+    pub(crate) thread_id: String,
+    pub(crate) num_turns: u32,
+}
+
+impl ClientRequest for ThreadRollbackParams {
+    const METHOD: &'static str = "thread/rollback";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ThreadListParams {
+    pub(crate) cursor: Option<String>,
+    pub(crate) limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadListResponse {
+    #[serde(default)]
+    pub(crate) data: Vec<Value>,
+    #[serde(default, alias = "next_cursor")]
+    pub(crate) next_cursor: Option<String>,
+}
+
+impl ClientRequest for ThreadListParams {
+    const METHOD: &'static str = "thread/list";
+    type Response = ThreadListResponse;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadArchiveParams {
+    pub(crate) thread_id: String,
+}
+
+impl ClientRequest for ThreadArchiveParams {
+    const METHOD: &'static str = "thread/archive";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnStartParams {
+    pub(crate) thread_id: String,
+    pub(crate) input: Vec<UserInput>,
+    pub(crate) cwd: String,
+    pub(crate) approval_policy: ApprovalPolicy,
+    pub(crate) sandbox_policy: SandboxPolicy,
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) collaboration_mode: Option<Value>,
+    /// JSON schema the final agent message must conform to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) output_schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TurnStartResponse {
+    pub(crate) turn: TurnRef,
+}
+
+impl ClientRequest for TurnStartParams {
+    const METHOD: &'static str = "turn/start";
+    type Response = TurnStartResponse;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnInterruptParams {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+}
+
+impl ClientRequest for TurnInterruptParams {
+    const METHOD: &'static str = "turn/interrupt";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewStartParams {
+    pub(crate) thread_id: String,
+    pub(crate) target: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) delivery: Option<String>,
+}
+
+impl ClientRequest for ReviewStartParams {
+    const METHOD: &'static str = "review/start";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct ModelListParams {}
+
+impl ClientRequest for ModelListParams {
+    const METHOD: &'static str = "model/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct CollaborationModeListParams {}
+
+impl ClientRequest for CollaborationModeListParams {
+    const METHOD: &'static str = "collaborationMode/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SkillsListParams {
+    pub(crate) cwd: String,
+}
+
+impl ClientRequest for SkillsListParams {
+    const METHOD: &'static str = "skills/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnNotification {
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    #[serde(default)]
+    pub(crate) turn: Option<TurnRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct AgentMessageDelta {
+    #[serde(default)]
+    pub(crate) delta: String,
+}
+
+/// Notifications from the app-server that the backend itself acts on.
+#[derive(Debug, Clone)]
+pub(crate) enum ServerNotification {
+    TurnStarted(TurnNotification),
+    TurnCompleted(TurnNotification),
+    TurnError(Option<String>),
+    AgentMessageDelta(AgentMessageDelta),
+}
+
+pub(crate) fn parse_notification(value: &Value) -> Option<ServerNotification> {
+    let method = value.get("method")?.as_str()?;
+    let params = value.get("params").cloned().unwrap_or(Value::Null);
+    match method {
+        "turn/started" => serde_json::from_value(params)
+            .ok()
+            .map(ServerNotification::TurnStarted),
+        "turn/completed" => serde_json::from_value(params)
+            .ok()
+            .map(ServerNotification::TurnCompleted),
+        "turn/error" => Some(ServerNotification::TurnError(
+            params
+                .get("error")
+                .and_then(|error| error.as_str().or_else(|| error.get("message")?.as_str()))
+                .map(|message| message.to_string()),
+        )),
+        "item/agentMessage/delta" => serde_json::from_value(params)
+            .ok()
+            .map(ServerNotification::AgentMessageDelta),
+        _ => None,
+    }
+}
+
+/// Maps the UI access mode onto the approval and sandbox policies sent with a turn.
+pub(crate) fn access_mode_policies(
+    access_mode: &str,
+    workspace_path: &str,
+) -> (ApprovalPolicy, SandboxPolicy) {
+    match access_mode {
+        "full-access" => (ApprovalPolicy::Never, SandboxPolicy::DangerFullAccess),
+        "read-only" => (ApprovalPolicy::OnRequest, SandboxPolicy::ReadOnly),
+        _ => (
+            ApprovalPolicy::OnRequest,
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![workspace_path.to_string()],
+                network_access: true,
+            },
+        ),
+    }
+}
+
+/// Builds turn input from the composer text and attached image paths or URLs.
+pub(crate) fn build_user_input(text: &str, images: Option<Vec<String>>) -> Vec<UserInput> {
+    let mut input = Vec::new();
+    let trimmed_text = text.trim();
+    if !trimmed_text.is_empty() {
+        input.push(UserInput::Text {
+            text: trimmed_text.to_string(),
+        });
+    }
+    for path in images.unwrap_or_default() {
+        let trimmed = path.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with("data:")
+            || trimmed.starts_with("http://")
+            || trimmed.starts_with("https://")
+        {
+            input.push(UserInput::Image {
+                url: trimmed.to_string(),
+            });
+        } else {
+            input.push(UserInput::LocalImage {
+                path: trimmed.to_string(),
+            });
+        }
+    }
+    input
+}
+
+/// Extracts the typed result of a response, turning JSON-RPC errors into messages.
+pub(crate) fn parse_response<R: ClientRequest>(response: &Value) -> Result<R::Response, String> {
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(format!("{} failed: {message}", R::METHOD));
+    }
+    let result = response.get("result").cloned().unwrap_or(Value::Null);
+    serde_json::from_value(result).map_err(|err| {
+        format!(
+            "Unexpected {} response from codex app-server ({err}). {}",
+            R::METHOD,
+            upgrade_hint()
+        )
+    })
+}
+
+pub(crate) fn is_method_not_found(response: &Value) -> bool {
+    response
+        .get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_i64)
+        == Some(METHOD_NOT_FOUND_CODE)
+}
+
+pub(crate) fn unsupported_method_error(method: &str) -> String {
+    format!(
+        "The installed Codex does not support the `{method}` app-server method. {}",
+        upgrade_hint()
+    )
+}
+
+fn upgrade_hint() -> String {
+    let (major, minor, patch) = MIN_CODEX_VERSION;
+    format!(
+        "Open Vibe requires Codex {major}.{minor}.{patch} or newer; update Codex and reconnect."
+    )
+}
+
+/// Parses the Codex version out of the `initialize` user agent,
+/// e.g. `codex_cli_rs/0.58.0 (Mac OS 15.1.0; arm64) ...`.
+pub(crate) fn parse_codex_version(user_agent: &str) -> Option<(u64, u64, u64)> {
+    let (_, rest) = user_agent.split_once('/')?;
+    let version = rest.split_whitespace().next()?;
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Checks the `initialize` response and returns the negotiated Codex version.
+/// Unknown user agents are accepted; only versions known to be too old are rejected.
+pub(crate) fn negotiate_protocol(response: &Value) -> Result<Option<String>, String> {
+    if is_method_not_found(response) {
+        return Err(format!(
+            "The installed Codex does not speak the app-server protocol. {}",
+            upgrade_hint()
+        ));
+    }
+    let initialize = parse_response::<InitializeParams>(response)?;
+    let Some(user_agent) = initialize.user_agent else {
+        return Ok(None);
+    };
+    let Some(version) = parse_codex_version(&user_agent) else {
+        return Ok(None);
+    };
+    let (major, minor, patch) = version;
+    if version < MIN_CODEX_VERSION {
+        return Err(format!(
+            "Codex {major}.{minor}.{patch} speaks an incompatible app-server protocol. {}",
+            upgrade_hint()
+        ));
+    }
+    Ok(Some(format!("{major}.{minor}.{patch}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        access_mode_policies, build_user_input, negotiate_protocol, parse_codex_version,
+        parse_notification, parse_response, ApprovalPolicy, SandboxPolicy, ServerNotification,
+        ThreadListParams, ThreadStartParams, TurnStartParams, UserInput,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn turn_start_params_serialize_to_protocol_shape() {
+        let (approval_policy, sandbox_policy) = access_mode_policies("current", "/repo");
+        let params = TurnStartParams {
+            thread_id: "thread-1".to_string(),
+            input: build_user_input(" hi ", Some(vec!["/tmp/a.png".to_string()])),
+            cwd: "/repo".to_string(),
+            approval_policy,
+            sandbox_policy,
+            model: None,
+            effort: Some("high".to_string()),
+            collaboration_mode: None,
+            output_schema: None,
+        };
+        let value = serde_json::to_value(params).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "threadId": "thread-1",
+                "input": [
+                    { "type": "text", "text": "hi" },
+                    { "type": "localImage", "path": "/tmp/a.png" }
+                ],
+                "cwd": "/repo",
+                "approvalPolicy": "on-request",
+                "sandboxPolicy": {
+                    "type": "workspaceWrite",
+                    "writableRoots": ["/repo"],
+                    "networkAccess": true
+                },
+                "model": null,
+                "effort": "high"
+            })
+        );
+    }
+
+    #[test]
+    fn access_mode_policies_full_access_never_asks() {
+        let (approval_policy, sandbox_policy) = access_mode_policies("full-access", "/repo");
+        assert_eq!(approval_policy, ApprovalPolicy::Never);
+        assert_eq!(sandbox_policy, SandboxPolicy::DangerFullAccess);
+    }
+
+    #[test]
+    fn build_user_input_detects_remote_images() {
+        let input = build_user_input("", Some(vec!["https://x/y.png".to_string()]));
+        assert_eq!(
+            input,
+            vec![UserInput::Image {
+                url: "https://x/y.png".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_response_reads_thread_start_result() {
+        let response = json!({ "id": 1, "result": { "thread": { "id": "thread-9" } } });
+        let parsed = parse_response::<ThreadStartParams>(&response).unwrap();
+        assert_eq!(parsed.thread.id, "thread-9");
+    }
+
+    #[test]
+    fn parse_response_reports_errors_with_method() {
+        let response = json!({ "id": 1, "error": { "code": -1, "message": "boom" } });
+        let error = parse_response::<ThreadStartParams>(&response).unwrap_err();
+        assert_eq!(error, "thread/start failed: boom");
+    }
+
+    #[test]
+    fn parse_response_accepts_snake_case_cursor() {
+        let response = json!({ "result": { "data": [{ "id": "a" }], "next_cursor": "c" } });
+        let parsed = parse_response::<ThreadListParams>(&response).unwrap();
+        assert_eq!(parsed.data.len(), 1);
+        assert_eq!(parsed.next_cursor.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn parse_codex_version_reads_user_agent() {
+        assert_eq!(
+            parse_codex_version("codex_cli_rs/0.58.0 (Mac OS 15.1.0; arm64) vscode/1.0"),
+            Some((0, 58, 0))
+        );
+        assert_eq!(
+            parse_codex_version("openvibe/1.2.3-alpha.1"),
+            Some((1, 2, 3))
+        );
+        assert_eq!(parse_codex_version("no-version"), None);
+    }
+
+    #[test]
+    fn negotiate_protocol_rejects_old_codex() {
+        let response = json!({ "result": { "userAgent": "codex_cli_rs/0.20.0 (Linux)" } });
+        let error = negotiate_protocol(&response).unwrap_err();
+        assert!(error.contains("0.20.0"));
+        let response = json!({ "result": { "userAgent": "codex_cli_rs/0.60.1 (Linux)" } });
+        assert_eq!(
+            negotiate_protocol(&response).unwrap().as_deref(),
+            Some("0.60.1")
+        );
+        let response = json!({ "result": {} });
+        assert_eq!(negotiate_protocol(&response).unwrap(), None);
+    }
+
+    #[test]
+    fn parse_notification_reads_turn_events() {
+        let value = json!({ "method": "item/agentMessage/delta", "params": { "delta": "hi" } });
+        match parse_notification(&value) {
+            Some(ServerNotification::AgentMessageDelta(delta)) => assert_eq!(delta.delta, "hi"),
+            other => panic!("unexpected notification: {other:?}"),
+        }
+        let value = json!({ "method": "turn/error", "params": { "error": "bad" } });
+        match parse_notification(&value) {
+            Some(ServerNotification::TurnError(message)) => {
+                assert_eq!(message.as_deref(), Some("bad"))
+            }
+            other => panic!("unexpected notification: {other:?}"),
+        }
+        let value = json!({ "method": "item/started", "params": {} });
+        assert!(parse_notification(&value).is_none());
+    }
+}
@@ -0,0 +1,298 @@
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex as StdMutex, RwLock};
+
+use crate::backend::approvals::{is_absolute_path, relative_to_root};
+use crate::backend::protocol::{access_mode_policies, ApprovalPolicy, SandboxPolicy};
+use crate::types::{SandboxProfile, SandboxProfileMode};
+
+/// App-wide profiles from `AppSettings`, shared by every session.
+pub(crate) type SharedSandboxProfiles = Arc<RwLock<Vec<SandboxProfile>>>;
+
+pub(crate) fn shared_sandbox_profiles(profiles: Vec<SandboxProfile>) -> SharedSandboxProfiles {
+    Arc::new(RwLock::new(profiles))
+}
+
+pub(crate) fn replace_sandbox_profiles(
+    shared: &SharedSandboxProfiles,
+    profiles: Vec<SandboxProfile>,
+) {
+    if let Ok(mut current) = shared.write() {
+        *current = profiles;
+    }
+}
+
+/// Profiles a session can resolve: its workspace's own, then the app-wide ones.
+pub(crate) struct SandboxProfiles {
+    global: SharedSandboxProfiles,
+    workspace: StdMutex<Vec<SandboxProfile>>,
+}
+
+impl SandboxProfiles {
+    pub(crate) fn new(global: SharedSandboxProfiles, workspace: Vec<SandboxProfile>) -> Self {
+        Self {
+            global,
+            workspace: StdMutex::new(workspace),
+        }
+    }
+
+    pub(crate) fn set_workspace_profiles(&self, profiles: Vec<SandboxProfile>) {
+        if let Ok(mut current) = self.workspace.lock() {
+            *current = profiles;
+        }
+    }
+
+    pub(crate) fn find(&self, id: &str) -> Option<SandboxProfile> {
+        let workspace = self
+            .workspace
+            .lock()
+            .ok()
+            .and_then(|profiles| profiles.iter().find(|profile| profile.id == id).cloned());
+        workspace.or_else(|| {
+            self.global
+                .read()
+                .ok()
+                .and_then(|profiles| profiles.iter().find(|profile| profile.id == id).cloned())
+        })
+    }
+}
+
+fn parse_approval_policy(profile: &SandboxProfile) -> Result<ApprovalPolicy, String> {
+    serde_json::from_value(Value::String(profile.approval_policy.clone())).map_err(|_| {
+        format!(
+            "Sandbox profile `{}` has unknown approval policy `{}`",
+            profile.id, profile.approval_policy
+        )
+    })
+}
+
+fn root_outside_workspace(profile: &SandboxProfile, root: &str) -> String {
+    format!(
+        "Sandbox profile `{}` has writable root `{root}` outside the workspace",
+        profile.id
+    )
+}
+
+/// Resolves a relative writable root against the workspace, following symlinks when the
+/// path exists. Roots that leave the workspace are rejected.
+fn resolve_relative_root(
+    profile: &SandboxProfile,
+    root: &str,
+    workspace_path: &str,
+) -> Result<Option<String>, String> {
+    let relative = relative_to_root(root, workspace_path)
+        .ok_or_else(|| root_outside_workspace(profile, root))?;
+    if relative.is_empty() {
+        return Ok(None);
+    }
+    let resolved = Path::new(workspace_path).join(&relative);
+    if let (Ok(canonical), Ok(workspace)) = (
+        resolved.canonicalize(),
+        Path::new(workspace_path).canonicalize(),
+    ) {
+        if !canonical.starts_with(&workspace) {
+            return Err(root_outside_workspace(profile, root));
+        }
+    }
+    Ok(Some(resolved.to_string_lossy().to_string()))
+}
+
+/// Checks ids, approval policies and relative writable roots before profiles are saved.
+pub(crate) fn validate_sandbox_profiles(profiles: &[SandboxProfile]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for profile in profiles {
+        if profile.id.trim().is_empty() {
+            return Err("Sandbox profiles need an id.".to_string());
+        }
+        if !seen.insert(profile.id.as_str()) {
+            return Err(format!("Duplicate sandbox profile id `{}`", profile.id));
+        }
+        parse_approval_policy(profile)?;
+        for root in &profile.writable_roots {
+            let root = root.trim();
+            // Relative roots are resolved per workspace; here only `..` escapes are known.
+            if !root.is_empty() && !is_absolute_path(root) && relative_to_root(root, "").is_none() {
+                return Err(root_outside_workspace(profile, root));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Maps a profile onto turn policies. Relative writable roots are taken from the
+/// workspace root, which is always writable under `workspace-write`, and may not leave it;
+/// absolute roots are used as given.
+pub(crate) fn profile_policies(
+    profile: &SandboxProfile,
+    workspace_path: &str,
+) -> Result<(ApprovalPolicy, SandboxPolicy), String> {
+    let approval_policy = parse_approval_policy(profile)?;
+    let sandbox_policy = match profile.sandbox {
+        SandboxProfileMode::ReadOnly => SandboxPolicy::ReadOnly,
+        SandboxProfileMode::FullAccess => SandboxPolicy::DangerFullAccess,
+        SandboxProfileMode::WorkspaceWrite => {
+            let mut writable_roots = vec![workspace_path.to_string()];
+            for root in &profile.writable_roots {
+                let root = root.trim();
+                if root.is_empty() {
+                    continue;
+                }
+                let root = if is_absolute_path(root) {
+                    root.to_string()
+                } else {
+                    match resolve_relative_root(profile, root, workspace_path)? {
+                        Some(root) => root,
+                        None => continue,
+                    }
+                };
+                if !writable_roots.contains(&root) {
+                    writable_roots.push(root);
+                }
+            }
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots,
+                network_access: profile.network_access,
+            }
+        }
+    };
+    Ok((approval_policy, sandbox_policy))
+}
+
+/// Policies for a turn: the named profile when one is selected, otherwise the access mode.
+pub(crate) fn turn_policies(
+    profiles: &SandboxProfiles,
+    workspace_path: &str,
+    access_mode: Option<&str>,
+    sandbox_profile: Option<&str>,
+) -> Result<(ApprovalPolicy, SandboxPolicy), String> {
+    match sandbox_profile.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => {
+            let profile = profiles
+                .find(id)
+                .ok_or_else(|| format!("Unknown sandbox profile `{id}`"))?;
+            profile_policies(&profile, workspace_path)
+        }
+        None => Ok(access_mode_policies(
+            access_mode.unwrap_or("current"),
+            workspace_path,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        profile_policies, shared_sandbox_profiles, turn_policies, validate_sandbox_profiles,
+        SandboxProfiles,
+    };
+    use crate::backend::protocol::{ApprovalPolicy, SandboxPolicy};
+    use crate::types::{SandboxProfile, SandboxProfileMode};
+    use uuid::Uuid;
+
+    fn profile(id: &str) -> SandboxProfile {
+        serde_json::from_value(serde_json::json!({ "id": id, "name": id })).unwrap()
+    }
+
+    #[test]
+    fn workspace_write_profile_adds_roots_and_denies_network() {
+        let offline = SandboxProfile {
+            writable_roots: vec![
+                "/tmp/cache".to_string(),
+                "target/../cache".to_string(),
+                ".".to_string(),
+                " ".to_string(),
+            ],
+            network_access: false,
+            approval_policy: "never".to_string(),
+            ..profile("offline")
+        };
+        let (approval_policy, sandbox_policy) = profile_policies(&offline, "/repo").unwrap();
+        assert_eq!(approval_policy, ApprovalPolicy::Never);
+        assert_eq!(
+            sandbox_policy,
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![
+                    "/repo".to_string(),
+                    "/tmp/cache".to_string(),
+                    "/repo/cache".to_string(),
+                ],
+                network_access: false,
+            }
+        );
+    }
+
+    #[test]
+    fn workspace_profiles_shadow_global_ones() {
+        let global = shared_sandbox_profiles(vec![
+            profile("shared"),
+            SandboxProfile {
+                sandbox: SandboxProfileMode::ReadOnly,
+                ..profile("review")
+            },
+        ]);
+        let profiles = SandboxProfiles::new(
+            global,
+            vec![SandboxProfile {
+                sandbox: SandboxProfileMode::FullAccess,
+                ..profile("shared")
+            }],
+        );
+        let (_, shared) = turn_policies(&profiles, "/repo", None, Some("shared")).unwrap();
+        assert_eq!(shared, SandboxPolicy::DangerFullAccess);
+        let (_, review) = turn_policies(&profiles, "/repo", None, Some("review")).unwrap();
+        assert_eq!(review, SandboxPolicy::ReadOnly);
+        let (_, fallback) = turn_policies(&profiles, "/repo", Some("read-only"), None).unwrap();
+        assert_eq!(fallback, SandboxPolicy::ReadOnly);
+        assert!(turn_policies(&profiles, "/repo", None, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn validation_rejects_duplicates_and_bad_policies() {
+        assert!(validate_sandbox_profiles(&[profile("a"), profile("b")]).is_ok());
+        assert!(validate_sandbox_profiles(&[profile("a"), profile("a")]).is_err());
+        assert!(validate_sandbox_profiles(&[profile(" ")]).is_err());
+        let bad = SandboxProfile {
+            approval_policy: "sometimes".to_string(),
+            ..profile("a")
+        };
+        assert!(validate_sandbox_profiles(&[bad]).is_err());
+    }
+
+    #[test]
+    fn relative_roots_may_not_leave_the_workspace() {
+        let escaping = SandboxProfile {
+            writable_roots: vec!["../sibling".to_string()],
+            ..profile("escaping")
+        };
+        assert!(validate_sandbox_profiles(std::slice::from_ref(&escaping)).is_err());
+        assert!(profile_policies(&escaping, "/repo").is_err());
+        let absolute = SandboxProfile {
+            writable_roots: vec!["/var/cache".to_string()],
+            ..profile("absolute")
+        };
+        assert!(validate_sandbox_profiles(std::slice::from_ref(&absolute)).is_ok());
+        assert!(profile_policies(&absolute, "/repo").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relative_roots_may_not_escape_through_symlinks() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        let workspace = dir.join("repo");
+        std::fs::create_dir_all(workspace.join("cache")).unwrap();
+        std::os::unix::fs::symlink(&dir, workspace.join("outside")).unwrap();
+        let workspace_path = workspace.to_string_lossy().to_string();
+        let linked = SandboxProfile {
+            writable_roots: vec!["outside".to_string()],
+            ..profile("linked")
+        };
+        assert!(profile_policies(&linked, &workspace_path).is_err());
+        let inside = SandboxProfile {
+            writable_roots: vec!["cache".to_string()],
+            ..profile("inside")
+        };
+        assert!(profile_policies(&inside, &workspace_path).is_ok());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
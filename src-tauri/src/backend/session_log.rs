@@ -0,0 +1,419 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entries kept in memory per workspace session.
+pub(crate) const SESSION_LOG_CAPACITY: usize = 2_000;
+/// Size at which the on-disk log is rotated.
+const LOG_FILE_MAX_BYTES: u64 = 2 * 1024 * 1024;
+/// Rotated files kept next to the active one (`<id>.log.1` .. `<id>.log.N`).
+const LOG_FILE_MAX_ROTATIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SessionLogKind {
+    /// A line the app-server wrote to stderr.
+    Stderr,
+    /// A stdout line that was not valid JSON.
+    ParseError,
+    /// Process lifecycle: spawn, exit, respawn, idle shutdown.
+    Lifecycle,
+}
+
+impl SessionLogKind {
+    fn label(self) -> &'static str {
+        match self {
+            SessionLogKind::Stderr => "stderr",
+            SessionLogKind::ParseError => "parse-error",
+            SessionLogKind::Lifecycle => "lifecycle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionLogEntry {
+    pub(crate) ts: u64,
+    pub(crate) kind: SessionLogKind,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionLogFilter {
+    #[serde(default)]
+    pub(crate) kinds: Option<Vec<SessionLogKind>>,
+    /// Case-insensitive substring match on the message.
+    #[serde(default)]
+    pub(crate) query: Option<String>,
+    /// Only entries at or after this unix timestamp (ms).
+    #[serde(default)]
+    pub(crate) since: Option<u64>,
+    /// Keep only the newest `limit` matches.
+    #[serde(default)]
+    pub(crate) limit: Option<usize>,
+}
+
+impl SessionLogFilter {
+    fn matches(&self, entry: &SessionLogEntry, query: Option<&str>) -> bool {
+        if let Some(kinds) = self.kinds.as_ref() {
+            if !kinds.contains(&entry.kind) {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            if entry.ts < since {
+                return false;
+            }
+        }
+        if let Some(query) = query {
+            if !entry.message.to_lowercase().contains(query) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub(crate) fn apply<'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'a SessionLogEntry>,
+    ) -> Vec<SessionLogEntry> {
+        let query = self
+            .query
+            .as_deref()
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty());
+        let mut matched: Vec<SessionLogEntry> = entries
+            .into_iter()
+            .filter(|entry| self.matches(entry, query.as_deref()))
+            .cloned()
+            .collect();
+        if let Some(limit) = self.limit {
+            if matched.len() > limit {
+                matched.drain(..matched.len() - limit);
+            }
+        }
+        matched
+    }
+}
+
+pub(crate) fn logs_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("logs")
+}
+
+/// File name stem for per-workspace files, with anything but `[A-Za-z0-9_-]` replaced.
+pub(crate) fn workspace_file_stem(workspace_id: &str) -> String {
+    workspace_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn log_file_path(dir: &Path, workspace_id: &str) -> PathBuf {
+    dir.join(format!("{}.log", workspace_file_stem(workspace_id)))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+/// JSONL log file that is shifted to `.1`, `.2`, ... once it grows too large.
+pub(crate) struct RotatingLogFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+}
+
+impl RotatingLogFile {
+    pub(crate) fn open(path: PathBuf) -> Result<Self, String> {
+        Self::open_with_limit(path, LOG_FILE_MAX_BYTES)
+    }
+
+    /// Opens a log that rotates once it would grow past `max_bytes`.
+    pub(crate) fn open_with_limit(path: PathBuf, max_bytes: u64) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| e.to_string())?;
+        let written = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+        })
+    }
+
+    pub(crate) fn append(&mut self, line: &str) {
+        if self.written + line.len() as u64 > self.max_bytes && self.written > 0 {
+            self.rotate();
+        }
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.written += line.len() as u64;
+        }
+    }
+
+    fn rotate(&mut self) {
+        let _ = fs::remove_file(rotated_path(&self.path, LOG_FILE_MAX_ROTATIONS));
+        for index in (1..LOG_FILE_MAX_ROTATIONS).rev() {
+            let _ = fs::rename(
+                rotated_path(&self.path, index),
+                rotated_path(&self.path, index + 1),
+            );
+        }
+        let _ = fs::rename(&self.path, rotated_path(&self.path, 1));
+        if let Ok(file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+        {
+            self.file = file;
+            self.written = 0;
+        }
+    }
+}
+
+struct SessionLogInner {
+    entries: VecDeque<SessionLogEntry>,
+    file: Option<RotatingLogFile>,
+}
+
+/// Bounded in-memory log of what an app-server printed, optionally mirrored to disk.
+pub(crate) struct SessionLog {
+    capacity: usize,
+    inner: Mutex<SessionLogInner>,
+}
+
+impl SessionLog {
+    pub(crate) fn new(capacity: usize, log_dir: Option<&Path>, workspace_id: &str) -> Self {
+        let file = log_dir.and_then(|dir| {
+            RotatingLogFile::open(log_file_path(dir, workspace_id))
+                .map_err(|error| eprintln!("[app-server] log file unavailable: {error}"))
+                .ok()
+        });
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(SessionLogInner {
+                entries: VecDeque::new(),
+                file,
+            }),
+        }
+    }
+
+    pub(crate) fn push(&self, kind: SessionLogKind, message: &str) {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        let entry = SessionLogEntry {
+            ts,
+            kind,
+            message: message.to_string(),
+        };
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        if let Some(file) = inner.file.as_mut() {
+            if let Ok(mut line) = serde_json::to_string(&entry) {
+                line.push('\n');
+                file.append(&line);
+            }
+        }
+        inner.entries.push_back(entry);
+        while inner.entries.len() > self.capacity {
+            inner.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn query(&self, filter: &SessionLogFilter) -> Vec<SessionLogEntry> {
+        self.inner
+            .lock()
+            .map(|inner| filter.apply(inner.entries.iter()))
+            .unwrap_or_default()
+    }
+
+    fn entries(&self) -> Vec<SessionLogEntry> {
+        self.inner
+            .lock()
+            .map(|inner| inner.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Reads a JSONL file and its rotations, oldest first, skipping unparsable lines.
+pub(crate) fn read_rotated_jsonl<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+    let mut paths: Vec<PathBuf> = (1..=LOG_FILE_MAX_ROTATIONS)
+        .rev()
+        .map(|index| rotated_path(path, index))
+        .collect();
+    paths.push(path.to_path_buf());
+    let mut entries = Vec::new();
+    for path in paths {
+        let Ok(file) = File::open(&path) else {
+            continue;
+        };
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if let Ok(entry) = serde_json::from_str::<T>(&line) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries
+}
+
+/// Reads a workspace's log files, oldest rotation first.
+pub(crate) fn read_log_files(dir: &Path, workspace_id: &str) -> Vec<SessionLogEntry> {
+    read_rotated_jsonl(&log_file_path(dir, workspace_id))
+}
+
+/// Log history from disk followed by the live session's buffer. Disk entries from the
+/// live buffer's time span are skipped since the buffer mirrors them.
+pub(crate) fn query_workspace_log(
+    live: Option<&SessionLog>,
+    log_dir: Option<&Path>,
+    workspace_id: &str,
+    filter: &SessionLogFilter,
+) -> Vec<SessionLogEntry> {
+    let live = live.map(SessionLog::entries).unwrap_or_default();
+    let Some(dir) = log_dir else {
+        return filter.apply(&live);
+    };
+    let cutoff = live.first().map(|entry| entry.ts);
+    let history = read_log_files(dir, workspace_id)
+        .into_iter()
+        .filter(|entry| cutoff.is_none_or(|cutoff| entry.ts < cutoff));
+    let merged: Vec<SessionLogEntry> = history.chain(live).collect();
+    filter.apply(&merged)
+}
+
+/// Plain-text rendering used when exporting logs.
+pub(crate) fn format_log_entries(entries: &[SessionLogEntry]) -> String {
+    let mut output = String::new();
+    for entry in entries {
+        output.push_str(&format!(
+            "{} [{}] {}\n",
+            entry.ts,
+            entry.kind.label(),
+            entry.message
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_log_entries, query_workspace_log, read_log_files, rotated_path, RotatingLogFile,
+        SessionLog, SessionLogEntry, SessionLogFilter, SessionLogKind,
+    };
+    use uuid::Uuid;
+
+    fn entry(ts: u64, kind: SessionLogKind, message: &str) -> SessionLogEntry {
+        SessionLogEntry {
+            ts,
+            kind,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn session_log_keeps_newest_entries() {
+        let log = SessionLog::new(2, None, "ws");
+        log.push(SessionLogKind::Stderr, "one");
+        log.push(SessionLogKind::Stderr, "two");
+        log.push(SessionLogKind::ParseError, "three");
+        let messages: Vec<String> = log
+            .query(&SessionLogFilter::default())
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, vec!["two", "three"]);
+    }
+
+    #[test]
+    fn filter_matches_kind_query_since_and_limit() {
+        let entries = vec![
+            entry(1, SessionLogKind::Stderr, "WARN rate limited"),
+            entry(2, SessionLogKind::ParseError, "not json"),
+            entry(3, SessionLogKind::Stderr, "warn again"),
+            entry(4, SessionLogKind::Stderr, "info"),
+        ];
+        let filter = SessionLogFilter {
+            kinds: Some(vec![SessionLogKind::Stderr]),
+            query: Some(" warn ".to_string()),
+            since: None,
+            limit: None,
+        };
+        assert_eq!(filter.apply(&entries).len(), 2);
+        let filter = SessionLogFilter {
+            since: Some(2),
+            limit: Some(1),
+            ..SessionLogFilter::default()
+        };
+        assert_eq!(filter.apply(&entries), vec![entries[3].clone()]);
+    }
+
+    #[test]
+    fn log_files_rotate_and_read_back_in_order() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        let path = dir.join("ws.log");
+        let mut file = RotatingLogFile::open(path.clone()).expect("open log");
+        let first = serde_json::to_string(&entry(1, SessionLogKind::Stderr, "first")).unwrap();
+        file.append(&format!("{first}\n"));
+        file.rotate();
+        let second = serde_json::to_string(&entry(2, SessionLogKind::Stderr, "second")).unwrap();
+        file.append(&format!("{second}\n"));
+        assert!(rotated_path(&path, 1).exists());
+
+        let entries = read_log_files(&dir, "ws");
+        let messages: Vec<&str> = entries.iter().map(|entry| entry.message.as_str()).collect();
+        assert_eq!(messages, vec!["first", "second"]);
+        assert_eq!(
+            format_log_entries(&entries[..1]),
+            "1 [stderr] first\n".to_string()
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn workspace_log_merges_disk_history_with_live_buffer() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        {
+            let earlier = SessionLog::new(10, Some(&dir), "ws");
+            earlier.push(SessionLogKind::Lifecycle, "earlier session");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let live = SessionLog::new(1, Some(&dir), "ws");
+        live.push(SessionLogKind::Stderr, "evicted");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        live.push(SessionLogKind::Stderr, "current");
+
+        let entries =
+            query_workspace_log(Some(&live), Some(&dir), "ws", &SessionLogFilter::default());
+        let messages: Vec<&str> = entries.iter().map(|entry| entry.message.as_str()).collect();
+        assert_eq!(messages, vec!["earlier session", "evicted", "current"]);
+
+        let without_dir =
+            query_workspace_log(Some(&live), None, "ws", &SessionLogFilter::default());
+        assert_eq!(without_dir.len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
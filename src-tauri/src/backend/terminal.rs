@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::backend::events::{EventSink, TerminalExit, TerminalOutput};
+
+/// Running terminals keyed by `workspace_id:terminal_id`.
+pub(crate) type TerminalSessions = Mutex<HashMap<String, Arc<TerminalSession>>>;
+
+pub(crate) struct TerminalSession {
+    pub(crate) id: String,
+    pub(crate) master: Mutex<Box<dyn portable_pty::MasterPty + Send>>,
+    pub(crate) writer: Mutex<Box<dyn Write + Send>>,
+    pub(crate) child: Mutex<Box<dyn portable_pty::Child + Send>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TerminalSessionInfo {
+    id: String,
+}
+
+fn terminal_key(workspace_id: &str, terminal_id: &str) -> String {
+    format!("{workspace_id}:{terminal_id}")
+}
+
+fn find_on_path(candidates: &[&str]) -> Option<String> {
+    let path_var = std::env::var_os("PATH")?;
+    for dir in std::env::split_paths(&path_var) {
+        for candidate in candidates {
+            let path = dir.join(candidate);
+            if path.is_file() {
+                return Some(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    None
+}
+
+fn resolve_shell_command() -> (String, Vec<String>) {
+    if cfg!(windows) {
+        let shell = std::env::var("COMSPEC")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .or_else(|| find_on_path(&["pwsh.exe", "powershell.exe", "cmd.exe"]))
+            .unwrap_or_else(|| "C:\\Windows\\System32\\cmd.exe".to_string());
+        let shell_lower = shell.to_lowercase();
+        let args = if shell_lower.ends_with("pwsh.exe") || shell_lower.ends_with("powershell.exe") {
+            vec!["-NoLogo".to_string()]
+        } else {
+            Vec::new()
+        };
+        return (shell, args);
+    }
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+    (shell, vec!["-i".to_string()])
+}
+
+fn is_terminal_closed_error(message: &str) -> bool {
+    let lower = message.to_ascii_lowercase();
+    lower.contains("broken pipe")
+        || lower.contains("input/output error")
+        || lower.contains("os error 5")
+        || lower.contains("eio")
+        || lower.contains("io error")
+        || lower.contains("not connected")
+        || lower.contains("closed")
+}
+
+async fn get_terminal_session(
+    sessions: &TerminalSessions,
+    key: &str,
+) -> Result<Arc<TerminalSession>, String> {
+    let sessions = sessions.lock().await;
+    sessions
+        .get(key)
+        .cloned()
+        .ok_or_else(|| "Terminal session not found".to_string())
+}
+
+fn resolve_locale() -> String {
+    let candidate = std::env::var("LC_ALL")
+        .or_else(|_| std::env::var("LANG"))
+        .unwrap_or_else(|_| "en_US.UTF-8".to_string());
+    let lower = candidate.to_lowercase();
+    if lower.contains("utf-8") || lower.contains("utf8") {
+        return candidate;
+    }
+    "en_US.UTF-8".to_string()
+}
+
+fn spawn_terminal_reader(
+    event_sink: impl EventSink,
+    workspace_id: String,
+    terminal_id: String,
+    mut reader: Box<dyn Read + Send>,
+) {
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => {
+                    pending.extend_from_slice(&buffer[..count]);
+                    loop {
+                        match std::str::from_utf8(&pending) {
+                            Ok(decoded) => {
+                                if !decoded.is_empty() {
+                                    let payload = TerminalOutput {
+                                        workspace_id: workspace_id.clone(),
+                                        terminal_id: terminal_id.clone(),
+                                        data: decoded.to_string(),
+                                    };
+                                    event_sink.emit_terminal_output(payload);
+                                }
+                                pending.clear();
+                                break;
+                            }
+                            Err(error) => {
+                                let valid_up_to = error.valid_up_to();
+                                if valid_up_to == 0 {
+                                    if error.error_len().is_none() {
+                                        break;
+                                    }
+                                    let invalid_len = error.error_len().unwrap_or(1);
+                                    pending.drain(..invalid_len.min(pending.len()));
+                                    continue;
+                                }
+                                let chunk =
+                                    String::from_utf8_lossy(&pending[..valid_up_to]).to_string();
+                                if !chunk.is_empty() {
+                                    let payload = TerminalOutput {
+                                        workspace_id: workspace_id.clone(),
+                                        terminal_id: terminal_id.clone(),
+                                        data: chunk,
+                                    };
+                                    event_sink.emit_terminal_output(payload);
+                                }
+                                pending.drain(..valid_up_to);
+                                if error.error_len().is_none() {
+                                    break;
+                                }
+                                let invalid_len = error.error_len().unwrap_or(1);
+                                pending.drain(..invalid_len.min(pending.len()));
+                            }
+                        }
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        event_sink.emit_terminal_exit(TerminalExit {
+            workspace_id,
+            terminal_id,
+        });
+    });
+}
+
+/// Spawns the user's shell in a pty rooted at `cwd`. Reopening an existing terminal id
+/// returns the running session.
+pub(crate) async fn open_terminal(
+    sessions: &TerminalSessions,
+    event_sink: impl EventSink,
+    workspace_id: String,
+    terminal_id: String,
+    cwd: PathBuf,
+    cols: u16,
+    rows: u16,
+) -> Result<TerminalSessionInfo, String> {
+    if terminal_id.is_empty() {
+        return Err("Terminal id is required".to_string());
+    }
+    let key = terminal_key(&workspace_id, &terminal_id);
+    {
+        let sessions = sessions.lock().await;
+        if let Some(existing) = sessions.get(&key) {
+            return Ok(TerminalSessionInfo {
+                id: existing.id.clone(),
+            });
+        }
+    }
+
+    let pty_system = native_pty_system();
+    let size = PtySize {
+        rows: rows.max(2),
+        cols: cols.max(2),
+        pixel_width: 0,
+        pixel_height: 0,
+    };
+    let pair = pty_system
+        .openpty(size)
+        .map_err(|e| format!("Failed to open pty: {e}"))?;
+
+    let (shell, shell_args) = resolve_shell_command();
+    let mut cmd = CommandBuilder::new(shell);
+    cmd.cwd(cwd);
+    for arg in shell_args {
+        cmd.arg(arg);
+    }
+    cmd.env("TERM", "xterm-256color");
+    let locale = resolve_locale();
+    cmd.env("LANG", &locale);
+    cmd.env("LC_ALL", &locale);
+    cmd.env("LC_CTYPE", &locale);
+
+    let child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to spawn shell: {e}"))?;
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to open pty reader: {e}"))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("Failed to open pty writer: {e}"))?;
+
+    let session = Arc::new(TerminalSession {
+        id: terminal_id.clone(),
+        master: Mutex::new(pair.master),
+        writer: Mutex::new(writer),
+        child: Mutex::new(child),
+    });
+    let session_id = session.id.clone();
+
+    {
+        let mut sessions = sessions.lock().await;
+        if let Some(existing) = sessions.get(&key) {
+            let id = existing.id.clone();
+            drop(sessions);
+            let _ = tokio::task::spawn_blocking(move || {
+                let mut child = session.child.blocking_lock();
+                let _ = child.kill();
+            })
+            .await;
+            return Ok(TerminalSessionInfo { id });
+        }
+        sessions.insert(key, session);
+    }
+    spawn_terminal_reader(event_sink, workspace_id, terminal_id, reader);
+
+    Ok(TerminalSessionInfo { id: session_id })
+}
+
+pub(crate) async fn write_terminal(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+    data: String,
+) -> Result<(), String> {
+    let key = terminal_key(workspace_id, terminal_id);
+    let session = get_terminal_session(sessions, &key).await?;
+    let write_result = tokio::task::spawn_blocking(move || {
+        let mut writer = session.writer.blocking_lock();
+        writer
+            .write_all(data.as_bytes())
+            .map_err(|e| format!("Failed to write to pty: {e}"))?;
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to flush pty: {e}"))?;
+        Ok::<(), String>(())
+    })
+    .await
+    .map_err(|e| format!("Terminal write task failed: {e}"))?;
+
+    if let Err(err) = write_result {
+        if is_terminal_closed_error(&err) {
+            let mut sessions = sessions.lock().await;
+            sessions.remove(&key);
+        }
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub(crate) async fn resize_terminal(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    let key = terminal_key(workspace_id, terminal_id);
+    let session = get_terminal_session(sessions, &key).await?;
+    let size = PtySize {
+        rows: rows.max(2),
+        cols: cols.max(2),
+        pixel_width: 0,
+        pixel_height: 0,
+    };
+    let resize_result = tokio::task::spawn_blocking(move || {
+        let master = session.master.blocking_lock();
+        master
+            .resize(size)
+            .map_err(|e| format!("Failed to resize pty: {e}"))
+    })
+    .await
+    .map_err(|e| format!("Terminal resize task failed: {e}"))?;
+    if let Err(err) = resize_result {
+        if is_terminal_closed_error(&err) {
+            let mut sessions = sessions.lock().await;
+            sessions.remove(&key);
+        }
+        return Err(err);
+    }
+    Ok(())
+}
+
+pub(crate) async fn close_terminal(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+) -> Result<(), String> {
+    let key = terminal_key(workspace_id, terminal_id);
+    let mut sessions = sessions.lock().await;
+    let session = sessions
+        .remove(&key)
+        .ok_or_else(|| "Terminal session not found".to_string())?;
+    drop(sessions);
+    let _ = tokio::task::spawn_blocking(move || {
+        let mut child = session.child.blocking_lock();
+        let _ = child.kill();
+    })
+    .await;
+    Ok(())
+}
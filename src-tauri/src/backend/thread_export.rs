@@ -0,0 +1,690 @@
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ThreadExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadExportSession {
+    pub(crate) cwd: Option<String>,
+    pub(crate) model: Option<String>,
+    pub(crate) cli_version: Option<String>,
+    pub(crate) started_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum ThreadExportItem {
+    Message {
+        role: String,
+        text: String,
+        timestamp: Option<String>,
+    },
+    Reasoning {
+        text: String,
+        timestamp: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolCall {
+        call_id: Option<String>,
+        name: String,
+        input: String,
+        output: Option<String>,
+        timestamp: Option<String>,
+    },
+    FileDiff {
+        path: String,
+        diff: String,
+        timestamp: Option<String>,
+    },
+}
+
+/// Normalized view of a rollout file, independent of the rollout schema version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadExport {
+    pub(crate) thread_id: String,
+    pub(crate) session: ThreadExportSession,
+    pub(crate) items: Vec<ThreadExportItem>,
+}
+
+struct RolloutEntry {
+    kind: String,
+    timestamp: Option<String>,
+    payload: Value,
+}
+
+fn payload_str<'a>(payload: &'a Value, key: &str) -> Option<&'a str> {
+    payload.get(key).and_then(Value::as_str)
+}
+
+fn content_text(payload: &Value) -> Option<String> {
+    let parts: Vec<&str> = match payload.get("content") {
+        Some(Value::Array(entries)) => entries
+            .iter()
+            .filter_map(|entry| payload_str(entry, "text"))
+            .filter(|text| !text.trim().is_empty())
+            .collect(),
+        Some(Value::String(text)) => vec![text.as_str()],
+        _ => payload_str(payload, "text").into_iter().collect(),
+    };
+    let text = parts.join("\n");
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn reasoning_text(payload: &Value) -> Option<String> {
+    let parts: Vec<&str> = payload
+        .get("summary")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| payload_str(entry, "text"))
+        .filter(|text| !text.trim().is_empty())
+        .collect();
+    let text = parts.join("\n\n");
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn tool_output_text(output: &Value) -> String {
+    match output {
+        Value::String(text) => serde_json::from_str::<Value>(text)
+            .ok()
+            .and_then(|parsed| {
+                parsed
+                    .get("output")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| text.clone()),
+        Value::Object(_) => output
+            .get("content")
+            .or_else(|| output.get("output"))
+            .map(tool_output_text)
+            .unwrap_or_else(|| output.to_string()),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn tool_input_text(payload: &Value) -> String {
+    if let Some(arguments) = payload_str(payload, "arguments") {
+        return serde_json::from_str::<Value>(arguments)
+            .ok()
+            .and_then(|parsed| {
+                let command = parsed.get("command")?;
+                Some(match command {
+                    Value::Array(parts) => parts
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    other => other.as_str()?.to_string(),
+                })
+            })
+            .unwrap_or_else(|| arguments.to_string());
+    }
+    if let Some(input) = payload_str(payload, "input") {
+        return input.to_string();
+    }
+    payload
+        .get("action")
+        .and_then(|action| action.get("command"))
+        .and_then(Value::as_array)
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+fn file_change_diff(path: &str, change: &Value) -> String {
+    if let Some(diff) = change
+        .get("update")
+        .and_then(|update| payload_str(update, "unified_diff"))
+        .or_else(|| payload_str(change, "unified_diff"))
+    {
+        return diff.to_string();
+    }
+    if let Some(content) = change
+        .get("add")
+        .and_then(|add| payload_str(add, "content"))
+        .or_else(|| payload_str(change, "content"))
+    {
+        let mut diff = format!("--- /dev/null\n+++ {path}\n");
+        for line in content.lines() {
+            diff.push('+');
+            diff.push_str(line);
+            diff.push('\n');
+        }
+        return diff;
+    }
+    if change.get("delete").is_some() {
+        return format!("--- {path}\n+++ /dev/null\n");
+    }
+    String::new()
+}
+
+/// Builds an export from rollout JSONL. Messages come from `event_msg` records when the
+/// rollout has them and from `response_item` messages otherwise, like history streaming.
+pub(crate) fn parse_rollout(thread_id: &str, contents: &str) -> ThreadExport {
+    let entries: Vec<RolloutEntry> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+        .filter_map(|value| {
+            Some(RolloutEntry {
+                kind: payload_str(&value, "type")?.to_string(),
+                timestamp: payload_str(&value, "timestamp").map(str::to_string),
+                payload: value.get("payload")?.clone(),
+            })
+        })
+        .collect();
+    let has_event_messages = entries.iter().any(|entry| {
+        entry.kind == "event_msg"
+            && matches!(
+                payload_str(&entry.payload, "type"),
+                Some("user_message" | "agent_message")
+            )
+    });
+    let has_patch_events = entries.iter().any(|entry| {
+        entry.kind == "event_msg"
+            && payload_str(&entry.payload, "type") == Some("patch_apply_begin")
+    });
+
+    let mut session = ThreadExportSession::default();
+    let mut items = Vec::new();
+    let mut calls_by_id: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        let payload = &entry.payload;
+        let timestamp = entry.timestamp.clone();
+        let payload_type = payload_str(payload, "type").unwrap_or("");
+        match (entry.kind.as_str(), payload_type) {
+            ("session_meta", _) => {
+                session.cwd = payload_str(payload, "cwd").map(str::to_string);
+                session.cli_version = payload_str(payload, "cli_version").map(str::to_string);
+                session.started_at = payload_str(payload, "timestamp")
+                    .map(str::to_string)
+                    .or(timestamp);
+            }
+            ("turn_context", _) => {
+                if let Some(model) = payload_str(payload, "model") {
+                    session.model = Some(model.to_string());
+                }
+            }
+            ("event_msg", "user_message" | "agent_message") => {
+                let Some(text) = payload_str(payload, "message").filter(|t| !t.trim().is_empty())
+                else {
+                    continue;
+                };
+                let role = if payload_type == "user_message" {
+                    "user"
+                } else {
+                    "assistant"
+                };
+                items.push(ThreadExportItem::Message {
+                    role: role.to_string(),
+                    text: text.to_string(),
+                    timestamp,
+                });
+            }
+            ("event_msg", "patch_apply_begin") => {
+                let Some(changes) = payload.get("changes").and_then(Value::as_object) else {
+                    continue;
+                };
+                for (path, change) in changes {
+                    let diff = file_change_diff(path, change);
+                    if diff.is_empty() {
+                        continue;
+                    }
+                    items.push(ThreadExportItem::FileDiff {
+                        path: path.clone(),
+                        diff,
+                        timestamp: timestamp.clone(),
+                    });
+                }
+            }
+            ("event_msg", "turn_diff") if !has_patch_events => {
+                if let Some(diff) = payload_str(payload, "unified_diff").filter(|d| !d.is_empty()) {
+                    items.push(ThreadExportItem::FileDiff {
+                        path: "(turn)".to_string(),
+                        diff: diff.to_string(),
+                        timestamp,
+                    });
+                }
+            }
+            ("response_item", "message") if !has_event_messages => {
+                let role = payload_str(payload, "role").unwrap_or("");
+                if role != "user" && role != "assistant" {
+                    continue;
+                }
+                if let Some(text) = content_text(payload) {
+                    items.push(ThreadExportItem::Message {
+                        role: role.to_string(),
+                        text,
+                        timestamp,
+                    });
+                }
+            }
+            ("response_item", "reasoning") => {
+                if let Some(text) = reasoning_text(payload) {
+                    items.push(ThreadExportItem::Reasoning { text, timestamp });
+                }
+            }
+            ("response_item", "function_call" | "custom_tool_call" | "local_shell_call") => {
+                let call_id = payload_str(payload, "call_id").map(str::to_string);
+                let name =
+                    payload_str(payload, "name").unwrap_or(if payload_type == "local_shell_call" {
+                        "shell"
+                    } else {
+                        "tool"
+                    });
+                if let Some(call_id) = call_id.as_ref() {
+                    calls_by_id.insert(call_id.clone(), items.len());
+                }
+                items.push(ThreadExportItem::ToolCall {
+                    call_id,
+                    name: name.to_string(),
+                    input: tool_input_text(payload),
+                    output: None,
+                    timestamp,
+                });
+            }
+            ("response_item", "function_call_output" | "custom_tool_call_output") => {
+                let output = tool_output_text(payload.get("output").unwrap_or(&Value::Null));
+                let index = payload_str(payload, "call_id")
+                    .and_then(|call_id| calls_by_id.get(call_id).copied());
+                if let Some(ThreadExportItem::ToolCall { output: slot, .. }) =
+                    index.and_then(|index| items.get_mut(index))
+                {
+                    *slot = Some(output);
+                }
+            }
+            _ => {}
+        }
+    }
+    ThreadExport {
+        thread_id: thread_id.to_string(),
+        session,
+        items,
+    }
+}
+
+/// A backtick fence longer than any run of backticks inside `content`.
+fn code_fence(content: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in content.chars() {
+        if ch == '`' {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn push_code_block(output: &mut String, language: &str, content: &str) {
+    let fence = code_fence(content);
+    output.push_str(&format!(
+        "{fence}{language}\n{}\n{fence}\n\n",
+        content.trim_end()
+    ));
+}
+
+pub(crate) fn render_markdown(export: &ThreadExport) -> String {
+    let mut output = format!("# Thread {}\n\n", export.thread_id);
+    let session = &export.session;
+    for (label, value) in [
+        ("Workspace", session.cwd.as_deref()),
+        ("Model", session.model.as_deref()),
+        ("Started", session.started_at.as_deref()),
+        ("Codex", session.cli_version.as_deref()),
+    ] {
+        if let Some(value) = value {
+            output.push_str(&format!("- **{label}:** {value}\n"));
+        }
+    }
+    output.push('\n');
+    for item in &export.items {
+        match item {
+            ThreadExportItem::Message { role, text, .. } => {
+                let heading = if role == "user" { "User" } else { "Assistant" };
+                output.push_str(&format!("## {heading}\n\n{}\n\n", text.trim_end()));
+            }
+            ThreadExportItem::Reasoning { text, .. } => {
+                output.push_str("> **Reasoning**\n>\n");
+                for line in text.trim_end().lines() {
+                    output.push_str(&format!("> {line}\n"));
+                }
+                output.push('\n');
+            }
+            ThreadExportItem::ToolCall {
+                name,
+                input,
+                output: tool_output,
+                ..
+            } => {
+                output.push_str(&format!("### Tool: {name}\n\n"));
+                push_code_block(&mut output, "", input);
+                if let Some(tool_output) = tool_output.as_deref().filter(|text| !text.is_empty()) {
+                    output.push_str("**Output**\n\n");
+                    push_code_block(&mut output, "", tool_output);
+                }
+            }
+            ThreadExportItem::FileDiff { path, diff, .. } => {
+                output.push_str(&format!("### Diff: {path}\n\n"));
+                push_code_block(&mut output, "diff", diff);
+            }
+        }
+    }
+    output
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+fn html_diff(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let class = if line.starts_with("+++") || line.starts_with("---") {
+                "meta"
+            } else if line.starts_with('+') {
+                "add"
+            } else if line.starts_with('-') {
+                "del"
+            } else if line.starts_with("@@") {
+                "hunk"
+            } else {
+                "ctx"
+            };
+            format!("<span class=\"{class}\">{}</span>", escape_html(line))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;\
+max-width:960px;margin:2rem auto;padding:0 1rem;color:#1f2328;background:#fff;line-height:1.5}\
+h1{font-size:1.4rem}dl{display:grid;grid-template-columns:max-content 1fr;gap:.2rem 1rem;color:#57606a}\
+dt{font-weight:600}dd{margin:0}section{border:1px solid #d0d7de;border-radius:8px;margin:1rem 0;padding:.75rem 1rem}\
+section h2{font-size:.8rem;text-transform:uppercase;letter-spacing:.05em;color:#57606a;margin:0 0 .5rem}\
+.user{background:#f6f8fa}.reasoning{color:#57606a;font-style:italic}\
+.text{white-space:pre-wrap;margin:0}pre{background:#f6f8fa;padding:.75rem;border-radius:6px;overflow:auto;font-size:.85rem}\
+.add{color:#1a7f37}.del{color:#cf222e}.hunk{color:#8250df}.meta{color:#57606a;font-weight:600}";
+
+pub(crate) fn render_html(export: &ThreadExport) -> String {
+    let title = escape_html(&format!("Thread {}", export.thread_id));
+    let mut body = format!("<h1>{title}</h1>\n<dl>\n");
+    let session = &export.session;
+    for (label, value) in [
+        ("Workspace", session.cwd.as_deref()),
+        ("Model", session.model.as_deref()),
+        ("Started", session.started_at.as_deref()),
+        ("Codex", session.cli_version.as_deref()),
+    ] {
+        if let Some(value) = value {
+            body.push_str(&format!(
+                "<dt>{label}</dt><dd>{}</dd>\n",
+                escape_html(value)
+            ));
+        }
+    }
+    body.push_str("</dl>\n");
+    for item in &export.items {
+        match item {
+            ThreadExportItem::Message { role, text, .. } => {
+                let (class, heading) = if role == "user" {
+                    ("user", "User")
+                } else {
+                    ("assistant", "Assistant")
+                };
+                body.push_str(&format!(
+                    "<section class=\"{class}\"><h2>{heading}</h2><p class=\"text\">{}</p></section>\n",
+                    escape_html(text.trim_end())
+                ));
+            }
+            ThreadExportItem::Reasoning { text, .. } => {
+                body.push_str(&format!(
+                    "<section class=\"reasoning\"><h2>Reasoning</h2><p class=\"text\">{}</p></section>\n",
+                    escape_html(text.trim_end())
+                ));
+            }
+            ThreadExportItem::ToolCall {
+                name,
+                input,
+                output,
+                ..
+            } => {
+                body.push_str(&format!(
+                    "<section class=\"tool\"><h2>Tool: {}</h2><pre>{}</pre>",
+                    escape_html(name),
+                    escape_html(input.trim_end())
+                ));
+                if let Some(output) = output.as_deref().filter(|text| !text.is_empty()) {
+                    body.push_str(&format!(
+                        "<details><summary>Output</summary><pre>{}</pre></details>",
+                        escape_html(output.trim_end())
+                    ));
+                }
+                body.push_str("</section>\n");
+            }
+            ThreadExportItem::FileDiff { path, diff, .. } => {
+                body.push_str(&format!(
+                    "<section class=\"diff\"><h2>Diff: {}</h2><pre>{}</pre></section>\n",
+                    escape_html(path),
+                    html_diff(diff)
+                ));
+            }
+        }
+    }
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
+
+pub(crate) fn render_export(
+    export: &ThreadExport,
+    format: ThreadExportFormat,
+) -> Result<String, String> {
+    match format {
+        ThreadExportFormat::Markdown => Ok(render_markdown(export)),
+        ThreadExportFormat::Html => Ok(render_html(export)),
+        ThreadExportFormat::Json => {
+            serde_json::to_string_pretty(export).map_err(|err| err.to_string())
+        }
+    }
+}
+
+/// Reads a rollout file and renders it in the requested format.
+pub(crate) async fn export_rollout_file(
+    thread_id: &str,
+    path: &str,
+    format: ThreadExportFormat,
+) -> Result<String, String> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|err| format!("Failed to read {path}: {err}"))?;
+    render_export(&parse_rollout(thread_id, &contents), format)
+}
+
+/// Finds the rollout of `thread_id` under the `sessions` and `archived_sessions`
+/// directories of the given Codex homes. Rollouts are named `rollout-<timestamp>-<id>.jsonl`.
+pub(crate) fn find_rollout_file(codex_homes: &[PathBuf], thread_id: &str) -> Option<PathBuf> {
+    let thread_id = thread_id.trim();
+    if thread_id.is_empty() || thread_id.contains(['/', '\\']) {
+        return None;
+    }
+    let suffix = format!("-{thread_id}");
+    let is_rollout = |path: &Path| {
+        path.extension().and_then(|ext| ext.to_str()) == Some("jsonl")
+            && path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.ends_with(&suffix))
+    };
+    codex_homes
+        .iter()
+        .flat_map(|home| [home.join("sessions"), home.join("archived_sessions")])
+        .filter(|root| root.is_dir())
+        .find_map(|root| {
+            WalkBuilder::new(root)
+                .standard_filters(false)
+                .build()
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+                .map(|entry| entry.into_path())
+                .find(|path| is_rollout(path))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        code_fence, find_rollout_file, parse_rollout, render_export, render_html, render_markdown,
+        ThreadExportFormat, ThreadExportItem,
+    };
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn rollout() -> String {
+        [
+            json!({ "timestamp": "t0", "type": "session_meta", "payload": {
+                "id": "thread-1", "cwd": "/repo", "cli_version": "0.98.0", "timestamp": "2026-01-01T00:00:00Z"
+            } }),
+            json!({ "timestamp": "t1", "type": "turn_context", "payload": { "model": "gpt-5" } }),
+            json!({ "timestamp": "t2", "type": "response_item", "payload": {
+                "type": "message", "role": "user",
+                "content": [{ "type": "input_text", "text": "<environment_context>" }]
+            } }),
+            json!({ "timestamp": "t2", "type": "event_msg", "payload": {
+                "type": "user_message", "message": "Fix the <bug>"
+            } }),
+            json!({ "timestamp": "t3", "type": "response_item", "payload": {
+                "type": "reasoning", "summary": [{ "type": "summary_text", "text": "Look at main" }]
+            } }),
+            json!({ "timestamp": "t4", "type": "response_item", "payload": {
+                "type": "function_call", "name": "shell", "call_id": "call-1",
+                "arguments": "{\"command\":[\"cargo\",\"test\"]}"
+            } }),
+            json!({ "timestamp": "t5", "type": "response_item", "payload": {
+                "type": "function_call_output", "call_id": "call-1",
+                "output": "{\"output\":\"ok\",\"metadata\":{}}"
+            } }),
+            json!({ "timestamp": "t6", "type": "event_msg", "payload": {
+                "type": "patch_apply_begin", "call_id": "call-2",
+                "changes": { "src/main.rs": { "update": { "unified_diff": "@@ -1 +1 @@\n-old\n+new\n" } } }
+            } }),
+            json!({ "timestamp": "t7", "type": "event_msg", "payload": {
+                "type": "agent_message", "message": "Done"
+            } }),
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    #[test]
+    fn parse_rollout_collects_messages_tools_and_diffs() {
+        let export = parse_rollout("thread-1", &rollout());
+        assert_eq!(export.session.cwd.as_deref(), Some("/repo"));
+        assert_eq!(export.session.model.as_deref(), Some("gpt-5"));
+        let kinds: Vec<&str> = export
+            .items
+            .iter()
+            .map(|item| match item {
+                ThreadExportItem::Message { .. } => "message",
+                ThreadExportItem::Reasoning { .. } => "reasoning",
+                ThreadExportItem::ToolCall { .. } => "tool",
+                ThreadExportItem::FileDiff { .. } => "diff",
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec!["message", "reasoning", "tool", "diff", "message"]
+        );
+        match &export.items[2] {
+            ThreadExportItem::ToolCall { input, output, .. } => {
+                assert_eq!(input, "cargo test");
+                assert_eq!(output.as_deref(), Some("ok"));
+            }
+            other => panic!("unexpected item: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn markdown_and_html_render_every_item() {
+        let export = parse_rollout("thread-1", &rollout());
+        let markdown = render_markdown(&export);
+        assert!(markdown.contains("## User\n\nFix the <bug>"));
+        assert!(markdown.contains("### Tool: shell"));
+        assert!(markdown.contains("```diff\n@@ -1 +1 @@"));
+        let html = render_html(&export);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Fix the &lt;bug&gt;"));
+        assert!(html.contains("<span class=\"add\">+new</span>"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn json_export_round_trips() {
+        let export = parse_rollout("thread-1", &rollout());
+        let rendered = render_export(&export, ThreadExportFormat::Json).unwrap();
+        let parsed: super::ThreadExport = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed, export);
+        assert!(rendered.contains("\"type\": \"toolCall\""));
+    }
+
+    #[test]
+    fn code_fence_outgrows_backticks_in_content() {
+        assert_eq!(code_fence("plain"), "```");
+        assert_eq!(code_fence("has ```` inside"), "`````");
+    }
+
+    #[test]
+    fn find_rollout_file_matches_the_thread_id_suffix() {
+        let home = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        let day = home.join("sessions").join("2026").join("09").join("14");
+        let archived = home.join("archived_sessions");
+        std::fs::create_dir_all(&day).expect("create sessions dir");
+        std::fs::create_dir_all(&archived).expect("create archived dir");
+        let live = day.join("rollout-2026-09-14T10-00-00-thread-1.jsonl");
+        let old = archived.join("rollout-2026-09-01T10-00-00-thread-2.jsonl");
+        std::fs::write(&live, "").expect("write rollout");
+        std::fs::write(&old, "").expect("write rollout");
+        std::fs::write(day.join("rollout-2026-09-14T10-00-00-thread-10.jsonl"), "")
+            .expect("write rollout");
+
+        let homes = vec![home.clone()];
+        assert_eq!(find_rollout_file(&homes, "thread-1"), Some(live));
+        assert_eq!(find_rollout_file(&homes, "thread-2"), Some(old));
+        assert_eq!(find_rollout_file(&homes, "thread-3"), None);
+        assert_eq!(find_rollout_file(&homes, "../thread-1"), None);
+        let _ = std::fs::remove_dir_all(home);
+    }
+}
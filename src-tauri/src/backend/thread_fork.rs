@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::history_stream::{parse_history, StreamMessage};
+use crate::backend::protocol::{
+    parse_response, ThreadArchiveParams, ThreadForkParams, ThreadRollbackParams,
+};
+use crate::types::WorkspaceInfo;
+
+/// Where a fork cuts the source thread, in user turns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ForkPoint {
+    pub(crate) total_turns: u32,
+    /// Turns kept in the fork. A user message starts a new turn, so forking from one
+    /// keeps the turns before it and hands its text back as a draft.
+    pub(crate) keep_turns: u32,
+    pub(crate) draft: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ForkedThread {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) rolled_back_turns: u32,
+    /// Text of the user message the fork was taken from, for the composer.
+    pub(crate) draft: Option<String>,
+    /// The worktree created for the fork, when one was requested.
+    pub(crate) worktree: Option<WorkspaceInfo>,
+}
+
+/// Maps a `stream_thread_history` message index (the `N` in `history-<thread>-N`) to a
+/// turn count, reading the rollout with the history stream's own parser.
+pub(crate) fn locate_fork_point(contents: &str, message_index: u64) -> Result<ForkPoint, String> {
+    let messages = parse_history("", contents);
+    let Some(position) = messages
+        .iter()
+        .position(|message| message.index == message_index)
+    else {
+        return Err(format!(
+            "Message {message_index} not found in thread history"
+        ));
+    };
+    let is_user = |message: &StreamMessage| message.role == "user";
+    let total_turns = messages.iter().filter(|message| is_user(message)).count() as u32;
+    let selected = &messages[position];
+    let users_through = messages[..=position]
+        .iter()
+        .filter(|message| is_user(message))
+        .count() as u32;
+    Ok(if is_user(selected) {
+        ForkPoint {
+            total_turns,
+            keep_turns: users_through - 1,
+            draft: Some(selected.text.clone()),
+        }
+    } else {
+        ForkPoint {
+            total_turns,
+            keep_turns: users_through,
+            draft: None,
+        }
+    })
+}
+
+/// Forks `thread_id` on `session` and rolls the copy back to `point`. The source thread is
+/// left untouched; a fork that cannot be trimmed is archived rather than returned.
+pub(crate) async fn fork_thread_at(
+    session: &WorkspaceSession,
+    thread_id: &str,
+    rollout_path: &str,
+    point: &ForkPoint,
+) -> Result<String, String> {
+    let response = session
+        .request(&ThreadForkParams {
+            thread_id: thread_id.to_string(),
+            path: Some(rollout_path.to_string()),
+            cwd: Some(session.entry.path.clone()),
+        })
+        .await?;
+    let forked_id = parse_response::<ThreadForkParams>(&response)?.thread.id;
+    let rollback = point.total_turns.saturating_sub(point.keep_turns);
+    if rollback > 0 {
+        let result = session
+            .request(&ThreadRollbackParams {
+                thread_id: forked_id.clone(),
+                num_turns: rollback,
+            })
+            .await
+            .and_then(|response| parse_response::<ThreadRollbackParams>(&response));
+        if let Err(error) = result {
+            let _ = session
+                .request(&ThreadArchiveParams {
+                    thread_id: forked_id,
+                })
+                .await;
+            return Err(format!("Failed to trim forked thread: {error}"));
+        }
+    }
+    Ok(forked_id)
+}
+
+/// Worktree and session access that forks and fan-outs need, provided by the app and by
+/// the daemon.
+pub(crate) trait ForkHost {
+    async fn add_worktree(&self, parent_id: &str, branch: &str) -> Result<WorkspaceInfo, String>;
+    async fn remove_worktree(&self, id: &str) -> Result<(), String>;
+    async fn session(&self, workspace_id: &str) -> Result<Arc<WorkspaceSession>, String>;
+}
+
+/// Forks `thread_id` at a `history-<thread>-N` index, optionally inside a new worktree of
+/// the workspace. A worktree created for a fork that then fails is removed again.
+pub(crate) async fn fork_thread<H: ForkHost>(
+    host: &H,
+    workspace_id: &str,
+    thread_id: &str,
+    rollout_path: &str,
+    message_index: u64,
+    worktree_branch: Option<&str>,
+) -> Result<ForkedThread, String> {
+    let contents = tokio::fs::read_to_string(rollout_path)
+        .await
+        .map_err(|err| format!("Failed to read {rollout_path}: {err}"))?;
+    let point = locate_fork_point(&contents, message_index)?;
+    let worktree = match worktree_branch.filter(|branch| !branch.trim().is_empty()) {
+        Some(branch) => Some(host.add_worktree(workspace_id, branch).await?),
+        None => None,
+    };
+    let target_id = worktree
+        .as_ref()
+        .map_or(workspace_id, |info| info.id.as_str())
+        .to_string();
+    let forked = async {
+        let session = host.session(&target_id).await?;
+        fork_thread_at(&session, thread_id, rollout_path, &point).await
+    }
+    .await;
+    let forked_id = match forked {
+        Ok(forked_id) => forked_id,
+        Err(error) => {
+            if let Some(info) = worktree.as_ref() {
+                if let Err(cleanup) = host.remove_worktree(&info.id).await {
+                    eprintln!("[fork] failed to remove worktree {}: {cleanup}", info.id);
+                }
+            }
+            return Err(error);
+        }
+    };
+    Ok(ForkedThread {
+        workspace_id: target_id,
+        thread_id: forked_id,
+        rolled_back_turns: point.total_turns.saturating_sub(point.keep_turns),
+        draft: point.draft,
+        worktree,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fork_thread, locate_fork_point, ForkHost, ForkPoint};
+    use crate::backend::app_server::WorkspaceSession;
+    use crate::types::WorkspaceInfo;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    fn rollout(lines: &[serde_json::Value]) -> String {
+        lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn event(kind: &str, message: &str) -> serde_json::Value {
+        json!({ "type": "event_msg", "payload": { "type": kind, "message": message } })
+    }
+
+    #[test]
+    fn numbering_matches_history_stream() {
+        // The environment context response item precedes the first event message, so
+        // history indices for event messages start at 2.
+        let contents = rollout(&[
+            json!({ "type": "response_item", "payload": {
+                "type": "message", "role": "user",
+                "content": [{ "type": "input_text", "text": "<environment_context>" }]
+            } }),
+            event("user_message", "first"),
+            event("agent_message", "answer one"),
+            json!({ "type": "response_item", "payload": {
+                "type": "message", "role": "assistant", "content": [{ "text": "dup" }]
+            } }),
+            event("user_message", "second"),
+            event("agent_message", "answer two"),
+        ]);
+        assert_eq!(
+            locate_fork_point(&contents, 3).unwrap(),
+            ForkPoint {
+                total_turns: 2,
+                keep_turns: 1,
+                draft: None,
+            }
+        );
+        assert_eq!(
+            locate_fork_point(&contents, 4).unwrap(),
+            ForkPoint {
+                total_turns: 2,
+                keep_turns: 1,
+                draft: Some("second".to_string()),
+            }
+        );
+        assert!(locate_fork_point(&contents, 1).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_response_items() {
+        let contents = rollout(&[
+            json!({ "type": "response_item", "payload": {
+                "type": "message", "role": "user", "content": [{ "text": "hello" }]
+            } }),
+            json!({ "type": "response_item", "payload": {
+                "type": "message", "role": "assistant", "content": [{ "text": "hi" }]
+            } }),
+        ]);
+        assert_eq!(
+            locate_fork_point(&contents, 1).unwrap(),
+            ForkPoint {
+                total_turns: 1,
+                keep_turns: 0,
+                draft: Some("hello".to_string()),
+            }
+        );
+    }
+
+    #[derive(Default)]
+    struct FailingHost {
+        removed: Mutex<Vec<String>>,
+    }
+
+    impl ForkHost for FailingHost {
+        async fn add_worktree(
+            &self,
+            parent_id: &str,
+            branch: &str,
+        ) -> Result<WorkspaceInfo, String> {
+            serde_json::from_value(json!({
+                "id": format!("{parent_id}-{branch}"),
+                "name": branch,
+                "path": "/tmp/worktree",
+                "connected": false,
+                "kind": "worktree",
+                "parentId": parent_id,
+                "settings": {},
+            }))
+            .map_err(|err| err.to_string())
+        }
+
+        async fn remove_worktree(&self, id: &str) -> Result<(), String> {
+            self.removed.lock().unwrap().push(id.to_string());
+            Ok(())
+        }
+
+        async fn session(&self, _workspace_id: &str) -> Result<Arc<WorkspaceSession>, String> {
+            Err("workspace not connected".to_string())
+        }
+    }
+
+    #[test]
+    fn failed_fork_removes_its_worktree() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rollout.jsonl");
+        std::fs::write(&path, rollout(&[event("user_message", "first")])).unwrap();
+        let path = path.to_string_lossy().to_string();
+        let host = FailingHost::default();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let error = fork_thread(&host, "ws-1", "thread-1", &path, 1, Some("fork"))
+                .await
+                .unwrap_err();
+            assert_eq!(error, "workspace not connected");
+            assert_eq!(*host.removed.lock().unwrap(), vec!["ws-1-fork".to_string()]);
+
+            assert!(fork_thread(&host, "ws-1", "thread-1", &path, 1, None)
+                .await
+                .is_err());
+            assert_eq!(host.removed.lock().unwrap().len(), 1);
+        });
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
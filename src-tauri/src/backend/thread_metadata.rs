@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use crate::storage::{now_ms, read_json_store, write_json_atomic};
+
+/// Local annotations for a thread that `thread/list` does not carry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadMetadata {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    #[serde(default)]
+    pub(crate) notes: Option<String>,
+    #[serde(default)]
+    pub(crate) custom_title: Option<String>,
+    #[serde(default)]
+    pub(crate) pinned: bool,
+    #[serde(default)]
+    pub(crate) linked_issue: Option<u64>,
+    #[serde(default)]
+    pub(crate) linked_pull_request: Option<u64>,
+    /// Workspace id of the worktree the thread's work lives in.
+    #[serde(default)]
+    pub(crate) linked_worktree_id: Option<String>,
+    #[serde(default)]
+    pub(crate) updated_at: u64,
+}
+
+impl ThreadMetadata {
+    fn normalize(mut self) -> Self {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags.iter().map(|tag| tag.trim()) {
+            if !tag.is_empty() && !tags.iter().any(|seen| seen.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
+            }
+        }
+        self.tags = tags;
+        let trim = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        self.notes = trim(self.notes);
+        self.custom_title = trim(self.custom_title);
+        self.linked_worktree_id = trim(self.linked_worktree_id);
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.notes.is_none()
+            && self.custom_title.is_none()
+            && !self.pinned
+            && self.linked_issue.is_none()
+            && self.linked_pull_request.is_none()
+            && self.linked_worktree_id.is_none()
+    }
+
+    fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|own| own.eq_ignore_ascii_case(tag.trim()))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadMetadataQuery {
+    pub(crate) workspace_id: Option<String>,
+    /// Every tag must be present, compared case-insensitively.
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    pub(crate) pinned: Option<bool>,
+}
+
+type MetadataKey = (String, String);
+
+/// Thread metadata keyed by workspace and thread id, persisted as JSON.
+pub(crate) struct ThreadMetadataStore {
+    path: Option<PathBuf>,
+    /// Why the file on disk could not be loaded; saving would overwrite it, so it is refused.
+    load_error: Option<String>,
+    entries: StdMutex<HashMap<MetadataKey, ThreadMetadata>>,
+}
+
+impl ThreadMetadataStore {
+    /// Loads stored metadata from `path`; without a path the store lives in memory only.
+    pub(crate) fn load(path: Option<PathBuf>) -> Arc<Self> {
+        let loaded: Result<Vec<ThreadMetadata>, String> = match path.as_ref() {
+            Some(path) => read_json_store(path, "thread metadata"),
+            None => Ok(Vec::new()),
+        };
+        let (entries, load_error) = match loaded {
+            Ok(entries) => (entries, None),
+            Err(err) => {
+                eprintln!("{err}");
+                (Vec::new(), Some(err))
+            }
+        };
+        Arc::new(Self {
+            path,
+            load_error,
+            entries: StdMutex::new(
+                entries
+                    .into_iter()
+                    .map(|entry| ((entry.workspace_id.clone(), entry.thread_id.clone()), entry))
+                    .collect(),
+            ),
+        })
+    }
+
+    fn persist(&self, entries: &HashMap<MetadataKey, ThreadMetadata>) -> Result<(), String> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if let Some(err) = &self.load_error {
+            return Err(err.clone());
+        }
+        let mut sorted: Vec<&ThreadMetadata> = entries.values().collect();
+        sorted
+            .sort_by(|a, b| (&a.workspace_id, &a.thread_id).cmp(&(&b.workspace_id, &b.thread_id)));
+        write_json_atomic(path, &sorted)
+            .map_err(|err| format!("failed to write thread metadata: {err}"))
+    }
+
+    /// Saves `entries`, putting `key` back to `previous` when the save fails.
+    fn persist_or_restore(
+        &self,
+        entries: &mut HashMap<MetadataKey, ThreadMetadata>,
+        key: MetadataKey,
+        previous: Option<ThreadMetadata>,
+    ) -> Result<(), String> {
+        let result = self.persist(entries);
+        if result.is_err() {
+            match previous {
+                Some(previous) => entries.insert(key, previous),
+                None => entries.remove(&key),
+            };
+        }
+        result
+    }
+
+    pub(crate) fn get(&self, workspace_id: &str, thread_id: &str) -> Option<ThreadMetadata> {
+        let key = (workspace_id.to_string(), thread_id.to_string());
+        self.entries
+            .lock()
+            .ok()
+            .and_then(|entries| entries.get(&key).cloned())
+    }
+
+    /// Replaces a thread's metadata. Records left without any data are removed, so the
+    /// returned value is `None` in that case. Nothing changes when the store cannot be saved.
+    pub(crate) fn set(&self, metadata: ThreadMetadata) -> Result<Option<ThreadMetadata>, String> {
+        if metadata.workspace_id.trim().is_empty() || metadata.thread_id.trim().is_empty() {
+            return Err("workspaceId and threadId are required".to_string());
+        }
+        let mut metadata = metadata.normalize();
+        metadata.updated_at = now_ms();
+        let mut entries = self.entries.lock().map_err(|err| err.to_string())?;
+        let key = (metadata.workspace_id.clone(), metadata.thread_id.clone());
+        let (previous, stored) = if metadata.is_empty() {
+            (entries.remove(&key), None)
+        } else {
+            (
+                entries.insert(key.clone(), metadata.clone()),
+                Some(metadata),
+            )
+        };
+        self.persist_or_restore(&mut entries, key, previous)?;
+        Ok(stored)
+    }
+
+    pub(crate) fn delete(&self, workspace_id: &str, thread_id: &str) -> Result<bool, String> {
+        let key = (workspace_id.to_string(), thread_id.to_string());
+        let mut entries = self.entries.lock().map_err(|err| err.to_string())?;
+        let Some(previous) = entries.remove(&key) else {
+            return Ok(false);
+        };
+        self.persist_or_restore(&mut entries, key, Some(previous))?;
+        Ok(true)
+    }
+
+    /// Matching records, pinned first and then most recently updated.
+    pub(crate) fn query(&self, query: &ThreadMetadataQuery) -> Vec<ThreadMetadata> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        let mut matches: Vec<ThreadMetadata> = entries
+            .values()
+            .filter(|entry| {
+                query
+                    .workspace_id
+                    .as_deref()
+                    .is_none_or(|id| entry.workspace_id == id)
+            })
+            .filter(|entry| query.pinned.is_none_or(|pinned| entry.pinned == pinned))
+            .filter(|entry| query.tags.iter().all(|tag| entry.has_tag(tag)))
+            .cloned()
+            .collect();
+        matches.sort_by(|a, b| {
+            b.pinned
+                .cmp(&a.pinned)
+                .then(b.updated_at.cmp(&a.updated_at))
+                .then_with(|| a.thread_id.cmp(&b.thread_id))
+        });
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ThreadMetadata, ThreadMetadataQuery, ThreadMetadataStore};
+    use crate::storage::block_store_dir;
+    use uuid::Uuid;
+
+    fn metadata(workspace_id: &str, thread_id: &str, tags: &[&str]) -> ThreadMetadata {
+        ThreadMetadata {
+            workspace_id: workspace_id.to_string(),
+            thread_id: thread_id.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..ThreadMetadata::default()
+        }
+    }
+
+    #[test]
+    fn set_normalizes_and_drops_empty_records() {
+        let store = ThreadMetadataStore::load(None);
+        let stored = store
+            .set(ThreadMetadata {
+                custom_title: Some("  Auth refactor ".to_string()),
+                notes: Some("   ".to_string()),
+                ..metadata("ws-1", "t-1", &["bug", " Bug", "", "auth"])
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tags, vec!["bug", "auth"]);
+        assert_eq!(stored.custom_title.as_deref(), Some("Auth refactor"));
+        assert!(stored.notes.is_none());
+
+        assert!(store.set(metadata("ws-1", "t-1", &[])).unwrap().is_none());
+        assert!(store.get("ws-1", "t-1").is_none());
+        assert!(store.set(metadata("", "t-1", &["x"])).is_err());
+    }
+
+    #[test]
+    fn query_filters_by_tags_and_workspace() {
+        let store = ThreadMetadataStore::load(None);
+        store
+            .set(metadata("ws-1", "t-1", &["bug", "auth"]))
+            .unwrap();
+        store.set(metadata("ws-1", "t-2", &["bug"])).unwrap();
+        store
+            .set(ThreadMetadata {
+                pinned: true,
+                ..metadata("ws-2", "t-3", &["BUG"])
+            })
+            .unwrap();
+
+        let bugs = store.query(&ThreadMetadataQuery {
+            tags: vec!["bug".to_string()],
+            ..ThreadMetadataQuery::default()
+        });
+        assert_eq!(bugs.len(), 3);
+        assert_eq!(bugs[0].thread_id, "t-3");
+
+        let auth = store.query(&ThreadMetadataQuery {
+            workspace_id: Some("ws-1".to_string()),
+            tags: vec!["bug".to_string(), "auth".to_string()],
+            pinned: None,
+        });
+        assert_eq!(auth.len(), 1);
+        assert_eq!(auth[0].thread_id, "t-1");
+    }
+
+    #[test]
+    fn metadata_persists_across_loads() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("thread_metadata.json");
+        let store = ThreadMetadataStore::load(Some(path.clone()));
+        store
+            .set(ThreadMetadata {
+                linked_pull_request: Some(42),
+                linked_worktree_id: Some("wt-1".to_string()),
+                ..metadata("ws-1", "t-1", &[])
+            })
+            .unwrap();
+        store.set(metadata("ws-1", "t-2", &["keep"])).unwrap();
+        assert!(store.delete("ws-1", "t-2").unwrap());
+        assert!(!store.delete("ws-1", "t-2").unwrap());
+
+        let reloaded = ThreadMetadataStore::load(Some(path));
+        let entry = reloaded.get("ws-1", "t-1").unwrap();
+        assert_eq!(entry.linked_pull_request, Some(42));
+        assert_eq!(entry.linked_worktree_id.as_deref(), Some("wt-1"));
+        assert!(reloaded.get("ws-1", "t-2").is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn corrupt_files_are_kept_aside() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("thread_metadata.json");
+        std::fs::write(&path, "[{\"workspaceId\":").unwrap();
+
+        let store = ThreadMetadataStore::load(Some(path.clone()));
+        store.set(metadata("ws-1", "t-1", &["new"])).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("thread_metadata.json.bak")).unwrap(),
+            "[{\"workspaceId\":"
+        );
+        assert!(ThreadMetadataStore::load(Some(path))
+            .get("ws-1", "t-1")
+            .is_some());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn failed_saves_leave_the_store_unchanged() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        let store_dir = dir.join("store");
+        let store = ThreadMetadataStore::load(Some(store_dir.join("thread_metadata.json")));
+        store.set(metadata("ws-1", "t-1", &["keep"])).unwrap();
+        block_store_dir(&store_dir);
+
+        let error = store
+            .set(metadata("ws-1", "t-1", &["changed"]))
+            .unwrap_err();
+        assert!(
+            error.starts_with("failed to write thread metadata"),
+            "{error}"
+        );
+        assert!(store.set(metadata("ws-1", "t-2", &["new"])).is_err());
+        assert!(store.delete("ws-1", "t-1").is_err());
+        assert_eq!(store.get("ws-1", "t-1").unwrap().tags, vec!["keep"]);
+        assert!(store.get("ws-1", "t-2").is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
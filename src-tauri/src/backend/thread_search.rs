@@ -0,0 +1,538 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use ignore::WalkBuilder;
+
+use crate::backend::history_stream::parse_history;
+use crate::backend::thread_export::{parse_rollout, ThreadExportItem};
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const SNIPPET_CONTEXT_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ThreadSearchKind {
+    User,
+    Agent,
+    Command,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadSearchQuery {
+    /// Words are matched as prefixes and must all appear; quoted text must appear verbatim.
+    pub(crate) query: String,
+    pub(crate) workspace_id: Option<String>,
+    /// Inclusive bounds in epoch milliseconds.
+    pub(crate) since: Option<i64>,
+    pub(crate) until: Option<i64>,
+    pub(crate) model: Option<String>,
+    pub(crate) limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadSearchHit {
+    pub(crate) thread_id: String,
+    pub(crate) path: String,
+    /// The `N` of `history-<thread>-N`, as `stream_thread_history` and `fork_thread` number
+    /// messages. Command hits carry the index of the message they follow.
+    pub(crate) item_index: u64,
+    pub(crate) kind: ThreadSearchKind,
+    pub(crate) snippet: String,
+    pub(crate) timestamp: Option<i64>,
+    pub(crate) cwd: Option<String>,
+    pub(crate) model: Option<String>,
+    pub(crate) score: u32,
+}
+
+struct IndexedMessage {
+    item_index: u64,
+    kind: ThreadSearchKind,
+    text: String,
+    /// `text` lowercased, for phrase matching.
+    lowercase: String,
+    timestamp: Option<i64>,
+}
+
+/// One rollout file; rebuilt only when its size or mtime changes.
+struct IndexedRollout {
+    stamp: (u64, Option<SystemTime>),
+    thread_id: String,
+    cwd: Option<String>,
+    model: Option<String>,
+    messages: Vec<IndexedMessage>,
+    /// Token -> (message position, term frequency).
+    terms: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+/// In-memory inverted index over every rollout under the given session roots.
+#[derive(Default)]
+pub(crate) struct ThreadSearchIndex {
+    rollouts: HashMap<PathBuf, IndexedRollout>,
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn parse_timestamp_ms(value: Option<&str>) -> Option<i64> {
+    DateTime::parse_from_rfc3339(value?)
+        .ok()
+        .map(|timestamp| timestamp.timestamp_millis())
+}
+
+/// Reads the id from `session_meta`, falling back to the UUID suffix of
+/// `rollout-<timestamp>-<uuid>.jsonl`.
+fn rollout_thread_id(contents: &str, path: &Path) -> String {
+    let from_meta = contents.lines().take(5).find_map(|line| {
+        let value: Value = serde_json::from_str(line).ok()?;
+        if value.get("type")?.as_str()? != "session_meta" {
+            return None;
+        }
+        Some(value.get("payload")?.get("id")?.as_str()?.to_string())
+    });
+    from_meta.unwrap_or_else(|| {
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("");
+        let start = stem.len().saturating_sub(36);
+        stem.get(start..).unwrap_or(stem).to_string()
+    })
+}
+
+fn index_rollout(path: &Path, contents: &str, stamp: (u64, Option<SystemTime>)) -> IndexedRollout {
+    let thread_id = rollout_thread_id(contents, path);
+    let export = parse_rollout(&thread_id, contents);
+    // The export keeps the same messages as the history stream, in the same order, so
+    // each export message takes the next history index.
+    let mut history = parse_history(&thread_id, contents).into_iter();
+    let mut item_index = None;
+    let mut messages = Vec::new();
+    let mut terms: BTreeMap<String, Vec<(usize, u32)>> = BTreeMap::new();
+    for item in &export.items {
+        let (kind, text, timestamp) = match item {
+            ThreadExportItem::Message {
+                role,
+                text,
+                timestamp,
+            } => {
+                item_index = history.next().map(|message| message.index);
+                let kind = if role == "user" {
+                    ThreadSearchKind::User
+                } else {
+                    ThreadSearchKind::Agent
+                };
+                (kind, text, timestamp)
+            }
+            ThreadExportItem::ToolCall {
+                input, timestamp, ..
+            } => (ThreadSearchKind::Command, input, timestamp),
+            _ => continue,
+        };
+        let Some(item_index) = item_index else {
+            continue;
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        let position = messages.len();
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for token in tokenize(text) {
+            *frequencies.entry(token).or_default() += 1;
+        }
+        for (token, count) in frequencies {
+            terms.entry(token).or_default().push((position, count));
+        }
+        messages.push(IndexedMessage {
+            item_index,
+            kind,
+            text: text.clone(),
+            lowercase: text.to_lowercase(),
+            timestamp: parse_timestamp_ms(timestamp.as_deref()),
+        });
+    }
+    IndexedRollout {
+        stamp,
+        thread_id,
+        cwd: export.session.cwd,
+        model: export.session.model,
+        messages,
+        terms,
+    }
+}
+
+/// The `sessions` and `archived_sessions` directories of each Codex home that exist.
+pub(crate) fn session_search_roots(codex_homes: &[PathBuf]) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for home in codex_homes {
+        for root in [home.join("sessions"), home.join("archived_sessions")] {
+            if root.is_dir() && !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+    }
+    roots
+}
+
+fn rollout_files(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for root in roots {
+        if !root.exists() {
+            continue;
+        }
+        for entry in WalkBuilder::new(root).standard_filters(false).build() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            if entry.path().extension().and_then(|ext| ext.to_str()) == Some("jsonl") {
+                files.push(entry.into_path());
+            }
+        }
+    }
+    files
+}
+
+struct ParsedQuery {
+    words: Vec<String>,
+    phrases: Vec<String>,
+}
+
+fn parse_query(query: &str) -> ParsedQuery {
+    let mut words = Vec::new();
+    let mut phrases = Vec::new();
+    for (index, segment) in query.split('"').enumerate() {
+        if index % 2 == 1 {
+            let phrase = segment.trim().to_lowercase();
+            if !phrase.is_empty() {
+                phrases.push(phrase);
+            }
+        }
+        words.extend(tokenize(segment));
+    }
+    ParsedQuery { words, phrases }
+}
+
+/// A window of `text` around the first case-insensitive match of the lowercase `needle`,
+/// cut on char boundaries and keeping the original case.
+fn make_snippet(text: &str, needle: &str) -> String {
+    let collapsed = |value: &str| value.split_whitespace().collect::<Vec<_>>().join(" ");
+    // Lowercasing can change lengths, so track where each char of `text` starts.
+    let mut lowercase = String::with_capacity(text.len());
+    let mut char_starts = Vec::new();
+    for ch in text.chars() {
+        char_starts.push(lowercase.len());
+        lowercase.extend(ch.to_lowercase());
+    }
+    let Some(byte_offset) = lowercase.find(needle) else {
+        let snippet: String = text.chars().take(SNIPPET_CONTEXT_CHARS * 2).collect();
+        return collapsed(&snippet);
+    };
+    let match_char = char_starts.partition_point(|&start| start <= byte_offset) - 1;
+    let match_end = char_starts.partition_point(|&start| start < byte_offset + needle.len());
+    let start = match_char.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let total = char_starts.len();
+    let end = (match_end + SNIPPET_CONTEXT_CHARS).min(total);
+    let body: String = text.chars().skip(start).take(end - start).collect();
+    let mut snippet = collapsed(&body);
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < total {
+        snippet.push('…');
+    }
+    snippet
+}
+
+impl ThreadSearchIndex {
+    /// Brings the index in line with the files under `roots`, re-reading only rollouts
+    /// that changed since the last refresh. Rollouts under other roots are kept.
+    pub(crate) fn refresh(&mut self, roots: &[PathBuf]) {
+        let files = rollout_files(roots);
+        let present: HashSet<&PathBuf> = files.iter().collect();
+        self.rollouts.retain(|path, _| {
+            present.contains(path) || !roots.iter().any(|root| path.starts_with(root))
+        });
+        for path in &files {
+            let Ok(metadata) = std::fs::metadata(path) else {
+                continue;
+            };
+            let stamp = (metadata.len(), metadata.modified().ok());
+            if self
+                .rollouts
+                .get(path)
+                .is_some_and(|indexed| indexed.stamp == stamp)
+            {
+                continue;
+            }
+            let Ok(contents) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            self.rollouts
+                .insert(path.clone(), index_rollout(path, &contents, stamp));
+        }
+    }
+
+    /// Searches indexed rollouts under `roots`, optionally limited to threads whose cwd is
+    /// inside `workspace_path`. Best matches come first, newer messages break ties.
+    pub(crate) fn search(
+        &self,
+        query: &ThreadSearchQuery,
+        roots: &[PathBuf],
+        workspace_path: Option<&Path>,
+    ) -> Vec<ThreadSearchHit> {
+        let parsed = parse_query(&query.query);
+        if parsed.words.is_empty() {
+            return Vec::new();
+        }
+        let model = query
+            .model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty());
+        let mut hits = Vec::new();
+        for (path, rollout) in &self.rollouts {
+            if !roots.iter().any(|root| path.starts_with(root)) {
+                continue;
+            }
+            if let Some(workspace_path) = workspace_path {
+                let in_workspace = rollout
+                    .cwd
+                    .as_deref()
+                    .is_some_and(|cwd| Path::new(cwd).starts_with(workspace_path));
+                if !in_workspace {
+                    continue;
+                }
+            }
+            if model.is_some() && rollout.model.as_deref() != model {
+                continue;
+            }
+            let mut scores: Option<HashMap<usize, u32>> = None;
+            for word in &parsed.words {
+                let mut matched: HashMap<usize, u32> = HashMap::new();
+                for (_, postings) in rollout
+                    .terms
+                    .range(word.clone()..)
+                    .take_while(|(token, _)| token.starts_with(word.as_str()))
+                {
+                    for (position, count) in postings {
+                        *matched.entry(*position).or_default() += count;
+                    }
+                }
+                scores = Some(match scores {
+                    None => matched,
+                    Some(previous) => previous
+                        .into_iter()
+                        .filter_map(|(position, score)| {
+                            matched
+                                .get(&position)
+                                .map(|count| (position, score + count))
+                        })
+                        .collect(),
+                });
+            }
+            for (position, score) in scores.unwrap_or_default() {
+                let message = &rollout.messages[position];
+                if !parsed
+                    .phrases
+                    .iter()
+                    .all(|phrase| message.lowercase.contains(phrase.as_str()))
+                {
+                    continue;
+                }
+                let in_range = match message.timestamp {
+                    Some(timestamp) => {
+                        query.since.is_none_or(|since| timestamp >= since)
+                            && query.until.is_none_or(|until| timestamp <= until)
+                    }
+                    None => query.since.is_none() && query.until.is_none(),
+                };
+                if !in_range {
+                    continue;
+                }
+                let needle = parsed.phrases.first().unwrap_or(&parsed.words[0]).as_str();
+                hits.push(ThreadSearchHit {
+                    thread_id: rollout.thread_id.clone(),
+                    path: path.to_string_lossy().to_string(),
+                    item_index: message.item_index,
+                    kind: message.kind,
+                    snippet: make_snippet(&message.text, needle),
+                    timestamp: message.timestamp,
+                    cwd: rollout.cwd.clone(),
+                    model: rollout.model.clone(),
+                    score,
+                });
+            }
+        }
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.timestamp.cmp(&a.timestamp))
+                .then_with(|| a.item_index.cmp(&b.item_index))
+        });
+        hits.truncate(query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).max(1));
+        hits
+    }
+
+    pub(crate) fn indexed_rollouts(&self) -> usize {
+        self.rollouts.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_snippet, ThreadSearchIndex, ThreadSearchKind, ThreadSearchQuery};
+    use serde_json::json;
+    use std::path::{Path, PathBuf};
+    use uuid::Uuid;
+
+    fn write_rollout(root: &Path, thread_id: &str, cwd: &str, model: &str, lines: &[(&str, &str)]) {
+        let dir = root.join("2026").join("09").join("14");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut rows = vec![
+            json!({ "timestamp": "2026-09-14T10:00:00Z", "type": "session_meta",
+                "payload": { "id": thread_id, "cwd": cwd } }),
+            json!({ "timestamp": "2026-09-14T10:00:00Z", "type": "turn_context",
+                "payload": { "model": model } }),
+        ];
+        for (kind, text) in lines {
+            rows.push(
+                json!({ "timestamp": "2026-09-14T10:01:00Z", "type": "event_msg",
+                "payload": { "type": kind, "message": text } }),
+            );
+        }
+        rows.push(
+            json!({ "timestamp": "2026-09-14T10:02:00Z", "type": "response_item",
+            "payload": { "type": "function_call", "name": "shell", "call_id": "c1",
+                "arguments": "{\"command\":[\"rg\",\"redirect_uri\"]}" } }),
+        );
+        let contents = rows
+            .iter()
+            .map(|row| row.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(
+            dir.join(format!("rollout-2026-09-14T10-00-00-{thread_id}.jsonl")),
+            contents,
+        )
+        .unwrap();
+    }
+
+    fn sessions_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        write_rollout(
+            &root,
+            "thread-auth",
+            "/repo/app",
+            "gpt-5",
+            &[
+                ("user_message", "The login redirect loops after auth"),
+                ("agent_message", "Fixed the auth redirect in middleware"),
+            ],
+        );
+        write_rollout(
+            &root,
+            "thread-docs",
+            "/repo/docs",
+            "gpt-5-mini",
+            &[("user_message", "Update the README")],
+        );
+        root
+    }
+
+    fn query(text: &str) -> ThreadSearchQuery {
+        ThreadSearchQuery {
+            query: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_messages_and_commands_with_prefix_terms() {
+        let root = sessions_root();
+        let roots = [root.clone()];
+        let mut index = ThreadSearchIndex::default();
+        index.refresh(&roots);
+        assert_eq!(index.indexed_rollouts(), 2);
+
+        let hits = index.search(&query("auth redir"), &roots, None);
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| hit.thread_id == "thread-auth"));
+        let indices: Vec<u64> = hits.iter().map(|hit| hit.item_index).collect();
+        assert_eq!(indices, vec![1, 2]);
+        assert_eq!(hits[0].snippet, "The login redirect loops after auth");
+        assert_eq!(hits[1].kind, ThreadSearchKind::Agent);
+
+        let mut hits = index.search(&query("redirect_uri"), &roots, None);
+        hits.sort_by(|a, b| a.thread_id.cmp(&b.thread_id));
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| hit.kind == ThreadSearchKind::Command));
+        let indices: Vec<u64> = hits.iter().map(|hit| hit.item_index).collect();
+        assert_eq!(indices, vec![2, 1]);
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn applies_phrase_workspace_model_and_date_filters() {
+        let root = sessions_root();
+        let roots = [root.clone()];
+        let mut index = ThreadSearchIndex::default();
+        index.refresh(&roots);
+
+        assert_eq!(
+            index
+                .search(&query("\"redirect loops\""), &roots, None)
+                .len(),
+            1
+        );
+        assert!(index
+            .search(&query("the"), &roots, Some(Path::new("/repo/docs")))
+            .iter()
+            .all(|hit| hit.thread_id == "thread-docs"));
+        let by_model = ThreadSearchQuery {
+            model: Some("gpt-5-mini".to_string()),
+            ..query("the")
+        };
+        assert_eq!(index.search(&by_model, &roots, None).len(), 1);
+        let too_late = ThreadSearchQuery {
+            since: Some(1_900_000_000_000),
+            ..query("auth")
+        };
+        assert!(index.search(&too_late, &roots, None).is_empty());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn refresh_drops_deleted_rollouts() {
+        let root = sessions_root();
+        let roots = [root.clone()];
+        let mut index = ThreadSearchIndex::default();
+        index.refresh(&roots);
+        std::fs::remove_dir_all(root.join("2026")).unwrap();
+        index.refresh(&roots);
+        assert_eq!(index.indexed_rollouts(), 0);
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn snippet_centers_on_match() {
+        let text = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let snippet = make_snippet(&text, "needle");
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+        assert_eq!(
+            make_snippet("Fix the İstanbul REDIRECT", "redirect"),
+            "Fix the İstanbul REDIRECT"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{
+    ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider,
+};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    ClientConfig, DigitallySignedStruct, Error as TlsError, SignatureScheme,
+};
+
+/// SHA-256 of a DER certificate as colon separated upper-case hex, the form
+/// `openssl x509 -fingerprint -sha256` prints.
+pub(crate) fn cert_fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Lower-case hex without separators, so pasted fingerprints compare regardless of format.
+pub(crate) fn normalize_fingerprint(value: &str) -> String {
+    value
+        .chars()
+        .filter(|ch| ch.is_ascii_hexdigit())
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect()
+}
+
+pub(crate) fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+/// Accepts exactly the certificate with the pinned fingerprint. Hostnames and expiry are
+/// not checked: the pin already identifies the daemon, which uses a self-signed cert.
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: String,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        let presented = normalize_fingerprint(&cert_fingerprint(end_entity.as_ref()));
+        if presented == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(TlsError::General(format!(
+                "remote backend certificate fingerprint {} does not match the pinned one",
+                cert_fingerprint(end_entity.as_ref())
+            )))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+pub(crate) fn pinned_client_config(fingerprint: &str) -> Result<Arc<ClientConfig>, String> {
+    let fingerprint = normalize_fingerprint(fingerprint);
+    if fingerprint.len() != 64 {
+        return Err("TLS fingerprint must be a SHA-256 hash (64 hex digits).".to_string());
+    }
+    let provider = provider();
+    let verifier = PinnedCertVerifier {
+        fingerprint,
+        provider: Arc::clone(&provider),
+    };
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Server name to present for `host:port`. The pinned verifier ignores it, but the
+/// handshake still needs a syntactically valid one.
+pub(crate) fn server_name_for_host(host: &str) -> ServerName<'static> {
+    let host = host.trim();
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    ServerName::try_from(name.to_string())
+        .unwrap_or_else(|_| ServerName::try_from("localhost").expect("valid server name"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_fingerprint, pinned_client_config, server_name_for_host};
+
+    #[test]
+    fn fingerprints_compare_in_any_format() {
+        assert_eq!(normalize_fingerprint("AB:cd:0F"), "abcd0f");
+        assert_eq!(normalize_fingerprint(" ab cd 0f "), "abcd0f");
+        assert!(pinned_client_config("AB:CD").is_err());
+    }
+
+    #[test]
+    fn server_names_strip_ports() {
+        assert_eq!(server_name_for_host("dev-box:4732").to_str(), "dev-box");
+        assert_eq!(server_name_for_host("10.0.0.5:4732").to_str(), "10.0.0.5");
+        assert_eq!(server_name_for_host("[::1]:4732").to_str(), "::1");
+    }
+}
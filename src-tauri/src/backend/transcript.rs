@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::backend::session_log::{workspace_file_stem, RotatingLogFile};
+
+/// Size at which a transcript is rotated; older traffic is kept in `.1` .. `.N`.
+const TRANSCRIPT_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TranscriptDirection {
+    /// Written by us to the app-server's stdin.
+    Out,
+    /// Read from the app-server's stdout.
+    In,
+    /// Read from the app-server's stderr.
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TranscriptEntry {
+    pub(crate) ts: u64,
+    pub(crate) direction: TranscriptDirection,
+    pub(crate) line: String,
+}
+
+/// Transcripts live next to the other app data, one JSONL file per workspace.
+pub(crate) fn transcripts_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("transcripts")
+}
+
+pub(crate) fn transcript_path(dir: &Path, workspace_id: &str) -> PathBuf {
+    dir.join(format!("{}.jsonl", workspace_file_stem(workspace_id)))
+}
+
+/// Appends app-server traffic for one workspace to a rotating JSONL transcript.
+pub(crate) struct TranscriptRecorder {
+    file: Mutex<RotatingLogFile>,
+}
+
+impl TranscriptRecorder {
+    pub(crate) fn open(dir: &Path, workspace_id: &str) -> Result<Self, String> {
+        let file = RotatingLogFile::open_with_limit(
+            transcript_path(dir, workspace_id),
+            TRANSCRIPT_MAX_BYTES,
+        )?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub(crate) fn record(&self, direction: TranscriptDirection, line: &str) {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        let entry = TranscriptEntry {
+            ts,
+            direction,
+            line: line.to_string(),
+        };
+        let Ok(mut serialized) = serde_json::to_string(&entry) else {
+            return;
+        };
+        serialized.push('\n');
+        if let Ok(mut file) = self.file.lock() {
+            file.append(&serialized);
+        }
+    }
+}
+
+pub(crate) fn read_transcript(path: &Path) -> Result<Vec<TranscriptEntry>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: TranscriptEntry = serde_json::from_str(&line)
+            .map_err(|e| format!("Invalid transcript line {}: {e}", index + 1))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// One message we sent, followed by everything the app-server wrote until our next message.
+struct Exchange {
+    method: Option<String>,
+    request_id: Option<Value>,
+    replies: Vec<(TranscriptDirection, String)>,
+    consumed: bool,
+}
+
+/// Answers live client messages from a recorded transcript.
+///
+/// Each incoming message is matched to the next unused recorded message with the same
+/// method, and the lines recorded after it are played back with request ids rewritten
+/// to the ids used by the live client.
+pub(crate) struct TranscriptReplay {
+    preamble: Vec<(TranscriptDirection, String)>,
+    exchanges: Vec<Exchange>,
+    id_map: HashMap<String, Value>,
+}
+
+impl TranscriptReplay {
+    pub(crate) fn new(entries: Vec<TranscriptEntry>) -> Self {
+        let mut preamble = Vec::new();
+        let mut exchanges: Vec<Exchange> = Vec::new();
+        for entry in entries {
+            match entry.direction {
+                TranscriptDirection::Out => {
+                    let value: Value = serde_json::from_str(&entry.line).unwrap_or(Value::Null);
+                    exchanges.push(Exchange {
+                        method: value
+                            .get("method")
+                            .and_then(Value::as_str)
+                            .map(|method| method.to_string()),
+                        request_id: value.get("id").cloned(),
+                        replies: Vec::new(),
+                        consumed: false,
+                    });
+                }
+                direction => match exchanges.last_mut() {
+                    Some(exchange) => exchange.replies.push((direction, entry.line)),
+                    None => preamble.push((direction, entry.line)),
+                },
+            }
+        }
+        Self {
+            preamble,
+            exchanges,
+            id_map: HashMap::new(),
+        }
+    }
+
+    /// Lines the app-server wrote before receiving anything.
+    pub(crate) fn take_preamble(&mut self) -> Vec<(TranscriptDirection, String)> {
+        std::mem::take(&mut self.preamble)
+    }
+
+    pub(crate) fn respond(&mut self, message: &Value) -> Vec<(TranscriptDirection, String)> {
+        let method = message.get("method").and_then(Value::as_str);
+        let live_id = message.get("id").cloned();
+        let Some(exchange) = self
+            .exchanges
+            .iter_mut()
+            .find(|exchange| !exchange.consumed && exchange.method.as_deref() == method)
+        else {
+            return match (method, live_id) {
+                (Some(method), Some(id)) => vec![(
+                    TranscriptDirection::In,
+                    json!({
+                        "id": id,
+                        "error": {
+                            "code": -32601,
+                            "message": format!("no recorded response for {method}"),
+                        }
+                    })
+                    .to_string(),
+                )],
+                _ => Vec::new(),
+            };
+        };
+        exchange.consumed = true;
+        if method.is_some() {
+            if let (Some(recorded_id), Some(live_id)) = (exchange.request_id.as_ref(), live_id) {
+                self.id_map.insert(recorded_id.to_string(), live_id);
+            }
+        }
+
+        let mut output = Vec::new();
+        for (direction, line) in &exchange.replies {
+            if *direction != TranscriptDirection::In {
+                output.push((*direction, line.clone()));
+                continue;
+            }
+            let Ok(mut value) = serde_json::from_str::<Value>(line) else {
+                output.push((*direction, line.clone()));
+                continue;
+            };
+            let is_response = value.get("method").is_none()
+                && (value.get("result").is_some() || value.get("error").is_some());
+            if is_response {
+                let live_id = value
+                    .get("id")
+                    .and_then(|id| self.id_map.remove(&id.to_string()));
+                if let Some(live_id) = live_id {
+                    value["id"] = live_id;
+                }
+            }
+            output.push((*direction, value.to_string()));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{transcript_path, TranscriptDirection, TranscriptEntry, TranscriptReplay};
+    use serde_json::{json, Value};
+    use std::path::Path;
+
+    fn entry(direction: TranscriptDirection, value: Value) -> TranscriptEntry {
+        TranscriptEntry {
+            ts: 0,
+            direction,
+            line: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn replay_rewrites_response_ids_to_live_ids() {
+        let mut replay = TranscriptReplay::new(vec![
+            entry(
+                TranscriptDirection::Out,
+                json!({ "id": 7, "method": "thread/list", "params": {} }),
+            ),
+            entry(
+                TranscriptDirection::In,
+                json!({ "method": "codex/event", "params": {} }),
+            ),
+            entry(
+                TranscriptDirection::In,
+                json!({ "id": 7, "result": { "data": [] } }),
+            ),
+        ]);
+        let output = replay.respond(&json!({ "id": 1, "method": "thread/list", "params": {} }));
+        assert_eq!(output.len(), 2);
+        let response: Value = serde_json::from_str(&output[1].1).unwrap();
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(response["result"], json!({ "data": [] }));
+    }
+
+    #[test]
+    fn replay_answers_unknown_requests_with_error() {
+        let mut replay = TranscriptReplay::new(Vec::new());
+        let output = replay.respond(&json!({ "id": 3, "method": "model/list" }));
+        assert_eq!(output.len(), 1);
+        let response: Value = serde_json::from_str(&output[0].1).unwrap();
+        assert_eq!(response["id"], json!(3));
+        assert!(response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("model/list"));
+    }
+
+    #[test]
+    fn replay_consumes_matching_requests_in_order() {
+        let mut replay = TranscriptReplay::new(vec![
+            entry(
+                TranscriptDirection::Out,
+                json!({ "id": 1, "method": "model/list" }),
+            ),
+            entry(
+                TranscriptDirection::In,
+                json!({ "id": 1, "result": { "data": ["a"] } }),
+            ),
+            entry(
+                TranscriptDirection::Out,
+                json!({ "id": 2, "method": "model/list" }),
+            ),
+            entry(
+                TranscriptDirection::In,
+                json!({ "id": 2, "result": { "data": ["b"] } }),
+            ),
+        ]);
+        let first = replay.respond(&json!({ "id": 10, "method": "model/list" }));
+        let second = replay.respond(&json!({ "id": 11, "method": "model/list" }));
+        let first: Value = serde_json::from_str(&first[0].1).unwrap();
+        let second: Value = serde_json::from_str(&second[0].1).unwrap();
+        assert_eq!(first["result"]["data"], json!(["a"]));
+        assert_eq!(second["id"], json!(11));
+        assert_eq!(second["result"]["data"], json!(["b"]));
+    }
+
+    #[test]
+    fn transcript_path_sanitizes_workspace_id() {
+        assert_eq!(
+            transcript_path(Path::new("/data"), "../ws 1"),
+            Path::new("/data").join("___ws_1.jsonl")
+        );
+    }
+}
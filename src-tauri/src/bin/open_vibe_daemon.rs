@@ -9,12 +9,17 @@ mod codex_args;
 mod codex_config;
 #[path = "../codex_home.rs"]
 mod codex_home;
+#[path = "../daemon/mod.rs"]
+mod daemon;
 #[path = "../file_io.rs"]
 mod file_io;
 #[path = "../file_ops.rs"]
 mod file_ops;
 #[path = "../file_policy.rs"]
 mod file_policy;
+#[allow(dead_code)]
+#[path = "../git_utils.rs"]
+mod git_utils;
 #[path = "../rules.rs"]
 mod rules;
 #[path = "../storage.rs"]
@@ -26,33 +31,75 @@ mod types;
 mod utils;
 
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Map, Value};
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 use std::io::Read;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use ignore::WalkBuilder;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
 use tokio::process::Command;
 use tokio::sync::{broadcast, mpsc, Mutex, OnceCell};
+use tokio_rustls::TlsAcceptor;
 use utils::{git_env_path, resolve_git_binary};
 use uuid::Uuid;
 
-use backend::app_server::{spawn_workspace_session, WorkspaceSession};
+use backend::app_server::{
+    install_session, live_session, reap_idle_sessions, spawn_workspace_session, ParkedThreads,
+    SessionMap, SessionServices, WorkspaceSession, IDLE_REAP_INTERVAL,
+};
+use backend::approvals::{query_audit_log, ApprovalAuditEntry};
+use backend::checkpoints::{diff_checkpoint, list_checkpoints, restore_checkpoint};
+use backend::event_bus::EventBus;
 use backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use backend::fan_out::{start_fan_out, FanOutRequest};
+use backend::git_ops;
+use backend::headless::{progress_event, run_headless_prompt, HeadlessRequest, HeadlessResult};
+use backend::message_queue::{
+    queue_changed_event, run_queue_dispatcher, start_user_turn, MessageQueue, OutgoingMessage,
+    QueueAction, ThreadQueue,
+};
+use backend::orphaned_sessions::find_orphaned_sessions;
+use backend::protocol::{
+    parse_response, ApprovalPolicy, CollaborationModeListParams, ModelListParams,
+    ReviewStartParams, SkillsListParams, ThreadArchiveParams, ThreadListParams, ThreadResumeParams,
+    ThreadStartParams, TurnInterruptParams,
+};
+use backend::sandbox_profiles::{
+    replace_sandbox_profiles, shared_sandbox_profiles, validate_sandbox_profiles,
+    SharedSandboxProfiles,
+};
+use backend::session_log::{self, query_workspace_log, SessionLogEntry, SessionLogFilter};
+use backend::terminal::{
+    close_terminal, open_terminal, resize_terminal, write_terminal, TerminalSessions,
+};
+use backend::thread_export::{export_rollout_file, find_rollout_file, ThreadExportFormat};
+use backend::thread_fork::{fork_thread, ForkHost};
+use backend::thread_metadata::{ThreadMetadata, ThreadMetadataQuery, ThreadMetadataStore};
+use backend::thread_search::{
+    session_search_roots, ThreadSearchHit, ThreadSearchIndex, ThreadSearchQuery,
+};
+use backend::transcript;
+use daemon::event_replay::{
+    Backlog, EventReplay, ReplayEvent, ReplayFilter, Sequenced, DEFAULT_REPLAY_CAPACITY,
+};
+use daemon::tls;
 use storage::{read_settings, read_workspaces, write_settings, write_workspaces};
 use types::{
-    AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
-    WorktreeSetupStatus,
+    AppSettings, OrphanedSession, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
+    WorktreeInfo, WorktreeSetupStatus,
 };
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
+/// Clients that connect but never finish the TLS handshake are dropped after this long.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 const WORKTREE_SETUP_MARKERS_DIR: &str = "worktree-setup";
 const WORKTREE_SETUP_MARKER_EXT: &str = "ran";
 
@@ -72,43 +119,69 @@ fn normalize_setup_script(script: Option<String>) -> Option<String> {
 
 #[derive(Clone)]
 struct DaemonEventSink {
-    tx: broadcast::Sender<DaemonEvent>,
+    replay: Arc<EventReplay<DaemonEvent>>,
 }
 
 #[derive(Clone)]
 enum DaemonEvent {
     AppServer(AppServerEvent),
-    #[allow(dead_code)]
     TerminalOutput(TerminalOutput),
+    TerminalExit(TerminalExit),
+}
+
+impl ReplayEvent for DaemonEvent {
+    fn workspace_id(&self) -> &str {
+        match self {
+            DaemonEvent::AppServer(event) => &event.workspace_id,
+            DaemonEvent::TerminalOutput(event) => &event.workspace_id,
+            DaemonEvent::TerminalExit(event) => &event.workspace_id,
+        }
+    }
+
+    /// Terminal output is too chatty to replay; it would push app-server events out of
+    /// the workspace's buffer.
+    fn replayable(&self) -> bool {
+        !matches!(self, DaemonEvent::TerminalOutput(_))
+    }
 }
 
 impl EventSink for DaemonEventSink {
     fn emit_app_server_event(&self, event: AppServerEvent) {
-        let _ = self.tx.send(DaemonEvent::AppServer(event));
+        self.replay.publish(DaemonEvent::AppServer(event));
     }
 
     fn emit_terminal_output(&self, event: TerminalOutput) {
-        let _ = self.tx.send(DaemonEvent::TerminalOutput(event));
+        self.replay.publish(DaemonEvent::TerminalOutput(event));
     }
 
-    fn emit_terminal_exit(&self, _event: TerminalExit) {}
+    fn emit_terminal_exit(&self, event: TerminalExit) {
+        self.replay.publish(DaemonEvent::TerminalExit(event));
+    }
 }
 
 struct DaemonConfig {
     listen: SocketAddr,
     token: Option<String>,
     data_dir: PathBuf,
+    tls: bool,
 }
 
 struct DaemonState {
     data_dir: PathBuf,
     workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: Arc<SessionMap>,
+    parked_threads: Arc<ParkedThreads>,
     global_session: OnceCell<Arc<WorkspaceSession>>,
     storage_path: PathBuf,
     settings_path: PathBuf,
     app_settings: Mutex<AppSettings>,
+    event_bus: Arc<EventBus>,
     event_sink: DaemonEventSink,
+    message_queue: Arc<MessageQueue>,
+    sandbox_profiles: SharedSandboxProfiles,
+    thread_metadata: Arc<ThreadMetadataStore>,
+    thread_search: Arc<std::sync::Mutex<ThreadSearchIndex>>,
+    terminal_sessions: TerminalSessions,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -123,15 +196,37 @@ impl DaemonState {
         let settings_path = config.data_dir.join("settings.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        let sandbox_profiles = shared_sandbox_profiles(app_settings.sandbox_profiles.clone());
         Self {
             data_dir: config.data_dir.clone(),
             workspaces: Mutex::new(workspaces),
-            sessions: Mutex::new(HashMap::new()),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            parked_threads: Arc::new(std::sync::Mutex::new(HashMap::new())),
             global_session: OnceCell::new(),
             storage_path,
             settings_path,
             app_settings: Mutex::new(app_settings),
+            event_bus: EventBus::new(),
             event_sink,
+            message_queue: MessageQueue::load(Some(config.data_dir.join("message_queue.json"))),
+            sandbox_profiles,
+            thread_metadata: ThreadMetadataStore::load(Some(
+                config.data_dir.join("thread_metadata.json"),
+            )),
+            thread_search: Arc::new(std::sync::Mutex::new(ThreadSearchIndex::default())),
+            terminal_sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn session_services(&self) -> SessionServices {
+        let record_transcripts = self.app_settings.lock().await.record_app_server_transcripts;
+        SessionServices {
+            event_bus: self.event_bus.clone(),
+            transcript_dir: record_transcripts.then(|| transcript::transcripts_dir(&self.data_dir)),
+            log_dir: Some(session_log::logs_dir(&self.data_dir)),
+            sandbox_profiles: self.sandbox_profiles.clone(),
+            sessions: Arc::downgrade(&self.sessions),
+            parked_threads: self.parked_threads.clone(),
         }
     }
 
@@ -145,8 +240,7 @@ impl DaemonState {
             return;
         };
 
-        let mut child = session.child.lock().await;
-        let _ = child.kill().await;
+        session.kill().await;
     }
 
     async fn list_workspaces(&self) -> Vec<WorkspaceInfo> {
@@ -216,6 +310,7 @@ impl DaemonState {
             codex_args,
             codex_home,
             client_version,
+            self.session_services().await,
             self.event_sink.clone(),
         )
         .await?;
@@ -341,6 +436,7 @@ impl DaemonState {
             codex_args,
             codex_home,
             client_version,
+            self.session_services().await,
             self.event_sink.clone(),
         )
         .await?;
@@ -472,6 +568,7 @@ impl DaemonState {
             let list = {
                 let mut workspaces = self.workspaces.lock().await;
                 for workspace_id in ids_to_remove {
+                    self.event_sink.replay.remove_workspace(&workspace_id);
                     workspaces.remove(&workspace_id);
                 }
                 workspaces.values().cloned().collect::<Vec<_>>()
@@ -529,6 +626,7 @@ impl DaemonState {
         let _ = run_git_command(&parent_path, &["worktree", "prune", "--expire", "now"]).await;
 
         self.kill_session(&entry.id).await;
+        self.event_sink.replay.remove_workspace(&entry.id);
 
         let list = {
             let mut workspaces = self.workspaces.lock().await;
@@ -651,6 +749,7 @@ impl DaemonState {
                 codex_args,
                 codex_home,
                 client_version,
+                self.session_services().await,
                 self.event_sink.clone(),
             )
             .await
@@ -771,6 +870,7 @@ impl DaemonState {
     ) -> Result<WorkspaceInfo, String> {
         let mut settings = settings;
         settings.worktree_setup_script = normalize_setup_script(settings.worktree_setup_script);
+        validate_sandbox_profiles(&settings.sandbox_profiles)?;
 
         let (
             previous_entry,
@@ -844,6 +944,7 @@ impl DaemonState {
                 codex_args,
                 codex_home,
                 client_version.clone(),
+                self.session_services().await,
                 self.event_sink.clone(),
             )
             .await
@@ -861,8 +962,7 @@ impl DaemonState {
                 .await
                 .insert(entry_snapshot.id.clone(), new_session)
             {
-                let mut child = old_session.child.lock().await;
-                let _ = child.kill().await;
+                old_session.kill().await;
             }
         }
         if codex_home_changed || codex_args_changed {
@@ -897,6 +997,7 @@ impl DaemonState {
                     next_child_args,
                     next_child_home,
                     client_version.clone(),
+                    self.session_services().await,
                     self.event_sink.clone(),
                 )
                 .await
@@ -916,11 +1017,22 @@ impl DaemonState {
                     .await
                     .insert(child.id.clone(), new_session)
                 {
-                    let mut child = old_session.child.lock().await;
-                    let _ = child.kill().await;
+                    old_session.kill().await;
                 }
             }
         }
+        if let Some(session) = self.sessions.lock().await.get(&id) {
+            session
+                .approvals
+                .set_rules(entry_snapshot.settings.approval_rules.clone());
+            session
+                .sandbox_profiles
+                .set_workspace_profiles(entry_snapshot.settings.sandbox_profiles.clone());
+            session.checkpoints_enabled.store(
+                !entry_snapshot.settings.disable_checkpoints,
+                Ordering::SeqCst,
+            );
+        }
         if worktree_setup_script_changed && !entry_snapshot.kind.is_worktree() {
             let child_ids = child_entries
                 .iter()
@@ -1012,6 +1124,7 @@ impl DaemonState {
                 codex_args,
                 codex_home,
                 client_version,
+                self.session_services().await,
                 self.event_sink.clone(),
             )
             .await
@@ -1029,8 +1142,7 @@ impl DaemonState {
                 .await
                 .insert(entry_snapshot.id.clone(), new_session)
             {
-                let mut child = old_session.child.lock().await;
-                let _ = child.kill().await;
+                old_session.kill().await;
             }
         }
         Ok(WorkspaceInfo {
@@ -1047,11 +1159,12 @@ impl DaemonState {
     }
 
     async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
+        let observed = self.sessions.lock().await.get(&id).cloned();
+        if observed
+            .as_ref()
+            .is_some_and(|session| session.is_alive() || session.is_respawning())
         {
-            let sessions = self.sessions.lock().await;
-            if sessions.contains_key(&id) {
-                return Ok(());
-            }
+            return Ok(());
         }
 
         let entry = {
@@ -1087,11 +1200,12 @@ impl DaemonState {
             codex_args,
             codex_home,
             client_version,
+            self.session_services().await,
             self.event_sink.clone(),
         )
         .await?;
 
-        self.sessions.lock().await.insert(id, session);
+        install_session(&self.sessions, observed.as_ref(), session).await;
         Ok(())
     }
 
@@ -1108,20 +1222,70 @@ impl DaemonState {
         let _ = codex_config::write_steer_enabled(settings.experimental_steer_enabled);
         let _ =
             codex_config::write_unified_exec_enabled(settings.experimental_unified_exec_enabled);
+        validate_sandbox_profiles(&settings.sandbox_profiles)?;
         write_settings(&self.settings_path, &settings)?;
+        replace_sandbox_profiles(&self.sandbox_profiles, settings.sandbox_profiles.clone());
         let mut current = self.app_settings.lock().await;
         *current = settings.clone();
         Ok(settings)
     }
 
+    /// Returns the session for a workspace, reconnecting it if it was shut down
+    /// while idle or is no longer running.
     async fn get_session(&self, workspace_id: &str) -> Result<Arc<WorkspaceSession>, String> {
-        let sessions = self.sessions.lock().await;
-        sessions
-            .get(workspace_id)
-            .cloned()
+        if let Some(session) = live_session(&self.sessions, workspace_id).await? {
+            return Ok(session);
+        }
+        self.connect_workspace(workspace_id.to_string(), daemon_client_version())
+            .await?;
+        live_session(&self.sessions, workspace_id)
+            .await?
             .ok_or("workspace not connected".to_string())
     }
 
+    async fn get_workspace_logs(
+        &self,
+        workspace_id: String,
+        filter: SessionLogFilter,
+    ) -> Vec<SessionLogEntry> {
+        let session = self.sessions.lock().await.get(&workspace_id).cloned();
+        let log_dir = session_log::logs_dir(&self.data_dir);
+        query_workspace_log(
+            session.as_ref().map(|session| &session.log),
+            Some(&log_dir),
+            &workspace_id,
+            &filter,
+        )
+    }
+
+    async fn get_approval_audit_log(
+        &self,
+        workspace_id: String,
+        limit: Option<usize>,
+    ) -> Vec<ApprovalAuditEntry> {
+        let session = self.sessions.lock().await.get(&workspace_id).cloned();
+        let log_dir = session_log::logs_dir(&self.data_dir);
+        query_audit_log(
+            session.as_ref().map(|session| &session.approvals),
+            Some(&log_dir),
+            &workspace_id,
+            limit,
+        )
+    }
+
+    async fn reap_idle_sessions(&self) {
+        let timeout_minutes = self.app_settings.lock().await.idle_session_timeout_minutes;
+        if timeout_minutes == 0 {
+            return;
+        }
+        reap_idle_sessions(
+            &self.sessions,
+            Duration::from_secs(timeout_minutes * 60),
+            &self.event_sink,
+        )
+        .await;
+    }
+
     async fn ensure_global_session(
         &self,
         client_version: &str,
@@ -1159,6 +1323,7 @@ impl DaemonState {
             codex_args,
             codex_home,
             client_version.to_string(),
+            self.session_services().await,
             self.event_sink.clone(),
         )
         .await?;
@@ -1170,6 +1335,14 @@ impl DaemonState {
         Ok(session)
     }
 
+    async fn workspace_entry(&self, workspace_id: &str) -> Result<WorkspaceEntry, String> {
+        let workspaces = self.workspaces.lock().await;
+        workspaces
+            .get(workspace_id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())
+    }
+
     async fn list_workspace_files(&self, workspace_id: String) -> Result<Vec<String>, String> {
         let entry = {
             let workspaces = self.workspaces.lock().await;
@@ -1257,11 +1430,11 @@ impl DaemonState {
 
     async fn start_thread(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let params = json!({
-            "cwd": session.entry.path,
-            "approvalPolicy": "on-request"
-        });
-        session.send_request("thread/start", params).await
+        let params = ThreadStartParams {
+            cwd: session.entry.path.clone(),
+            approval_policy: ApprovalPolicy::OnRequest,
+        };
+        session.request(&params).await
     }
 
     async fn resume_thread(
@@ -1270,10 +1443,24 @@ impl DaemonState {
         thread_id: String,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let params = json!({
-            "threadId": thread_id
-        });
-        session.send_request("thread/resume", params).await
+        session.request(&ThreadResumeParams { thread_id }).await
+    }
+
+    async fn scan_orphaned_sessions(
+        &self,
+        client_version: &str,
+    ) -> Result<Vec<OrphanedSession>, String> {
+        let global = self.ensure_global_session(client_version).await?;
+        let workspace_sessions: Vec<Arc<WorkspaceSession>> = self
+            .sessions
+            .lock()
+            .await
+            .values()
+            .filter(|session| session.is_alive())
+            .cloned()
+            .collect();
+        let workspaces = self.workspaces.lock().await.clone();
+        find_orphaned_sessions(&global, &workspace_sessions, workspaces).await
     }
 
     async fn list_threads(
@@ -1281,13 +1468,26 @@ impl DaemonState {
         workspace_id: String,
         cursor: Option<String>,
         limit: Option<u32>,
+        timeout_ms: Option<u32>,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let params = json!({
-            "cursor": cursor,
-            "limit": limit
-        });
-        session.send_request("thread/list", params).await
+        let params = ThreadListParams { cursor, limit };
+        match timeout_ms {
+            Some(timeout_ms) => {
+                session
+                    .request_with_timeout(&params, Some(Duration::from_millis(timeout_ms.into())))
+                    .await
+            }
+            None => session.request(&params).await,
+        }
+    }
+
+    async fn cancel_app_server_requests(&self, workspace_id: &str, method: &str) -> usize {
+        let session = self.sessions.lock().await.get(workspace_id).cloned();
+        match session {
+            Some(session) => session.cancel_requests(method).await,
+            None => 0,
+        }
     }
 
     async fn list_threads_global(&self, client_version: String) -> Result<Value, String> {
@@ -1298,26 +1498,14 @@ impl DaemonState {
         let mut pages = 0;
         loop {
             pages += 1;
-            let params = json!({
-                "cursor": cursor,
-                "limit": 2000,
-            });
-            let response = session.send_request("thread/list", params).await?;
-            let result = response.get("result").unwrap_or(&response);
-            let data = result
-                .get("data")
-                .and_then(|value| value.as_array())
-                .cloned()
-                .unwrap_or_default();
-            let next_cursor = result
-                .get("nextCursor")
-                .or_else(|| result.get("next_cursor"))
-                .and_then(|value| value.as_str())
-                .map(|value| value.to_string());
-            if !data.is_empty() {
-                all_threads.extend(data);
-            }
-            cursor = next_cursor;
+            let params = ThreadListParams {
+                cursor: cursor.take(),
+                limit: Some(2000),
+            };
+            let response = session.request(&params).await?;
+            let page = parse_response::<ThreadListParams>(&response)?;
+            all_threads.extend(page.data);
+            cursor = page.next_cursor;
             if cursor.is_none() {
                 break;
             }
@@ -1340,8 +1528,7 @@ impl DaemonState {
         thread_id: String,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let params = json!({ "threadId": thread_id });
-        session.send_request("thread/archive", params).await
+        session.request(&ThreadArchiveParams { thread_id }).await
     }
 
     async fn send_user_message(
@@ -1352,67 +1539,34 @@ impl DaemonState {
         model: Option<String>,
         effort: Option<String>,
         access_mode: Option<String>,
+        sandbox_profile: Option<String>,
         images: Option<Vec<String>>,
         collaboration_mode: Option<Value>,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-        let sandbox_policy = match access_mode.as_str() {
-            "full-access" => json!({
-                "type": "dangerFullAccess"
-            }),
-            "read-only" => json!({
-                "type": "readOnly"
-            }),
-            _ => json!({
-                "type": "workspaceWrite",
-                "writableRoots": [session.entry.path],
-                "networkAccess": true
-            }),
+        session.approvals.mark_human_interaction();
+        let message = OutgoingMessage {
+            text,
+            model,
+            effort,
+            access_mode,
+            sandbox_profile,
+            images,
+            collaboration_mode,
         };
+        start_user_turn(&session, thread_id, message).await
+    }
 
-        let approval_policy = if access_mode == "full-access" {
-            "never"
-        } else {
-            "on-request"
-        };
-
-        let trimmed_text = text.trim();
-        let mut input: Vec<Value> = Vec::new();
-        if !trimmed_text.is_empty() {
-            input.push(json!({ "type": "text", "text": trimmed_text }));
-        }
-        if let Some(paths) = images {
-            for path in paths {
-                let trimmed = path.trim();
-                if trimmed.is_empty() {
-                    continue;
-                }
-                if trimmed.starts_with("data:")
-                    || trimmed.starts_with("http://")
-                    || trimmed.starts_with("https://")
-                {
-                    input.push(json!({ "type": "image", "url": trimmed }));
-                } else {
-                    input.push(json!({ "type": "localImage", "path": trimmed }));
-                }
-            }
-        }
-        if input.is_empty() {
-            return Err("empty user message".to_string());
-        }
-
-        let params = json!({
-            "threadId": thread_id,
-            "input": input,
-            "cwd": session.entry.path,
-            "approvalPolicy": approval_policy,
-            "sandboxPolicy": sandbox_policy,
-            "model": model,
-            "effort": effort,
-            "collaborationMode": collaboration_mode,
-        });
-        session.send_request("turn/start", params).await
+    fn update_message_queue(
+        &self,
+        workspace_id: &str,
+        thread_id: &str,
+        action: QueueAction,
+    ) -> Result<ThreadQueue, String> {
+        let queue = self.message_queue.apply(workspace_id, thread_id, action)?;
+        self.event_sink
+            .emit_app_server_event(queue_changed_event(&queue));
+        Ok(queue)
     }
 
     async fn turn_interrupt(
@@ -1422,11 +1576,10 @@ impl DaemonState {
         turn_id: String,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let params = json!({
-            "threadId": thread_id,
-            "turnId": turn_id
-        });
-        session.send_request("turn/interrupt", params).await
+        session.approvals.mark_human_interaction();
+        session
+            .request(&TurnInterruptParams { thread_id, turn_id })
+            .await
     }
 
     async fn start_review(
@@ -1437,26 +1590,23 @@ impl DaemonState {
         delivery: Option<String>,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let mut params = Map::new();
-        params.insert("threadId".to_string(), json!(thread_id));
-        params.insert("target".to_string(), target);
-        if let Some(delivery) = delivery {
-            params.insert("delivery".to_string(), json!(delivery));
-        }
-        session
-            .send_request("review/start", Value::Object(params))
-            .await
+        let params = ReviewStartParams {
+            thread_id,
+            target,
+            delivery,
+        };
+        session.request(&params).await
     }
 
     async fn model_list(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        session.send_request("model/list", json!({})).await
+        session.request(&ModelListParams::default()).await
     }
 
     async fn collaboration_mode_list(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
         session
-            .send_request("collaborationMode/list", json!({}))
+            .request(&CollaborationModeListParams::default())
             .await
     }
 
@@ -1469,10 +1619,40 @@ impl DaemonState {
 
     async fn skills_list(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let params = json!({
-            "cwd": session.entry.path
-        });
-        session.send_request("skills/list", params).await
+        let params = SkillsListParams {
+            cwd: session.entry.path.clone(),
+        };
+        session.request(&params).await
+    }
+
+    async fn run_headless_prompt(
+        &self,
+        workspace_id: String,
+        request: HeadlessRequest,
+        run_id: Option<String>,
+    ) -> Result<HeadlessResult, String> {
+        let session = self.get_session(&workspace_id).await?;
+        run_headless_prompt(&session, request, |progress| {
+            if let Some(run_id) = run_id.as_deref() {
+                self.event_sink.emit_app_server_event(progress_event(
+                    &workspace_id,
+                    run_id,
+                    &progress,
+                ));
+            }
+        })
+        .await
+    }
+
+    async fn generate_commit_message(&self, workspace_id: String) -> Result<String, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        let prompt = git_ops::commit_message_prompt(&entry)?;
+        let session = self.get_session(&workspace_id).await?;
+        let result = run_headless_prompt(&session, HeadlessRequest::new(prompt), |_| {}).await?;
+        if result.text.is_empty() {
+            return Err("No commit message was generated".to_string());
+        }
+        Ok(result.text)
     }
 
     async fn respond_to_server_request(
@@ -1482,6 +1662,7 @@ impl DaemonState {
         result: Value,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
+        session.approvals.mark_human_interaction();
         session.send_response(request_id, result).await?;
         Ok(json!({ "ok": true }))
     }
@@ -1538,6 +1719,87 @@ impl DaemonState {
             .or_else(codex_home::resolve_default_codex_home)
             .ok_or("Unable to resolve CODEX_HOME".to_string())
     }
+
+    /// The default Codex home followed by every workspace override.
+    async fn known_codex_homes(&self) -> Vec<PathBuf> {
+        let mut codex_homes: Vec<PathBuf> = codex_home::resolve_default_codex_home()
+            .into_iter()
+            .collect();
+        let workspaces = self.workspaces.lock().await;
+        for entry in workspaces.values() {
+            let parent_entry = entry
+                .parent_id
+                .as_ref()
+                .and_then(|parent_id| workspaces.get(parent_id));
+            if let Some(home) = codex_home::resolve_workspace_codex_home(entry, parent_entry) {
+                if !codex_homes.contains(&home) {
+                    codex_homes.push(home);
+                }
+            }
+        }
+        codex_homes
+    }
+
+    /// Looks a thread's rollout up in the Codex homes this daemon knows about, so clients
+    /// never name files on the daemon host directly.
+    async fn resolve_rollout_path(&self, thread_id: &str) -> Result<PathBuf, String> {
+        let codex_homes = self.known_codex_homes().await;
+        let thread_id = thread_id.to_string();
+        tokio::task::spawn_blocking(move || find_rollout_file(&codex_homes, &thread_id))
+            .await
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| "rollout not found for thread".to_string())
+    }
+
+    async fn search_threads(
+        &self,
+        query: ThreadSearchQuery,
+    ) -> Result<Vec<ThreadSearchHit>, String> {
+        let (codex_homes, workspace_path) = match query.workspace_id.as_deref() {
+            Some(workspace_id) => (
+                vec![self.resolve_codex_home_for_workspace(workspace_id).await?],
+                Some(self.resolve_workspace_root(workspace_id).await?),
+            ),
+            None => (self.known_codex_homes().await, None),
+        };
+        let index = Arc::clone(&self.thread_search);
+        tokio::task::spawn_blocking(move || {
+            let search_roots = session_search_roots(&codex_homes);
+            let mut index = index
+                .lock()
+                .map_err(|_| "thread search index unavailable".to_string())?;
+            index.refresh(&search_roots);
+            Ok(index.search(&query, &search_roots, workspace_path.as_deref()))
+        })
+        .await
+        .map_err(|err| err.to_string())?
+    }
+}
+
+/// Lets forks and fan-outs create worktrees and reach sessions on the daemon.
+struct DaemonForkHost<'a> {
+    state: &'a DaemonState,
+    client_version: String,
+}
+
+impl ForkHost for DaemonForkHost<'_> {
+    async fn add_worktree(&self, parent_id: &str, branch: &str) -> Result<WorkspaceInfo, String> {
+        self.state
+            .add_worktree(
+                parent_id.to_string(),
+                branch.to_string(),
+                self.client_version.clone(),
+            )
+            .await
+    }
+
+    async fn remove_worktree(&self, id: &str) -> Result<(), String> {
+        self.state.remove_worktree(id.to_string()).await
+    }
+
+    async fn session(&self, workspace_id: &str) -> Result<Arc<WorkspaceSession>, String> {
+        self.state.get_session(workspace_id).await
+    }
 }
 
 fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
@@ -1918,11 +2180,15 @@ fn default_data_dir() -> PathBuf {
         .join("open-vibe-daemon")
 }
 
+fn daemon_client_version() -> String {
+    format!("daemon-{}", env!("CARGO_PKG_VERSION"))
+}
+
 fn usage() -> String {
     format!(
         "\
-USAGE:\n  open-vibe-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth]\n\n\
-OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --token <token>        Shared token required by clients\n  --insecure-no-auth      Disable auth (dev only)\n  -h, --help             Show this help\n"
+USAGE:\n  open-vibe-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth] [--tls]\n\n\
+OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --token <token>        Shared token required by clients\n  --insecure-no-auth      Disable auth (dev only)\n  --tls                  Serve TLS with a self-signed cert kept in the data dir\n  -h, --help             Show this help\n"
     )
 }
 
@@ -1936,6 +2202,7 @@ fn parse_args() -> Result<DaemonConfig, String> {
         .filter(|value| !value.is_empty());
     let mut insecure_no_auth = false;
     let mut data_dir: Option<PathBuf> = None;
+    let mut tls = false;
 
     let mut args = env::args().skip(1);
     while let Some(arg) = args.next() {
@@ -1968,6 +2235,9 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 insecure_no_auth = true;
                 token = None;
             }
+            "--tls" => {
+                tls = true;
+            }
             _ => return Err(format!("Unknown argument: {arg}")),
         }
     }
@@ -1983,6 +2253,7 @@ fn parse_args() -> Result<DaemonConfig, String> {
         listen,
         token,
         data_dir: data_dir.unwrap_or_else(default_data_dir),
+        tls,
     })
 }
 
@@ -2008,8 +2279,9 @@ fn build_result_response(id: Option<u64>, result: Value) -> Option<String> {
     )
 }
 
-fn build_event_notification(event: DaemonEvent) -> Option<String> {
-    let payload = match event {
+fn build_event_notification(sequenced: Sequenced<DaemonEvent>) -> Option<String> {
+    let seq = sequenced.seq;
+    let mut payload = match sequenced.event {
         DaemonEvent::AppServer(payload) => json!({
             "method": "app-server-event",
             "params": payload,
@@ -2018,7 +2290,12 @@ fn build_event_notification(event: DaemonEvent) -> Option<String> {
             "method": "terminal-output",
             "params": payload,
         }),
+        DaemonEvent::TerminalExit(payload) => json!({
+            "method": "terminal-exit",
+            "params": payload,
+        }),
     };
+    payload["seq"] = json!(seq);
     serde_json::to_string(&payload).ok()
 }
 
@@ -2067,6 +2344,15 @@ fn parse_optional_u32(value: &Value, key: &str) -> Option<u32> {
     }
 }
 
+fn parse_terminal_size(value: &Value) -> (u16, u16) {
+    let dimension = |key: &str, fallback: u32| {
+        parse_optional_u32(value, key)
+            .unwrap_or(fallback)
+            .min(u32::from(u16::MAX)) as u16
+    };
+    (dimension("cols", 80), dimension("rows", 24))
+}
+
 fn parse_optional_string_array(value: &Value, key: &str) -> Option<Vec<String>> {
     match value {
         Value::Object(map) => map
@@ -2294,13 +2580,332 @@ async fn handle_rpc_request(
             let thread_id = parse_string(&params, "threadId")?;
             state.resume_thread(workspace_id, thread_id).await
         }
+        "scan_orphaned_sessions" => {
+            let orphans = state.scan_orphaned_sessions(&client_version).await?;
+            serde_json::to_value(orphans).map_err(|err| err.to_string())
+        }
         "list_threads" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let cursor = parse_optional_string(&params, "cursor");
             let limit = parse_optional_u32(&params, "limit");
-            state.list_threads(workspace_id, cursor, limit).await
+            let timeout_ms = parse_optional_u32(&params, "timeoutMs");
+            state
+                .list_threads(workspace_id, cursor, limit, timeout_ms)
+                .await
+        }
+        "cancel_app_server_requests" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let method = parse_string(&params, "method")?;
+            let canceled = state
+                .cancel_app_server_requests(&workspace_id, &method)
+                .await;
+            Ok(json!(canceled))
         }
         "list_threads_global" => state.list_threads_global(client_version).await,
+        "get_workspace_logs" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let filter: SessionLogFilter = parse_optional_value(&params, "filter")
+                .filter(|value| !value.is_null())
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|err| err.to_string())?
+                .unwrap_or_default();
+            let entries = state.get_workspace_logs(workspace_id, filter).await;
+            serde_json::to_value(entries).map_err(|err| err.to_string())
+        }
+        "get_approval_audit_log" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let limit = parse_optional_u32(&params, "limit").map(|limit| limit as usize);
+            let entries = state.get_approval_audit_log(workspace_id, limit).await;
+            serde_json::to_value(entries).map_err(|err| err.to_string())
+        }
+        "search_threads" => {
+            let query: ThreadSearchQuery = serde_json::from_value(
+                parse_optional_value(&params, "query").ok_or("missing `query`")?,
+            )
+            .map_err(|err| format!("invalid `query`: {err}"))?;
+            let hits = state.search_threads(query).await?;
+            serde_json::to_value(hits).map_err(|err| err.to_string())
+        }
+        "export_thread" => {
+            let thread_id = parse_string(&params, "threadId")?;
+            let format: ThreadExportFormat =
+                serde_json::from_value(Value::String(parse_string(&params, "format")?))
+                    .map_err(|_| "invalid `format`".to_string())?;
+            let path = state.resolve_rollout_path(&thread_id).await?;
+            let rendered = export_rollout_file(&thread_id, &path.to_string_lossy(), format).await?;
+            Ok(Value::String(rendered))
+        }
+        "fan_out_prompt" => {
+            let request: FanOutRequest = serde_json::from_value(
+                parse_optional_value(&params, "request").ok_or("missing `request`")?,
+            )
+            .map_err(|err| format!("invalid `request`: {err}"))?;
+            let host = DaemonForkHost {
+                state,
+                client_version,
+            };
+            let started = start_fan_out(&host, request, state.event_sink.clone()).await?;
+            serde_json::to_value(started).map_err(|err| err.to_string())
+        }
+        "fork_thread" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let message_index = parse_optional_value(&params, "messageIndex")
+                .and_then(|value| value.as_u64())
+                .ok_or("missing or invalid `messageIndex`")?;
+            let worktree_branch = parse_optional_string(&params, "worktreeBranch");
+            let path = state.resolve_rollout_path(&thread_id).await?;
+            let host = DaemonForkHost {
+                state,
+                client_version,
+            };
+            let forked = fork_thread(
+                &host,
+                &workspace_id,
+                &thread_id,
+                &path.to_string_lossy(),
+                message_index,
+                worktree_branch.as_deref(),
+            )
+            .await?;
+            serde_json::to_value(forked).map_err(|err| err.to_string())
+        }
+        "get_message_queue" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let queue = state.message_queue.snapshot(&workspace_id, &thread_id);
+            serde_json::to_value(queue).map_err(|err| err.to_string())
+        }
+        "update_message_queue" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let action: QueueAction = parse_optional_value(&params, "action")
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|err| err.to_string())?
+                .ok_or("missing `action`")?;
+            let queue = state.update_message_queue(&workspace_id, &thread_id, action)?;
+            serde_json::to_value(queue).map_err(|err| err.to_string())
+        }
+        "get_thread_metadata" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let metadata = state.thread_metadata.get(&workspace_id, &thread_id);
+            serde_json::to_value(metadata).map_err(|err| err.to_string())
+        }
+        "set_thread_metadata" => {
+            let metadata: ThreadMetadata = parse_optional_value(&params, "metadata")
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|err| err.to_string())?
+                .ok_or("missing `metadata`")?;
+            let stored = state.thread_metadata.set(metadata)?;
+            serde_json::to_value(stored).map_err(|err| err.to_string())
+        }
+        "delete_thread_metadata" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            state
+                .thread_metadata
+                .delete(&workspace_id, &thread_id)
+                .map(Value::Bool)
+        }
+        "query_thread_metadata" => {
+            let query: ThreadMetadataQuery = parse_optional_value(&params, "query")
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|err| err.to_string())?
+                .unwrap_or_default();
+            serde_json::to_value(state.thread_metadata.query(&query)).map_err(|err| err.to_string())
+        }
+        "list_git_checkpoints" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            let checkpoints = list_checkpoints(&entry, &thread_id).await?;
+            serde_json::to_value(checkpoints).map_err(|err| err.to_string())
+        }
+        "diff_git_checkpoint" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let checkpoint_id = parse_string(&params, "checkpointId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            let diff = diff_checkpoint(&entry, &thread_id, &checkpoint_id).await?;
+            Ok(Value::String(diff))
+        }
+        "restore_git_checkpoint" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let checkpoint_id = parse_string(&params, "checkpointId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            let backup = restore_checkpoint(&entry, &thread_id, &checkpoint_id).await?;
+            serde_json::to_value(backup).map_err(|err| err.to_string())
+        }
+        "get_git_status" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::git_status(&entry)
+        }
+        "get_git_diffs" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            let diffs = git_ops::git_diffs(&entry).await?;
+            serde_json::to_value(diffs).map_err(|err| err.to_string())
+        }
+        "get_commit_message_prompt" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::commit_message_prompt(&entry).map(Value::String)
+        }
+        "generate_commit_message" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state
+                .generate_commit_message(workspace_id)
+                .await
+                .map(Value::String)
+        }
+        "get_git_log" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let limit = parse_optional_u32(&params, "limit").map(|value| value as usize);
+            let entry = state.workspace_entry(&workspace_id).await?;
+            let log = git_ops::git_log(&entry, limit)?;
+            serde_json::to_value(log).map_err(|err| err.to_string())
+        }
+        "get_git_commit_diff" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let sha = parse_string(&params, "sha")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            let diffs = git_ops::commit_diff(&entry, &sha)?;
+            serde_json::to_value(diffs).map_err(|err| err.to_string())
+        }
+        "list_git_roots" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let depth = parse_optional_u32(&params, "depth").map(|value| value as usize);
+            let entry = state.workspace_entry(&workspace_id).await?;
+            serde_json::to_value(git_ops::git_roots(&entry, depth)).map_err(|err| err.to_string())
+        }
+        "stage_git_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::stage_file(&entry, &path).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "stage_git_all" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::stage_all(&entry).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "unstage_git_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::unstage_file(&entry, &path).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "revert_git_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::revert_file(&entry, &path).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "revert_git_all" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::revert_all(&entry).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "commit_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let message = parse_string(&params, "message")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::commit(&entry, &message).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "push_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::push(&entry).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "pull_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::pull(&entry).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "sync_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::sync(&entry).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "list_git_branches" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::list_branches(&entry)
+        }
+        "checkout_git_branch" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::checkout(&entry, &name)?;
+            Ok(json!({ "ok": true }))
+        }
+        "create_git_branch" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            let entry = state.workspace_entry(&workspace_id).await?;
+            git_ops::create_branch(&entry, &name)?;
+            Ok(json!({ "ok": true }))
+        }
+        "terminal_open" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let (cols, rows) = parse_terminal_size(&params);
+            let entry = state.workspace_entry(&workspace_id).await?;
+            let info = open_terminal(
+                &state.terminal_sessions,
+                state.event_sink.clone(),
+                workspace_id,
+                terminal_id,
+                PathBuf::from(&entry.path),
+                cols,
+                rows,
+            )
+            .await?;
+            serde_json::to_value(info).map_err(|err| err.to_string())
+        }
+        "terminal_write" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let data = parse_string(&params, "data")?;
+            write_terminal(&state.terminal_sessions, &workspace_id, &terminal_id, data).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "terminal_resize" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let (cols, rows) = parse_terminal_size(&params);
+            resize_terminal(
+                &state.terminal_sessions,
+                &workspace_id,
+                &terminal_id,
+                cols,
+                rows,
+            )
+            .await?;
+            Ok(json!({ "ok": true }))
+        }
+        "terminal_close" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            close_terminal(&state.terminal_sessions, &workspace_id, &terminal_id).await?;
+            Ok(json!({ "ok": true }))
+        }
         "archive_thread" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
@@ -2313,6 +2918,7 @@ async fn handle_rpc_request(
             let model = parse_optional_string(&params, "model");
             let effort = parse_optional_string(&params, "effort");
             let access_mode = parse_optional_string(&params, "accessMode");
+            let sandbox_profile = parse_optional_string(&params, "sandboxProfile");
             let images = parse_optional_string_array(&params, "images");
             let collaboration_mode = parse_optional_value(&params, "collaborationMode");
             state
@@ -2323,6 +2929,7 @@ async fn handle_rpc_request(
                     model,
                     effort,
                     access_mode,
+                    sandbox_profile,
                     images,
                     collaboration_mode,
                 )
@@ -2363,6 +2970,23 @@ async fn handle_rpc_request(
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.skills_list(workspace_id).await
         }
+        "run_headless_prompt" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let mut request = HeadlessRequest::new(parse_string(&params, "prompt")?);
+            request.output_schema =
+                parse_optional_value(&params, "outputSchema").filter(|value| !value.is_null());
+            if let Some(secs) = parse_optional_u32(&params, "timeoutSecs").filter(|secs| *secs > 0)
+            {
+                request.timeout = Duration::from_secs(u64::from(secs));
+            }
+            request.model = parse_optional_string(&params, "model");
+            request.effort = parse_optional_string(&params, "effort");
+            let run_id = parse_optional_string(&params, "runId");
+            let result = state
+                .run_headless_prompt(workspace_id, request, run_id)
+                .await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
         "respond_to_server_request" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let map = params.as_object().ok_or("missing requestId")?;
@@ -2385,34 +3009,124 @@ async fn handle_rpc_request(
     }
 }
 
+fn send_event(
+    sequenced: Sequenced<DaemonEvent>,
+    filter: &ReplayFilter,
+    delivered: &AtomicU64,
+    out_tx_events: &mpsc::UnboundedSender<String>,
+) -> bool {
+    let seq = sequenced.seq;
+    if filter.allows(sequenced.event.workspace_id()) {
+        if let Some(payload) = build_event_notification(sequenced) {
+            if out_tx_events.send(payload).is_err() {
+                return false;
+            }
+        }
+    }
+    delivered.store(seq, Ordering::SeqCst);
+    true
+}
+
 async fn forward_events(
-    mut rx: broadcast::Receiver<DaemonEvent>,
+    replay: Arc<EventReplay<DaemonEvent>>,
+    mut rx: broadcast::Receiver<Sequenced<DaemonEvent>>,
+    backlog: Backlog<DaemonEvent>,
+    filter: ReplayFilter,
+    delivered: Arc<AtomicU64>,
     out_tx_events: mpsc::UnboundedSender<String>,
 ) {
+    let mut last_seq = backlog.latest_seq;
+    for sequenced in backlog.events {
+        if !send_event(sequenced, &filter, &delivered, &out_tx_events) {
+            return;
+        }
+    }
     loop {
-        let event = match rx.recv().await {
-            Ok(event) => event,
-            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+        let sequenced = match rx.recv().await {
+            Ok(sequenced) => sequenced,
+            Err(broadcast::error::RecvError::Lagged(_)) => {
+                // Recover whatever the channel dropped from the replay buffer.
+                let missed = replay.since(last_seq, &filter);
+                last_seq = missed.latest_seq;
+                for sequenced in missed.events {
+                    if !send_event(sequenced, &filter, &delivered, &out_tx_events) {
+                        return;
+                    }
+                }
+                continue;
+            }
             Err(broadcast::error::RecvError::Closed) => break,
         };
-
-        let Some(payload) = build_event_notification(event) else {
+        if sequenced.seq <= last_seq {
             continue;
-        };
-
-        if out_tx_events.send(payload).is_err() {
+        }
+        last_seq = sequenced.seq;
+        if !send_event(sequenced, &filter, &delivered, &out_tx_events) {
             break;
         }
     }
 }
 
-async fn handle_client(
-    socket: TcpStream,
+/// Starts forwarding events to a client. Replays buffered events after `lastSeq`, from
+/// the start when `streamId` names an older stream, or else continues from `resume_from`
+/// (what the previous subscription delivered). The response to `id` is queued first.
+fn subscribe_client(
+    replay: &Arc<EventReplay<DaemonEvent>>,
+    id: Option<u64>,
+    params: &Value,
+    resume_from: Option<u64>,
+    delivered: &Arc<AtomicU64>,
+    out_tx: &mpsc::UnboundedSender<String>,
+) -> tokio::task::JoinHandle<()> {
+    let filter = match parse_optional_string_array(params, "workspaceIds") {
+        Some(workspace_ids) => ReplayFilter::workspaces(workspace_ids),
+        None => ReplayFilter::default(),
+    };
+    let same_stream = match parse_optional_string(params, "streamId") {
+        Some(stream_id) => stream_id == replay.stream_id(),
+        None => true,
+    };
+    let last_seq = if same_stream {
+        params
+            .get("lastSeq")
+            .and_then(|value| value.as_u64())
+            .or(resume_from)
+    } else {
+        Some(0)
+    };
+    let (rx, backlog) = replay.subscribe_since(last_seq, &filter);
+    let replayed = backlog.events.len();
+    let result = json!({
+        "ok": true,
+        "streamId": replay.stream_id(),
+        "seq": backlog.latest_seq,
+        "replayed": replayed,
+        "truncated": backlog.truncated,
+    });
+    if let Some(response) = build_result_response(id, result) {
+        let _ = out_tx.send(response);
+    }
+    let start_seq = last_seq.map_or(backlog.latest_seq, |seq| seq.min(backlog.latest_seq));
+    delivered.store(start_seq, Ordering::SeqCst);
+    tokio::spawn(forward_events(
+        Arc::clone(replay),
+        rx,
+        backlog,
+        filter,
+        Arc::clone(delivered),
+        out_tx.clone(),
+    ))
+}
+
+async fn handle_client<S>(
+    socket: S,
     config: Arc<DaemonConfig>,
     state: Arc<DaemonState>,
-    events: broadcast::Sender<DaemonEvent>,
-) {
-    let (reader, mut writer) = socket.into_split();
+    events: Arc<EventReplay<DaemonEvent>>,
+) where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(socket);
     let mut lines = BufReader::new(reader).lines();
 
     let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
@@ -2429,11 +3143,17 @@ async fn handle_client(
 
     let mut authenticated = config.token.is_none();
     let mut events_task: Option<tokio::task::JoinHandle<()>> = None;
+    let delivered = Arc::new(AtomicU64::new(0));
 
     if authenticated {
-        let rx = events.subscribe();
-        let out_tx_events = out_tx.clone();
-        events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
+        events_task = Some(subscribe_client(
+            &events,
+            None,
+            &Value::Null,
+            None,
+            &delivered,
+            &out_tx,
+        ));
     }
 
     while let Ok(Some(line)) = lines.next_line().await {
@@ -2477,15 +3197,35 @@ async fn handle_client(
                 let _ = out_tx.send(response);
             }
 
-            let rx = events.subscribe();
-            let out_tx_events = out_tx.clone();
-            events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
+            events_task = Some(subscribe_client(
+                &events,
+                None,
+                &Value::Null,
+                None,
+                &delivered,
+                &out_tx,
+            ));
+
+            continue;
+        }
 
+        if method == "subscribe" {
+            let resume_from = events_task.take().map(|task| {
+                task.abort();
+                delivered.load(Ordering::SeqCst)
+            });
+            events_task = Some(subscribe_client(
+                &events,
+                id,
+                &params,
+                resume_from,
+                &delivered,
+                &out_tx,
+            ));
             continue;
         }
 
-        let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
-        let result = handle_rpc_request(&state, &method, params, client_version).await;
+        let result = handle_rpc_request(&state, &method, params, daemon_client_version()).await;
         let response = match result {
             Ok(result) => build_result_response(id, result),
             Err(message) => build_error_response(id, &message),
@@ -2517,19 +3257,55 @@ fn main() {
         .expect("failed to build tokio runtime");
 
     runtime.block_on(async move {
-        let (events_tx, _events_rx) = broadcast::channel::<DaemonEvent>(2048);
+        let events = Arc::new(EventReplay::<DaemonEvent>::new(DEFAULT_REPLAY_CAPACITY));
         let event_sink = DaemonEventSink {
-            tx: events_tx.clone(),
+            replay: Arc::clone(&events),
         };
         let state = Arc::new(DaemonState::load(&config, event_sink));
         let config = Arc::new(config);
 
+        let dispatcher_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            run_queue_dispatcher(
+                Arc::clone(&dispatcher_state.message_queue),
+                Arc::clone(&dispatcher_state.event_bus),
+                Arc::clone(&dispatcher_state.sessions),
+                dispatcher_state.event_sink.clone(),
+            )
+            .await;
+        });
+
+        let reaper_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(IDLE_REAP_INTERVAL).await;
+                reaper_state.reap_idle_sessions().await;
+            }
+        });
+
+        let tls_acceptor = if config.tls {
+            let (server_config, fingerprint) = tls::load_or_create_server_config(&config.data_dir)
+                .unwrap_or_else(|err| {
+                    eprintln!("{err}");
+                    std::process::exit(1);
+                });
+            eprintln!("TLS certificate fingerprint (SHA-256): {fingerprint}");
+            Some(TlsAcceptor::from(server_config))
+        } else {
+            None
+        };
+
         let listener = TcpListener::bind(config.listen)
             .await
             .unwrap_or_else(|err| panic!("failed to bind {}: {err}", config.listen));
         eprintln!(
-            "open-vibe-daemon listening on {} (data dir: {})",
+            "open-vibe-daemon listening on {}{} (data dir: {})",
             config.listen,
+            if tls_acceptor.is_some() {
+                " with TLS"
+            } else {
+                ""
+            },
             state
                 .storage_path
                 .parent()
@@ -2539,12 +3315,31 @@ fn main() {
 
         loop {
             match listener.accept().await {
-                Ok((socket, _addr)) => {
+                Ok((socket, addr)) => {
                     let config = Arc::clone(&config);
                     let state = Arc::clone(&state);
-                    let events = events_tx.clone();
+                    let events = Arc::clone(&events);
+                    let tls_acceptor = tls_acceptor.clone();
                     tokio::spawn(async move {
-                        handle_client(socket, config, state, events).await;
+                        match tls_acceptor {
+                            Some(acceptor) => {
+                                match tokio::time::timeout(
+                                    TLS_HANDSHAKE_TIMEOUT,
+                                    acceptor.accept(socket),
+                                )
+                                .await
+                                {
+                                    Ok(Ok(stream)) => {
+                                        handle_client(stream, config, state, events).await
+                                    }
+                                    Ok(Err(err)) => {
+                                        eprintln!("TLS handshake with {addr} failed: {err}")
+                                    }
+                                    Err(_) => eprintln!("TLS handshake with {addr} timed out"),
+                                }
+                            }
+                            None => handle_client(socket, config, state, events).await,
+                        }
                     });
                 }
                 Err(_) => continue,
@@ -2552,3 +3347,121 @@ fn main() {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        daemon_client_version, handle_rpc_request, DaemonConfig, DaemonEvent, DaemonEventSink,
+        DaemonState, EventReplay, ReplayFilter, WorkspaceEntry, WorkspaceKind, WorkspaceSettings,
+        DEFAULT_REPLAY_CAPACITY,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    #[cfg(unix)]
+    #[test]
+    fn terminal_rpcs_stream_output_until_closed() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        std::env::set_var("SHELL", "/bin/sh");
+        let config = DaemonConfig {
+            listen: "127.0.0.1:0".parse().expect("listen address"),
+            token: None,
+            data_dir: dir.clone(),
+            tls: false,
+        };
+        let events = Arc::new(EventReplay::<DaemonEvent>::new(DEFAULT_REPLAY_CAPACITY));
+        let state = DaemonState::load(
+            &config,
+            DaemonEventSink {
+                replay: Arc::clone(&events),
+            },
+        );
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            state.workspaces.lock().await.insert(
+                "ws-1".to_string(),
+                WorkspaceEntry {
+                    id: "ws-1".to_string(),
+                    name: "Repo".to_string(),
+                    path: dir.to_string_lossy().to_string(),
+                    codex_bin: None,
+                    kind: WorkspaceKind::Main,
+                    parent_id: None,
+                    worktree: None,
+                    settings: WorkspaceSettings::default(),
+                },
+            );
+            let (mut rx, _) = events.subscribe_since(None, &ReplayFilter::default());
+            let call = |method: &'static str, params: Value| {
+                handle_rpc_request(&state, method, params, daemon_client_version())
+            };
+            let terminal = |extra: Value| {
+                let mut params = json!({ "workspaceId": "ws-1", "terminalId": "term-1" });
+                for (key, value) in extra.as_object().expect("object") {
+                    params[key] = value.clone();
+                }
+                params
+            };
+
+            let opened = call("terminal_open", terminal(json!({ "cols": 80, "rows": 24 })))
+                .await
+                .expect("open terminal");
+            assert_eq!(opened["id"], "term-1");
+            call(
+                "terminal_resize",
+                terminal(json!({ "cols": 100, "rows": 30 })),
+            )
+            .await
+            .expect("resize terminal");
+            // The shell computes the marker, so the echoed input cannot match it.
+            call(
+                "terminal_write",
+                terminal(json!({ "data": "echo open-vibe-$((40 + 2))\n" })),
+            )
+            .await
+            .expect("write terminal");
+            tokio::time::timeout(Duration::from_secs(10), async {
+                let mut output = String::new();
+                while !output.contains("open-vibe-42") {
+                    if let DaemonEvent::TerminalOutput(event) =
+                        rx.recv().await.expect("event").event
+                    {
+                        assert_eq!(event.workspace_id, "ws-1");
+                        assert_eq!(event.terminal_id, "term-1");
+                        output.push_str(&event.data);
+                    }
+                }
+            })
+            .await
+            .expect("terminal output");
+
+            call("terminal_close", terminal(json!({})))
+                .await
+                .expect("close terminal");
+            tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    if let DaemonEvent::TerminalExit(event) = rx.recv().await.expect("event").event
+                    {
+                        assert_eq!(event.terminal_id, "term-1");
+                        return;
+                    }
+                }
+            })
+            .await
+            .expect("terminal exit");
+            assert_eq!(
+                call("terminal_write", terminal(json!({ "data": "ls\n" })))
+                    .await
+                    .unwrap_err(),
+                "Terminal session not found"
+            );
+        });
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,104 @@
+#[path = "../backend"]
+mod backend {
+    #[allow(dead_code)]
+    pub(crate) mod session_log;
+    #[allow(dead_code)]
+    pub(crate) mod transcript;
+}
+
+use serde_json::Value;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use backend::transcript::{read_transcript, TranscriptDirection, TranscriptReplay};
+
+const TRANSCRIPT_ENV: &str = "OPENVIBE_REPLAY_TRANSCRIPT";
+
+fn usage() -> String {
+    format!(
+        "\
+USAGE:\n  open-vibe-replay --transcript <path> [app-server]\n\n\
+OPTIONS:\n  --transcript <path>    JSONL transcript recorded by OpenVibe (or set {TRANSCRIPT_ENV})\n  --version              Print a Codex-style version string\n  -h, --help             Show this help\n\n\
+Other arguments are accepted and ignored so Codex args can be passed through.\n"
+    )
+}
+
+fn parse_args() -> Result<PathBuf, String> {
+    let mut transcript = env::var(TRANSCRIPT_ENV)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{}", usage());
+                std::process::exit(0);
+            }
+            "--version" => {
+                println!("codex-cli 0.0.0 (open-vibe-replay)");
+                std::process::exit(0);
+            }
+            "--transcript" => {
+                let value = args.next().ok_or("--transcript requires a value")?;
+                transcript = Some(PathBuf::from(value.trim()));
+            }
+            _ => {}
+        }
+    }
+
+    transcript.ok_or_else(|| format!("Missing --transcript (or set {TRANSCRIPT_ENV})"))
+}
+
+fn write_lines(lines: Vec<(TranscriptDirection, String)>) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    let mut stderr = io::stderr().lock();
+    for (direction, line) in lines {
+        match direction {
+            TranscriptDirection::Stderr => writeln!(stderr, "{line}")?,
+            _ => writeln!(stdout, "{line}")?,
+        }
+    }
+    stdout.flush()?;
+    stderr.flush()
+}
+
+fn main() {
+    let transcript_path = match parse_args() {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("{err}\n\n{}", usage());
+            std::process::exit(2);
+        }
+    };
+    let entries = match read_transcript(&transcript_path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut replay = TranscriptReplay::new(entries);
+    if write_lines(replay.take_preamble()).is_err() {
+        return;
+    }
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            eprintln!("open-vibe-replay: ignoring invalid JSON input");
+            continue;
+        };
+        if write_lines(replay.respond(&message)).is_err() {
+            break;
+        }
+    }
+}
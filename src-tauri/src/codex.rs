@@ -1,4 +1,3 @@
-use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
 use std::io::ErrorKind;
 use std::path::PathBuf;
@@ -6,19 +5,45 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use tauri::{AppHandle, State};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tauri::{AppHandle, Manager, State};
 use tokio::process::Command;
-use tokio::sync::mpsc;
 use tokio::time::timeout;
 use uuid::Uuid;
 
-pub(crate) use crate::backend::app_server::WorkspaceSession;
 use crate::backend::app_server::{
-    build_codex_command_with_bin, build_codex_path_env, check_codex_installation,
-    spawn_workspace_session as spawn_workspace_session_inner,
+    build_codex_command_with_bin, build_codex_path_env, check_codex_installation, live_session,
+    reap_idle_sessions, spawn_workspace_session as spawn_workspace_session_inner, SessionServices,
+    IDLE_REAP_INTERVAL,
 };
+pub(crate) use crate::backend::app_server::{
+    install_session, ParkedThreads, SessionMap, WorkspaceSession,
+};
+use crate::backend::approvals::{query_audit_log, ApprovalAuditEntry};
 use crate::backend::events::EventSink;
+use crate::backend::fan_out::{start_fan_out, FanOutRequest, FanOutStarted};
+use crate::backend::headless::{
+    extract_json_output, progress_event, run_headless_prompt as run_headless_prompt_inner,
+    HeadlessRequest, HeadlessResult,
+};
+use crate::backend::history_stream::{
+    HistoryStreamParser, RolloutTail, StreamMessage, FOLLOW_POLL_INTERVAL,
+};
+use crate::backend::message_queue::{
+    queue_changed_event, start_user_turn, OutgoingMessage, QueueAction, ThreadQueue,
+};
+use crate::backend::orphaned_sessions::{find_orphaned_sessions, list_all_threads};
+use crate::backend::protocol::{
+    parse_response, ApprovalPolicy, CollaborationModeListParams, ModelListParams,
+    ReviewStartParams, SkillsListParams, ThreadArchiveParams, ThreadListParams, ThreadResumeParams,
+    ThreadStartParams, TurnInterruptParams,
+};
+use crate::backend::session_log::{
+    self, format_log_entries, query_workspace_log, SessionLogEntry, SessionLogFilter,
+};
+use crate::backend::thread_export::{export_rollout_file, ThreadExportFormat};
+use crate::backend::thread_fork::{self, ForkHost, ForkedThread};
+use crate::backend::thread_metadata::{ThreadMetadata, ThreadMetadataQuery};
+use crate::backend::transcript;
 use crate::codex_args::apply_codex_args;
 use crate::codex_config;
 use crate::codex_home::{resolve_default_codex_home, resolve_workspace_codex_home};
@@ -26,7 +51,10 @@ use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
 use crate::rules;
 use crate::state::AppState;
-use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+use crate::types::{
+    OrphanedSession, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
+};
+use crate::workspaces::{add_worktree, connect_workspace_inner, remove_worktree};
 
 const GLOBAL_WORKSPACE_ID: &str = "__global__";
 
@@ -36,101 +64,6 @@ pub(crate) struct HistoryStreamState {
     pub(crate) cancel: Arc<AtomicBool>,
 }
 
-#[derive(Deserialize)]
-struct RolloutLine {
-    #[serde(rename = "type")]
-    kind: Option<String>,
-    payload: Option<Value>,
-}
-
-#[derive(Serialize)]
-struct StreamMessage {
-    id: String,
-    kind: String,
-    role: String,
-    text: String,
-}
-
-fn extract_response_item_text(payload: &Value) -> Option<String> {
-    if let Some(content) = payload.get("content") {
-        if let Some(arr) = content.as_array() {
-            let parts: Vec<String> = arr
-                .iter()
-                .filter_map(|entry| {
-                    entry
-                        .get("text")
-                        .and_then(|text| text.as_str())
-                        .map(|text| text.to_string())
-                })
-                .filter(|text| !text.trim().is_empty())
-                .collect();
-            if !parts.is_empty() {
-                return Some(if parts.len() == 1 {
-                    parts[0].clone()
-                } else {
-                    parts.join("\n")
-                });
-            }
-        } else if let Some(text) = content.as_str() {
-            if !text.trim().is_empty() {
-                return Some(text.to_string());
-            }
-        }
-    }
-    payload
-        .get("text")
-        .and_then(|text| text.as_str())
-        .map(|text| text.to_string())
-}
-
-fn build_stream_message(
-    thread_id: &str,
-    message_index: &mut u64,
-    role: &str,
-    text: &str,
-) -> Option<StreamMessage> {
-    if role != "user" && role != "assistant" {
-        return None;
-    }
-    if text.trim().is_empty() {
-        return None;
-    }
-    *message_index = message_index.saturating_add(1);
-    Some(StreamMessage {
-        id: format!("history-{thread_id}-{message_index}"),
-        kind: "message".to_string(),
-        role: role.to_string(),
-        text: text.to_string(),
-    })
-}
-
-fn extract_event_stream_message(
-    payload: &Value,
-    thread_id: &str,
-    message_index: &mut u64,
-) -> Option<StreamMessage> {
-    let event_type = payload.get("type")?.as_str()?;
-    let (role, text) = match event_type {
-        "user_message" => ("user", payload.get("message")?.as_str()?),
-        "agent_message" => ("assistant", payload.get("message")?.as_str()?),
-        _ => return None,
-    };
-    build_stream_message(thread_id, message_index, role, text)
-}
-
-fn extract_response_item_stream_message(
-    payload: &Value,
-    thread_id: &str,
-    message_index: &mut u64,
-) -> Option<StreamMessage> {
-    if payload.get("type")?.as_str()? != "message" {
-        return None;
-    }
-    let role = payload.get("role")?.as_str()?;
-    let text = extract_response_item_text(payload)?;
-    build_stream_message(thread_id, message_index, role, &text)
-}
-
 pub(crate) async fn spawn_workspace_session(
     entry: WorkspaceEntry,
     default_codex_bin: Option<String>,
@@ -139,6 +72,14 @@ pub(crate) async fn spawn_workspace_session(
     codex_home: Option<PathBuf>,
 ) -> Result<Arc<WorkspaceSession>, String> {
     let client_version = app_handle.package_info().version.to_string();
+    let services = SessionServices {
+        event_bus: app_handle.state::<AppState>().event_bus.clone(),
+        transcript_dir: app_server_transcript_dir(&app_handle).await,
+        log_dir: app_server_log_dir(&app_handle),
+        sandbox_profiles: app_handle.state::<AppState>().sandbox_profiles.clone(),
+        sessions: Arc::downgrade(&app_handle.state::<AppState>().sessions),
+        parked_threads: app_handle.state::<AppState>().parked_threads.clone(),
+    };
     let event_sink = TauriEventSink::new(app_handle);
     spawn_workspace_session_inner(
         entry,
@@ -146,11 +87,67 @@ pub(crate) async fn spawn_workspace_session(
         codex_args,
         codex_home,
         client_version,
+        services,
         event_sink,
     )
     .await
 }
 
+async fn app_server_transcript_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    let state = app_handle.state::<AppState>();
+    if !state
+        .app_settings
+        .lock()
+        .await
+        .record_app_server_transcripts
+    {
+        return None;
+    }
+    let data_dir = app_handle.path().app_data_dir().ok()?;
+    Some(transcript::transcripts_dir(&data_dir))
+}
+
+fn app_server_log_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    let data_dir = app_handle.path().app_data_dir().ok()?;
+    Some(session_log::logs_dir(&data_dir))
+}
+
+/// Returns the session for a workspace, reconnecting it if it was shut down
+/// while idle or is no longer running.
+async fn workspace_session(
+    state: &AppState,
+    app: &AppHandle,
+    workspace_id: &str,
+) -> Result<Arc<WorkspaceSession>, String> {
+    if let Some(session) = live_session(&state.sessions, workspace_id).await? {
+        return Ok(session);
+    }
+    connect_workspace_inner(workspace_id, state, app.clone(), false).await?;
+    live_session(&state.sessions, workspace_id)
+        .await?
+        .ok_or_else(|| "workspace not connected".to_string())
+}
+
+/// Periodically shuts down workspace sessions that exceeded the idle timeout.
+/// Commands reconnect them on demand through `workspace_session`.
+pub(crate) async fn run_idle_session_reaper(app_handle: AppHandle) {
+    loop {
+        tokio::time::sleep(IDLE_REAP_INTERVAL).await;
+        let state = app_handle.state::<AppState>();
+        let timeout_minutes = state.app_settings.lock().await.idle_session_timeout_minutes;
+        if timeout_minutes == 0 || remote_backend::is_remote_mode(&*state).await {
+            continue;
+        }
+        let event_sink = TauriEventSink::new(app_handle.clone());
+        reap_idle_sessions(
+            &state.sessions,
+            Duration::from_secs(timeout_minutes * 60),
+            &event_sink,
+        )
+        .await;
+    }
+}
+
 pub(crate) async fn ensure_global_session(
     state: &AppState,
     app_handle: &AppHandle,
@@ -206,21 +203,6 @@ pub(crate) async fn ensure_global_session(
     Ok(session)
 }
 
-fn extract_thread_list_data(response: &Value) -> (Vec<Value>, Option<String>) {
-    let result = response.get("result").unwrap_or(response);
-    let data = result
-        .get("data")
-        .and_then(|value| value.as_array())
-        .cloned()
-        .unwrap_or_default();
-    let next_cursor = result
-        .get("nextCursor")
-        .or_else(|| result.get("next_cursor"))
-        .and_then(|value| value.as_str())
-        .map(|value| value.to_string());
-    (data, next_cursor)
-}
-
 #[tauri::command]
 pub(crate) async fn codex_doctor(
     codex_bin: Option<String>,
@@ -344,15 +326,12 @@ pub(crate) async fn start_thread(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let params = json!({
-        "cwd": session.entry.path,
-        "approvalPolicy": "on-request"
-    });
-    session.send_request("thread/start", params).await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    let params = ThreadStartParams {
+        cwd: session.entry.path.clone(),
+        approval_policy: ApprovalPolicy::OnRequest,
+    };
+    session.request(&params).await
 }
 
 #[tauri::command]
@@ -372,14 +351,8 @@ pub(crate) async fn resume_thread(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let params = json!({
-        "threadId": thread_id
-    });
-    session.send_request("thread/resume", params).await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    session.request(&ThreadResumeParams { thread_id }).await
 }
 
 #[tauri::command]
@@ -387,6 +360,7 @@ pub(crate) async fn list_threads(
     workspace_id: String,
     cursor: Option<String>,
     limit: Option<u32>,
+    timeout_ms: Option<u32>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
@@ -395,20 +369,53 @@ pub(crate) async fn list_threads(
             &*state,
             app,
             "list_threads",
-            json!({ "workspaceId": workspace_id, "cursor": cursor, "limit": limit }),
+            json!({
+                "workspaceId": workspace_id,
+                "cursor": cursor,
+                "limit": limit,
+                "timeoutMs": timeout_ms,
+            }),
         )
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let params = json!({
-        "cursor": cursor,
-        "limit": limit,
-    });
-    session.send_request("thread/list", params).await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    let params = ThreadListParams { cursor, limit };
+    match timeout_ms {
+        Some(timeout_ms) => {
+            session
+                .request_with_timeout(&params, Some(Duration::from_millis(timeout_ms.into())))
+                .await
+        }
+        None => session.request(&params).await,
+    }
+}
+
+/// Stops waiting for in-flight app-server requests of `method` so a hung call can be
+/// abandoned from the UI. Returns how many requests were canceled.
+#[tauri::command]
+pub(crate) async fn cancel_app_server_requests(
+    workspace_id: String,
+    method: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_app_server_requests",
+            json!({ "workspaceId": workspace_id, "method": method }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    Ok(match session {
+        Some(session) => session.cancel_requests(&method).await,
+        None => 0,
+    })
 }
 
 #[tauri::command]
@@ -422,25 +429,7 @@ pub(crate) async fn list_threads_global(
 
     let start = Instant::now();
     let session = ensure_global_session(&state, &app).await?;
-    let mut all_threads: Vec<Value> = Vec::new();
-    let mut cursor: Option<String> = None;
-    let mut pages = 0;
-    loop {
-        pages += 1;
-        let params = json!({
-            "cursor": cursor,
-            "limit": 2000,
-        });
-        let response = session.send_request("thread/list", params).await?;
-        let (data, next_cursor) = extract_thread_list_data(&response);
-        if !data.is_empty() {
-            all_threads.extend(data);
-        }
-        cursor = next_cursor;
-        if cursor.is_none() {
-            break;
-        }
-    }
+    let (all_threads, pages) = list_all_threads(&session).await?;
     eprintln!(
         "[perf] list_threads_global total={} pages={} ms={}",
         all_threads.len(),
@@ -453,11 +442,42 @@ pub(crate) async fn list_threads_global(
     }))
 }
 
+/// Finds CLI sessions that no workspace shows. Unregistered ones can be added as a
+/// workspace from their `cwd`; unlisted and unknown ones can be resumed in the matching
+/// workspace.
+#[tauri::command]
+pub(crate) async fn scan_orphaned_sessions(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<OrphanedSession>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "scan_orphaned_sessions", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let global = ensure_global_session(&state, &app).await?;
+    let workspace_sessions: Vec<Arc<WorkspaceSession>> = state
+        .sessions
+        .lock()
+        .await
+        .values()
+        .filter(|session| session.is_alive())
+        .cloned()
+        .collect();
+    let workspaces = state.workspaces.lock().await.clone();
+    find_orphaned_sessions(&global, &workspace_sessions, workspaces).await
+}
+
+/// Streams a rollout file as `openvibe/thread_history/chunk` events. With `follow`, the
+/// stream emits `caught_up` after the existing lines and keeps tailing the file until it
+/// is stopped or replaced.
 #[tauri::command]
 pub(crate) async fn stream_thread_history(
     workspace_id: String,
     thread_id: String,
     path: Option<String>,
+    follow: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<String, String> {
@@ -469,6 +489,7 @@ pub(crate) async fn stream_thread_history(
         .map(|value| value.trim().to_string())
         .filter(|value| !value.is_empty())
         .ok_or_else(|| "thread history path unavailable".to_string())?;
+    let follow = follow.unwrap_or(false);
 
     let stream_id = Uuid::new_v4().to_string();
     let cancel = Arc::new(AtomicBool::new(false));
@@ -488,131 +509,225 @@ pub(crate) async fn stream_thread_history(
     let event_sink = TauriEventSink::new(app);
     let stream_id_for_task = stream_id.clone();
     tokio::spawn(async move {
-        let file = match tokio::fs::File::open(&resolved_path).await {
-            Ok(file) => file,
+        let emit = |method: &str, params: Value| {
+            event_sink.emit_app_server_event(crate::backend::events::AppServerEvent {
+                workspace_id: workspace_id.clone(),
+                message: json!({ "method": method, "params": params }),
+            });
+        };
+        let emit_items = |items: &mut Vec<StreamMessage>| {
+            if items.is_empty() {
+                return;
+            }
+            emit(
+                "openvibe/thread_history/chunk",
+                json!({
+                    "threadId": thread_id,
+                    "streamId": stream_id_for_task,
+                    "items": std::mem::take(items),
+                }),
+            );
+        };
+        let emit_error = |message: String| {
+            emit(
+                "openvibe/thread_history/error",
+                json!({
+                    "threadId": thread_id,
+                    "streamId": stream_id_for_task,
+                    "message": message,
+                }),
+            );
+        };
+
+        let mut tail = match RolloutTail::open(std::path::Path::new(&resolved_path)).await {
+            Ok(tail) => tail,
             Err(err) => {
-                let payload = crate::backend::events::AppServerEvent {
-                    workspace_id,
-                    message: json!({
-                        "method": "openvibe/thread_history/error",
-                        "params": {
-                            "threadId": thread_id,
-                            "streamId": stream_id_for_task,
-                            "message": err.to_string(),
-                        }
-                    }),
-                };
-                event_sink.emit_app_server_event(payload);
+                emit_error(err.to_string());
                 return;
             }
         };
 
-        let mut lines = BufReader::new(file).lines();
+        let mut parser = HistoryStreamParser::new(&thread_id);
         let mut buffer: Vec<StreamMessage> = Vec::new();
-        let mut pending_response_items: Vec<StreamMessage> = Vec::new();
-        let mut message_index = 0u64;
-        let mut seen_event_message = false;
         let chunk_size = 40usize;
 
-        while let Ok(Some(line)) = lines.next_line().await {
-            if cancel.load(Ordering::SeqCst) {
-                break;
+        while !cancel.load(Ordering::SeqCst) {
+            match tail.next_line().await {
+                Ok(Some(line)) => buffer.extend(parser.push_line(&line)),
+                Ok(None) => break,
+                Err(err) => {
+                    emit_error(err.to_string());
+                    break;
+                }
+            }
+            if buffer.len() >= chunk_size {
+                emit_items(&mut buffer);
             }
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
+        }
+        if !follow {
+            if let Some(line) = tail.take_partial() {
+                buffer.extend(parser.push_line(&line));
             }
-            let value: Value = match serde_json::from_str(trimmed) {
-                Ok(value) => value,
-                Err(_) => continue,
-            };
-            let line: RolloutLine = match serde_json::from_value(value) {
-                Ok(line) => line,
-                Err(_) => continue,
-            };
-            let Some(kind) = line.kind.as_deref() else {
-                continue;
-            };
-            let Some(payload) = line.payload.as_ref() else {
-                continue;
-            };
-
-            match kind {
-                "event_msg" => {
-                    if let Some(item) =
-                        extract_event_stream_message(payload, &thread_id, &mut message_index)
-                    {
-                        if !seen_event_message {
-                            seen_event_message = true;
-                            pending_response_items.clear();
+        }
+        buffer.extend(parser.finish_initial());
+        emit_items(&mut buffer);
+
+        if follow && !cancel.load(Ordering::SeqCst) {
+            emit(
+                "openvibe/thread_history/caught_up",
+                json!({ "threadId": thread_id, "streamId": stream_id_for_task }),
+            );
+            // Keep reading appended lines until the stream is stopped or replaced.
+            while !cancel.load(Ordering::SeqCst) {
+                match tail.next_line().await {
+                    Ok(Some(line)) => {
+                        buffer.extend(parser.push_line(&line));
+                        if buffer.len() >= chunk_size {
+                            emit_items(&mut buffer);
                         }
-                        buffer.push(item);
                     }
-                }
-                "response_item" => {
-                    if seen_event_message {
-                        continue;
+                    Ok(None) => {
+                        emit_items(&mut buffer);
+                        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
                     }
-                    if let Some(item) = extract_response_item_stream_message(
-                        payload,
-                        &thread_id,
-                        &mut message_index,
-                    ) {
-                        pending_response_items.push(item);
+                    Err(err) => {
+                        emit_error(err.to_string());
+                        break;
                     }
                 }
-                _ => {}
-            }
-            if buffer.len() >= chunk_size {
-                let payload = crate::backend::events::AppServerEvent {
-                    workspace_id: workspace_id.clone(),
-                    message: json!({
-                        "method": "openvibe/thread_history/chunk",
-                        "params": {
-                            "threadId": thread_id,
-                            "streamId": stream_id_for_task,
-                            "items": buffer,
-                        }
-                    }),
-                };
-                event_sink.emit_app_server_event(payload);
-                buffer = Vec::new();
             }
         }
 
-        if !seen_event_message && !pending_response_items.is_empty() {
-            buffer.extend(pending_response_items.drain(..));
-        }
+        emit(
+            "openvibe/thread_history/done",
+            json!({ "threadId": thread_id, "streamId": stream_id_for_task }),
+        );
+    });
 
-        if !buffer.is_empty() {
-            let payload = crate::backend::events::AppServerEvent {
-                workspace_id: workspace_id.clone(),
-                message: json!({
-                    "method": "openvibe/thread_history/chunk",
-                    "params": {
-                        "threadId": thread_id,
-                        "streamId": stream_id_for_task,
-                        "items": buffer,
-                    }
-                }),
-            };
-            event_sink.emit_app_server_event(payload);
-        }
+    Ok(stream_id)
+}
 
-        let payload = crate::backend::events::AppServerEvent {
-            workspace_id,
-            message: json!({
-                "method": "openvibe/thread_history/done",
-                "params": {
-                    "threadId": thread_id,
-                    "streamId": stream_id_for_task,
-                }
+/// Forks a thread at a `stream_thread_history` message index into a new thread,
+/// optionally inside a fresh worktree of the workspace.
+/// In remote mode the daemon finds the rollout from `thread_id` and `path` is unused.
+#[tauri::command]
+pub(crate) async fn fork_thread(
+    workspace_id: String,
+    thread_id: String,
+    path: String,
+    message_index: u64,
+    worktree_branch: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ForkedThread, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "fork_thread",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "messageIndex": message_index,
+                "worktreeBranch": worktree_branch,
             }),
-        };
-        event_sink.emit_app_server_event(payload);
-    });
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
 
-    Ok(stream_id)
+    let host = AppForkHost { state, app };
+    thread_fork::fork_thread(
+        &host,
+        &workspace_id,
+        &thread_id,
+        path.trim(),
+        message_index,
+        worktree_branch.as_deref(),
+    )
+    .await
+}
+
+/// Lets forks and fan-outs create worktrees and reach sessions in the app.
+struct AppForkHost<'a> {
+    state: State<'a, AppState>,
+    app: AppHandle,
+}
+
+impl ForkHost for AppForkHost<'_> {
+    async fn add_worktree(&self, parent_id: &str, branch: &str) -> Result<WorkspaceInfo, String> {
+        add_worktree(
+            parent_id.to_string(),
+            branch.to_string(),
+            self.state.clone(),
+            self.app.clone(),
+        )
+        .await
+    }
+
+    async fn remove_worktree(&self, id: &str) -> Result<(), String> {
+        remove_worktree(id.to_string(), self.state.clone(), self.app.clone()).await
+    }
+
+    async fn session(&self, workspace_id: &str) -> Result<Arc<WorkspaceSession>, String> {
+        workspace_session(&self.state, &self.app, workspace_id).await
+    }
+}
+
+/// Sends one prompt to several workspaces at once, creating worktrees when only a count is
+/// given. Returns once every lane is running; lane reports (final message, diff, token
+/// usage, duration) arrive as `openvibe/fan_out/*` events.
+#[tauri::command]
+pub(crate) async fn fan_out_prompt(
+    request: FanOutRequest,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FanOutStarted, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "fan_out_prompt",
+            json!({ "request": request }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let event_sink = TauriEventSink::new(app.clone());
+    let host = AppForkHost { state, app };
+    start_fan_out(&host, request, event_sink).await
+}
+
+/// Renders a thread's rollout file as Markdown, standalone HTML or normalized JSON.
+/// When `output_path` is set the export is also written there. In remote mode the daemon
+/// finds the rollout from `thread_id` and `path` is unused.
+#[tauri::command]
+pub(crate) async fn export_thread(
+    thread_id: String,
+    path: String,
+    format: ThreadExportFormat,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    let rendered = if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "export_thread",
+            json!({ "threadId": thread_id, "format": format }),
+        )
+        .await?;
+        serde_json::from_value(response).map_err(|err| err.to_string())?
+    } else {
+        export_rollout_file(&thread_id, path.trim(), format).await?
+    };
+    if let Some(output_path) = output_path.filter(|value| !value.trim().is_empty()) {
+        tokio::fs::write(&output_path, &rendered)
+            .await
+            .map_err(|err| format!("Failed to write {output_path}: {err}"))?;
+    }
+    Ok(rendered)
 }
 
 #[tauri::command]
@@ -651,14 +766,8 @@ pub(crate) async fn archive_thread(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let params = json!({
-        "threadId": thread_id
-    });
-    session.send_request("thread/archive", params).await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    session.request(&ThreadArchiveParams { thread_id }).await
 }
 
 #[tauri::command]
@@ -669,6 +778,7 @@ pub(crate) async fn send_user_message(
     model: Option<String>,
     effort: Option<String>,
     access_mode: Option<String>,
+    sandbox_profile: Option<String>,
     images: Option<Vec<String>>,
     collaboration_mode: Option<Value>,
     state: State<'_, AppState>,
@@ -688,6 +798,7 @@ pub(crate) async fn send_user_message(
         payload.insert("model".to_string(), json!(model));
         payload.insert("effort".to_string(), json!(effort));
         payload.insert("accessMode".to_string(), json!(access_mode));
+        payload.insert("sandboxProfile".to_string(), json!(sandbox_profile));
         payload.insert("images".to_string(), json!(images));
         if let Some(mode) = collaboration_mode {
             if !mode.is_null() {
@@ -703,72 +814,157 @@ pub(crate) async fn send_user_message(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-    let sandbox_policy = match access_mode.as_str() {
-        "full-access" => json!({
-            "type": "dangerFullAccess"
-        }),
-        "read-only" => json!({
-            "type": "readOnly"
-        }),
-        _ => json!({
-            "type": "workspaceWrite",
-            "writableRoots": [session.entry.path],
-            "networkAccess": true
-        }),
-    };
-
-    let approval_policy = if access_mode == "full-access" {
-        "never"
-    } else {
-        "on-request"
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    session.approvals.mark_human_interaction();
+    let message = OutgoingMessage {
+        text,
+        model,
+        effort,
+        access_mode,
+        sandbox_profile,
+        images,
+        collaboration_mode,
     };
+    start_user_turn(&session, thread_id, message).await
+}
 
-    let trimmed_text = text.trim();
-    let mut input: Vec<Value> = Vec::new();
-    if !trimmed_text.is_empty() {
-        input.push(json!({ "type": "text", "text": trimmed_text }));
+#[tauri::command]
+pub(crate) async fn get_message_queue(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadQueue, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_message_queue",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    if let Some(paths) = images {
-        for path in paths {
-            let trimmed = path.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-            if trimmed.starts_with("data:")
-                || trimmed.starts_with("http://")
-                || trimmed.starts_with("https://")
-            {
-                input.push(json!({ "type": "image", "url": trimmed }));
-            } else {
-                input.push(json!({ "type": "localImage", "path": trimmed }));
+    Ok(state.message_queue.snapshot(&workspace_id, &thread_id))
+}
+
+/// Enqueues, edits, reorders, pauses or clears a thread's queued messages. Queued
+/// messages are sent one per completed turn.
+#[tauri::command]
+pub(crate) async fn update_message_queue(
+    workspace_id: String,
+    thread_id: String,
+    action: QueueAction,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadQueue, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let action = match action {
+            QueueAction::Enqueue { mut message } => {
+                message.images = message.images.map(|paths| {
+                    paths
+                        .into_iter()
+                        .map(remote_backend::normalize_path_for_remote)
+                        .collect()
+                });
+                QueueAction::Enqueue { message }
             }
-        }
+            other => other,
+        };
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "update_message_queue",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "action": action }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let queue = state
+        .message_queue
+        .apply(&workspace_id, &thread_id, action)?;
+    TauriEventSink::new(app).emit_app_server_event(queue_changed_event(&queue));
+    Ok(queue)
+}
+
+#[tauri::command]
+pub(crate) async fn get_thread_metadata(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<ThreadMetadata>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_thread_metadata",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    if input.is_empty() {
-        return Err("empty user message".to_string());
+    Ok(state.thread_metadata.get(&workspace_id, &thread_id))
+}
+
+/// Replaces a thread's tags, notes, title, pin and links. Returns `None` once the record
+/// holds nothing and was dropped.
+#[tauri::command]
+pub(crate) async fn set_thread_metadata(
+    metadata: ThreadMetadata,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<ThreadMetadata>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_thread_metadata",
+            json!({ "metadata": metadata }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
+    state.thread_metadata.set(metadata)
+}
 
-    let mut params = Map::new();
-    params.insert("threadId".to_string(), json!(thread_id));
-    params.insert("input".to_string(), json!(input));
-    params.insert("cwd".to_string(), json!(session.entry.path));
-    params.insert("approvalPolicy".to_string(), json!(approval_policy));
-    params.insert("sandboxPolicy".to_string(), json!(sandbox_policy));
-    params.insert("model".to_string(), json!(model));
-    params.insert("effort".to_string(), json!(effort));
-    if let Some(mode) = collaboration_mode {
-        if !mode.is_null() {
-            params.insert("collaborationMode".to_string(), mode);
-        }
+#[tauri::command]
+pub(crate) async fn delete_thread_metadata(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "delete_thread_metadata",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    session
-        .send_request("turn/start", Value::Object(params))
-        .await
+    state.thread_metadata.delete(&workspace_id, &thread_id)
+}
+
+#[tauri::command]
+pub(crate) async fn query_thread_metadata(
+    query: ThreadMetadataQuery,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ThreadMetadata>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "query_thread_metadata",
+            json!({ "query": query }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(state.thread_metadata.query(&query))
 }
 
 #[tauri::command]
@@ -787,12 +983,9 @@ pub(crate) async fn collaboration_mode_list(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
+    let session = workspace_session(&state, &app, &workspace_id).await?;
     session
-        .send_request("collaborationMode/list", json!({}))
+        .request(&CollaborationModeListParams::default())
         .await
 }
 
@@ -814,15 +1007,10 @@ pub(crate) async fn turn_interrupt(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let params = json!({
-        "threadId": thread_id,
-        "turnId": turn_id,
-    });
-    session.send_request("turn/interrupt", params).await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    session.approvals.mark_human_interaction();
+    let params = TurnInterruptParams { thread_id, turn_id };
+    session.request(&params).await
 }
 
 #[tauri::command]
@@ -849,19 +1037,13 @@ pub(crate) async fn start_review(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let mut params = Map::new();
-    params.insert("threadId".to_string(), json!(thread_id));
-    params.insert("target".to_string(), target);
-    if let Some(delivery) = delivery {
-        params.insert("delivery".to_string(), json!(delivery));
-    }
-    session
-        .send_request("review/start", Value::Object(params))
-        .await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    let params = ReviewStartParams {
+        thread_id,
+        target,
+        delivery,
+    };
+    session.request(&params).await
 }
 
 #[tauri::command]
@@ -880,12 +1062,8 @@ pub(crate) async fn model_list(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let params = json!({});
-    session.send_request("model/list", params).await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    session.request(&ModelListParams::default()).await
 }
 
 #[tauri::command]
@@ -904,10 +1082,7 @@ pub(crate) async fn account_rate_limits(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
+    let session = workspace_session(&state, &app, &workspace_id).await?;
     session
         .send_request("account/rateLimits/read", Value::Null)
         .await
@@ -929,14 +1104,93 @@ pub(crate) async fn skills_list(
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    let params = json!({
-        "cwd": session.entry.path
-    });
-    session.send_request("skills/list", params).await
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    let params = SkillsListParams {
+        cwd: session.entry.path.clone(),
+    };
+    session.request(&params).await
+}
+
+async fn workspace_log_entries(
+    state: &AppState,
+    app: &AppHandle,
+    workspace_id: String,
+    filter: SessionLogFilter,
+) -> Result<Vec<SessionLogEntry>, String> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "get_workspace_logs",
+            json!({ "workspaceId": workspace_id, "filter": filter }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    let log_dir = app_server_log_dir(app);
+    Ok(query_workspace_log(
+        session.as_ref().map(|session| &session.log),
+        log_dir.as_deref(),
+        &workspace_id,
+        &filter,
+    ))
+}
+
+#[tauri::command]
+pub(crate) async fn get_workspace_logs(
+    workspace_id: String,
+    filter: Option<SessionLogFilter>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SessionLogEntry>, String> {
+    workspace_log_entries(&state, &app, workspace_id, filter.unwrap_or_default()).await
+}
+
+/// Writes the matching log entries as plain text to `path` and returns the entry count.
+#[tauri::command]
+pub(crate) async fn export_workspace_logs(
+    workspace_id: String,
+    path: String,
+    filter: Option<SessionLogFilter>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    let entries =
+        workspace_log_entries(&state, &app, workspace_id, filter.unwrap_or_default()).await?;
+    std::fs::write(&path, format_log_entries(&entries))
+        .map_err(|err| format!("Failed to write {path}: {err}"))?;
+    Ok(entries.len())
+}
+
+/// Returns the newest approval decisions made for a workspace, live or from disk.
+#[tauri::command]
+pub(crate) async fn get_approval_audit_log(
+    workspace_id: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ApprovalAuditEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_approval_audit_log",
+            json!({ "workspaceId": workspace_id, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    let log_dir = app_server_log_dir(&app);
+    Ok(query_audit_log(
+        session.as_ref().map(|session| &session.approvals),
+        log_dir.as_deref(),
+        &workspace_id,
+        limit,
+    ))
 }
 
 #[tauri::command]
@@ -958,10 +1212,8 @@ pub(crate) async fn respond_to_server_request(
         return Ok(());
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    session.approvals.mark_human_interaction();
     session.send_response(request_id, result).await
 }
 
@@ -970,23 +1222,20 @@ pub(crate) async fn respond_to_server_request(
 pub(crate) async fn get_commit_message_prompt(
     workspace_id: String,
     state: State<'_, AppState>,
+    app: AppHandle,
 ) -> Result<String, String> {
-    // Get the diff from git
-    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
-
-    if diff.trim().is_empty() {
-        return Err("No changes to generate commit message for".to_string());
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_commit_message_prompt",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    let prompt = format!(
-        "Generate a concise git commit message for the following changes. \
-Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
-Keep the summary line under 72 characters. \
-Only output the commit message, nothing else.\n\n\
-Changes:\n{diff}"
-    );
-
-    Ok(prompt)
+    crate::git::commit_message_prompt(&workspace_id, &state).await
 }
 
 #[tauri::command]
@@ -1058,175 +1307,78 @@ async fn resolve_codex_home_for_workspace(
         .ok_or("Unable to resolve CODEX_HOME".to_string())
 }
 
-/// Generates a commit message in the background without showing in the main chat
+/// Runs a one-shot prompt on a hidden thread and returns the reply. When `run_id` is
+/// given, progress is emitted as `headless/progress` app-server events.
 #[tauri::command]
-pub(crate) async fn generate_commit_message(
+pub(crate) async fn run_headless_prompt(
     workspace_id: String,
+    prompt: String,
+    output_schema: Option<Value>,
+    timeout_secs: Option<u64>,
+    model: Option<String>,
+    effort: Option<String>,
+    run_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    // Get the diff from git
-    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
-
-    if diff.trim().is_empty() {
-        return Err("No changes to generate commit message for".to_string());
-    }
-
-    let prompt = format!(
-        "Generate a concise git commit message for the following changes. \
-Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
-Keep the summary line under 72 characters. \
-Only output the commit message, nothing else.\n\n\
-Changes:\n{diff}"
-    );
-
-    // Get the session
-    let session = {
-        let sessions = state.sessions.lock().await;
-        sessions
-            .get(&workspace_id)
-            .ok_or("workspace not connected")?
-            .clone()
-    };
-
-    // Create a background thread
-    let thread_params = json!({
-        "cwd": session.entry.path,
-        "approvalPolicy": "never"  // Never ask for approval in background
-    });
-    let thread_result = session.send_request("thread/start", thread_params).await?;
-
-    // Handle error response
-    if let Some(error) = thread_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting thread");
-        return Err(error_msg.to_string());
-    }
-
-    // Extract threadId - try multiple paths since response format may vary
-    let thread_id = thread_result
-        .get("result")
-        .and_then(|r| r.get("threadId"))
-        .or_else(|| {
-            thread_result
-                .get("result")
-                .and_then(|r| r.get("thread"))
-                .and_then(|t| t.get("id"))
-        })
-        .or_else(|| thread_result.get("threadId"))
-        .or_else(|| thread_result.get("thread").and_then(|t| t.get("id")))
-        .and_then(|t| t.as_str())
-        .ok_or_else(|| {
-            format!(
-                "Failed to get threadId from thread/start response: {:?}",
-                thread_result
-            )
-        })?
-        .to_string();
-
-    // Create channel for receiving events
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-
-    // Register callback for this thread
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.insert(thread_id.clone(), tx);
+    app: AppHandle,
+) -> Result<HeadlessResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "run_headless_prompt",
+            json!({
+                "workspaceId": workspace_id,
+                "prompt": prompt,
+                "outputSchema": output_schema,
+                "timeoutSecs": timeout_secs,
+                "model": model,
+                "effort": effort,
+                "runId": run_id,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    // Start a turn with the commit message prompt
-    let turn_params = json!({
-        "threadId": thread_id,
-        "input": [{ "type": "text", "text": prompt }],
-        "cwd": session.entry.path,
-        "approvalPolicy": "never",
-        "sandboxPolicy": { "type": "readOnly" },
-    });
-    let turn_result = session.send_request("turn/start", turn_params).await;
-    let turn_result = match turn_result {
-        Ok(result) => result,
-        Err(error) => {
-            // Clean up if turn fails to start
-            {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
-            }
-            let archive_params = json!({ "threadId": thread_id.as_str() });
-            let _ = session.send_request("thread/archive", archive_params).await;
-            return Err(error);
-        }
-    };
-
-    if let Some(error) = turn_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting turn");
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
-        }
-        let archive_params = json!({ "threadId": thread_id.as_str() });
-        let _ = session.send_request("thread/archive", archive_params).await;
-        return Err(error_msg.to_string());
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    let mut request = HeadlessRequest::new(prompt);
+    request.output_schema = output_schema;
+    if let Some(secs) = timeout_secs.filter(|secs| *secs > 0) {
+        request.timeout = Duration::from_secs(secs);
     }
-
-    // Collect assistant text from events
-    let mut commit_message = String::new();
-    let timeout_duration = Duration::from_secs(60);
-    let collect_result = timeout(timeout_duration, async {
-        while let Some(event) = rx.recv().await {
-            let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
-
-            match method {
-                "item/agentMessage/delta" => {
-                    // Extract text delta from agent messages
-                    if let Some(params) = event.get("params") {
-                        if let Some(delta) = params.get("delta").and_then(|d| d.as_str()) {
-                            commit_message.push_str(delta);
-                        }
-                    }
-                }
-                "turn/completed" => {
-                    // Turn completed, we can stop listening
-                    break;
-                }
-                "turn/error" => {
-                    // Error occurred
-                    let error_msg = event
-                        .get("params")
-                        .and_then(|p| p.get("error"))
-                        .and_then(|e| e.as_str())
-                        .unwrap_or("Unknown error during commit message generation");
-                    return Err(error_msg.to_string());
-                }
-                _ => {
-                    // Ignore other events (turn/started, item/started, item/completed, reasoning events, etc.)
-                }
-            }
+    request.model = model;
+    request.effort = effort;
+    let event_sink = TauriEventSink::new(app.clone());
+    run_headless_prompt_inner(&session, request, |progress| {
+        if let Some(run_id) = run_id.as_deref() {
+            event_sink.emit_app_server_event(progress_event(&workspace_id, run_id, &progress));
         }
-        Ok(())
     })
-    .await;
-
-    // Unregister callback
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.remove(&thread_id);
-    }
-
-    // Archive the thread to clean up
-    let archive_params = json!({ "threadId": thread_id });
-    let _ = session.send_request("thread/archive", archive_params).await;
+    .await
+}
 
-    // Handle timeout or collection error
-    match collect_result {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => return Err(e),
-        Err(_) => return Err("Timeout waiting for commit message generation".to_string()),
+/// Generates a commit message in the background without showing in the main chat
+#[tauri::command]
+pub(crate) async fn generate_commit_message(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "generate_commit_message",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    let trimmed = commit_message.trim().to_string();
+    let prompt = crate::git::commit_message_prompt(&workspace_id, &state).await?;
+    let session = workspace_session(&state, &app, &workspace_id).await?;
+    let result = run_headless_prompt_inner(&session, HeadlessRequest::new(prompt), |_| {}).await?;
+    let trimmed = result.text;
     if trimmed.is_empty() {
         return Err("No commit message was generated".to_string());
     }
@@ -1256,13 +1408,7 @@ pub(crate) async fn generate_run_metadata(
         return Err("Prompt is required.".to_string());
     }
 
-    let session = {
-        let sessions = state.sessions.lock().await;
-        sessions
-            .get(&workspace_id)
-            .ok_or("workspace not connected")?
-            .clone()
-    };
+    let session = workspace_session(&state, &app, &workspace_id).await?;
 
     let title_prompt = format!(
         "You create concise run metadata for a coding task.\n\
@@ -1284,131 +1430,15 @@ Examples:\n\
 Task:\n{cleaned_prompt}"
     );
 
-    let thread_params = json!({
-        "cwd": session.entry.path,
-        "approvalPolicy": "never"
-    });
-    let thread_result = session.send_request("thread/start", thread_params).await?;
-
-    if let Some(error) = thread_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting thread");
-        return Err(error_msg.to_string());
-    }
-
-    let thread_id = thread_result
-        .get("result")
-        .and_then(|r| r.get("threadId"))
-        .or_else(|| {
-            thread_result
-                .get("result")
-                .and_then(|r| r.get("thread"))
-                .and_then(|t| t.get("id"))
-        })
-        .or_else(|| thread_result.get("threadId"))
-        .or_else(|| thread_result.get("thread").and_then(|t| t.get("id")))
-        .and_then(|t| t.as_str())
-        .ok_or_else(|| {
-            format!(
-                "Failed to get threadId from thread/start response: {:?}",
-                thread_result
-            )
-        })?
-        .to_string();
-
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.insert(thread_id.clone(), tx);
-    }
-
-    let turn_params = json!({
-        "threadId": thread_id,
-        "input": [{ "type": "text", "text": title_prompt }],
-        "cwd": session.entry.path,
-        "approvalPolicy": "never",
-        "sandboxPolicy": { "type": "readOnly" },
-    });
-    let turn_result = session.send_request("turn/start", turn_params).await;
-    let turn_result = match turn_result {
-        Ok(result) => result,
-        Err(error) => {
-            {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
-            }
-            let archive_params = json!({ "threadId": thread_id.as_str() });
-            let _ = session.send_request("thread/archive", archive_params).await;
-            return Err(error);
-        }
-    };
-
-    if let Some(error) = turn_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting turn");
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
-        }
-        let archive_params = json!({ "threadId": thread_id.as_str() });
-        let _ = session.send_request("thread/archive", archive_params).await;
-        return Err(error_msg.to_string());
-    }
-
-    let mut response_text = String::new();
-    let timeout_duration = Duration::from_secs(60);
-    let collect_result = timeout(timeout_duration, async {
-        while let Some(event) = rx.recv().await {
-            let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
-            match method {
-                "item/agentMessage/delta" => {
-                    if let Some(params) = event.get("params") {
-                        if let Some(delta) = params.get("delta").and_then(|d| d.as_str()) {
-                            response_text.push_str(delta);
-                        }
-                    }
-                }
-                "turn/completed" => break,
-                "turn/error" => {
-                    let error_msg = event
-                        .get("params")
-                        .and_then(|p| p.get("error"))
-                        .and_then(|e| e.as_str())
-                        .unwrap_or("Unknown error during metadata generation");
-                    return Err(error_msg.to_string());
-                }
-                _ => {}
-            }
-        }
-        Ok(())
-    })
-    .await;
-
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.remove(&thread_id);
-    }
-
-    let archive_params = json!({ "threadId": thread_id });
-    let _ = session.send_request("thread/archive", archive_params).await;
-
-    match collect_result {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => return Err(e),
-        Err(_) => return Err("Timeout waiting for metadata generation".to_string()),
-    }
-
-    let trimmed = response_text.trim();
+    let result =
+        run_headless_prompt_inner(&session, HeadlessRequest::new(title_prompt), |_| {}).await?;
+    let trimmed = result.text.as_str();
     if trimmed.is_empty() {
         return Err("No metadata was generated".to_string());
     }
 
     let json_value =
-        extract_json_value(trimmed).ok_or_else(|| "Failed to parse metadata JSON".to_string())?;
+        extract_json_output(trimmed).ok_or_else(|| "Failed to parse metadata JSON".to_string())?;
     let title = json_value
         .get("title")
         .and_then(|v| v.as_str())
@@ -1429,15 +1459,6 @@ Task:\n{cleaned_prompt}"
     }))
 }
 
-fn extract_json_value(raw: &str) -> Option<Value> {
-    let start = raw.find('{')?;
-    let end = raw.rfind('}')?;
-    if end <= start {
-        return None;
-    }
-    serde_json::from_str::<Value>(&raw[start..=end]).ok()
-}
-
 fn sanitize_run_worktree_name(value: &str) -> String {
     let trimmed = value.trim().to_lowercase();
     let mut cleaned = String::new();
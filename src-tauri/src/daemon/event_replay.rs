@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events kept per workspace, so a busy workspace cannot evict another's backlog.
+pub(crate) const DEFAULT_REPLAY_CAPACITY: usize = 2048;
+const DEFAULT_CHANNEL_CAPACITY: usize = 2048;
+
+/// An event the replay buffer can attribute to a workspace.
+pub(crate) trait ReplayEvent: Clone {
+    fn workspace_id(&self) -> &str;
+
+    /// Whether the event is kept for resuming clients. Others still get a sequence
+    /// number but only reach live subscribers.
+    fn replayable(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Sequenced<T> {
+    pub(crate) seq: u64,
+    pub(crate) event: T,
+}
+
+/// Events a resuming subscriber missed. `truncated` means some were already evicted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Backlog<T> {
+    pub(crate) events: Vec<Sequenced<T>>,
+    pub(crate) latest_seq: u64,
+    pub(crate) truncated: bool,
+}
+
+/// Limits a subscription to a set of workspaces. `None` matches every workspace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ReplayFilter {
+    pub(crate) workspace_ids: Option<HashSet<String>>,
+}
+
+impl ReplayFilter {
+    pub(crate) fn workspaces<I, S>(workspace_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            workspace_ids: Some(workspace_ids.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    pub(crate) fn allows(&self, workspace_id: &str) -> bool {
+        match &self.workspace_ids {
+            Some(ids) => ids.contains(workspace_id),
+            None => true,
+        }
+    }
+}
+
+struct WorkspaceBuffer<T> {
+    events: VecDeque<Sequenced<T>>,
+    /// Highest sequence number dropped from `events`.
+    evicted_seq: u64,
+}
+
+struct ReplayState<T> {
+    next_seq: u64,
+    buffers: HashMap<String, WorkspaceBuffer<T>>,
+}
+
+/// Numbers events, keeps the most recent ones of each workspace for clients resuming
+/// after a disconnect, and fans them out to live subscribers.
+pub(crate) struct EventReplay<T> {
+    stream_id: String,
+    capacity: usize,
+    state: Mutex<ReplayState<T>>,
+    tx: broadcast::Sender<Sequenced<T>>,
+}
+
+impl<T: ReplayEvent> EventReplay<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let (tx, _rx) = broadcast::channel(DEFAULT_CHANNEL_CAPACITY);
+        Self {
+            stream_id: Uuid::new_v4().to_string(),
+            capacity: capacity.max(1),
+            state: Mutex::new(ReplayState {
+                next_seq: 1,
+                buffers: HashMap::new(),
+            }),
+            tx,
+        }
+    }
+
+    /// Identifies this sequence; a client holding a cursor from another stream must start over.
+    pub(crate) fn stream_id(&self) -> &str {
+        &self.stream_id
+    }
+
+    pub(crate) fn publish(&self, event: T) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        if !event.replayable() {
+            let _ = self.tx.send(Sequenced { seq, event });
+            return seq;
+        }
+        let buffer = state
+            .buffers
+            .entry(event.workspace_id().to_string())
+            .or_insert_with(|| WorkspaceBuffer {
+                events: VecDeque::new(),
+                evicted_seq: 0,
+            });
+        let sequenced = Sequenced { seq, event };
+        if buffer.events.len() == self.capacity {
+            if let Some(evicted) = buffer.events.pop_front() {
+                buffer.evicted_seq = evicted.seq;
+            }
+        }
+        buffer.events.push_back(sequenced.clone());
+        // Sent under the lock so live delivery order matches sequence order.
+        let _ = self.tx.send(sequenced);
+        seq
+    }
+
+    /// Drops what is kept for a workspace that no longer exists.
+    pub(crate) fn remove_workspace(&self, workspace_id: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        state.buffers.remove(workspace_id);
+    }
+
+    pub(crate) fn since(&self, last_seq: u64, filter: &ReplayFilter) -> Backlog<T> {
+        let state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        Self::backlog(&state, last_seq, filter)
+    }
+
+    /// Subscribes to live events and returns everything after `last_seq` that is still
+    /// buffered for the filtered workspaces, with no gap or overlap between the two.
+    pub(crate) fn subscribe_since(
+        &self,
+        last_seq: Option<u64>,
+        filter: &ReplayFilter,
+    ) -> (broadcast::Receiver<Sequenced<T>>, Backlog<T>) {
+        let state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        let rx = self.tx.subscribe();
+        let latest_seq = state.next_seq - 1;
+        let backlog = match last_seq {
+            Some(last_seq) => Self::backlog(&state, last_seq, filter),
+            None => Backlog {
+                events: Vec::new(),
+                latest_seq,
+                truncated: false,
+            },
+        };
+        (rx, backlog)
+    }
+
+    fn backlog(state: &ReplayState<T>, last_seq: u64, filter: &ReplayFilter) -> Backlog<T> {
+        let latest_seq = state.next_seq - 1;
+        if last_seq >= latest_seq {
+            return Backlog {
+                events: Vec::new(),
+                latest_seq,
+                truncated: false,
+            };
+        }
+        let mut events = Vec::new();
+        let mut truncated = false;
+        for (workspace_id, buffer) in &state.buffers {
+            if !filter.allows(workspace_id) {
+                continue;
+            }
+            truncated |= buffer.evicted_seq > last_seq;
+            events.extend(
+                buffer
+                    .events
+                    .iter()
+                    .filter(|entry| entry.seq > last_seq)
+                    .cloned(),
+            );
+        }
+        events.sort_by_key(|entry| entry.seq);
+        Backlog {
+            events,
+            latest_seq,
+            truncated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl ReplayEvent for (&'static str, &'static str) {
+        fn workspace_id(&self) -> &str {
+            self.0
+        }
+
+        fn replayable(&self) -> bool {
+            self.1 != "live-only"
+        }
+    }
+
+    fn all() -> ReplayFilter {
+        ReplayFilter::default()
+    }
+
+    fn seqs<T>(events: &[Sequenced<T>]) -> Vec<u64> {
+        events.iter().map(|entry| entry.seq).collect()
+    }
+
+    #[test]
+    fn publish_assigns_increasing_sequence_numbers() {
+        let replay = EventReplay::new(8);
+        assert_eq!(replay.since(0, &all()).latest_seq, 0);
+        assert_eq!(replay.publish(("ws-1", "a")), 1);
+        assert_eq!(replay.publish(("ws-1", "b")), 2);
+        assert_eq!(replay.since(0, &all()).latest_seq, 2);
+    }
+
+    #[test]
+    fn since_returns_events_after_cursor() {
+        let replay = EventReplay::new(8);
+        for event in ["a", "b", "c"] {
+            replay.publish(("ws-1", event));
+        }
+        let backlog = replay.since(1, &all());
+        assert_eq!(seqs(&backlog.events), vec![2, 3]);
+        assert_eq!(backlog.latest_seq, 3);
+        assert!(!backlog.truncated);
+        assert!(replay.since(3, &all()).events.is_empty());
+    }
+
+    #[test]
+    fn since_reports_evicted_events() {
+        let replay = EventReplay::new(2);
+        for event in ["a", "b", "c", "d"] {
+            replay.publish(("ws-1", event));
+        }
+        let backlog = replay.since(1, &all());
+        assert_eq!(seqs(&backlog.events), vec![3, 4]);
+        assert!(backlog.truncated);
+        assert!(!replay.since(2, &all()).truncated);
+    }
+
+    #[test]
+    fn eviction_is_tracked_per_workspace() {
+        let replay = EventReplay::new(2);
+        replay.publish(("ws-2", "quiet"));
+        for event in ["a", "b", "c", "d"] {
+            replay.publish(("ws-1", event));
+        }
+        let quiet = replay.since(0, &ReplayFilter::workspaces(["ws-2"]));
+        assert_eq!(seqs(&quiet.events), vec![1]);
+        assert!(!quiet.truncated);
+        let backlog = replay.since(0, &all());
+        assert_eq!(seqs(&backlog.events), vec![1, 4, 5]);
+        assert!(backlog.truncated);
+    }
+
+    #[test]
+    fn live_only_events_are_not_kept() {
+        let replay = EventReplay::new(1);
+        let (mut rx, _) = replay.subscribe_since(None, &all());
+        replay.publish(("ws-1", "a"));
+        replay.publish(("ws-1", "live-only"));
+        assert_eq!(rx.try_recv().expect("live event").seq, 1);
+        assert_eq!(rx.try_recv().expect("live event").seq, 2);
+        let backlog = replay.since(0, &all());
+        assert_eq!(seqs(&backlog.events), vec![1]);
+        assert!(!backlog.truncated);
+    }
+
+    #[test]
+    fn removed_workspaces_drop_their_backlog() {
+        let replay = EventReplay::new(8);
+        replay.publish(("ws-1", "a"));
+        replay.publish(("ws-2", "b"));
+        replay.remove_workspace("ws-1");
+        assert_eq!(seqs(&replay.since(0, &all()).events), vec![2]);
+    }
+
+    #[test]
+    fn cursor_from_the_future_replays_nothing() {
+        let replay = EventReplay::new(4);
+        replay.publish(("ws-1", "a"));
+        let backlog = replay.since(42, &all());
+        assert!(backlog.events.is_empty());
+        assert!(!backlog.truncated);
+    }
+
+    #[test]
+    fn subscribe_since_continues_without_gap() {
+        let replay = EventReplay::new(8);
+        replay.publish(("ws-1", "a"));
+        replay.publish(("ws-1", "b"));
+        let (mut rx, backlog) = replay.subscribe_since(Some(0), &all());
+        assert_eq!(seqs(&backlog.events), vec![1, 2]);
+        replay.publish(("ws-1", "c"));
+        let next = rx.try_recv().expect("live event");
+        assert_eq!(next.seq, 3);
+        assert_eq!(next.event, ("ws-1", "c"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn subscribe_without_cursor_skips_backlog() {
+        let replay = EventReplay::new(8);
+        replay.publish(("ws-1", "a"));
+        let (_rx, backlog) = replay.subscribe_since(None, &all());
+        assert!(backlog.events.is_empty());
+        assert_eq!(backlog.latest_seq, 1);
+    }
+
+    #[test]
+    fn filter_matches_selected_workspaces() {
+        assert!(ReplayFilter::default().allows("ws-1"));
+        let filter = ReplayFilter::workspaces(["ws-1", "ws-2"]);
+        assert!(filter.allows("ws-2"));
+        assert!(!filter.allows("ws-3"));
+    }
+}
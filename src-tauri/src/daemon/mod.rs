@@ -0,0 +1,2 @@
+pub(crate) mod event_replay;
+pub(crate) mod tls;
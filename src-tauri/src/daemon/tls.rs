@@ -0,0 +1,135 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+
+use crate::backend::tls::{cert_fingerprint, provider};
+
+const CERT_FILE: &str = "daemon-tls-cert.pem";
+const KEY_FILE: &str = "daemon-tls-key.pem";
+
+/// Writes the private key to a fresh file that is owner-only from the moment it exists.
+fn write_key_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+/// Loads the daemon certificate from `data_dir`, generating a self-signed one on first
+/// run. Returns the server config and the certificate fingerprint clients should pin.
+pub(crate) fn load_or_create_server_config(
+    data_dir: &Path,
+) -> Result<(Arc<ServerConfig>, String), String> {
+    let cert_path = data_dir.join(CERT_FILE);
+    let key_path = data_dir.join(KEY_FILE);
+    if !cert_path.exists() || !key_path.exists() {
+        std::fs::create_dir_all(data_dir).map_err(|err| err.to_string())?;
+        let certified = rcgen::generate_simple_self_signed(vec![
+            "localhost".to_string(),
+            "open-vibe-daemon".to_string(),
+        ])
+        .map_err(|err| format!("Failed to generate TLS certificate: {err}"))?;
+        write_key_file(&key_path, &certified.key_pair.serialize_pem())
+            .map_err(|err| format!("Failed to write {}: {err}", key_path.display()))?;
+        std::fs::write(&cert_path, certified.cert.pem())
+            .map_err(|err| format!("Failed to write {}: {err}", cert_path.display()))?;
+    }
+
+    let cert = CertificateDer::from_pem_file(&cert_path)
+        .map_err(|err| format!("Failed to read {}: {err}", cert_path.display()))?;
+    let key = PrivateKeyDer::from_pem_file(&key_path)
+        .map_err(|err| format!("Failed to read {}: {err}", key_path.display()))?;
+    let fingerprint = cert_fingerprint(cert.as_ref());
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .map_err(|err| format!("Invalid TLS certificate: {err}"))?;
+    Ok((Arc::new(config), fingerprint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_or_create_server_config, KEY_FILE};
+    use crate::backend::tls::{pinned_client_config, server_name_for_host};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::{TlsAcceptor, TlsConnector};
+    use uuid::Uuid;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    async fn handshake(fingerprint: &str, server: TlsAcceptor) -> Result<String, String> {
+        let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+        let server_task = tokio::spawn(async move {
+            let mut stream = server.accept(server_io).await.ok()?;
+            stream.write_all(b"hello").await.ok()?;
+            stream.flush().await.ok()
+        });
+        let connector = TlsConnector::from(pinned_client_config(fingerprint)?);
+        let mut stream = connector
+            .connect(server_name_for_host("dev-box:4732"), client_io)
+            .await
+            .map_err(|err| err.to_string())?;
+        let mut buffer = [0u8; 5];
+        stream
+            .read_exact(&mut buffer)
+            .await
+            .map_err(|err| err.to_string())?;
+        let _ = server_task.await;
+        Ok(String::from_utf8_lossy(&buffer).to_string())
+    }
+
+    #[test]
+    fn generated_certificate_is_reused_and_pinned() {
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        let (config, fingerprint) = load_or_create_server_config(&dir).unwrap();
+        let (_, reloaded) = load_or_create_server_config(&dir).unwrap();
+        assert_eq!(fingerprint, reloaded);
+
+        let acceptor = TlsAcceptor::from(config);
+        let greeting = block_on(handshake(&fingerprint, acceptor.clone())).unwrap();
+        assert_eq!(greeting, "hello");
+
+        let wrong = "00".repeat(32);
+        let error = block_on(handshake(&wrong, acceptor)).unwrap_err();
+        assert!(error.contains("fingerprint"), "{error}");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_key_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        load_or_create_server_config(&dir).unwrap();
+        let mode = std::fs::metadata(dir.join(KEY_FILE))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
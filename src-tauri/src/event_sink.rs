@@ -1,44 +1,116 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use tauri::{AppHandle, Emitter, Manager};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::backend::event_coalescer::{CoalescerStats, DeltaCoalescer};
 use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use crate::state::AppState;
+
+/// Shared delta batching for every `TauriEventSink`, configured by
+/// `deltaCoalesceWindowMs`; a window of 0 emits every event as it arrives.
+#[derive(Default)]
+pub(crate) struct EventCoalescing {
+    window_ms: AtomicU64,
+    coalescer: Mutex<DeltaCoalescer>,
+    flush_scheduled: AtomicBool,
+}
+
+impl EventCoalescing {
+    pub(crate) fn new(window_ms: u64) -> Arc<Self> {
+        let coalescing = Self::default();
+        coalescing.window_ms.store(window_ms, Ordering::SeqCst);
+        Arc::new(coalescing)
+    }
+
+    pub(crate) fn set_window_ms(&self, window_ms: u64) {
+        self.window_ms.store(window_ms, Ordering::SeqCst);
+    }
+
+    pub(crate) fn stats(&self) -> CoalescerStats {
+        self.coalescer
+            .lock()
+            .map(|coalescer| coalescer.stats())
+            .unwrap_or_default()
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
     app: AppHandle,
+    coalescing: Option<Arc<EventCoalescing>>,
 }
 
 impl TauriEventSink {
     pub(crate) fn new(app: AppHandle) -> Self {
-        Self { app }
+        let coalescing = app
+            .try_state::<AppState>()
+            .map(|state| Arc::clone(&state.event_coalescing));
+        Self { app, coalescing }
+    }
+
+    fn schedule_flush(&self, coalescing: &Arc<EventCoalescing>, window_ms: u64) {
+        if coalescing.flush_scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let app = self.app.clone();
+        let coalescing = Arc::clone(coalescing);
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(window_ms)).await;
+            coalescing.flush_scheduled.store(false, Ordering::SeqCst);
+            // Emit under the lock, as `emit_app_server_event` does, so a later event
+            // cannot reach the webview before this flushed delta.
+            let Ok(mut coalescer) = coalescing.coalescer.lock() else {
+                return;
+            };
+            if let Some(event) = coalescer.flush() {
+                emit_app_server_event_now(&app, event);
+            }
+        });
+    }
+}
+
+/// Returns counters for app-server events emitted to and coalesced before the webview.
+#[tauri::command]
+pub(crate) fn get_event_sink_stats(state: State<'_, AppState>) -> CoalescerStats {
+    state.event_coalescing.stats()
+}
+
+fn emit_app_server_event_now(app: &AppHandle, event: AppServerEvent) {
+    static APP_SERVER_EMITTED: AtomicBool = AtomicBool::new(false);
+    let result = match app.get_webview_window("main") {
+        Some(window) => window.emit("app-server-event", event),
+        None => app.emit("app-server-event", event),
+    };
+    match result {
+        Ok(_) => {
+            if !APP_SERVER_EMITTED.swap(true, Ordering::SeqCst) {
+                eprintln!("[event] app-server-event emitted");
+            }
+        }
+        Err(err) => {
+            eprintln!("[event] emit app-server-event failed: {err}");
+        }
     }
 }
 
 impl EventSink for TauriEventSink {
     fn emit_app_server_event(&self, event: AppServerEvent) {
-        static APP_SERVER_EMITTED: AtomicBool = AtomicBool::new(false);
-        if let Some(window) = self.app.get_webview_window("main") {
-            match window.emit("app-server-event", event) {
-                Ok(_) => {
-                    if !APP_SERVER_EMITTED.swap(true, Ordering::SeqCst) {
-                        eprintln!("[event] app-server-event emitted");
-                    }
-                }
-                Err(err) => {
-                    eprintln!("[event] emit app-server-event failed: {err}");
-                }
-            }
+        let Some(coalescing) = self.coalescing.as_ref() else {
+            emit_app_server_event_now(&self.app, event);
             return;
+        };
+        let window_ms = coalescing.window_ms.load(Ordering::SeqCst);
+        let Ok(mut coalescer) = coalescing.coalescer.lock() else {
+            emit_app_server_event_now(&self.app, event);
+            return;
+        };
+        // Emit while holding the lock so concurrent sessions cannot reorder a flush.
+        for ready in coalescer.push(event, window_ms > 0) {
+            emit_app_server_event_now(&self.app, ready);
         }
-        match self.app.emit("app-server-event", event) {
-            Ok(_) => {
-                if !APP_SERVER_EMITTED.swap(true, Ordering::SeqCst) {
-                    eprintln!("[event] app-server-event emitted");
-                }
-            }
-            Err(err) => {
-                eprintln!("[event] emit app-server-event failed: {err}");
-            }
+        if coalescer.has_pending() {
+            self.schedule_flush(coalescing, window_ms);
         }
     }
 
@@ -2,10 +2,13 @@ use std::path::PathBuf;
 use std::process::Stdio;
 
 use serde::Serialize;
+use serde_json::Value;
 use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::task::JoinHandle;
 
+use crate::backend::event_bus::spawn_bridge_observer;
 use crate::state::AppState;
 use crate::types::AppSettings;
 
@@ -42,6 +45,7 @@ pub(crate) struct HappyBridgeState {
     child: Option<Child>,
     stdin: Option<ChildStdin>,
     config: Option<HappyBridgeConfig>,
+    forwarder: Option<JoinHandle<()>>,
 }
 
 impl Default for HappyBridgeState {
@@ -50,6 +54,7 @@ impl Default for HappyBridgeState {
             child: None,
             stdin: None,
             config: None,
+            forwarder: None,
         }
     }
 }
@@ -88,6 +93,9 @@ pub(crate) async fn apply_settings(
         stop_child(&mut child).await;
     }
     guard.stdin = None;
+    if let Some(forwarder) = guard.forwarder.take() {
+        forwarder.abort();
+    }
 
     guard.config = Some(config.clone());
 
@@ -129,6 +137,7 @@ pub(crate) async fn apply_settings(
     }
     guard.stdin = child.stdin.take();
     guard.child = Some(child);
+    guard.forwarder = Some(spawn_event_forwarder(app.clone(), state));
     Ok(())
 }
 
@@ -139,18 +148,25 @@ pub(crate) async fn shutdown(state: &AppState) {
     }
     guard.stdin = None;
     guard.config = None;
+    if let Some(forwarder) = guard.forwarder.take() {
+        forwarder.abort();
+    }
 }
 
 #[tauri::command]
 pub(crate) async fn happy_bridge_send(
-    command: serde_json::Value,
+    command: Value,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     let mut guard = state.happy_bridge.lock().await;
     let Some(stdin) = guard.stdin.as_mut() else {
         return Err("Happy bridge is not running".to_string());
     };
-    let line = serde_json::to_string(&command)
+    write_command(stdin, &command).await
+}
+
+async fn write_command(stdin: &mut ChildStdin, command: &Value) -> Result<(), String> {
+    let line = serde_json::to_string(command)
         .map_err(|err| format!("Failed to serialize happy bridge command: {err}"))?;
     stdin
         .write_all(line.as_bytes())
@@ -163,6 +179,21 @@ pub(crate) async fn happy_bridge_send(
     Ok(())
 }
 
+/// Mirrors turn events from the event bus so the bridge sees the same threads as the UI.
+fn spawn_event_forwarder(app: AppHandle, state: &AppState) -> JoinHandle<()> {
+    spawn_bridge_observer(&state.event_bus, move |command| {
+        let app = app.clone();
+        async move {
+            let state = app.state::<AppState>();
+            let mut guard = state.happy_bridge.lock().await;
+            let Some(stdin) = guard.stdin.as_mut() else {
+                return Err("Happy bridge is not running".to_string());
+            };
+            write_command(stdin, &command).await
+        }
+    })
+}
+
 async fn stop_child(child: &mut Child) {
     let _ = child.kill().await;
     let _ = child.wait().await;
@@ -161,6 +161,20 @@ pub fn run() {
                 }
             });
             let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(codex::run_idle_session_reaper(app_handle));
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app_handle.state::<state::AppState>();
+                let event_sink = event_sink::TauriEventSink::new(app_handle.clone());
+                backend::message_queue::run_queue_dispatcher(
+                    state.message_queue.clone(),
+                    state.event_bus.clone(),
+                    state.sessions.clone(),
+                    event_sink,
+                )
+                .await;
+            });
+            let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 let state = app_handle.state::<state::AppState>();
                 if remote_backend::is_remote_mode(&*state).await {
@@ -211,6 +225,7 @@ pub fn run() {
             menu::menu_set_accelerators,
             codex::codex_doctor,
             workspaces::list_workspaces,
+            workspaces::set_remote_event_workspaces,
             workspaces::is_workspace_path_dir,
             workspaces::add_workspace,
             workspaces::add_clone,
@@ -226,17 +241,30 @@ pub fn run() {
             workspaces::update_workspace_codex_bin,
             codex::start_thread,
             codex::send_user_message,
+            codex::get_message_queue,
+            codex::update_message_queue,
+            codex::get_thread_metadata,
+            codex::set_thread_metadata,
+            codex::delete_thread_metadata,
+            codex::query_thread_metadata,
             codex::turn_interrupt,
             codex::start_review,
             codex::respond_to_server_request,
+            codex::get_approval_audit_log,
             codex::remember_approval_rule,
             codex::get_commit_message_prompt,
             codex::generate_commit_message,
             codex::generate_run_metadata,
+            codex::run_headless_prompt,
             codex::resume_thread,
             codex::list_threads,
+            codex::cancel_app_server_requests,
             codex::list_threads_global,
+            codex::scan_orphaned_sessions,
             codex::stream_thread_history,
+            codex::export_thread,
+            codex::fork_thread,
+            codex::fan_out_prompt,
             codex::stop_thread_history_stream,
             codex::archive_thread,
             codex::collaboration_mode_list,
@@ -253,6 +281,9 @@ pub fn run() {
             git::unstage_git_file,
             git::revert_git_file,
             git::revert_git_all,
+            git::list_git_checkpoints,
+            git::diff_git_checkpoint,
+            git::restore_git_checkpoint,
             git::commit_git,
             git::push_git,
             git::pull_git,
@@ -271,6 +302,9 @@ pub fn run() {
             codex::model_list,
             codex::account_rate_limits,
             codex::skills_list,
+            codex::get_workspace_logs,
+            codex::export_workspace_logs,
+            event_sink::get_event_sink_stats,
             prompts::prompts_list,
             prompts::prompts_create,
             prompts::prompts_update,
@@ -292,6 +326,7 @@ pub fn run() {
             dictation::dictation_cancel,
             local_usage::local_usage_snapshot,
             local_usage::thread_token_usage,
+            local_usage::search_threads,
             happy_bridge::happy_bridge_status,
             happy_bridge::happy_bridge_send,
         ])
@@ -340,15 +375,13 @@ async fn shutdown_children(app_handle: &tauri::AppHandle) {
         guard.values().cloned().collect::<Vec<_>>()
     };
     for session in sessions {
-        let mut child = session.child.lock().await;
-        let _ = child.kill().await;
-        let _ = child.wait().await;
+        session.kill().await;
+        let _ = session.child.lock().await.wait().await;
     }
 
     if let Some(session) = state.global_session.get() {
-        let mut child = session.child.lock().await;
-        let _ = child.kill().await;
-        let _ = child.wait().await;
+        session.kill().await;
+        let _ = session.child.lock().await.wait().await;
     }
 
     let terminals = {
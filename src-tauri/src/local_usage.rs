@@ -1,14 +1,18 @@
 use chrono::{DateTime, Duration, Local, TimeZone, Utc};
 use ignore::WalkBuilder;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::backend::orphaned_sessions::{path_matches_workspace, sessions_roots};
+use crate::backend::thread_search::{ThreadSearchHit, ThreadSearchQuery};
 use crate::codex_home::{resolve_default_codex_home, resolve_workspace_codex_home};
+use crate::remote_backend;
 use crate::state::AppState;
 use crate::types::{
     LocalUsageDay, LocalUsageModel, LocalUsageSnapshot, LocalUsageTotals, WorkspaceEntry,
@@ -89,6 +93,46 @@ pub(crate) async fn thread_token_usage(
     Ok(usage)
 }
 
+/// Full-text search over user and agent messages and commands in every known rollout.
+#[tauri::command]
+pub(crate) async fn search_threads(
+    query: ThreadSearchQuery,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ThreadSearchHit>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "search_threads", json!({ "query": query }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let (sessions_roots, workspace_path) = {
+        let workspaces = state.workspaces.lock().await;
+        let workspace_path = match query.workspace_id.as_deref() {
+            Some(workspace_id) => {
+                let entry = workspaces
+                    .get(workspace_id)
+                    .ok_or_else(|| "workspace not found".to_string())?;
+                Some(PathBuf::from(&entry.path))
+            }
+            None => None,
+        };
+        let roots = resolve_sessions_roots(&workspaces, workspace_path.as_deref());
+        (roots, workspace_path)
+    };
+    let search_roots = build_session_search_roots(&sessions_roots);
+    let index = Arc::clone(&state.thread_search);
+    tokio::task::spawn_blocking(move || {
+        let mut index = index
+            .lock()
+            .map_err(|_| "thread search index unavailable".to_string())?;
+        index.refresh(&search_roots);
+        Ok(index.search(&query, &search_roots, workspace_path.as_deref()))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
 fn scan_local_usage(
     days: u32,
     workspace_path: Option<&Path>,
@@ -536,11 +580,6 @@ fn extract_cwd(value: &Value) -> Option<String> {
         .map(|cwd| cwd.to_string())
 }
 
-fn path_matches_workspace(cwd: &str, workspace_path: &Path) -> bool {
-    let cwd_path = Path::new(cwd);
-    cwd_path == workspace_path || cwd_path.starts_with(workspace_path)
-}
-
 fn make_day_keys(days: u32) -> Vec<String> {
     let today = Local::now().date_naive();
     (0..days)
@@ -570,31 +609,7 @@ fn resolve_sessions_roots(
             .collect();
     }
 
-    let mut roots = Vec::new();
-    let mut seen = HashSet::new();
-
-    if let Some(root) = resolve_codex_sessions_root(None) {
-        if seen.insert(root.clone()) {
-            roots.push(root);
-        }
-    }
-
-    for entry in workspaces.values() {
-        let parent_entry = entry
-            .parent_id
-            .as_ref()
-            .and_then(|parent_id| workspaces.get(parent_id));
-        let Some(codex_home) = resolve_workspace_codex_home(entry, parent_entry) else {
-            continue;
-        };
-        if let Some(root) = resolve_codex_sessions_root(Some(codex_home)) {
-            if seen.insert(root.clone()) {
-                roots.push(root);
-            }
-        }
-    }
-
-    roots
+    sessions_roots(workspaces)
 }
 
 fn build_session_search_roots(sessions_roots: &[PathBuf]) -> Vec<PathBuf> {
@@ -9,10 +9,12 @@ use serde_json::{json, Map, Value};
 use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
-use tokio::sync::{mpsc, Mutex as TokioMutex};
+use tokio::sync::Mutex as TokioMutex;
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use uuid::Uuid;
 
+use crate::backend::event_bus::spawn_bridge_observer;
 use crate::remote_backend;
 use crate::state::AppState;
 use crate::types::AppSettings;
@@ -52,6 +54,7 @@ pub(crate) struct NanobotBridgeState {
     provider_system_hash_by_key: HashMap<String, String>,
     provider_tools_hash_by_key: HashMap<String, String>,
     provider_turn_lock_by_key: HashMap<String, Arc<TokioMutex<()>>>,
+    forwarder: Option<JoinHandle<()>>,
 }
 
 impl Default for NanobotBridgeState {
@@ -65,6 +68,7 @@ impl Default for NanobotBridgeState {
             provider_system_hash_by_key: HashMap::new(),
             provider_tools_hash_by_key: HashMap::new(),
             provider_turn_lock_by_key: HashMap::new(),
+            forwarder: None,
         }
     }
 }
@@ -794,11 +798,7 @@ async fn run_provider_completion(
             temperature,
         );
 
-        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.insert(thread_id.clone(), tx);
-        }
+        let mut events = session.claim_thread_events(&thread_id);
 
         let mut turn_params = Map::new();
         turn_params.insert("threadId".to_string(), json!(thread_id.clone()));
@@ -831,8 +831,7 @@ async fn run_provider_completion(
         let turn_result = match turn_result {
             Ok(Ok(value)) => value,
             Ok(Err(error)) => {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
+                drop(events);
                 archive_provider_thread(&session, &thread_id).await;
                 clear_provider_thread_for_key(app, provider_key).await;
                 if attempt == 0 {
@@ -842,8 +841,7 @@ async fn run_provider_completion(
                 return Err(error);
             }
             Err(_) => {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
+                drop(events);
                 archive_provider_thread(&session, &thread_id).await;
                 clear_provider_thread_for_key(app, provider_key).await;
                 if attempt == 0 {
@@ -854,8 +852,7 @@ async fn run_provider_completion(
             }
         };
         if let Some(error) = extract_error_message(&turn_result) {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
+            drop(events);
             archive_provider_thread(&session, &thread_id).await;
             clear_provider_thread_for_key(app, provider_key).await;
             if attempt == 0 {
@@ -871,19 +868,19 @@ async fn run_provider_completion(
                 let mut text = String::new();
                 let first_event = timeout(
                     Duration::from_secs(PROVIDER_TURN_FIRST_EVENT_TIMEOUT_SECS),
-                    rx.recv(),
+                    events.recv(),
                 )
                 .await
                 .map_err(|_| "provider turn stalled waiting for output".to_string())?
                 .ok_or_else(|| "provider turn stream closed".to_string())?;
 
-                let mut pending = Some(first_event);
+                let mut pending = Some(first_event.message);
                 loop {
                     let event = if let Some(value) = pending.take() {
                         value
                     } else {
-                        match rx.recv().await {
-                            Some(value) => value,
+                        match events.recv().await {
+                            Some(event) => event.message,
                             None => break,
                         }
                     };
@@ -935,10 +932,7 @@ async fn run_provider_completion(
         )
         .await;
 
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
-        }
+        drop(events);
 
         match collect_result {
             Ok(Ok(text)) => {
@@ -1218,6 +1212,9 @@ pub(crate) async fn apply_settings(
     }
     guard.stdin = None;
     guard.provider_turn_lock_by_key.clear();
+    if let Some(forwarder) = guard.forwarder.take() {
+        forwarder.abort();
+    }
     guard.config = Some(config.clone());
 
     if !config.should_run() {
@@ -1250,6 +1247,7 @@ pub(crate) async fn apply_settings(
 
     guard.stdin = child.stdin.take();
     guard.child = Some(child);
+    guard.forwarder = Some(spawn_event_forwarder(app.clone(), state));
     Ok(())
 }
 
@@ -1261,6 +1259,17 @@ pub(crate) async fn shutdown(state: &AppState) {
     guard.stdin = None;
     guard.provider_turn_lock_by_key.clear();
     guard.config = None;
+    if let Some(forwarder) = guard.forwarder.take() {
+        forwarder.abort();
+    }
+}
+
+/// Mirrors turn events from the event bus so the daemon sees the same threads as the UI.
+fn spawn_event_forwarder(app: AppHandle, state: &AppState) -> JoinHandle<()> {
+    spawn_bridge_observer(&state.event_bus, move |command| {
+        let app = app.clone();
+        async move { send_daemon_command(&app, command).await }
+    })
 }
 
 #[tauri::command]
@@ -263,6 +263,12 @@ enum BridgeCommand {
         role: String,
         content: String,
     },
+    /// A turn event of a thread the app is showing, mirrored from its event bus.
+    AppServerEvent {
+        #[serde(rename = "threadId")]
+        thread_id: String,
+        method: String,
+    },
 }
 
 fn panic_to_string(payload: Box<dyn std::any::Any + Send>) -> String {
@@ -1007,6 +1013,29 @@ async fn run_daemon() -> Result<(), String> {
                     let _ = waiter.send(result);
                 }
             }
+            BridgeCommand::AppServerEvent { thread_id, method } => {
+                let active = match method.as_str() {
+                    "turn/started" => true,
+                    "turn/completed" => false,
+                    _ => continue,
+                };
+                let session_key = {
+                    let guard = routes.lock().await;
+                    let Some(session_key) = guard.session_key_by_thread.get(&thread_id) else {
+                        continue;
+                    };
+                    session_key.clone()
+                };
+                emit_event(
+                    &out_tx,
+                    json!({
+                        "type": "thread-activity",
+                        "threadId": thread_id,
+                        "sessionKey": session_key,
+                        "active": active,
+                    }),
+                );
+            }
             BridgeCommand::ThreadMessage {
                 message_id,
                 thread_id,
@@ -1,20 +1,68 @@
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
 
-use tauri::{AppHandle, Emitter};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_rustls::TlsConnector;
 
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::tls::{pinned_client_config, server_name_for_host};
+use crate::event_sink::TauriEventSink;
 use crate::state::AppState;
-use crate::types::BackendMode;
+use crate::types::{AppSettings, BackendMode};
 
 const DEFAULT_REMOTE_HOST: &str = "127.0.0.1:4732";
 const DISCONNECTED_MESSAGE: &str = "remote backend disconnected";
+const CONNECTION_STATE_EVENT: &str = "remote-backend-connection";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+const IDEMPOTENT_METHODS: &[&str] = &[
+    "list_workspaces",
+    "is_workspace_path_dir",
+    "worktree_setup_status",
+    "list_workspace_files",
+    "read_workspace_file",
+    "file_read",
+    "get_app_settings",
+    "get_codex_config_path",
+    "get_config_model",
+    "list_threads",
+    "scan_orphaned_sessions",
+    "get_workspace_logs",
+    "get_approval_audit_log",
+    "export_thread",
+    "search_threads",
+    "get_message_queue",
+    "get_thread_metadata",
+    "query_thread_metadata",
+    "get_git_status",
+    "get_git_diffs",
+    "get_commit_message_prompt",
+    "get_git_log",
+    "get_git_commit_diff",
+    "list_git_roots",
+    "list_git_branches",
+    "list_git_checkpoints",
+    "model_list",
+    "collaboration_mode_list",
+    "account_rate_limits",
+    "skills_list",
+];
+
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
 
 type PendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
+type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
 
 pub(crate) fn normalize_path_for_remote(path: String) -> String {
     let trimmed = path.trim();
@@ -56,6 +104,8 @@ pub(crate) struct RemoteBackend {
 }
 
 struct RemoteBackendInner {
+    generation: u64,
+    host: String,
     out_tx: mpsc::UnboundedSender<String>,
     pending: Arc<Mutex<PendingMap>>,
     next_id: AtomicU64,
@@ -63,12 +113,24 @@ struct RemoteBackendInner {
 }
 
 impl RemoteBackend {
+    pub(crate) fn is_connected(&self) -> bool {
+        self.inner.connected.load(Ordering::SeqCst)
+    }
+
     pub(crate) async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
-        if !self.inner.connected.load(Ordering::SeqCst) {
+        self.call_with_id(self.next_request_id(), method, params)
+            .await
+    }
+
+    fn next_request_id(&self) -> u64 {
+        self.inner.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    async fn call_with_id(&self, id: u64, method: &str, params: Value) -> Result<Value, String> {
+        if !self.is_connected() {
             return Err(DISCONNECTED_MESSAGE.to_string());
         }
 
-        let id = self.inner.next_id.fetch_add(1, Ordering::SeqCst);
         let (tx, rx) = oneshot::channel();
         self.inner.pending.lock().await.insert(id, tx);
 
@@ -87,6 +149,122 @@ impl RemoteBackend {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EventCursor {
+    stream_id: String,
+    seq: u64,
+}
+
+/// Where the daemon event stream stands, kept across reconnects.
+#[derive(Default)]
+pub(crate) struct RemoteEventState {
+    /// Last daemon event applied, so the next connection can resume.
+    cursor: StdMutex<Option<EventCursor>>,
+    /// Workspaces the app shows; `None` until the frontend reports them, which subscribes to all.
+    workspaces: StdMutex<Option<Vec<String>>>,
+}
+
+impl RemoteEventState {
+    fn cursor(&self) -> std::sync::MutexGuard<'_, Option<EventCursor>> {
+        self.cursor.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn workspaces(&self) -> std::sync::MutexGuard<'_, Option<Vec<String>>> {
+        self.workspaces
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Per-connection state for the `subscribe` handshake. While resuming, events that
+/// arrive before the subscribe response are dropped because the daemon replays them.
+struct EventResume {
+    subscribe_id: AtomicU64,
+    holding: AtomicBool,
+}
+
+/// Applies the subscribe response. A different stream means the daemon restarted or the
+/// host changed, so the cursor starts over from what this connection has already seen.
+fn apply_subscribe_result(cursor: &mut Option<EventCursor>, result: &Value, last_seen: u64) {
+    let Some(stream_id) = result.get("streamId").and_then(Value::as_str) else {
+        return;
+    };
+    if cursor
+        .as_ref()
+        .is_some_and(|cursor| cursor.stream_id == stream_id)
+    {
+        return;
+    }
+    *cursor = Some(EventCursor {
+        stream_id: stream_id.to_string(),
+        seq: last_seen,
+    });
+}
+
+/// Parameters for `subscribe`: where to resume and which workspaces to stream.
+fn subscribe_params(cursor: Option<EventCursor>, workspace_ids: Option<Vec<String>>) -> Value {
+    let mut params = json!({});
+    if let Some(cursor) = cursor {
+        params["streamId"] = json!(cursor.stream_id);
+        params["lastSeq"] = json!(cursor.seq);
+    }
+    if let Some(workspace_ids) = workspace_ids {
+        params["workspaceIds"] = json!(workspace_ids);
+    }
+    params
+}
+
+/// Records `seq` and returns false when the event was already applied.
+fn advance_event_cursor(cursor: &mut Option<EventCursor>, seq: u64) -> bool {
+    match cursor {
+        Some(cursor) if seq <= cursor.seq => false,
+        Some(cursor) => {
+            cursor.seq = seq;
+            true
+        }
+        None => true,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ConnectionState {
+    Connected,
+    Disconnected,
+    Reconnecting,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionStateEvent {
+    state: ConnectionState,
+    host: String,
+    attempt: u32,
+    retry_in_ms: Option<u64>,
+    error: Option<String>,
+    /// Set on `Connected` when the daemon no longer had every event since the last one
+    /// applied, so threads must be reloaded.
+    missed_events: bool,
+}
+
+fn emit_connection_state(app: &AppHandle, event: ConnectionStateEvent) {
+    let _ = app.emit(CONNECTION_STATE_EVENT, event);
+}
+
+/// Methods that only read state, so a call lost to a dropped connection can be sent again.
+fn is_idempotent(method: &str) -> bool {
+    IDEMPOTENT_METHODS.contains(&method)
+}
+
+/// Exponential backoff for reconnect attempt `attempt` (1-based), capped at
+/// `RECONNECT_MAX_DELAY`.
+fn reconnect_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    RECONNECT_BASE_DELAY
+        .saturating_mul(1 << exponent)
+        .min(RECONNECT_MAX_DELAY)
+}
+
 pub(crate) async fn is_remote_mode(state: &AppState) -> bool {
     let settings = state.app_settings.lock().await;
     matches!(settings.backend_mode, BackendMode::Remote)
@@ -98,43 +276,144 @@ pub(crate) async fn call_remote(
     method: &str,
     params: Value,
 ) -> Result<Value, String> {
-    let client = ensure_remote_backend(state, app).await?;
-    match client.call(method, params).await {
-        Ok(value) => Ok(value),
-        Err(err) => {
-            *state.remote_backend.lock().await = None;
-            Err(err)
+    let client = ensure_remote_backend(state, app.clone()).await?;
+    match client.call(method, params.clone()).await {
+        Err(err)
+            if err == DISCONNECTED_MESSAGE
+                && is_idempotent(method)
+                && state.app_settings.lock().await.remote_backend_retry_reads =>
+        {
+            let client = ensure_remote_backend(state, app).await?;
+            client.call(method, params).await
         }
+        result => result,
     }
 }
 
-async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<RemoteBackend, String> {
+/// Limits daemon events to `workspace_ids`, now and on every later reconnect.
+pub(crate) async fn set_event_workspaces(
+    state: &AppState,
+    workspace_ids: Vec<String>,
+) -> Result<(), String> {
+    *state.remote_events.workspaces() = Some(workspace_ids.clone());
+    let client = state
+        .remote_backend
+        .lock()
+        .await
+        .clone()
+        .filter(|client| client.is_connected());
+    let Some(client) = client else {
+        // The next connection subscribes with the new list.
+        return Ok(());
+    };
+    // Without a cursor the daemon resumes from what this connection already received.
+    match client
+        .call("subscribe", subscribe_params(None, Some(workspace_ids)))
+        .await
     {
-        let guard = state.remote_backend.lock().await;
-        if let Some(client) = guard.as_ref() {
-            return Ok(client.clone());
-        }
+        Err(err) if !err.starts_with("unknown method") => Err(err),
+        _ => Ok(()),
     }
+}
 
-    let (host, token) = {
-        let settings = state.app_settings.lock().await;
-        (
-            settings.remote_backend_host.clone(),
-            settings.remote_backend_token.clone(),
-        )
-    };
+#[derive(Clone, PartialEq)]
+struct RemoteSettings {
+    host: String,
+    token: Option<String>,
+    tls_fingerprint: Option<String>,
+}
 
-    let resolved_host = if host.trim().is_empty() {
-        DEFAULT_REMOTE_HOST.to_string()
-    } else {
-        host
-    };
+async fn remote_settings(state: &AppState) -> RemoteSettings {
+    remote_settings_from(&*state.app_settings.lock().await)
+}
+
+fn remote_settings_from(settings: &AppSettings) -> RemoteSettings {
+    let host = settings.remote_backend_host.trim();
+    RemoteSettings {
+        host: if host.is_empty() {
+            DEFAULT_REMOTE_HOST.to_string()
+        } else {
+            host.to_string()
+        },
+        token: settings.remote_backend_token.clone(),
+        tls_fingerprint: settings
+            .remote_backend_tls_fingerprint
+            .clone()
+            .filter(|value| !value.trim().is_empty()),
+    }
+}
+
+/// Returns the cached client, replacing it with a fresh connection once it has dropped.
+async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<RemoteBackend, String> {
+    loop {
+        if let Some(client) = connected_client(state).await {
+            return Ok(client);
+        }
+        // Dial without holding the client lock so other calls and `set_event_workspaces`
+        // are not stuck behind the connect timeouts.
+        let settings = remote_settings(state).await;
+        let client = open_remote_backend(
+            app.clone(),
+            settings.clone(),
+            Arc::clone(&state.remote_events),
+        )
+        .await?;
+        if let Some(client) = install_client(state, client, &settings).await {
+            return Ok(client);
+        }
+    }
+}
 
-    let stream = TcpStream::connect(resolved_host.clone())
+async fn connected_client(state: &AppState) -> Option<RemoteBackend> {
+    state
+        .remote_backend
+        .lock()
         .await
-        .map_err(|err| format!("Failed to connect to remote backend at {resolved_host}: {err}"))?;
-    let (reader, mut writer) = stream.into_split();
+        .as_ref()
+        .filter(|client| client.is_connected())
+        .cloned()
+}
+
+/// Caches `client`, dialed with `dialed`, and returns the client to use. When another
+/// connection won the race, `client` is closed and the cached one returned; when the
+/// settings changed while dialing, `client` is closed and `None` returned.
+async fn install_client(
+    state: &AppState,
+    client: RemoteBackend,
+    dialed: &RemoteSettings,
+) -> Option<RemoteBackend> {
+    // Settings, then client: the order `update_app_settings` takes them in, so a settings
+    // change cannot slip in between the check and the swap.
+    let settings = state.app_settings.lock().await;
+    let mut guard = state.remote_backend.lock().await;
+    let current = guard
+        .as_ref()
+        .filter(|current| current.is_connected())
+        .cloned();
+    if current.is_some() || remote_settings_from(&settings) != *dialed {
+        client.inner.connected.store(false, Ordering::SeqCst);
+        return current;
+    }
+    *guard = Some(client.clone());
+    Some(client)
+}
+
+async fn open_remote_backend(
+    app: AppHandle,
+    settings: RemoteSettings,
+    events: Arc<RemoteEventState>,
+) -> Result<RemoteBackend, String> {
+    let RemoteSettings {
+        host,
+        token,
+        tls_fingerprint,
+    } = settings;
+    let (reader, mut writer) =
+        tokio::time::timeout(CONNECT_TIMEOUT, connect(&host, tls_fingerprint.as_deref()))
+            .await
+            .map_err(|_| format!("Timed out connecting to remote backend at {host}"))??;
 
+    let generation = NEXT_GENERATION.fetch_add(1, Ordering::SeqCst);
     let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
     let pending = Arc::new(Mutex::new(PendingMap::new()));
     let pending_for_writer = Arc::clone(&pending);
@@ -144,6 +423,13 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
     let connected_for_writer = Arc::clone(&connected);
     let connected_for_reader = Arc::clone(&connected);
 
+    let cursor = events.cursor().clone();
+    let resume = Arc::new(EventResume {
+        subscribe_id: AtomicU64::new(0),
+        holding: AtomicBool::new(cursor.is_some()),
+    });
+    let resume_for_reader = Arc::clone(&resume);
+
     let write_task = tokio::spawn(async move {
         while let Some(message) = out_rx.recv().await {
             if writer.write_all(message.as_bytes()).await.is_err()
@@ -154,24 +440,33 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
                 for (_, sender) in pending.drain() {
                     let _ = sender.send(Err(DISCONNECTED_MESSAGE.to_string()));
                 }
-                break;
+                return;
             }
         }
+        // Every client handle is gone; close our side so the daemon hangs up and the
+        // read task ends too.
+        let _ = writer.shutdown().await;
     });
 
     let app_for_reader = app.clone();
+    let events_for_reader = Arc::clone(&events);
     let read_task = tokio::spawn(async move {
         read_loop(
-            app_for_reader,
+            app_for_reader.clone(),
             reader,
             pending_for_reader,
             connected_for_reader,
+            resume_for_reader,
+            events_for_reader,
         )
         .await;
+        handle_disconnect(app_for_reader, generation).await;
     });
 
     let client = RemoteBackend {
         inner: Arc::new(RemoteBackendInner {
+            generation,
+            host: host.clone(),
             out_tx,
             pending,
             next_id: AtomicU64::new(1),
@@ -180,29 +475,165 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
     };
 
     if let Some(token) = token {
-        client
-            .call("auth", json!({ "token": token }))
-            .await
-            .map(|_| ())?;
+        let auth = tokio::time::timeout(
+            CONNECT_TIMEOUT,
+            client.call("auth", json!({ "token": token })),
+        )
+        .await
+        .map_err(|_| format!("Timed out authenticating with remote backend at {host}"))?;
+        if let Err(err) = auth {
+            client.inner.connected.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
     }
 
-    {
-        let mut guard = state.remote_backend.lock().await;
-        *guard = Some(client.clone());
-    }
+    let subscribe_id = client.next_request_id();
+    resume.subscribe_id.store(subscribe_id, Ordering::SeqCst);
+    let workspace_ids = events.workspaces().clone();
+    let params = subscribe_params(cursor, workspace_ids);
+    let subscribe = tokio::time::timeout(
+        CONNECT_TIMEOUT,
+        client.call_with_id(subscribe_id, "subscribe", params),
+    )
+    .await
+    .map_err(|_| format!("Timed out subscribing to remote backend at {host}"))?;
+    let missed_events = match subscribe {
+        Ok(result) => result.get("truncated").and_then(Value::as_bool) == Some(true),
+        // Older daemons have no `subscribe` and keep streaming every event.
+        Err(err) if err.starts_with("unknown method") => false,
+        Err(err) => {
+            client.inner.connected.store(false, Ordering::SeqCst);
+            return Err(err);
+        }
+    };
 
     drop((write_task, read_task));
 
+    emit_connection_state(
+        &app,
+        ConnectionStateEvent {
+            state: ConnectionState::Connected,
+            host,
+            attempt: 0,
+            retry_in_ms: None,
+            error: None,
+            missed_events,
+        },
+    );
     Ok(client)
 }
 
+/// Runs after a connection's read loop ends. Connections that were replaced or cleared on
+/// purpose are ignored; an unexpected drop clears the cached client and reconnects with
+/// backoff for as long as the app stays in remote mode. Boxed because reconnecting spawns
+/// this again from `open_remote_backend`.
+fn handle_disconnect(app: AppHandle, generation: u64) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        let state = app.state::<AppState>();
+        let host = {
+            let mut guard = state.remote_backend.lock().await;
+            match guard.as_ref() {
+                Some(client) if client.inner.generation == generation => {
+                    let host = client.inner.host.clone();
+                    *guard = None;
+                    host
+                }
+                _ => return,
+            }
+        };
+        emit_connection_state(
+            &app,
+            ConnectionStateEvent {
+                state: ConnectionState::Disconnected,
+                host,
+                attempt: 0,
+                retry_in_ms: None,
+                error: Some(DISCONNECTED_MESSAGE.to_string()),
+                missed_events: false,
+            },
+        );
+
+        let mut attempt: u32 = 0;
+        let mut last_error: Option<String> = None;
+        loop {
+            if !is_remote_mode(&state).await {
+                return;
+            }
+            attempt = attempt.saturating_add(1);
+            let delay = reconnect_delay(attempt);
+            let settings = remote_settings(&state).await;
+            emit_connection_state(
+                &app,
+                ConnectionStateEvent {
+                    state: ConnectionState::Reconnecting,
+                    host: settings.host.clone(),
+                    attempt,
+                    retry_in_ms: Some(delay.as_millis() as u64),
+                    error: last_error.take(),
+                    missed_events: false,
+                },
+            );
+            tokio::time::sleep(delay).await;
+            if !is_remote_mode(&state).await {
+                return;
+            }
+
+            if connected_client(&state).await.is_some() {
+                // A call already reconnected while we were waiting.
+                return;
+            }
+            // Dial without holding the client lock so calls are not stuck behind the
+            // connect timeout; they reconnect on their own and win any race.
+            let settings = remote_settings(&state).await;
+            let dialed = open_remote_backend(
+                app.clone(),
+                settings.clone(),
+                Arc::clone(&state.remote_events),
+            )
+            .await;
+            match dialed {
+                Ok(client) => {
+                    if install_client(&state, client, &settings).await.is_some() {
+                        return;
+                    }
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+    })
+}
+
+/// Opens the connection, over TLS pinned to `tls_fingerprint` when one is configured.
+async fn connect(
+    host: &str,
+    tls_fingerprint: Option<&str>,
+) -> Result<(BoxedReader, BoxedWriter), String> {
+    let stream = TcpStream::connect(host)
+        .await
+        .map_err(|err| format!("Failed to connect to remote backend at {host}: {err}"))?;
+    let Some(fingerprint) = tls_fingerprint else {
+        let (reader, writer) = stream.into_split();
+        return Ok((Box::new(reader), Box::new(writer)));
+    };
+    let connector = TlsConnector::from(pinned_client_config(fingerprint)?);
+    let stream = connector
+        .connect(server_name_for_host(host), stream)
+        .await
+        .map_err(|err| format!("TLS handshake with remote backend at {host} failed: {err}"))?;
+    let (reader, writer) = tokio::io::split(stream);
+    Ok((Box::new(reader), Box::new(writer)))
+}
+
 async fn read_loop(
     app: AppHandle,
-    reader: tokio::net::tcp::OwnedReadHalf,
+    reader: BoxedReader,
     pending: Arc<Mutex<PendingMap>>,
     connected: Arc<AtomicBool>,
+    resume: Arc<EventResume>,
+    events: Arc<RemoteEventState>,
 ) {
     let mut lines = BufReader::new(reader).lines();
+    let mut last_seen = 0;
 
     while let Ok(Some(line)) = lines.next_line().await {
         let trimmed = line.trim();
@@ -216,6 +647,12 @@ async fn read_loop(
         };
 
         if let Some(id) = message.get("id").and_then(|value| value.as_u64()) {
+            if id == resume.subscribe_id.load(Ordering::SeqCst) {
+                if let Some(result) = message.get("result") {
+                    apply_subscribe_result(&mut events.cursor(), result, last_seen);
+                }
+                resume.holding.store(false, Ordering::SeqCst);
+            }
             let sender = pending.lock().await.remove(&id);
             let Some(sender) = sender else {
                 continue;
@@ -239,13 +676,33 @@ async fn read_loop(
             .get("method")
             .and_then(|value| value.as_str())
             .unwrap_or("");
-        if method.is_empty() {
+        if method.is_empty() || resume.holding.load(Ordering::SeqCst) {
             continue;
         }
+        if let Some(seq) = message.get("seq").and_then(Value::as_u64) {
+            if !advance_event_cursor(&mut events.cursor(), seq) {
+                continue;
+            }
+            last_seen = seq;
+        }
         let params = message.get("params").cloned().unwrap_or(Value::Null);
         match method {
             "app-server-event" => {
-                let _ = app.emit("app-server-event", params);
+                let workspace_id = params
+                    .get("workspace_id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                match (workspace_id, params.get("message")) {
+                    (Some(workspace_id), Some(message)) => {
+                        TauriEventSink::new(app.clone()).emit_app_server_event(AppServerEvent {
+                            workspace_id,
+                            message: message.clone(),
+                        });
+                    }
+                    _ => {
+                        let _ = app.emit("app-server-event", params);
+                    }
+                }
             }
             "terminal-output" => {
                 let _ = app.emit("terminal-output", params);
@@ -263,3 +720,81 @@ async fn read_loop(
         let _ = sender.send(Err(DISCONNECTED_MESSAGE.to_string()));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        advance_event_cursor, apply_subscribe_result, is_idempotent, reconnect_delay,
+        subscribe_params, EventCursor, RECONNECT_MAX_DELAY,
+    };
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn reconnect_delay_doubles_up_to_the_cap() {
+        assert_eq!(reconnect_delay(1), Duration::from_millis(500));
+        assert_eq!(reconnect_delay(2), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(4), Duration::from_secs(4));
+        assert_eq!(reconnect_delay(7), RECONNECT_MAX_DELAY);
+        assert_eq!(reconnect_delay(u32::MAX), RECONNECT_MAX_DELAY);
+    }
+
+    #[test]
+    fn only_read_only_methods_are_retried() {
+        assert!(is_idempotent("list_workspaces"));
+        assert!(is_idempotent("list_threads"));
+        assert!(!is_idempotent("send_user_message"));
+        assert!(!is_idempotent("restore_git_checkpoint"));
+    }
+
+    #[test]
+    fn event_cursor_skips_events_already_applied() {
+        let mut cursor = Some(EventCursor {
+            stream_id: "stream-a".to_string(),
+            seq: 5,
+        });
+        assert!(!advance_event_cursor(&mut cursor, 5));
+        assert!(advance_event_cursor(&mut cursor, 6));
+        assert_eq!(cursor.as_ref().map(|cursor| cursor.seq), Some(6));
+
+        let mut empty = None;
+        assert!(advance_event_cursor(&mut empty, 1));
+        assert!(empty.is_none());
+    }
+
+    #[test]
+    fn subscribe_result_resets_cursor_for_a_new_stream() {
+        let mut cursor = Some(EventCursor {
+            stream_id: "stream-a".to_string(),
+            seq: 40,
+        });
+        apply_subscribe_result(
+            &mut cursor,
+            &json!({ "streamId": "stream-a", "seq": 45 }),
+            0,
+        );
+        assert_eq!(cursor.as_ref().map(|cursor| cursor.seq), Some(40));
+
+        apply_subscribe_result(&mut cursor, &json!({ "streamId": "stream-b", "seq": 3 }), 2);
+        assert_eq!(
+            cursor,
+            Some(EventCursor {
+                stream_id: "stream-b".to_string(),
+                seq: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn subscribe_params_carry_cursor_and_workspaces() {
+        assert_eq!(subscribe_params(None, None), json!({}));
+        let cursor = EventCursor {
+            stream_id: "stream-a".to_string(),
+            seq: 7,
+        };
+        assert_eq!(
+            subscribe_params(Some(cursor), Some(vec!["ws-1".to_string()])),
+            json!({ "streamId": "stream-a", "lastSeq": 7, "workspaceIds": ["ws-1"] })
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use tauri::{Manager, State, Window};
 
+use crate::backend::sandbox_profiles::{replace_sandbox_profiles, validate_sandbox_profiles};
 use crate::codex_config;
 use crate::happy_bridge;
 use crate::menu;
@@ -46,8 +47,14 @@ pub(crate) async fn update_app_settings(
     state: State<'_, AppState>,
     window: Window,
 ) -> Result<AppSettings, String> {
+    validate_sandbox_profiles(&settings.sandbox_profiles)?;
     let previous_settings = state.app_settings.lock().await.clone();
     let theme_changed = previous_settings.theme != settings.theme;
+    let remote_connection_changed = previous_settings.remote_backend_host
+        != settings.remote_backend_host
+        || previous_settings.remote_backend_token != settings.remote_backend_token
+        || previous_settings.remote_backend_tls_fingerprint
+            != settings.remote_backend_tls_fingerprint;
     let language_changed = previous_settings.language != settings.language;
     let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
     let _ = codex_config::write_collaboration_modes_enabled(
@@ -59,8 +66,16 @@ pub(crate) async fn update_app_settings(
         eprintln!("nanobot settings sync failed: {error}");
     }
     write_settings(&state.settings_path, &settings)?;
+    replace_sandbox_profiles(&state.sandbox_profiles, settings.sandbox_profiles.clone());
     let mut current = state.app_settings.lock().await;
     *current = settings.clone();
+    if remote_connection_changed {
+        // Reconnect with the new host, token or pinned certificate on the next call.
+        *state.remote_backend.lock().await = None;
+    }
+    state
+        .event_coalescing
+        .set_window_ms(settings.delta_coalesce_window_ms);
     if theme_changed {
         let _ = window::apply_window_appearance(&window, settings.theme.as_str());
     }
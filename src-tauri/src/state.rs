@@ -4,7 +4,13 @@ use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 use tokio::sync::{Mutex, OnceCell};
 
+use crate::backend::event_bus::EventBus;
+use crate::backend::message_queue::MessageQueue;
+use crate::backend::sandbox_profiles::{shared_sandbox_profiles, SharedSandboxProfiles};
+use crate::backend::thread_metadata::ThreadMetadataStore;
+use crate::backend::thread_search::ThreadSearchIndex;
 use crate::dictation::DictationState;
+use crate::event_sink::EventCoalescing;
 use crate::happy_bridge::HappyBridgeState;
 use crate::nanobot_bridge::NanobotBridgeState;
 use crate::storage::{read_settings, read_workspaces};
@@ -12,11 +18,19 @@ use crate::types::{AppSettings, WorkspaceEntry};
 
 pub(crate) struct AppState {
     pub(crate) workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
-    pub(crate) sessions: Mutex<HashMap<String, Arc<crate::codex::WorkspaceSession>>>,
+    pub(crate) sessions: Arc<crate::codex::SessionMap>,
+    pub(crate) parked_threads: Arc<crate::codex::ParkedThreads>,
     pub(crate) global_session: OnceCell<Arc<crate::codex::WorkspaceSession>>,
-    pub(crate) terminal_sessions: Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
+    pub(crate) terminal_sessions: crate::backend::terminal::TerminalSessions,
     pub(crate) remote_backend: Mutex<Option<crate::remote_backend::RemoteBackend>>,
+    pub(crate) remote_events: Arc<crate::remote_backend::RemoteEventState>,
     pub(crate) history_streams: Mutex<HashMap<String, crate::codex::HistoryStreamState>>,
+    pub(crate) event_bus: Arc<EventBus>,
+    pub(crate) event_coalescing: Arc<EventCoalescing>,
+    pub(crate) message_queue: Arc<MessageQueue>,
+    pub(crate) sandbox_profiles: SharedSandboxProfiles,
+    pub(crate) thread_search: Arc<std::sync::Mutex<ThreadSearchIndex>>,
+    pub(crate) thread_metadata: Arc<ThreadMetadataStore>,
     pub(crate) storage_path: PathBuf,
     pub(crate) settings_path: PathBuf,
     pub(crate) app_settings: Mutex<AppSettings>,
@@ -35,13 +49,23 @@ impl AppState {
         let settings_path = data_dir.join("settings.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        let event_coalescing = EventCoalescing::new(app_settings.delta_coalesce_window_ms);
+        let sandbox_profiles = shared_sandbox_profiles(app_settings.sandbox_profiles.clone());
         Self {
             workspaces: Mutex::new(workspaces),
-            sessions: Mutex::new(HashMap::new()),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            parked_threads: Arc::new(std::sync::Mutex::new(HashMap::new())),
             global_session: OnceCell::new(),
             terminal_sessions: Mutex::new(HashMap::new()),
             remote_backend: Mutex::new(None),
+            remote_events: Arc::default(),
             history_streams: Mutex::new(HashMap::new()),
+            event_bus: EventBus::new(),
+            event_coalescing,
+            message_queue: MessageQueue::load(Some(data_dir.join("message_queue.json"))),
+            sandbox_profiles,
+            thread_search: Arc::new(std::sync::Mutex::new(ThreadSearchIndex::default())),
+            thread_metadata: ThreadMetadataStore::load(Some(data_dir.join("thread_metadata.json"))),
             storage_path,
             settings_path,
             app_settings: Mutex::new(app_settings),
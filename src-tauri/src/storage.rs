@@ -1,5 +1,8 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::types::{AppSettings, WorkspaceEntry};
 
@@ -39,9 +42,88 @@ pub(crate) fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(
     std::fs::write(path, data).map_err(|e| e.to_string())
 }
 
+/// Milliseconds since the Unix epoch, as stored in the JSON stores.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Writes `value` as pretty JSON through a temp file and a rename, so readers never see a
+/// half-written file.
+pub(crate) fn write_json_atomic<T: Serialize + ?Sized>(
+    path: &Path,
+    value: &T,
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    std::fs::write(&tmp_path, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        e.to_string()
+    })
+}
+
+/// Reads a JSON store written by `write_json_atomic`; a missing file is the default value.
+/// A file that cannot be read or parsed is logged and moved to `<name>.bak` so the next
+/// save cannot overwrite it. When it cannot be moved, the error is returned instead and
+/// the caller must not save over the file.
+pub(crate) fn read_json_store<T: DeserializeOwned + Default>(
+    path: &Path,
+    label: &str,
+) -> Result<T, String> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let error = match std::fs::read_to_string(path) {
+        Ok(data) => match serde_json::from_str(&data) {
+            Ok(value) => return Ok(value),
+            Err(err) => err.to_string(),
+        },
+        Err(err) => err.to_string(),
+    };
+    eprintln!("failed to load {label} from {}: {error}", path.display());
+    let backup = backup_path(path);
+    match std::fs::rename(path, &backup) {
+        Ok(()) => {
+            eprintln!("kept the unreadable {label} as {}", backup.display());
+            Ok(T::default())
+        }
+        Err(err) => Err(format!(
+            "{label} at {} could not be loaded ({error}) or moved aside ({err}); not overwriting it",
+            path.display()
+        )),
+    }
+}
+
+/// `<name>.bak`, or `<name>.<n>.bak` when earlier backups exist.
+fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut backup = path.with_file_name(format!("{name}.bak"));
+    let mut index = 1;
+    while backup.exists() {
+        backup = path.with_file_name(format!("{name}.{index}.bak"));
+        index += 1;
+    }
+    backup
+}
+
+/// Replaces `dir` with a plain file, so saving a store inside it fails.
+#[cfg(test)]
+pub(crate) fn block_store_dir(dir: &Path) {
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::write(dir, "not a directory").expect("block store dir");
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{read_workspaces, write_workspaces};
+    use super::{read_json_store, read_workspaces, write_json_atomic, write_workspaces};
     use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
     use uuid::Uuid;
 
@@ -81,4 +163,39 @@ mod tests {
             Some("--profile personal")
         );
     }
+
+    #[test]
+    fn write_json_atomic_replaces_the_file_and_reports_errors() {
+        let temp_dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        let path = temp_dir.join("state.json");
+        write_json_atomic(&path, &vec![1, 2]).expect("first write");
+        write_json_atomic(&path, &vec![3]).expect("second write");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[\n  3\n]");
+        assert!(!temp_dir.join("state.json.tmp").exists());
+
+        let blocked = path.join("nested.json");
+        assert!(write_json_atomic(&blocked, &vec![1]).is_err());
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
+
+    #[test]
+    fn read_json_store_moves_unreadable_files_aside() {
+        let temp_dir = std::env::temp_dir().join(format!("open-vibe-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        let path = temp_dir.join("state.json");
+        assert_eq!(read_json_store::<Vec<u32>>(&path, "state"), Ok(Vec::new()));
+
+        std::fs::write(&path, "[1, 2").expect("write corrupt store");
+        assert_eq!(read_json_store::<Vec<u32>>(&path, "state"), Ok(Vec::new()));
+        assert!(!path.exists());
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.join("state.json.bak")).unwrap(),
+            "[1, 2"
+        );
+
+        std::fs::write(&path, "{").expect("write second corrupt store");
+        assert_eq!(read_json_store::<Vec<u32>>(&path, "state"), Ok(Vec::new()));
+        assert!(temp_dir.join("state.json.1.bak").exists());
+        let _ = std::fs::remove_dir_all(temp_dir);
+    }
 }
@@ -179,6 +179,31 @@ pub(crate) struct LocalUsageSnapshot {
     pub(crate) top_models: Vec<LocalUsageModel>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum OrphanedSessionKind {
+    /// The session's `cwd` is not inside any workspace.
+    Unregistered,
+    /// The `cwd` belongs to a workspace but `thread/list` does not return the thread.
+    Unlisted,
+    /// The workspace's threads could not be listed, so it may or may not show the thread.
+    Unknown,
+}
+
+/// A CLI rollout the app does not show.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OrphanedSession {
+    pub(crate) thread_id: String,
+    pub(crate) path: String,
+    pub(crate) cwd: String,
+    pub(crate) kind: OrphanedSessionKind,
+    /// Workspace whose path contains `cwd`, set for unlisted and unknown sessions.
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) started_at: Option<String>,
+    pub(crate) updated_at: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct BranchInfo {
     pub(crate) name: String,
@@ -281,6 +306,81 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) workspace_emoji: Option<String>,
     #[serde(default, rename = "workspaceColor")]
     pub(crate) workspace_color: Option<String>,
+    #[serde(default, rename = "approvalRules")]
+    pub(crate) approval_rules: Vec<ApprovalRule>,
+    /// Profiles offered by the composer for this workspace, on top of the global ones.
+    #[serde(default, rename = "sandboxProfiles")]
+    pub(crate) sandbox_profiles: Vec<SandboxProfile>,
+    /// Skips the git checkpoint taken before each turn.
+    #[serde(default, rename = "disableCheckpoints")]
+    pub(crate) disable_checkpoints: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ApprovalAction {
+    Approve,
+    Deny,
+    Escalate,
+}
+
+/// Automatic response to approval requests. Every condition that is set must match;
+/// the first matching rule in the list wins.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct ApprovalRule {
+    #[serde(default)]
+    pub(crate) id: String,
+    pub(crate) action: ApprovalAction,
+    /// Command tokens the requested command must start with.
+    #[serde(default, rename = "commandPrefix")]
+    pub(crate) command_prefix: Option<Vec<String>>,
+    /// Globs (relative to the workspace root) that every changed file must match.
+    #[serde(default, rename = "pathGlobs")]
+    pub(crate) path_globs: Option<Vec<String>>,
+    /// Sandbox policy types of the running turn, e.g. `workspaceWrite`.
+    #[serde(default, rename = "sandboxModes")]
+    pub(crate) sandbox_modes: Option<Vec<String>>,
+    /// Only match once nobody has interacted with the workspace for this long.
+    #[serde(default, rename = "minIdleSecs")]
+    pub(crate) min_idle_secs: Option<u64>,
+    /// Only match while someone interacted with the workspace within this window.
+    #[serde(default, rename = "maxIdleSecs")]
+    pub(crate) max_idle_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SandboxProfileMode {
+    ReadOnly,
+    #[default]
+    WorkspaceWrite,
+    FullAccess,
+}
+
+/// A named sandbox and approval setup the composer can pick instead of an access mode.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct SandboxProfile {
+    pub(crate) id: String,
+    #[serde(default)]
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) sandbox: SandboxProfileMode,
+    /// Writable in addition to the workspace root when the sandbox is `workspace-write`.
+    #[serde(default, rename = "writableRoots")]
+    pub(crate) writable_roots: Vec<String>,
+    #[serde(default = "default_sandbox_network_access", rename = "networkAccess")]
+    pub(crate) network_access: bool,
+    /// One of `untrusted`, `on-failure`, `on-request` or `never`.
+    #[serde(default = "default_sandbox_approval_policy", rename = "approvalPolicy")]
+    pub(crate) approval_policy: String,
+}
+
+fn default_sandbox_network_access() -> bool {
+    true
+}
+
+fn default_sandbox_approval_policy() -> String {
+    "on-request".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -315,6 +415,16 @@ pub(crate) struct AppSettings {
     pub(crate) remote_backend_host: String,
     #[serde(default, rename = "remoteBackendToken")]
     pub(crate) remote_backend_token: Option<String>,
+    /// SHA-256 fingerprint of the daemon's TLS certificate. When set the connection uses
+    /// TLS and only accepts that certificate.
+    #[serde(default, rename = "remoteBackendTlsFingerprint")]
+    pub(crate) remote_backend_tls_fingerprint: Option<String>,
+    /// Resend read-only requests that were lost to a dropped connection once reconnected.
+    #[serde(
+        default = "default_remote_backend_retry_reads",
+        rename = "remoteBackendRetryReads"
+    )]
+    pub(crate) remote_backend_retry_reads: bool,
     #[serde(default = "default_happy_enabled", rename = "happyEnabled")]
     pub(crate) happy_enabled: bool,
     #[serde(default = "default_happy_server_url", rename = "happyServerUrl")]
@@ -674,6 +784,14 @@ pub(crate) struct AppSettings {
         rename = "experimentalYunyiToken"
     )]
     pub(crate) experimental_yunyi_token: String,
+    #[serde(default, rename = "recordAppServerTranscripts")]
+    pub(crate) record_app_server_transcripts: bool,
+    /// Minutes without activity before a workspace app-server is shut down; 0 disables reaping.
+    #[serde(default, rename = "idleSessionTimeoutMinutes")]
+    pub(crate) idle_session_timeout_minutes: u64,
+    /// Window for merging streaming delta events before they reach the UI; 0 disables it.
+    #[serde(default, rename = "deltaCoalesceWindowMs")]
+    pub(crate) delta_coalesce_window_ms: u64,
     #[serde(default = "default_dictation_enabled", rename = "dictationEnabled")]
     pub(crate) dictation_enabled: bool,
     #[serde(default = "default_dictation_model_id", rename = "dictationModelId")]
@@ -743,6 +861,8 @@ pub(crate) struct AppSettings {
     pub(crate) open_app_targets: Vec<OpenAppTarget>,
     #[serde(default = "default_selected_open_app_id", rename = "selectedOpenAppId")]
     pub(crate) selected_open_app_id: String,
+    #[serde(default, rename = "sandboxProfiles")]
+    pub(crate) sandbox_profiles: Vec<SandboxProfile>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -766,6 +886,10 @@ fn default_remote_backend_host() -> String {
     "127.0.0.1:4732".to_string()
 }
 
+fn default_remote_backend_retry_reads() -> bool {
+    true
+}
+
 fn default_happy_enabled() -> bool {
     false
 }
@@ -1212,6 +1336,8 @@ impl Default for AppSettings {
             backend_mode: BackendMode::Local,
             remote_backend_host: default_remote_backend_host(),
             remote_backend_token: None,
+            remote_backend_tls_fingerprint: None,
+            remote_backend_retry_reads: default_remote_backend_retry_reads(),
             happy_enabled: default_happy_enabled(),
             happy_server_url: default_happy_server_url(),
             happy_token: None,
@@ -1300,6 +1426,9 @@ impl Default for AppSettings {
             experimental_thread_resume_streaming_enabled: false,
             experimental_yunyi_enabled: false,
             experimental_yunyi_token: default_experimental_yunyi_token(),
+            record_app_server_transcripts: false,
+            idle_session_timeout_minutes: 0,
+            delta_coalesce_window_ms: 0,
             dictation_enabled: false,
             dictation_model_id: default_dictation_model_id(),
             dictation_preferred_language: None,
@@ -1320,6 +1449,7 @@ impl Default for AppSettings {
             workspace_groups: default_workspace_groups(),
             open_app_targets: default_open_app_targets(),
             selected_open_app_id: default_selected_open_app_id(),
+            sandbox_profiles: Vec::new(),
         }
     }
 }
@@ -1337,6 +1467,8 @@ mod tests {
         assert!(matches!(settings.backend_mode, BackendMode::Local));
         assert_eq!(settings.remote_backend_host, "127.0.0.1:4732");
         assert!(settings.remote_backend_token.is_none());
+        assert!(settings.remote_backend_tls_fingerprint.is_none());
+        assert!(settings.remote_backend_retry_reads);
         assert!(!settings.happy_enabled);
         assert_eq!(settings.happy_server_url, "https://api.cluster-fluster.com");
         assert!(settings.happy_token.is_none());
@@ -1458,6 +1590,9 @@ mod tests {
         assert!(!settings.experimental_thread_resume_streaming_enabled);
         assert!(!settings.experimental_yunyi_enabled);
         assert!(settings.experimental_yunyi_token.is_empty());
+        assert!(!settings.record_app_server_transcripts);
+        assert_eq!(settings.idle_session_timeout_minutes, 0);
+        assert_eq!(settings.delta_coalesce_window_ms, 0);
         assert!(!settings.dictation_enabled);
         assert_eq!(settings.dictation_model_id, "base");
         assert!(settings.dictation_preferred_language.is_none());
@@ -1475,6 +1610,7 @@ mod tests {
         assert!(!settings.composer_code_block_copy_use_modifier);
         assert!(settings.workspace_groups.is_empty());
         assert_eq!(settings.selected_open_app_id, "vscode");
+        assert!(settings.sandbox_profiles.is_empty());
         assert_eq!(settings.open_app_targets.len(), 6);
         assert_eq!(settings.open_app_targets[0].id, "vscode");
     }
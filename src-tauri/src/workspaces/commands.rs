@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::Ordering;
 
 use serde_json::json;
 use tauri::{AppHandle, Manager, State};
@@ -22,7 +23,8 @@ use super::worktree::{
     unique_worktree_path_for_rename,
 };
 
-use crate::codex::spawn_workspace_session;
+use crate::backend::sandbox_profiles::validate_sandbox_profiles;
+use crate::codex::{install_session, spawn_workspace_session};
 use crate::codex_args::resolve_workspace_codex_args;
 use crate::codex_home::resolve_workspace_codex_home;
 use crate::git_utils::resolve_git_root;
@@ -106,6 +108,18 @@ pub(crate) async fn read_workspace_file(
     read_workspace_file_inner(&root, &path)
 }
 
+/// Tells the remote backend which workspaces' events to stream. Local mode has nothing to filter.
+#[tauri::command]
+pub(crate) async fn set_remote_event_workspaces(
+    workspace_ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if !remote_backend::is_remote_mode(&*state).await {
+        return Ok(());
+    }
+    remote_backend::set_event_workspaces(&*state, workspace_ids).await
+}
+
 #[tauri::command]
 pub(crate) async fn list_workspaces(
     state: State<'_, AppState>,
@@ -323,8 +337,7 @@ pub(crate) async fn add_workspace(
             let mut workspaces = state.workspaces.lock().await;
             workspaces.remove(&entry.id);
         }
-        let mut child = session.child.lock().await;
-        let _ = child.kill().await;
+        session.kill().await;
         return Err(error);
     }
 
@@ -458,8 +471,7 @@ pub(crate) async fn add_clone(
             let mut workspaces = state.workspaces.lock().await;
             workspaces.remove(&entry.id);
         }
-        let mut child = session.child.lock().await;
-        let _ = child.kill().await;
+        session.kill().await;
         let _ = tokio::fs::remove_dir_all(&destination_path).await;
         return Err(error);
     }
@@ -706,8 +718,7 @@ pub(crate) async fn remove_workspace(
     let parent_path = PathBuf::from(&entry.path);
     for child in &child_worktrees {
         if let Some(session) = state.sessions.lock().await.remove(&child.id) {
-            let mut child_process = session.child.lock().await;
-            let _ = child_process.kill().await;
+            session.kill().await;
         }
         let child_path = PathBuf::from(&child.path);
         if child_path.exists() {
@@ -731,8 +742,7 @@ pub(crate) async fn remove_workspace(
     let _ = run_git_command(&parent_path, &["worktree", "prune", "--expire", "now"]).await;
 
     if let Some(session) = state.sessions.lock().await.remove(&id) {
-        let mut child = session.child.lock().await;
-        let _ = child.kill().await;
+        session.kill().await;
     }
 
     {
@@ -774,8 +784,7 @@ pub(crate) async fn remove_worktree(
     };
 
     if let Some(session) = state.sessions.lock().await.remove(&entry.id) {
-        let mut child = session.child.lock().await;
-        let _ = child.kill().await;
+        session.kill().await;
     }
 
     let parent_path = PathBuf::from(&parent.path);
@@ -916,8 +925,7 @@ pub(crate) async fn rename_worktree(
     let was_connected = state.sessions.lock().await.contains_key(&entry_snapshot.id);
     if was_connected {
         if let Some(session) = state.sessions.lock().await.remove(&entry_snapshot.id) {
-            let mut child = session.child.lock().await;
-            let _ = child.kill().await;
+            session.kill().await;
         }
         let (default_bin, codex_args) = {
             let settings = state.app_settings.lock().await;
@@ -1207,6 +1215,7 @@ pub(crate) async fn update_workspace_settings(
 
     let mut settings = settings;
     settings.worktree_setup_script = normalize_setup_script(settings.worktree_setup_script);
+    validate_sandbox_profiles(&settings.sandbox_profiles)?;
 
     let (
         previous_entry,
@@ -1288,8 +1297,7 @@ pub(crate) async fn update_workspace_settings(
             .await
             .insert(entry_snapshot.id.clone(), new_session)
         {
-            let mut child = old_session.child.lock().await;
-            let _ = child.kill().await;
+            old_session.kill().await;
         }
     }
     if codex_home_changed || codex_args_changed {
@@ -1333,11 +1341,22 @@ pub(crate) async fn update_workspace_settings(
                 .await
                 .insert(child.id.clone(), new_session)
             {
-                let mut child = old_session.child.lock().await;
-                let _ = child.kill().await;
+                old_session.kill().await;
             }
         }
     }
+    if let Some(session) = state.sessions.lock().await.get(&id) {
+        session
+            .approvals
+            .set_rules(entry_snapshot.settings.approval_rules.clone());
+        session
+            .sandbox_profiles
+            .set_workspace_profiles(entry_snapshot.settings.sandbox_profiles.clone());
+        session.checkpoints_enabled.store(
+            !entry_snapshot.settings.disable_checkpoints,
+            Ordering::SeqCst,
+        );
+    }
     if worktree_setup_script_changed && !entry_snapshot.kind.is_worktree() {
         let child_ids = child_entries
             .iter()
@@ -1451,8 +1470,7 @@ pub(crate) async fn update_workspace_codex_bin(
             .await
             .insert(entry_snapshot.id.clone(), new_session)
         {
-            let mut child = old_session.child.lock().await;
-            let _ = child.kill().await;
+            old_session.kill().await;
         }
     }
     Ok(WorkspaceInfo {
@@ -1468,17 +1486,19 @@ pub(crate) async fn update_workspace_codex_bin(
     })
 }
 
-async fn connect_workspace_inner(
+pub(crate) async fn connect_workspace_inner(
     id: &str,
     state: &AppState,
     app: AppHandle,
     force_respawn: bool,
 ) -> Result<(), String> {
-    if !force_respawn {
-        let sessions = state.sessions.lock().await;
-        if sessions.contains_key(id) {
-            return Ok(());
-        }
+    let observed = state.sessions.lock().await.get(id).cloned();
+    if !force_respawn
+        && observed
+            .as_ref()
+            .is_some_and(|session| session.is_alive() || session.is_respawning())
+    {
+        return Ok(());
     }
 
     let (entry, parent_entry) = {
@@ -1507,15 +1527,7 @@ async fn connect_workspace_inner(
     let codex_home = resolve_workspace_codex_home(&entry, parent_entry.as_ref());
     let session =
         spawn_workspace_session(entry.clone(), default_bin, codex_args, app, codex_home).await?;
-    let old_session = state
-        .sessions
-        .lock()
-        .await
-        .insert(entry.id.clone(), session);
-    if let Some(old_session) = old_session {
-        let mut child = old_session.child.lock().await;
-        let _ = child.kill().await;
-    }
+    install_session(&state.sessions, observed.as_ref(), session).await;
     Ok(())
 }
 
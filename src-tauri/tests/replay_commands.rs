@@ -0,0 +1,402 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+/// Runs the daemon against `open_vibe_replay`, so workspace commands are answered from
+/// a recorded app-server transcript instead of a real Codex install.
+struct ReplayDaemon {
+    child: Child,
+    dir: PathBuf,
+    stream: BufReader<TcpStream>,
+    next_id: u64,
+}
+
+impl ReplayDaemon {
+    fn start(transcript: &[(&str, Value)]) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or(0);
+        let dir = std::env::temp_dir().join(format!(
+            "open-vibe-replay-test-{}-{nanos}",
+            std::process::id()
+        ));
+        let workspace_dir = dir.join("workspace");
+        let data_dir = dir.join("data");
+        fs::create_dir_all(&workspace_dir).expect("create workspace dir");
+        fs::create_dir_all(&data_dir).expect("create data dir");
+
+        let transcript_path = dir.join("transcript.jsonl");
+        write_transcript(&transcript_path, transcript);
+        let workspaces = json!([{
+            "id": "ws-1",
+            "name": "Replay",
+            "path": workspace_dir.to_string_lossy(),
+            "codex_bin": env!("CARGO_BIN_EXE_open_vibe_replay"),
+        }]);
+        fs::write(data_dir.join("workspaces.json"), workspaces.to_string())
+            .expect("write workspaces");
+        // The global session used by `list_threads_global` runs the default binary.
+        let settings = json!({ "codexBin": env!("CARGO_BIN_EXE_open_vibe_replay") });
+        fs::write(data_dir.join("settings.json"), settings.to_string()).expect("write settings");
+
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map(|addr| addr.port())
+            .expect("pick a free port");
+        let listen = format!("127.0.0.1:{port}");
+        let child = Command::new(env!("CARGO_BIN_EXE_open_vibe_daemon"))
+            .args(["--listen", &listen, "--insecure-no-auth", "--data-dir"])
+            .arg(&data_dir)
+            .env("OPENVIBE_REPLAY_TRANSCRIPT", &transcript_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn daemon");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let stream = loop {
+            match TcpStream::connect(&listen) {
+                Ok(stream) => break stream,
+                Err(error) if Instant::now() > deadline => {
+                    panic!("daemon did not start listening: {error}")
+                }
+                Err(_) => thread::sleep(Duration::from_millis(50)),
+            }
+        };
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .expect("set read timeout");
+        Self {
+            child,
+            dir,
+            stream: BufReader::new(stream),
+            next_id: 1,
+        }
+    }
+
+    fn workspace_dir(&self) -> PathBuf {
+        self.dir.join("workspace")
+    }
+
+    fn read_message(&mut self) -> Value {
+        let mut line = String::new();
+        self.stream.read_line(&mut line).expect("read from daemon");
+        serde_json::from_str(&line).expect("daemon sent invalid JSON")
+    }
+
+    /// Sends an RPC and returns its result, collecting notifications seen on the way.
+    fn call(
+        &mut self,
+        method: &str,
+        params: Value,
+        events: &mut Vec<Value>,
+    ) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({ "id": id, "method": method, "params": params });
+        let stream = self.stream.get_mut();
+        writeln!(stream, "{request}").expect("write to daemon");
+        loop {
+            let message = self.read_message();
+            if message.get("id").and_then(Value::as_u64) == Some(id) {
+                if let Some(error) = message.get("error") {
+                    return Err(error["message"].as_str().unwrap_or_default().to_string());
+                }
+                return Ok(message["result"].clone());
+            }
+            events.push(message);
+        }
+    }
+
+    fn wait_for_event(&mut self, events: &mut Vec<Value>, needle: &str) -> bool {
+        if events
+            .iter()
+            .any(|event| event.to_string().contains(needle))
+        {
+            return true;
+        }
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            let message = self.read_message();
+            let found = message.to_string().contains(needle);
+            events.push(message);
+            if found {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Drop for ReplayDaemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn write_transcript(path: &Path, lines: &[(&str, Value)]) {
+    let contents: String = lines
+        .iter()
+        .map(|(direction, value)| {
+            let entry = json!({ "ts": 0, "direction": direction, "line": value.to_string() });
+            format!("{entry}\n")
+        })
+        .collect();
+    fs::write(path, contents).expect("write transcript");
+}
+
+fn handshake() -> Vec<(&'static str, Value)> {
+    vec![
+        (
+            "out",
+            json!({ "id": 1, "method": "initialize", "params": {} }),
+        ),
+        ("in", json!({ "id": 1, "result": {} })),
+        ("out", json!({ "method": "initialized" })),
+    ]
+}
+
+#[test]
+fn replayed_session_answers_thread_and_model_commands() {
+    let mut transcript = handshake();
+    transcript.extend([
+        (
+            "out",
+            json!({ "id": 2, "method": "thread/list", "params": {} }),
+        ),
+        (
+            "in",
+            json!({
+                "id": 2,
+                "result": { "data": [{ "id": "thread-1", "preview": "hello" }], "nextCursor": null }
+            }),
+        ),
+        (
+            "out",
+            json!({ "id": 3, "method": "thread/start", "params": {} }),
+        ),
+        (
+            "in",
+            json!({ "method": "thread/started", "params": { "thread": { "id": "thread-2" } } }),
+        ),
+        (
+            "in",
+            json!({ "id": 3, "result": { "thread": { "id": "thread-2" } } }),
+        ),
+        (
+            "out",
+            json!({ "id": 4, "method": "model/list", "params": {} }),
+        ),
+        (
+            "in",
+            json!({ "id": 4, "result": { "data": [{ "id": "gpt-5" }] } }),
+        ),
+    ]);
+    let mut daemon = ReplayDaemon::start(&transcript);
+    let mut events = Vec::new();
+
+    daemon
+        .call("connect_workspace", json!({ "id": "ws-1" }), &mut events)
+        .expect("connect_workspace");
+
+    let threads = daemon
+        .call(
+            "list_threads",
+            json!({ "workspaceId": "ws-1", "limit": 20 }),
+            &mut events,
+        )
+        .expect("list_threads");
+    assert_eq!(threads["result"]["data"][0]["id"], json!("thread-1"));
+
+    let started = daemon
+        .call(
+            "start_thread",
+            json!({ "workspaceId": "ws-1" }),
+            &mut events,
+        )
+        .expect("start_thread");
+    assert_eq!(started["result"]["thread"]["id"], json!("thread-2"));
+    assert!(daemon.wait_for_event(&mut events, "thread/started"));
+
+    let models = daemon
+        .call("model_list", json!({ "workspaceId": "ws-1" }), &mut events)
+        .expect("model_list");
+    assert_eq!(models["result"]["data"][0]["id"], json!("gpt-5"));
+}
+
+#[test]
+fn replayed_session_surfaces_unrecorded_requests_as_errors() {
+    let mut daemon = ReplayDaemon::start(&handshake());
+    let mut events = Vec::new();
+    daemon
+        .call("connect_workspace", json!({ "id": "ws-1" }), &mut events)
+        .expect("connect_workspace");
+
+    let error = daemon
+        .call(
+            "list_threads",
+            json!({ "workspaceId": "ws-1" }),
+            &mut events,
+        )
+        .unwrap_err();
+    assert!(error.contains("thread/list"), "{error}");
+}
+
+#[test]
+fn replayed_session_starts_a_turn_for_a_user_message() {
+    let mut transcript = handshake();
+    transcript.extend([
+        (
+            "out",
+            json!({ "id": 2, "method": "turn/start", "params": {} }),
+        ),
+        (
+            "in",
+            json!({ "id": 2, "result": { "turn": { "id": "turn-1" } } }),
+        ),
+        (
+            "in",
+            json!({
+                "method": "item/agentMessage/delta",
+                "params": { "threadId": "thread-1", "turnId": "turn-1", "delta": "Hi there" }
+            }),
+        ),
+        (
+            "in",
+            json!({
+                "method": "turn/completed",
+                "params": { "threadId": "thread-1", "turn": { "id": "turn-1" } }
+            }),
+        ),
+    ]);
+    let mut daemon = ReplayDaemon::start(&transcript);
+    let mut events = Vec::new();
+    daemon
+        .call("connect_workspace", json!({ "id": "ws-1" }), &mut events)
+        .expect("connect_workspace");
+
+    let turn = daemon
+        .call(
+            "send_user_message",
+            json!({ "workspaceId": "ws-1", "threadId": "thread-1", "text": "hello" }),
+            &mut events,
+        )
+        .expect("send_user_message");
+    assert_eq!(turn["result"]["turn"]["id"], json!("turn-1"));
+    assert!(daemon.wait_for_event(&mut events, "Hi there"));
+    assert!(daemon.wait_for_event(&mut events, "turn/completed"));
+}
+
+#[test]
+fn replayed_global_session_lists_every_page_of_threads() {
+    let mut transcript = handshake();
+    transcript.extend([
+        (
+            "out",
+            json!({ "id": 2, "method": "thread/list", "params": {} }),
+        ),
+        (
+            "in",
+            json!({
+                "id": 2,
+                "result": { "data": [{ "id": "thread-1" }], "nextCursor": "page-2" }
+            }),
+        ),
+        (
+            "out",
+            json!({ "id": 3, "method": "thread/list", "params": { "cursor": "page-2" } }),
+        ),
+        (
+            "in",
+            json!({ "id": 3, "result": { "data": [{ "id": "thread-2" }], "nextCursor": null } }),
+        ),
+    ]);
+    let mut daemon = ReplayDaemon::start(&transcript);
+    let mut events = Vec::new();
+
+    let threads = daemon
+        .call("list_threads_global", json!({}), &mut events)
+        .expect("list_threads_global");
+    assert_eq!(
+        threads["data"],
+        json!([{ "id": "thread-1" }, { "id": "thread-2" }])
+    );
+    assert_eq!(threads["nextCursor"], Value::Null);
+}
+
+#[test]
+fn replayed_headless_turn_generates_a_commit_message() {
+    let mut transcript = handshake();
+    transcript.extend([
+        (
+            "out",
+            json!({ "id": 2, "method": "thread/start", "params": {} }),
+        ),
+        (
+            "in",
+            json!({ "id": 2, "result": { "thread": { "id": "headless-1" } } }),
+        ),
+        (
+            "out",
+            json!({ "id": 3, "method": "turn/start", "params": {} }),
+        ),
+        (
+            "in",
+            json!({ "id": 3, "result": { "turn": { "id": "turn-1" } } }),
+        ),
+        (
+            "in",
+            json!({
+                "method": "item/completed",
+                "params": {
+                    "threadId": "headless-1",
+                    "item": { "type": "agentMessage", "text": "docs: add notes" }
+                }
+            }),
+        ),
+        (
+            "in",
+            json!({
+                "method": "turn/completed",
+                "params": { "threadId": "headless-1", "turn": { "id": "turn-1" } }
+            }),
+        ),
+        (
+            "out",
+            json!({ "id": 4, "method": "thread/archive", "params": {} }),
+        ),
+        ("in", json!({ "id": 4, "result": {} })),
+    ]);
+    let mut daemon = ReplayDaemon::start(&transcript);
+    let workspace_dir = daemon.workspace_dir();
+    let status = Command::new("git")
+        .arg("init")
+        .arg("--quiet")
+        .current_dir(&workspace_dir)
+        .status()
+        .expect("run git init");
+    assert!(status.success());
+    fs::write(workspace_dir.join("NOTES.md"), "notes\n").expect("write change");
+    let mut events = Vec::new();
+    daemon
+        .call("connect_workspace", json!({ "id": "ws-1" }), &mut events)
+        .expect("connect_workspace");
+
+    let message = daemon
+        .call(
+            "generate_commit_message",
+            json!({ "workspaceId": "ws-1" }),
+            &mut events,
+        )
+        .expect("generate_commit_message");
+    assert_eq!(message, json!("docs: add notes"));
+}
@@ -69,6 +69,7 @@ import { Separator } from "@/components/ui/separator";
 import { SidebarTrigger } from "@/components/ui/sidebar";
 import { useAppSettingsController } from "./features/app/hooks/useAppSettingsController";
 import { useUpdaterController } from "./features/app/hooks/useUpdaterController";
+import { useRemoteBackendConnection } from "./features/app/hooks/useRemoteBackendConnection";
 import { useErrorToasts } from "./features/notifications/hooks/useErrorToasts";
 import { useComposerShortcuts } from "./features/composer/hooks/useComposerShortcuts";
 import { useComposerMenuActions } from "./features/composer/hooks/useComposerMenuActions";
@@ -125,6 +126,8 @@ import { DebugErrorBoundary } from "./features/app/components/DebugErrorBoundary
 import type {
   AccessMode,
   ComposerEditorSettings,
+  OrphanedSession,
+  SandboxProfile,
   ThemeColor,
   ThemePreference,
   WorkspaceInfo,
@@ -199,6 +202,7 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
   } = useDebugLog();
   useLiquidGlassEffect({ reduceTransparency, onDebug: addDebugEntry });
   const [accessMode, setAccessMode] = useState<AccessMode>("current");
+  const [sandboxProfileId, setSandboxProfileId] = useState<string | null>(null);
   const [activeTab, setActiveTab] = useState<
     "projects" | "codex" | "git" | "log"
   >("codex");
@@ -223,6 +227,7 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
     connectWorkspace,
     reconnectWorkspace,
     markWorkspaceConnected,
+    markWorkspaceDisconnected,
     updateWorkspaceSettings,
     updateWorkspaceCodexBin,
     createWorkspaceGroup,
@@ -804,6 +809,21 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
       "nanobot",
     [workspaces],
   );
+  const sandboxProfiles = useMemo<SandboxProfile[]>(() => {
+    const workspaceProfiles = activeWorkspace?.settings.sandboxProfiles ?? [];
+    const workspaceIds = new Set(workspaceProfiles.map((profile) => profile.id));
+    return [
+      ...workspaceProfiles,
+      ...appSettings.sandboxProfiles.filter(
+        (profile) => !workspaceIds.has(profile.id),
+      ),
+    ];
+  }, [activeWorkspace, appSettings.sandboxProfiles]);
+  const selectedSandboxProfileId = sandboxProfiles.some(
+    (profile) => profile.id === sandboxProfileId,
+  )
+    ? sandboxProfileId
+    : null;
 
   const {
     setActiveThreadId,
@@ -837,6 +857,7 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
     loadOlderThreadsForWorkspace,
     resetWorkspaceThreads,
     refreshThread,
+    resolveQueuedMessage,
     sendUserMessage,
     sendUserMessageToThread,
     retryHappyMessage,
@@ -848,11 +869,13 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
   } = useThreads({
     activeWorkspace,
     onWorkspaceConnected: markWorkspaceConnected,
+    onWorkspaceDisconnected: markWorkspaceDisconnected,
     onDebug: addDebugEntry,
     model: resolvedModel,
     effort: resolvedEffort,
     collaborationMode: collaborationModePayload,
     accessMode,
+    sandboxProfile: selectedSandboxProfileId,
     steerEnabled: appSettings.experimentalSteerEnabled,
     resumeStreamingEnabled: appSettings.experimentalThreadResumeStreamingEnabled,
     customPrompts: prompts,
@@ -1831,6 +1854,7 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
     connectWorkspace,
     sendUserMessage,
     startReview,
+    resolveQueuedMessage,
   });
 
   const otherDraftSource = useMemo(() => {
@@ -2162,6 +2186,42 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
     listThreadsForWorkspace,
     enabled: !isDetachedWindow && appSettings.refreshThreadsOnFocus,
   });
+  const remoteEventWorkspaceIds = useMemo(
+    () => (hasLoaded ? workspaces.map((workspace) => workspace.id) : null),
+    [hasLoaded, workspaces],
+  );
+  const handleRemoteMissedEvents = useCallback(() => {
+    void (async () => {
+      let latestWorkspaces = workspaces;
+      try {
+        const entries = await refreshWorkspaces();
+        if (entries) {
+          latestWorkspaces = entries;
+        }
+      } catch {
+        // Silent: refresh errors show in debug panel.
+      }
+      await Promise.allSettled(
+        latestWorkspaces.map((workspace) => listThreadsForWorkspace(workspace)),
+      );
+      if (activeWorkspaceId && activeThreadId) {
+        void refreshThread(activeWorkspaceId, activeThreadId);
+      }
+    })();
+  }, [
+    activeThreadId,
+    activeWorkspaceId,
+    listThreadsForWorkspace,
+    refreshThread,
+    refreshWorkspaces,
+    workspaces,
+  ]);
+  useRemoteBackendConnection({
+    enabled: appSettings.backendMode === "remote",
+    workspaceIds: remoteEventWorkspaceIds,
+    onDebug: addDebugEntry,
+    onMissedEvents: handleRemoteMissedEvents,
+  });
 
   const {
     handleAddWorkspace,
@@ -3167,6 +3227,9 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
     reasoningSupported,
     accessMode,
     onSelectAccessMode: setAccessMode,
+    sandboxProfiles,
+    selectedSandboxProfileId,
+    onSelectSandboxProfile: setSandboxProfileId,
     skills,
     prompts,
     files,
@@ -3559,6 +3622,19 @@ function MainApp({ detachedTab = null }: MainAppProps = {}) {
             onDownloadDictationModel: dictationModel.download,
             onCancelDictationDownload: dictationModel.cancel,
             onRemoveDictationModel: dictationModel.remove,
+            onOpenOrphanedSession: async (session: OrphanedSession) => {
+              let workspaceId = session.workspaceId;
+              if (!workspaceId) {
+                const workspace = await addWorkspaceFromPath(session.cwd);
+                if (!workspace) {
+                  return;
+                }
+                workspaceId = workspace.id;
+              }
+              closeSettings();
+              selectWorkspace(workspaceId);
+              openThreadTabForWorkspace(workspaceId, session.threadId);
+            },
           }}
         />
       </div>
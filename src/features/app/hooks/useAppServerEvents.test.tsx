@@ -241,6 +241,32 @@ describe("useAppServerEvents", () => {
     });
   });
 
+  it("marks workspaces disconnected after idle shutdown or failed respawn", async () => {
+    const handlers: Handlers = {
+      onWorkspaceConnected: vi.fn(),
+      onWorkspaceDisconnected: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-1",
+        message: { method: "codex/idleShutdown", params: { workspaceId: "ws-1" } },
+      });
+      listener?.({
+        workspace_id: "ws-2",
+        message: { method: "codex/respawnFailed", params: { workspaceId: "ws-2" } },
+      });
+    });
+    expect(handlers.onWorkspaceDisconnected).toHaveBeenNthCalledWith(1, "ws-1");
+    expect(handlers.onWorkspaceDisconnected).toHaveBeenNthCalledWith(2, "ws-2");
+    expect(handlers.onWorkspaceConnected).not.toHaveBeenCalled();
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
   it("ignores delta events missing required fields", async () => {
     const handlers: Handlers = {
       onAgentMessageDelta: vi.fn(),
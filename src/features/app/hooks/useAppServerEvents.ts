@@ -24,6 +24,7 @@ type AgentCompleted = {
 
 type AppServerEventHandlers = {
   onWorkspaceConnected?: (workspaceId: string) => void;
+  onWorkspaceDisconnected?: (workspaceId: string) => void;
   onApprovalRequest?: (request: ApprovalRequest) => void;
   onRequestUserInput?: (request: RequestUserInputRequest) => void;
   onAgentMessageDelta?: (event: AgentDelta) => void;
@@ -35,6 +36,11 @@ type AppServerEventHandlers = {
     streamId: string,
     items: ConversationItem[],
   ) => void;
+  onThreadHistoryCaughtUp?: (
+    workspaceId: string,
+    threadId: string,
+    streamId: string,
+  ) => void;
   onThreadHistoryCompleted?: (
     workspaceId: string,
     threadId: string,
@@ -260,6 +266,14 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         }
         return;
       }
+      if (method === "openvibe/thread_history/caught_up") {
+        const threadId = String(params?.threadId ?? params?.thread_id ?? "");
+        const streamId = String(params?.streamId ?? "");
+        if (threadId && streamId) {
+          handlers.onThreadHistoryCaughtUp?.(workspace_id, threadId, streamId);
+        }
+        return;
+      }
       if (method === "openvibe/thread_history/done") {
         const threadId = String(params?.threadId ?? params?.thread_id ?? "");
         const streamId = String(params?.streamId ?? "");
@@ -288,6 +302,12 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      // The session is gone until the next request reconnects it and resumes its threads.
+      if (method === "codex/idleShutdown" || method === "codex/respawnFailed") {
+        handlers.onWorkspaceDisconnected?.(workspace_id);
+        return;
+      }
+
       const requestId = message.id;
       const hasRequestId =
         typeof requestId === "number" || typeof requestId === "string";
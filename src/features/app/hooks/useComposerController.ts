@@ -1,5 +1,9 @@
 import { useCallback, useState } from "react";
-import type { QueuedMessage, WorkspaceInfo } from "../../../types";
+import type {
+  OutgoingMessage,
+  QueuedMessage,
+  WorkspaceInfo,
+} from "../../../types";
 import { useQueuedSend } from "../../threads/hooks/useQueuedSend";
 import { useComposerDraftStore } from "./useComposerDraftStore";
 
@@ -13,6 +17,7 @@ export function useComposerController({
   connectWorkspace,
   sendUserMessage,
   startReview,
+  resolveQueuedMessage,
 }: {
   activeThreadId: string | null;
   activeWorkspaceId: string | null;
@@ -23,6 +28,11 @@ export function useComposerController({
   connectWorkspace: (workspace: WorkspaceInfo) => Promise<void>;
   sendUserMessage: (text: string, images?: string[]) => Promise<void>;
   startReview: (text: string) => Promise<void>;
+  resolveQueuedMessage: (
+    threadId: string,
+    text: string,
+    images?: string[],
+  ) => OutgoingMessage | null;
 }) {
   const [prefillDraft, setPrefillDraft] = useState<QueuedMessage | null>(null);
   const [composerInsert, setComposerInsert] = useState<QueuedMessage | null>(
@@ -58,6 +68,7 @@ export function useComposerController({
     connectWorkspace,
     sendUserMessage,
     startReview,
+    resolveQueuedMessage,
     clearActiveImages,
   });
 
@@ -0,0 +1,108 @@
+// @vitest-environment jsdom
+import { act } from "react";
+import type { ComponentProps } from "react";
+import { createRoot } from "react-dom/client";
+import { beforeEach, describe, expect, it, vi } from "vitest";
+import {
+  subscribeRemoteBackendConnection,
+  type RemoteBackendConnectionEvent,
+} from "../../../services/events";
+import { setRemoteEventWorkspaces } from "../../../services/tauri";
+import { useRemoteBackendConnection } from "./useRemoteBackendConnection";
+
+vi.mock("../../../services/events", () => ({
+  subscribeRemoteBackendConnection: vi.fn(),
+}));
+
+vi.mock("../../../services/tauri", () => ({
+  setRemoteEventWorkspaces: vi.fn(),
+}));
+
+vi.mock("../../../services/toasts", () => ({
+  pushErrorToast: vi.fn(),
+}));
+
+function Harness(props: Parameters<typeof useRemoteBackendConnection>[0]) {
+  useRemoteBackendConnection(props);
+  return null;
+}
+
+async function mountHarness(props: ComponentProps<typeof Harness>) {
+  const container = document.createElement("div");
+  const root = createRoot(container);
+  await act(async () => {
+    root.render(<Harness {...props} />);
+  });
+  return { root };
+}
+
+const connected = (missedEvents: boolean): RemoteBackendConnectionEvent => ({
+  state: "connected",
+  host: "dev-box:4732",
+  attempt: 0,
+  retryInMs: null,
+  error: null,
+  missedEvents,
+});
+
+describe("useRemoteBackendConnection", () => {
+  let emit: (event: RemoteBackendConnectionEvent) => void = () => {};
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(setRemoteEventWorkspaces).mockResolvedValue(undefined);
+    vi.mocked(subscribeRemoteBackendConnection).mockImplementation((handler) => {
+      emit = handler;
+      return () => {};
+    });
+  });
+
+  it("reloads threads only after a resume that missed events", async () => {
+    const onMissedEvents = vi.fn();
+    const { root } = await mountHarness({
+      enabled: true,
+      workspaceIds: null,
+      onDebug: vi.fn(),
+      onMissedEvents,
+    });
+
+    act(() => {
+      emit(connected(false));
+    });
+    expect(onMissedEvents).not.toHaveBeenCalled();
+
+    act(() => {
+      emit(connected(true));
+    });
+    expect(onMissedEvents).toHaveBeenCalledTimes(1);
+
+    act(() => {
+      root.unmount();
+    });
+  });
+
+  it("subscribes to the listed workspaces once per distinct set", async () => {
+    const props = {
+      enabled: true,
+      workspaceIds: ["ws-1", "ws-2"],
+      onDebug: vi.fn(),
+      onMissedEvents: vi.fn(),
+    };
+    const { root } = await mountHarness(props);
+    expect(setRemoteEventWorkspaces).toHaveBeenCalledWith(["ws-1", "ws-2"]);
+
+    await act(async () => {
+      root.render(<Harness {...props} workspaceIds={["ws-1", "ws-2"]} />);
+    });
+    expect(setRemoteEventWorkspaces).toHaveBeenCalledTimes(1);
+
+    await act(async () => {
+      root.render(<Harness {...props} workspaceIds={["ws-1"]} />);
+    });
+    expect(setRemoteEventWorkspaces).toHaveBeenLastCalledWith(["ws-1"]);
+
+    act(() => {
+      root.unmount();
+    });
+  });
+});
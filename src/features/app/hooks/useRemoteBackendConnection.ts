@@ -0,0 +1,66 @@
+import { useCallback, useEffect } from "react";
+import { useTauriEvent } from "./useTauriEvent";
+import {
+  subscribeRemoteBackendConnection,
+  type RemoteBackendConnectionEvent,
+} from "../../../services/events";
+import { setRemoteEventWorkspaces } from "../../../services/tauri";
+import { pushErrorToast } from "../../../services/toasts";
+import type { DebugEntry } from "../../../types";
+
+type Params = {
+  enabled: boolean;
+  workspaceIds: string[] | null;
+  onDebug: (entry: DebugEntry) => void;
+  onMissedEvents: () => void;
+};
+
+export function useRemoteBackendConnection({
+  enabled,
+  workspaceIds,
+  onDebug,
+  onMissedEvents,
+}: Params) {
+  const handleEvent = useCallback(
+    (event: RemoteBackendConnectionEvent) => {
+      onDebug({
+        id: `${Date.now()}-client-remote-backend-${event.state}`,
+        timestamp: Date.now(),
+        source: event.state === "connected" ? "event" : "error",
+        label: `remote-backend/${event.state}`,
+        payload: event,
+      });
+      if (event.state === "disconnected") {
+        pushErrorToast({
+          id: "remote-backend-connection",
+          title: "Remote backend disconnected",
+          message: `Lost the connection to ${event.host}. Reconnecting…`,
+        });
+      }
+      if (event.state === "connected" && event.missedEvents) {
+        onMissedEvents();
+      }
+    },
+    [onDebug, onMissedEvents],
+  );
+
+  useTauriEvent(subscribeRemoteBackendConnection, handleEvent, { enabled });
+
+  // Keyed by contents so a new array holding the same ids does not resubscribe.
+  const workspaceIdsKey = workspaceIds ? JSON.stringify(workspaceIds) : null;
+  useEffect(() => {
+    if (!enabled || workspaceIdsKey === null) {
+      return;
+    }
+    const ids = JSON.parse(workspaceIdsKey) as string[];
+    void setRemoteEventWorkspaces(ids).catch((error) => {
+      onDebug({
+        id: `${Date.now()}-client-remote-backend-subscribe-error`,
+        timestamp: Date.now(),
+        source: "error",
+        label: "remote-backend/subscribe error",
+        payload: error instanceof Error ? error.message : String(error),
+      });
+    });
+  }, [enabled, onDebug, workspaceIdsKey]);
+}
@@ -5,6 +5,7 @@ import type {
   CustomPromptOption,
   DictationTranscript,
   QueuedMessage,
+  SandboxProfile,
   ThreadTokenUsage,
 } from "../../../types";
 import { computeDictationInsertion } from "../../../utils/dictation";
@@ -44,6 +45,9 @@ type ComposerProps = {
   reasoningSupported: boolean;
   accessMode: "read-only" | "current" | "full-access";
   onSelectAccessMode: (mode: "read-only" | "current" | "full-access") => void;
+  sandboxProfiles?: SandboxProfile[];
+  selectedSandboxProfileId?: string | null;
+  onSelectSandboxProfile?: (id: string | null) => void;
   skills: { name: string; description?: string }[];
   prompts: CustomPromptOption[];
   files: string[];
@@ -118,6 +122,9 @@ export function Composer({
   reasoningSupported,
   accessMode,
   onSelectAccessMode,
+  sandboxProfiles,
+  selectedSandboxProfileId,
+  onSelectSandboxProfile,
   skills,
   prompts,
   files,
@@ -745,6 +752,9 @@ export function Composer({
         reasoningSupported={reasoningSupported}
         accessMode={accessMode}
         onSelectAccessMode={onSelectAccessMode}
+        sandboxProfiles={sandboxProfiles}
+        selectedSandboxProfileId={selectedSandboxProfileId}
+        onSelectSandboxProfile={onSelectSandboxProfile}
         contextUsage={contextUsage}
       />
     </footer>
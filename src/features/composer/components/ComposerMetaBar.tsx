@@ -5,11 +5,17 @@ import { Button } from "@/components/ui/button";
 import {
   DropdownMenu,
   DropdownMenuContent,
+  DropdownMenuLabel,
   DropdownMenuRadioGroup,
   DropdownMenuRadioItem,
+  DropdownMenuSeparator,
   DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu";
-import type { AccessMode, ThreadTokenUsage } from "../../../types";
+import type {
+  AccessMode,
+  SandboxProfile,
+  ThreadTokenUsage,
+} from "../../../types";
 import { formatCollaborationModeLabel } from "../../../utils/collaborationModes";
 import { useI18n } from "../../../i18n";
 
@@ -27,9 +33,14 @@ type ComposerMetaBarProps = {
   reasoningSupported: boolean;
   accessMode: AccessMode;
   onSelectAccessMode: (mode: AccessMode) => void;
+  sandboxProfiles?: SandboxProfile[];
+  selectedSandboxProfileId?: string | null;
+  onSelectSandboxProfile?: (id: string | null) => void;
   contextUsage?: ThreadTokenUsage | null;
 };
 
+const PROFILE_VALUE_PREFIX = "profile:";
+
 export const ComposerMetaBar = memo(function ComposerMetaBar({
   disabled,
   collaborationModes,
@@ -44,6 +55,9 @@ export const ComposerMetaBar = memo(function ComposerMetaBar({
   reasoningSupported,
   accessMode,
   onSelectAccessMode,
+  sandboxProfiles = [],
+  selectedSandboxProfileId = null,
+  onSelectSandboxProfile,
   contextUsage = null,
 }: ComposerMetaBarProps) {
   const { t } = useI18n();
@@ -55,12 +69,27 @@ export const ComposerMetaBar = memo(function ComposerMetaBar({
   const effortLabel =
     reasoningOptions.find((effort) => effort === selectedEffort) ??
     (reasoningOptions.length === 0 ? t("composer.default") : selectedEffort);
-  const accessLabel =
-    accessMode === "read-only"
+  const selectedProfile = sandboxProfiles.find(
+    (profile) => profile.id === selectedSandboxProfileId,
+  );
+  const accessLabel = selectedProfile
+    ? selectedProfile.name || selectedProfile.id
+    : accessMode === "read-only"
       ? t("composer.access.readOnly")
       : accessMode === "full-access"
         ? t("composer.access.full")
         : t("composer.access.onRequest");
+  const accessValue = selectedProfile
+    ? `${PROFILE_VALUE_PREFIX}${selectedProfile.id}`
+    : accessMode;
+  const handleAccessChange = (value: string) => {
+    if (value.startsWith(PROFILE_VALUE_PREFIX)) {
+      onSelectSandboxProfile?.(value.slice(PROFILE_VALUE_PREFIX.length));
+      return;
+    }
+    onSelectSandboxProfile?.(null);
+    onSelectAccessMode(value as AccessMode);
+  };
   const contextWindow = contextUsage?.modelContextWindow ?? null;
   const lastTokens = contextUsage?.last.totalTokens ?? 0;
   const totalTokens = contextUsage?.total.totalTokens ?? 0;
@@ -233,8 +262,8 @@ export const ComposerMetaBar = memo(function ComposerMetaBar({
         </DropdownMenuTrigger>
         <DropdownMenuContent align="start">
           <DropdownMenuRadioGroup
-            value={accessMode}
-            onValueChange={(value) => onSelectAccessMode(value as AccessMode)}
+            value={accessValue}
+            onValueChange={handleAccessChange}
           >
             <DropdownMenuRadioItem value="read-only">
               {t("composer.access.readOnly")}
@@ -245,6 +274,20 @@ export const ComposerMetaBar = memo(function ComposerMetaBar({
             <DropdownMenuRadioItem value="full-access">
               {t("composer.access.full")}
             </DropdownMenuRadioItem>
+            {sandboxProfiles.length > 0 && onSelectSandboxProfile ? (
+              <>
+                <DropdownMenuSeparator />
+                <DropdownMenuLabel>{t("composer.access.profiles")}</DropdownMenuLabel>
+                {sandboxProfiles.map((profile) => (
+                  <DropdownMenuRadioItem
+                    key={profile.id}
+                    value={`${PROFILE_VALUE_PREFIX}${profile.id}`}
+                  >
+                    {profile.name || profile.id}
+                  </DropdownMenuRadioItem>
+                ))}
+              </>
+            ) : null}
           </DropdownMenuRadioGroup>
         </DropdownMenuContent>
       </DropdownMenu>
@@ -44,6 +44,7 @@ import type {
   RateLimitSnapshot,
   RequestUserInputRequest,
   RequestUserInputResponse,
+  SandboxProfile,
   SkillOption,
   ThemeColor,
   ThemePreference,
@@ -382,6 +383,9 @@ type LayoutNodesOptions = {
   reasoningSupported: boolean;
   accessMode: AccessMode;
   onSelectAccessMode: (mode: AccessMode) => void;
+  sandboxProfiles: SandboxProfile[];
+  selectedSandboxProfileId: string | null;
+  onSelectSandboxProfile: (id: string | null) => void;
   skills: SkillOption[];
   prompts: CustomPromptOption[];
   files: string[];
@@ -612,6 +616,9 @@ export function useLayoutNodes(options: LayoutNodesOptions): LayoutNodesResult {
         reasoningSupported={options.reasoningSupported}
         accessMode={options.accessMode}
         onSelectAccessMode={options.onSelectAccessMode}
+        sandboxProfiles={options.sandboxProfiles}
+        selectedSandboxProfileId={options.selectedSandboxProfileId}
+        onSelectSandboxProfile={options.onSelectSandboxProfile}
         skills={options.skills}
         prompts={options.prompts}
         files={options.files}
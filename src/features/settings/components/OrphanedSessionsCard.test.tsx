@@ -0,0 +1,78 @@
+// @vitest-environment jsdom
+import { cleanup, fireEvent, render, screen, waitFor } from "@testing-library/react";
+import { afterEach, beforeEach, describe, expect, it, vi } from "vitest";
+import { I18nProvider } from "../../../i18n";
+import { scanOrphanedSessions } from "../../../services/tauri";
+import type { OrphanedSession } from "../../../types";
+import { OrphanedSessionsCard } from "./OrphanedSessionsCard";
+
+vi.mock("../../../services/tauri", () => ({
+  scanOrphanedSessions: vi.fn(),
+}));
+
+const stray: OrphanedSession = {
+  threadId: "thread-stray",
+  path: "/home/me/.codex/sessions/rollout-stray.jsonl",
+  cwd: "/home/me/elsewhere",
+  kind: "unregistered",
+  workspaceId: null,
+  startedAt: null,
+  updatedAt: 2,
+};
+
+const unknown: OrphanedSession = {
+  threadId: "thread-unknown",
+  path: "/home/me/.codex/sessions/rollout-unknown.jsonl",
+  cwd: "/home/me/project",
+  kind: "unknown",
+  workspaceId: "ws-1",
+  startedAt: null,
+  updatedAt: 1,
+};
+
+const renderCard = (onOpenSession: (session: OrphanedSession) => Promise<void>) =>
+  render(
+    <I18nProvider language="en">
+      <OrphanedSessionsCard onOpenSession={onOpenSession} />
+    </I18nProvider>,
+  );
+
+describe("OrphanedSessionsCard", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  afterEach(() => {
+    cleanup();
+  });
+
+  it("adds the project for unregistered sessions and opens listed ones", async () => {
+    vi.mocked(scanOrphanedSessions).mockResolvedValue([stray, unknown]);
+    const onOpenSession = vi.fn().mockResolvedValue(undefined);
+    renderCard(onOpenSession);
+
+    fireEvent.click(screen.getByRole("button", { name: "Scan" }));
+
+    expect(
+      await screen.findByText("The project's threads could not be listed"),
+    ).toBeTruthy();
+    fireEvent.click(screen.getByRole("button", { name: "Add project and open" }));
+
+    await waitFor(() => expect(onOpenSession).toHaveBeenCalledWith(stray));
+    await waitFor(() =>
+      expect(screen.queryByText("/home/me/elsewhere")).toBeNull(),
+    );
+    expect(screen.getByRole("button", { name: "Open" })).toBeTruthy();
+  });
+
+  it("shows why the scan failed", async () => {
+    vi.mocked(scanOrphanedSessions).mockRejectedValue(
+      new Error("thread/list failed: timed out"),
+    );
+    renderCard(vi.fn());
+
+    fireEvent.click(screen.getByRole("button", { name: "Scan" }));
+
+    expect(await screen.findByText("thread/list failed: timed out")).toBeTruthy();
+  });
+});
@@ -0,0 +1,106 @@
+import { useState } from "react";
+import { Button } from "@/components/ui/button";
+import { useI18n, type TranslationKey } from "../../../i18n";
+import { scanOrphanedSessions } from "../../../services/tauri";
+import type { OrphanedSession, OrphanedSessionKind } from "../../../types";
+
+type OrphanedSessionsCardProps = {
+  onOpenSession: (session: OrphanedSession) => Promise<void>;
+};
+
+const KIND_LABELS: Record<OrphanedSessionKind, TranslationKey> = {
+  unregistered: "settings.projects.orphans.kind.unregistered",
+  unlisted: "settings.projects.orphans.kind.unlisted",
+  unknown: "settings.projects.orphans.kind.unknown",
+};
+
+/** Lists CLI sessions no project shows and opens them, adding the project if needed. */
+export function OrphanedSessionsCard({ onOpenSession }: OrphanedSessionsCardProps) {
+  const { t } = useI18n();
+  const [sessions, setSessions] = useState<OrphanedSession[] | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [scanning, setScanning] = useState(false);
+  const [openingId, setOpeningId] = useState<string | null>(null);
+
+  const handleScan = async () => {
+    setScanning(true);
+    setError(null);
+    try {
+      setSessions(await scanOrphanedSessions());
+    } catch (scanError) {
+      setError(scanError instanceof Error ? scanError.message : String(scanError));
+    } finally {
+      setScanning(false);
+    }
+  };
+
+  const handleOpen = async (session: OrphanedSession) => {
+    setOpeningId(session.threadId);
+    setError(null);
+    try {
+      await onOpenSession(session);
+      setSessions((prev) =>
+        prev ? prev.filter((entry) => entry.threadId !== session.threadId) : prev,
+      );
+    } catch (openError) {
+      setError(openError instanceof Error ? openError.message : String(openError));
+    } finally {
+      setOpeningId(null);
+    }
+  };
+
+  return (
+    <div className="space-y-3 rounded-md border border-border/60 p-3">
+      <div className="flex flex-wrap items-start justify-between gap-4">
+        <div className="space-y-1">
+          <div className="text-sm font-medium">
+            {t("settings.projects.orphans.title")}
+          </div>
+          <div className="text-sm text-muted-foreground">
+            {t("settings.projects.orphans.subtitle")}
+          </div>
+        </div>
+        <Button
+          type="button"
+          variant="outline"
+          size="sm"
+          onClick={() => void handleScan()}
+          disabled={scanning}
+        >
+          {t("settings.projects.orphans.scan")}
+        </Button>
+      </div>
+      {error ? <div className="text-sm text-destructive">{error}</div> : null}
+      {sessions && sessions.length === 0 ? (
+        <div className="text-sm text-muted-foreground">
+          {t("settings.projects.orphans.empty")}
+        </div>
+      ) : null}
+      {sessions?.map((session) => (
+        <div
+          key={session.threadId}
+          className="flex flex-wrap items-center justify-between gap-3 rounded-md border border-border/60 px-3 py-2"
+        >
+          <div className="min-w-0 flex-1 space-y-0.5">
+            <div className="truncate text-sm" title={session.cwd}>
+              {session.cwd}
+            </div>
+            <div className="text-xs text-muted-foreground">
+              {t(KIND_LABELS[session.kind])}
+            </div>
+          </div>
+          <Button
+            type="button"
+            size="sm"
+            onClick={() => void handleOpen(session)}
+            disabled={openingId !== null}
+          >
+            {session.kind === "unregistered"
+              ? t("settings.projects.orphans.addProject")
+              : t("settings.projects.orphans.open")}
+          </Button>
+        </div>
+      ))}
+    </div>
+  );
+}
@@ -0,0 +1,75 @@
+// @vitest-environment jsdom
+import { cleanup, fireEvent, render, screen, waitFor } from "@testing-library/react";
+import { afterEach, describe, expect, it, vi } from "vitest";
+import { I18nProvider } from "../../../i18n";
+import type { SandboxProfile } from "../../../types";
+import { SandboxProfilesCard } from "./SandboxProfilesCard";
+
+const offline: SandboxProfile = {
+  id: "offline",
+  name: "Offline",
+  sandbox: "workspace-write",
+  writableRoots: ["target"],
+  networkAccess: false,
+  approvalPolicy: "never",
+};
+
+const renderCard = (
+  profiles: SandboxProfile[],
+  onSave: (profiles: SandboxProfile[]) => Promise<void>,
+) =>
+  render(
+    <I18nProvider language="en">
+      <SandboxProfilesCard profiles={profiles} onSave={onSave} />
+    </I18nProvider>,
+  );
+
+describe("SandboxProfilesCard", () => {
+  afterEach(() => {
+    cleanup();
+  });
+
+  it("saves edited profiles with one writable root per line", async () => {
+    const onSave = vi.fn().mockResolvedValue(undefined);
+    renderCard([offline], onSave);
+
+    fireEvent.change(screen.getByLabelText("Extra writable roots"), {
+      target: { value: "target\n \n/tmp/cache" },
+    });
+    fireEvent.click(screen.getByRole("button", { name: "Add profile" }));
+    fireEvent.change(screen.getAllByLabelText("Id")[1], {
+      target: { value: "review" },
+    });
+    fireEvent.click(screen.getByRole("button", { name: "Save profiles" }));
+
+    await waitFor(() => expect(onSave).toHaveBeenCalledTimes(1));
+    expect(onSave).toHaveBeenCalledWith([
+      { ...offline, writableRoots: ["target", "/tmp/cache"] },
+      {
+        id: "review",
+        name: "review",
+        sandbox: "workspace-write",
+        writableRoots: [],
+        networkAccess: true,
+        approvalPolicy: "on-request",
+      },
+    ]);
+  });
+
+  it("shows why the profiles were rejected", async () => {
+    const onSave = vi
+      .fn()
+      .mockRejectedValue(
+        new Error(
+          "Sandbox profile `offline` has writable root `../x` outside the workspace",
+        ),
+      );
+    renderCard([offline], onSave);
+
+    fireEvent.click(screen.getByRole("button", { name: "Save profiles" }));
+
+    expect(
+      await screen.findByText(/has writable root `..\/x` outside the workspace/),
+    ).toBeTruthy();
+  });
+});
@@ -0,0 +1,254 @@
+import { useEffect, useState } from "react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { Switch } from "@/components/ui/switch";
+import { Textarea } from "@/components/ui/textarea";
+import { useI18n } from "../../../i18n";
+import type { SandboxProfile, SandboxProfileMode } from "../../../types";
+
+type SandboxProfileDraft = Omit<SandboxProfile, "writableRoots"> & {
+  writableRoots: string;
+};
+
+type SandboxProfilesCardProps = {
+  profiles: SandboxProfile[];
+  onSave: (profiles: SandboxProfile[]) => Promise<void>;
+};
+
+const SANDBOX_MODES: SandboxProfileMode[] = [
+  "read-only",
+  "workspace-write",
+  "full-access",
+];
+
+const APPROVAL_POLICIES: SandboxProfile["approvalPolicy"][] = [
+  "untrusted",
+  "on-failure",
+  "on-request",
+  "never",
+];
+
+const toDraft = (profile: SandboxProfile): SandboxProfileDraft => ({
+  ...profile,
+  writableRoots: profile.writableRoots.join("\n"),
+});
+
+const fromDraft = (draft: SandboxProfileDraft): SandboxProfile => ({
+  ...draft,
+  id: draft.id.trim(),
+  name: draft.name.trim() || draft.id.trim(),
+  writableRoots: draft.writableRoots
+    .split("\n")
+    .map((root) => root.trim())
+    .filter(Boolean),
+});
+
+/** Edits the app-wide sandbox profiles offered in the composer. */
+export function SandboxProfilesCard({ profiles, onSave }: SandboxProfilesCardProps) {
+  const { t } = useI18n();
+  const [drafts, setDrafts] = useState<SandboxProfileDraft[]>(() =>
+    profiles.map(toDraft),
+  );
+  const [error, setError] = useState<string | null>(null);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    setDrafts(profiles.map(toDraft));
+  }, [profiles]);
+
+  const updateDraft = (index: number, patch: Partial<SandboxProfileDraft>) => {
+    setDrafts((prev) =>
+      prev.map((draft, current) =>
+        current === index ? { ...draft, ...patch } : draft,
+      ),
+    );
+  };
+
+  const handleAdd = () => {
+    setDrafts((prev) => [
+      ...prev,
+      {
+        id: `profile-${prev.length + 1}`,
+        name: "",
+        sandbox: "workspace-write",
+        writableRoots: "",
+        networkAccess: true,
+        approvalPolicy: "on-request",
+      },
+    ]);
+  };
+
+  const handleSave = async () => {
+    setSaving(true);
+    setError(null);
+    try {
+      await onSave(drafts.map(fromDraft));
+    } catch (saveError) {
+      setError(
+        saveError instanceof Error ? saveError.message : String(saveError),
+      );
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <div className="space-y-3 rounded-md border border-border/60 p-4">
+      <div className="space-y-1">
+        <div className="text-sm font-medium">
+          {t("settings.codex.sandboxProfiles.title")}
+        </div>
+        <div className="text-sm text-muted-foreground">
+          {t("settings.codex.sandboxProfiles.subtitle")}
+        </div>
+      </div>
+      {drafts.length === 0 ? (
+        <div className="text-sm text-muted-foreground">
+          {t("settings.codex.sandboxProfiles.empty")}
+        </div>
+      ) : null}
+      {drafts.map((draft, index) => (
+        <div
+          key={index}
+          className="space-y-2 rounded-md border border-border/60 p-3"
+        >
+          <div className="grid gap-2 sm:grid-cols-2">
+            <div className="space-y-1">
+              <Label htmlFor={`sandbox-profile-id-${index}`}>
+                {t("settings.codex.sandboxProfiles.id")}
+              </Label>
+              <Input
+                id={`sandbox-profile-id-${index}`}
+                value={draft.id}
+                onChange={(event) => updateDraft(index, { id: event.target.value })}
+              />
+            </div>
+            <div className="space-y-1">
+              <Label htmlFor={`sandbox-profile-name-${index}`}>
+                {t("settings.codex.sandboxProfiles.name")}
+              </Label>
+              <Input
+                id={`sandbox-profile-name-${index}`}
+                value={draft.name}
+                onChange={(event) =>
+                  updateDraft(index, { name: event.target.value })
+                }
+              />
+            </div>
+            <div className="space-y-1">
+              <Label htmlFor={`sandbox-profile-mode-${index}`}>
+                {t("settings.codex.sandboxProfiles.sandbox")}
+              </Label>
+              <Select
+                value={draft.sandbox}
+                onValueChange={(value) =>
+                  updateDraft(index, { sandbox: value as SandboxProfileMode })
+                }
+              >
+                <SelectTrigger id={`sandbox-profile-mode-${index}`}>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  {SANDBOX_MODES.map((mode) => (
+                    <SelectItem key={mode} value={mode}>
+                      {mode}
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </div>
+            <div className="space-y-1">
+              <Label htmlFor={`sandbox-profile-approval-${index}`}>
+                {t("settings.codex.sandboxProfiles.approvalPolicy")}
+              </Label>
+              <Select
+                value={draft.approvalPolicy}
+                onValueChange={(value) =>
+                  updateDraft(index, {
+                    approvalPolicy: value as SandboxProfile["approvalPolicy"],
+                  })
+                }
+              >
+                <SelectTrigger id={`sandbox-profile-approval-${index}`}>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  {APPROVAL_POLICIES.map((policy) => (
+                    <SelectItem key={policy} value={policy}>
+                      {policy}
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </div>
+          </div>
+          {draft.sandbox === "workspace-write" ? (
+            <>
+              <div className="space-y-1">
+                <Label htmlFor={`sandbox-profile-roots-${index}`}>
+                  {t("settings.codex.sandboxProfiles.writableRoots")}
+                </Label>
+                <Textarea
+                  id={`sandbox-profile-roots-${index}`}
+                  value={draft.writableRoots}
+                  placeholder={t(
+                    "settings.codex.sandboxProfiles.writableRootsPlaceholder",
+                  )}
+                  onChange={(event) =>
+                    updateDraft(index, { writableRoots: event.target.value })
+                  }
+                />
+                <div className="text-xs text-muted-foreground">
+                  {t("settings.codex.sandboxProfiles.writableRootsHelp")}
+                </div>
+              </div>
+              <div className="flex items-center justify-between gap-4">
+                <Label htmlFor={`sandbox-profile-network-${index}`}>
+                  {t("settings.codex.sandboxProfiles.networkAccess")}
+                </Label>
+                <Switch
+                  id={`sandbox-profile-network-${index}`}
+                  checked={draft.networkAccess}
+                  onCheckedChange={(value) =>
+                    updateDraft(index, { networkAccess: value })
+                  }
+                />
+              </div>
+            </>
+          ) : null}
+          <Button
+            type="button"
+            variant="outline"
+            size="sm"
+            onClick={() =>
+              setDrafts((prev) => prev.filter((_, current) => current !== index))
+            }
+          >
+            {t("settings.codex.sandboxProfiles.remove")}
+          </Button>
+        </div>
+      ))}
+      {error ? <div className="text-sm text-destructive">{error}</div> : null}
+      <div className="flex flex-wrap items-center gap-2">
+        <Button type="button" variant="outline" onClick={handleAdd}>
+          {t("settings.codex.sandboxProfiles.add")}
+        </Button>
+        <Button
+          type="button"
+          onClick={() => void handleSave()}
+          disabled={saving}
+        >
+          {t("settings.codex.sandboxProfiles.save")}
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -25,6 +25,8 @@ const baseSettings: AppSettings = {
   backendMode: "local",
   remoteBackendHost: "127.0.0.1:4732",
   remoteBackendToken: null,
+  remoteBackendTlsFingerprint: null,
+  remoteBackendRetryReads: true,
   happyEnabled: false,
   happyServerUrl: "https://api.cluster-fluster.com",
   happyToken: null,
@@ -115,6 +117,9 @@ const baseSettings: AppSettings = {
   experimentalThreadResumeStreamingEnabled: false,
   experimentalYunyiEnabled: false,
   experimentalYunyiToken: "",
+  recordAppServerTranscripts: false,
+  idleSessionTimeoutMinutes: 0,
+  deltaCoalesceWindowMs: 0,
   dictationEnabled: false,
   dictationModelId: "base",
   dictationPreferredLanguage: null,
@@ -142,6 +147,7 @@ const baseSettings: AppSettings = {
     },
   ],
   selectedOpenAppId: "vscode",
+  sandboxProfiles: [],
 };
 
 const createDoctorResult = () => ({
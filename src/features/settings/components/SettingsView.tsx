@@ -24,6 +24,7 @@ import type {
   ModelOption,
   NanobotDingTalkTestResult,
   OpenAppTarget,
+  OrphanedSession,
   WorkspaceGroup,
   WorkspaceInfo,
   WorkspaceSettings,
@@ -261,6 +262,7 @@ export type SettingsViewProps = {
   onDownloadDictationModel?: () => void;
   onCancelDictationDownload?: () => void;
   onRemoveDictationModel?: () => void;
+  onOpenOrphanedSession?: (session: OrphanedSession) => Promise<void>;
   initialSection?: CodexSection;
 };
 
@@ -384,6 +386,7 @@ export function SettingsView({
   onDownloadDictationModel,
   onCancelDictationDownload,
   onRemoveDictationModel,
+  onOpenOrphanedSession,
   initialSection,
 }: SettingsViewProps) {
   const { t } = useI18n();
@@ -619,6 +622,9 @@ export function SettingsView({
   const [remoteTokenDraft, setRemoteTokenDraft] = useState(
     appSettings.remoteBackendToken ?? "",
   );
+  const [remoteFingerprintDraft, setRemoteFingerprintDraft] = useState(
+    appSettings.remoteBackendTlsFingerprint ?? "",
+  );
   const [happyServerDraft, setHappyServerDraft] = useState(
     appSettings.happyServerUrl,
   );
@@ -908,6 +914,10 @@ export function SettingsView({
     setRemoteTokenDraft(appSettings.remoteBackendToken ?? "");
   }, [appSettings.remoteBackendToken]);
 
+  useEffect(() => {
+    setRemoteFingerprintDraft(appSettings.remoteBackendTlsFingerprint ?? "");
+  }, [appSettings.remoteBackendTlsFingerprint]);
+
   useEffect(() => {
     setHappyServerDraft(appSettings.happyServerUrl);
   }, [appSettings.happyServerUrl]);
@@ -1493,6 +1503,20 @@ export function SettingsView({
     });
   };
 
+  const handleCommitRemoteFingerprint = async () => {
+    const nextFingerprint = remoteFingerprintDraft.trim()
+      ? remoteFingerprintDraft.trim()
+      : null;
+    setRemoteFingerprintDraft(nextFingerprint ?? "");
+    if (nextFingerprint === appSettings.remoteBackendTlsFingerprint) {
+      return;
+    }
+    await onUpdateAppSettings({
+      ...appSettings,
+      remoteBackendTlsFingerprint: nextFingerprint,
+    });
+  };
+
   const handleCommitHappyServer = async () => {
     const nextUrl = happyServerDraft.trim() || appSettings.happyServerUrl;
     setHappyServerDraft(nextUrl);
@@ -2123,14 +2147,14 @@ export function SettingsView({
               </TabsTrigger>
             </TabsList>
             <div className="flex min-h-0 flex-1 flex-col overflow-y-auto p-4">
-                            <ProjectsTabSection {...{ t, newGroupName, setNewGroupName, canCreateGroup, handleCreateGroup, groupError, workspaceGroups, groupDrafts, setGroupDrafts, handleRenameGroup, handleChooseGroupCopiesFolder, handleClearGroupCopiesFolder, onMoveWorkspaceGroup, handleDeleteGroup, groupedWorkspaces, onAssignWorkspaceGroup, ungroupedLabel, onMoveWorkspace, onDeleteWorkspace, projects, appSettings, onUpdateAppSettings, UNGROUPED_SELECT_VALUE, cn }} />
+                            <ProjectsTabSection {...{ t, newGroupName, setNewGroupName, canCreateGroup, handleCreateGroup, groupError, workspaceGroups, groupDrafts, setGroupDrafts, handleRenameGroup, handleChooseGroupCopiesFolder, handleClearGroupCopiesFolder, onMoveWorkspaceGroup, handleDeleteGroup, groupedWorkspaces, onAssignWorkspaceGroup, ungroupedLabel, onMoveWorkspace, onDeleteWorkspace, projects, appSettings, onUpdateAppSettings, onOpenOrphanedSession, UNGROUPED_SELECT_VALUE, cn }} />
                             <DisplayTabSection {...{ t, appSettings, onUpdateAppSettings, reduceTransparency, onToggleTransparency, scaleShortcutTitle, scaleShortcutText, scaleDraft, setScaleDraft, handleCommitScale, handleResetScale, uiFontDraft, setUiFontDraft, handleCommitUiFont, codeFontDraft, setCodeFontDraft, handleCommitCodeFont, codeFontSizeDraft, setCodeFontSizeDraft, handleCommitCodeFontSize, successSoundValue, handleSelectNotificationSound, notificationSoundSelectOptions, successVolumePercent, onTestNotificationSound, successSoundIsCustom, handlePickCustomSound, formatSoundPathLabel, errorSoundValue, errorVolumePercent, errorSoundIsCustom, CODE_FONT_SIZE_DEFAULT, CODE_FONT_SIZE_MAX, CODE_FONT_SIZE_MIN, DEFAULT_CODE_FONT_FAMILY, DEFAULT_UI_FONT_FAMILY }} />
                             <ComposerTabSection {...{ t, appSettings, handleComposerPresetChange, composerPresetLabels, onUpdateAppSettings }} />
                             <DictationTabSection {...{ t, platform, appSettings, onUpdateAppSettings, dictationModelStatus, onCancelDictationDownload, onDownloadDictationModel, dictationModels, selectedDictationModel, dictationProgress, dictationReady, onRemoveDictationModel, DICTATION_AUTO_VALUE, DICTATION_HOLD_OFF_VALUE }} />
                             <ShortcutsTabSection {...{ t, shortcutDrafts, handleShortcutKeyDown, updateShortcut, formatShortcut, getDefaultInterruptShortcut }} />
                             <OpenAppsTabSection {...{ t, openAppDrafts, openAppIconById, handleOpenAppDraftChange, handleCommitOpenApps, handleOpenAppKindChange, fileManagerLabel, openAppSelectedId, handleSelectOpenAppDefault, handleMoveOpenApp, handleDeleteOpenApp, handleAddOpenApp }} />
                             <NanobotTabSection {...{ t, appSettings, onUpdateAppSettings, models, nextNanobotClientId, nextNanobotClientSecret, nextNanobotAgentModel, nextNanobotAgentReasoningEffort, nextNanobotAllowFrom, nextNanobotEmailImapHost, nextNanobotEmailImapPort, nextNanobotEmailImapUsername, nextNanobotEmailImapPassword, nextNanobotEmailImapMailbox, nextNanobotEmailSmtpHost, nextNanobotEmailSmtpPort, nextNanobotEmailSmtpUsername, nextNanobotEmailSmtpPassword, nextNanobotEmailFromAddress, nextNanobotEmailAllowFrom, nextNanobotEmailPollIntervalSeconds, nextNanobotQqAppId, nextNanobotQqSecret, nextNanobotQqAllowFrom, nanobotClientIdDraft, setNanobotClientIdDraft, nanobotClientSecretDraft, setNanobotClientSecretDraft, nanobotAgentModelDraft, nanobotAgentModelSelectValue, handleSelectNanobotAgentModel, nanobotAgentReasoningEffortDraft, nanobotAgentReasoningOptions, handleSelectNanobotAgentReasoningEffort, nanobotAllowFromDraft, setNanobotAllowFromDraft, handleTestNanobotDingTalk, nanobotTestState, nanobotAwayDetected, nanobotBluetoothState, nanobotBluetoothDevices, onStartNanobotBluetoothScan, onStopNanobotBluetoothScan, nanobotWorkspace, nanobotCodexBinDraft, setNanobotCodexBinDraft, handleCommitNanobotCodexBin, nanobotCodexBinSaving, nanobotCodexBinSavedAt, nanobotEmailImapHostDraft, setNanobotEmailImapHostDraft, nanobotEmailImapPortDraft, setNanobotEmailImapPortDraft, nanobotEmailImapUsernameDraft, setNanobotEmailImapUsernameDraft, nanobotEmailImapPasswordDraft, setNanobotEmailImapPasswordDraft, nanobotEmailImapMailboxDraft, setNanobotEmailImapMailboxDraft, nanobotEmailSmtpHostDraft, setNanobotEmailSmtpHostDraft, nanobotEmailSmtpPortDraft, setNanobotEmailSmtpPortDraft, nanobotEmailSmtpUsernameDraft, setNanobotEmailSmtpUsernameDraft, nanobotEmailSmtpPasswordDraft, setNanobotEmailSmtpPasswordDraft, nanobotEmailFromAddressDraft, setNanobotEmailFromAddressDraft, nanobotEmailPollIntervalDraft, setNanobotEmailPollIntervalDraft, nanobotEmailAllowFromDraft, setNanobotEmailAllowFromDraft, nanobotQqAppIdDraft, setNanobotQqAppIdDraft, nanobotQqSecretDraft, setNanobotQqSecretDraft, nanobotQqAllowFromDraft, setNanobotQqAllowFromDraft, nanobotDirty, handleSaveNanobotSettings, isSavingSettings, handleClearNanobotThreads, nanobotCleanupState, nanobotConfigPath, nanobotConfigPathError, moltisTelegramTokenDraft, setMoltisTelegramTokenDraft, handleSaveMoltisTelegramToken, handleSyncMoltisMenu, moltisMenuSyncState, cn }} />
-                            <CodexTabSection {...{ t, codexPathDraft, setCodexPathDraft, handleBrowseCodex, codexArgsDraft, setCodexArgsDraft, codexDirty, handleSaveCodexSettings, isSavingSettings, handleRunDoctor, doctorState, projects, codexBinOverrideDrafts, setCodexBinOverrideDrafts, handleCommitCodexBinOverride, codexBinOverrideSaving, codexBinOverrideSavedAt, setCodexBinOverrideSaving, onUpdateWorkspaceCodexBin, setCodexBinOverrideSavedAt, appSettings, handleRunWorkspaceDoctor, codexBinOverrideDoctor, codexHomeOverrideDrafts, setCodexHomeOverrideDrafts, onUpdateWorkspaceSettings, codexArgsOverrideDrafts, setCodexArgsOverrideDrafts, onUpdateAppSettings, remoteHostDraft, setRemoteHostDraft, handleCommitRemoteHost, remoteTokenDraft, setRemoteTokenDraft, handleCommitRemoteToken, globalAgentsMeta, globalAgentsError, globalAgentsContent, globalAgentsLoading, globalAgentsRefreshDisabled, globalAgentsSaveDisabled, globalAgentsSaveLabel, setGlobalAgentsContent, refreshGlobalAgents, saveGlobalAgents, globalConfigMeta, globalConfigError, globalConfigContent, globalConfigLoading, globalConfigRefreshDisabled, globalConfigSaveDisabled, globalConfigSaveLabel, setGlobalConfigContent, refreshGlobalConfig, saveGlobalConfig, normalizeOverrideValue, cn }} />
+                            <CodexTabSection {...{ t, codexPathDraft, setCodexPathDraft, handleBrowseCodex, codexArgsDraft, setCodexArgsDraft, codexDirty, handleSaveCodexSettings, isSavingSettings, handleRunDoctor, doctorState, projects, codexBinOverrideDrafts, setCodexBinOverrideDrafts, handleCommitCodexBinOverride, codexBinOverrideSaving, codexBinOverrideSavedAt, setCodexBinOverrideSaving, onUpdateWorkspaceCodexBin, setCodexBinOverrideSavedAt, appSettings, handleRunWorkspaceDoctor, codexBinOverrideDoctor, codexHomeOverrideDrafts, setCodexHomeOverrideDrafts, onUpdateWorkspaceSettings, codexArgsOverrideDrafts, setCodexArgsOverrideDrafts, onUpdateAppSettings, remoteHostDraft, setRemoteHostDraft, handleCommitRemoteHost, remoteTokenDraft, setRemoteTokenDraft, handleCommitRemoteToken, remoteFingerprintDraft, setRemoteFingerprintDraft, handleCommitRemoteFingerprint, globalAgentsMeta, globalAgentsError, globalAgentsContent, globalAgentsLoading, globalAgentsRefreshDisabled, globalAgentsSaveDisabled, globalAgentsSaveLabel, setGlobalAgentsContent, refreshGlobalAgents, saveGlobalAgents, globalConfigMeta, globalConfigError, globalConfigContent, globalConfigLoading, globalConfigRefreshDisabled, globalConfigSaveDisabled, globalConfigSaveLabel, setGlobalConfigContent, refreshGlobalConfig, saveGlobalConfig, normalizeOverrideValue, cn }} />
                             <ExperimentalTabSection {...{ t, hasCodexHomeOverrides, fileManagerLabel, handleOpenConfig, openInFileManagerLabel, openConfigError, appSettings, onUpdateAppSettings, yunyiTokenDraft, setYunyiTokenDraft, handleCommitYunyiToken, happyServerDraft, setHappyServerDraft, handleCommitHappyServer }} />
             </div>
           </Tabs>
@@ -3,13 +3,15 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { Switch } from "@/components/ui/switch";
 import { TabsContent } from "@/components/ui/tabs";
 import { FileEditorCard } from "../../../shared/components/FileEditorCard";
+import { SandboxProfilesCard } from "../SandboxProfilesCard";
 import { SettingsSection } from "../SettingsSection";
 
 
 export function CodexTabSection(props: any) {
-  const {t, codexPathDraft, setCodexPathDraft, handleBrowseCodex, codexArgsDraft, setCodexArgsDraft, codexDirty, handleSaveCodexSettings, isSavingSettings, handleRunDoctor, doctorState, projects, codexBinOverrideDrafts, setCodexBinOverrideDrafts, handleCommitCodexBinOverride, codexBinOverrideSaving, codexBinOverrideSavedAt, setCodexBinOverrideSaving, onUpdateWorkspaceCodexBin, setCodexBinOverrideSavedAt, appSettings, handleRunWorkspaceDoctor, codexBinOverrideDoctor, codexHomeOverrideDrafts, setCodexHomeOverrideDrafts, onUpdateWorkspaceSettings, codexArgsOverrideDrafts, setCodexArgsOverrideDrafts, onUpdateAppSettings, remoteHostDraft, setRemoteHostDraft, handleCommitRemoteHost, remoteTokenDraft, setRemoteTokenDraft, handleCommitRemoteToken, globalAgentsMeta, globalAgentsError, globalAgentsContent, globalAgentsLoading, globalAgentsRefreshDisabled, globalAgentsSaveDisabled, globalAgentsSaveLabel, setGlobalAgentsContent, refreshGlobalAgents, saveGlobalAgents, globalConfigMeta, globalConfigError, globalConfigContent, globalConfigLoading, globalConfigRefreshDisabled, globalConfigSaveDisabled, globalConfigSaveLabel, setGlobalConfigContent, refreshGlobalConfig, saveGlobalConfig, normalizeOverrideValue, cn} = props;
+  const {t, codexPathDraft, setCodexPathDraft, handleBrowseCodex, codexArgsDraft, setCodexArgsDraft, codexDirty, handleSaveCodexSettings, isSavingSettings, handleRunDoctor, doctorState, projects, codexBinOverrideDrafts, setCodexBinOverrideDrafts, handleCommitCodexBinOverride, codexBinOverrideSaving, codexBinOverrideSavedAt, setCodexBinOverrideSaving, onUpdateWorkspaceCodexBin, setCodexBinOverrideSavedAt, appSettings, handleRunWorkspaceDoctor, codexBinOverrideDoctor, codexHomeOverrideDrafts, setCodexHomeOverrideDrafts, onUpdateWorkspaceSettings, codexArgsOverrideDrafts, setCodexArgsOverrideDrafts, onUpdateAppSettings, remoteHostDraft, setRemoteHostDraft, handleCommitRemoteHost, remoteTokenDraft, setRemoteTokenDraft, handleCommitRemoteToken, remoteFingerprintDraft, setRemoteFingerprintDraft, handleCommitRemoteFingerprint, globalAgentsMeta, globalAgentsError, globalAgentsContent, globalAgentsLoading, globalAgentsRefreshDisabled, globalAgentsSaveDisabled, globalAgentsSaveLabel, setGlobalAgentsContent, refreshGlobalAgents, saveGlobalAgents, globalConfigMeta, globalConfigError, globalConfigContent, globalConfigLoading, globalConfigRefreshDisabled, globalConfigSaveDisabled, globalConfigSaveLabel, setGlobalConfigContent, refreshGlobalConfig, saveGlobalConfig, normalizeOverrideValue, cn} = props;
 
   return (
 <TabsContent value="codex" className="mt-0">
@@ -523,6 +525,24 @@ export function CodexTabSection(props: any) {
                                   </Button>
                                 </div>
                               </div>
+                              <div className="flex items-center justify-between gap-4 md:col-span-2">
+                                <Label
+                                  htmlFor={`override-checkpoints-${workspace.id}`}
+                                >
+                                  {t(
+                                    "settings.codex.workspaceOverrides.checkpointsLabel",
+                                  )}
+                                </Label>
+                                <Switch
+                                  id={`override-checkpoints-${workspace.id}`}
+                                  checked={!workspace.settings.disableCheckpoints}
+                                  onCheckedChange={(value: boolean) =>
+                                    void onUpdateWorkspaceSettings(workspace.id, {
+                                      disableCheckpoints: !value,
+                                    })
+                                  }
+                                />
+                              </div>
                             </div>
                           </div>
                         </div>
@@ -645,6 +665,42 @@ export function CodexTabSection(props: any) {
                               aria-label={t("settings.codex.remote.tokenAria")}
                             />
                           </div>
+                          <Input
+                            value={remoteFingerprintDraft}
+                            placeholder={t(
+                              "settings.codex.remote.fingerprintPlaceholder",
+                            )}
+                            onChange={(event: any) =>
+                              setRemoteFingerprintDraft(event.target.value)
+                            }
+                            onBlur={() => {
+                              void handleCommitRemoteFingerprint();
+                            }}
+                            onKeyDown={(event: any) => {
+                              if (event.key === "Enter") {
+                                event.preventDefault();
+                                void handleCommitRemoteFingerprint();
+                              }
+                            }}
+                            aria-label={t(
+                              "settings.codex.remote.fingerprintAria",
+                            )}
+                          />
+                          <div className="flex items-center justify-between gap-4">
+                            <Label htmlFor="remote-backend-retry-reads">
+                              {t("settings.codex.remote.retryReads")}
+                            </Label>
+                            <Switch
+                              id="remote-backend-retry-reads"
+                              checked={appSettings.remoteBackendRetryReads}
+                              onCheckedChange={(value: boolean) =>
+                                void onUpdateAppSettings({
+                                  ...appSettings,
+                                  remoteBackendRetryReads: value,
+                                })
+                              }
+                            />
+                          </div>
                           <div className="text-sm text-muted-foreground">
                             {t("settings.codex.remote.help")}
                           </div>
@@ -729,6 +785,12 @@ export function CodexTabSection(props: any) {
                         help: "mt-2 text-xs text-muted-foreground",
                       }}
                     />
+                    <SandboxProfilesCard
+                      profiles={appSettings.sandboxProfiles}
+                      onSave={(sandboxProfiles) =>
+                        onUpdateAppSettings({ ...appSettings, sandboxProfiles })
+                      }
+                    />
                   </div>
                 </div>
               </TabsContent>
@@ -8,10 +8,11 @@ import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@
 import { Separator } from "@/components/ui/separator";
 import { Switch } from "@/components/ui/switch";
 import { TabsContent } from "@/components/ui/tabs";
+import { OrphanedSessionsCard } from "../OrphanedSessionsCard";
 import { SettingsSection } from "../SettingsSection";
 
 export function ProjectsTabSection(props: any) {
-  const {t, newGroupName, setNewGroupName, canCreateGroup, handleCreateGroup, groupError, workspaceGroups, groupDrafts, setGroupDrafts, handleRenameGroup, handleChooseGroupCopiesFolder, handleClearGroupCopiesFolder, onMoveWorkspaceGroup, handleDeleteGroup, groupedWorkspaces, onAssignWorkspaceGroup, ungroupedLabel, onMoveWorkspace, onDeleteWorkspace, projects, appSettings, onUpdateAppSettings, UNGROUPED_SELECT_VALUE, cn} = props;
+  const {t, newGroupName, setNewGroupName, canCreateGroup, handleCreateGroup, groupError, workspaceGroups, groupDrafts, setGroupDrafts, handleRenameGroup, handleChooseGroupCopiesFolder, handleClearGroupCopiesFolder, onMoveWorkspaceGroup, handleDeleteGroup, groupedWorkspaces, onAssignWorkspaceGroup, ungroupedLabel, onMoveWorkspace, onDeleteWorkspace, projects, appSettings, onUpdateAppSettings, onOpenOrphanedSession, UNGROUPED_SELECT_VALUE, cn} = props;
 
   return (
 <TabsContent value="projects" className="mt-0">
@@ -373,6 +374,9 @@ export function ProjectsTabSection(props: any) {
                         }
                       />
                     </div>
+                    {onOpenOrphanedSession ? (
+                      <OrphanedSessionsCard onOpenSession={onOpenOrphanedSession} />
+                    ) : null}
                   </SettingsSection>
                 </div>
               </TabsContent>
@@ -47,6 +47,8 @@ const defaultSettings: AppSettings = {
   backendMode: "local",
   remoteBackendHost: "127.0.0.1:4732",
   remoteBackendToken: null,
+  remoteBackendTlsFingerprint: null,
+  remoteBackendRetryReads: true,
   happyEnabled: false,
   happyServerUrl: DEFAULT_HAPPY_SERVER_URL,
   happyToken: null,
@@ -135,6 +137,9 @@ const defaultSettings: AppSettings = {
   experimentalThreadResumeStreamingEnabled: false,
   experimentalYunyiEnabled: false,
   experimentalYunyiToken: "",
+  recordAppServerTranscripts: false,
+  idleSessionTimeoutMinutes: 0,
+  deltaCoalesceWindowMs: 0,
   dictationEnabled: false,
   dictationModelId: "base",
   dictationPreferredLanguage: null,
@@ -153,6 +158,7 @@ const defaultSettings: AppSettings = {
   workspaceGroups: [],
   openAppTargets: DEFAULT_OPEN_APP_TARGETS,
   selectedOpenAppId: DEFAULT_OPEN_APP_ID,
+  sandboxProfiles: [],
 };
 
 function normalizeAppSettings(settings: AppSettings): AppSettings {
@@ -355,6 +361,7 @@ function normalizeAppSettings(settings: AppSettings): AppSettings {
       : "enter",
     openAppTargets: normalizedTargets,
     selectedOpenAppId,
+    sandboxProfiles: settings.sandboxProfiles ?? [],
   };
 }
 
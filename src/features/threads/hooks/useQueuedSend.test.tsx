@@ -1,9 +1,25 @@
 // @vitest-environment jsdom
 import { act, renderHook } from "@testing-library/react";
-import { describe, expect, it, vi } from "vitest";
-import type { WorkspaceInfo } from "../../../types";
+import { beforeEach, describe, expect, it, vi } from "vitest";
+import type {
+  AppServerEvent,
+  OutgoingMessage,
+  ThreadMessageQueue,
+  WorkspaceInfo,
+} from "../../../types";
+import { subscribeAppServerEvents } from "../../../services/events";
+import { getMessageQueue, updateMessageQueue } from "../../../services/tauri";
 import { useQueuedSend } from "./useQueuedSend";
 
+vi.mock("../../../services/events", () => ({
+  subscribeAppServerEvents: vi.fn(),
+}));
+
+vi.mock("../../../services/tauri", () => ({
+  getMessageQueue: vi.fn(),
+  updateMessageQueue: vi.fn(),
+}));
+
 const workspace: WorkspaceInfo = {
   id: "workspace-1",
   name: "OpenVibe",
@@ -12,6 +28,21 @@ const workspace: WorkspaceInfo = {
   settings: { sidebarCollapsed: false },
 };
 
+const backendQueue = (
+  threadId: string,
+  texts: string[],
+): ThreadMessageQueue => ({
+  workspaceId: workspace.id,
+  threadId,
+  paused: false,
+  items: texts.map((text, index) => ({
+    id: `queued-${index}`,
+    createdAt: index,
+    text,
+  })),
+  lastError: null,
+});
+
 const makeOptions = (
   overrides: Partial<Parameters<typeof useQueuedSend>[0]> = {},
 ) => ({
@@ -23,64 +54,114 @@ const makeOptions = (
   connectWorkspace: vi.fn().mockResolvedValue(undefined),
   sendUserMessage: vi.fn().mockResolvedValue(undefined),
   startReview: vi.fn().mockResolvedValue(undefined),
+  resolveQueuedMessage: vi.fn(
+    (_threadId: string, text: string, images: string[] = []) =>
+      ({ text, model: "gpt-5", images }) as OutgoingMessage,
+  ),
   clearActiveImages: vi.fn(),
   ...overrides,
 });
 
+let emitEvent: ((event: AppServerEvent) => void) | null = null;
+
+const flush = async () => {
+  await act(async () => {
+    await Promise.resolve();
+  });
+};
+
 describe("useQueuedSend", () => {
-  it("sends queued messages one at a time after processing completes", async () => {
-    const options = makeOptions();
-    const { result, rerender } = renderHook(
-      (props) => useQueuedSend(props),
-      { initialProps: options },
+  beforeEach(() => {
+    vi.clearAllMocks();
+    emitEvent = null;
+    vi.mocked(subscribeAppServerEvents).mockImplementation((callback) => {
+      emitEvent = callback;
+      return () => {};
+    });
+    vi.mocked(getMessageQueue).mockImplementation(async (_workspaceId, threadId) =>
+      backendQueue(threadId, []),
+    );
+    vi.mocked(updateMessageQueue).mockImplementation(
+      async (_workspaceId, threadId, action) =>
+        backendQueue(
+          threadId,
+          action.type === "enqueue" ? [action.message.text] : [],
+        ),
     );
+  });
 
-    await act(async () => {
-      await result.current.queueMessage("First");
-      await result.current.queueMessage("Second");
+  it("enqueues messages on the backend queue with resolved turn settings", async () => {
+    const options = makeOptions();
+    const { result } = renderHook((props) => useQueuedSend(props), {
+      initialProps: options,
     });
 
     await act(async () => {
-      await Promise.resolve();
+      await result.current.queueMessage("First", ["img-1"]);
     });
 
-    expect(options.sendUserMessage).toHaveBeenCalledTimes(1);
-    expect(options.sendUserMessage).toHaveBeenCalledWith("First", []);
-
-    await act(async () => {
-      rerender({ ...options, isProcessing: true });
-    });
-    await act(async () => {
-      await Promise.resolve();
-    });
-    await act(async () => {
-      rerender({ ...options, isProcessing: false });
-    });
-    await act(async () => {
-      await Promise.resolve();
+    expect(options.resolveQueuedMessage).toHaveBeenCalledWith(
+      "thread-1",
+      "First",
+      ["img-1"],
+    );
+    expect(updateMessageQueue).toHaveBeenCalledWith("workspace-1", "thread-1", {
+      type: "enqueue",
+      message: { text: "First", model: "gpt-5", images: ["img-1"] },
     });
-
-    expect(options.sendUserMessage).toHaveBeenCalledTimes(2);
-    expect(options.sendUserMessage).toHaveBeenLastCalledWith("Second", []);
+    expect(options.sendUserMessage).not.toHaveBeenCalled();
+    expect(options.clearActiveImages).toHaveBeenCalled();
+    expect(result.current.activeQueue.map((item) => item.text)).toEqual([
+      "First",
+    ]);
   });
 
-  it("waits for processing to start before sending the next queued message", async () => {
-    const options = makeOptions();
+  it("loads the backend queue for the active thread", async () => {
+    vi.mocked(getMessageQueue).mockResolvedValue(
+      backendQueue("thread-1", ["Saved"]),
+    );
     const { result } = renderHook((props) => useQueuedSend(props), {
-      initialProps: options,
+      initialProps: makeOptions(),
     });
 
-    await act(async () => {
-      await result.current.queueMessage("Alpha");
-      await result.current.queueMessage("Beta");
-    });
+    await flush();
 
-    await act(async () => {
-      await Promise.resolve();
-    });
+    expect(getMessageQueue).toHaveBeenCalledWith("workspace-1", "thread-1");
+    expect(result.current.activeQueue[0]?.text).toBe("Saved");
+  });
 
-    expect(options.sendUserMessage).toHaveBeenCalledTimes(1);
-    expect(options.sendUserMessage).toHaveBeenCalledWith("Alpha", []);
+  it("follows queue changes pushed by the backend", async () => {
+    const { result } = renderHook((props) => useQueuedSend(props), {
+      initialProps: makeOptions(),
+    });
+    await flush();
+
+    act(() => {
+      emitEvent?.({
+        workspace_id: "workspace-1",
+        message: {
+          method: "openvibe/queue/changed",
+          params: backendQueue("thread-1", ["Second", "Third"]),
+        },
+      });
+    });
+    expect(result.current.activeQueue.map((item) => item.text)).toEqual([
+      "Second",
+      "Third",
+    ]);
+
+    act(() => {
+      emitEvent?.({
+        workspace_id: "workspace-1",
+        message: {
+          method: "openvibe/queue/changed",
+          params: backendQueue("thread-1", ["Third"]),
+        },
+      });
+    });
+    expect(result.current.activeQueue.map((item) => item.text)).toEqual([
+      "Third",
+    ]);
   });
 
   it("queues send while processing when steer is disabled", async () => {
@@ -94,6 +175,7 @@ describe("useQueuedSend", () => {
     });
 
     expect(options.sendUserMessage).not.toHaveBeenCalled();
+    expect(updateMessageQueue).toHaveBeenCalledTimes(1);
     expect(result.current.activeQueue).toHaveLength(1);
     expect(result.current.activeQueue[0]?.text).toBe("Queued");
   });
@@ -110,64 +192,44 @@ describe("useQueuedSend", () => {
 
     expect(options.sendUserMessage).toHaveBeenCalledTimes(1);
     expect(options.sendUserMessage).toHaveBeenCalledWith("Steer", []);
-    expect(result.current.activeQueue).toHaveLength(0);
+    expect(updateMessageQueue).not.toHaveBeenCalled();
   });
 
-  it("retries queued send after failure", async () => {
+  it("does not enqueue when the message cannot be resolved", async () => {
     const options = makeOptions({
-      sendUserMessage: vi
-        .fn()
-        .mockRejectedValueOnce(new Error("boom"))
-        .mockResolvedValueOnce(undefined),
+      resolveQueuedMessage: vi.fn().mockReturnValue(null),
     });
     const { result } = renderHook((props) => useQueuedSend(props), {
       initialProps: options,
     });
 
     await act(async () => {
-      await result.current.queueMessage("Retry");
-    });
-
-    await act(async () => {
-      await Promise.resolve();
-    });
-    await act(async () => {
-      await Promise.resolve();
+      await result.current.queueMessage("/unknown-prompt");
     });
 
-    expect(options.sendUserMessage).toHaveBeenCalledTimes(2);
-    expect(options.sendUserMessage).toHaveBeenLastCalledWith("Retry", []);
+    expect(updateMessageQueue).not.toHaveBeenCalled();
+    expect(result.current.activeQueue).toHaveLength(0);
   });
 
-  it("queues messages per thread and only flushes the active thread", async () => {
-    const options = makeOptions({ isProcessing: true });
-    const { result, rerender } = renderHook(
-      (props) => useQueuedSend(props),
-      { initialProps: options },
+  it("removes queued messages through the backend queue", async () => {
+    vi.mocked(getMessageQueue).mockResolvedValue(
+      backendQueue("thread-1", ["Drop me"]),
     );
-
-    await act(async () => {
-      await result.current.queueMessage("Thread-1");
+    const { result } = renderHook((props) => useQueuedSend(props), {
+      initialProps: makeOptions(),
     });
+    await flush();
 
     await act(async () => {
-      rerender({ ...options, activeThreadId: "thread-2", isProcessing: false });
-    });
-    await act(async () => {
-      await Promise.resolve();
+      result.current.removeQueuedMessage("thread-1", "queued-0");
     });
+    await flush();
 
-    expect(options.sendUserMessage).not.toHaveBeenCalled();
-
-    await act(async () => {
-      rerender({ ...options, activeThreadId: "thread-1", isProcessing: false });
+    expect(updateMessageQueue).toHaveBeenCalledWith("workspace-1", "thread-1", {
+      type: "remove",
+      id: "queued-0",
     });
-    await act(async () => {
-      await Promise.resolve();
-    });
-
-    expect(options.sendUserMessage).toHaveBeenCalledTimes(1);
-    expect(options.sendUserMessage).toHaveBeenCalledWith("Thread-1", []);
+    expect(result.current.activeQueue).toHaveLength(0);
   });
 
   it("connects workspace before sending when disconnected", async () => {
@@ -191,44 +253,60 @@ describe("useQueuedSend", () => {
     expect(options.sendUserMessage).toHaveBeenCalledWith("Connect", []);
   });
 
-  it("ignores images for queued review messages and blocks while reviewing", async () => {
-    const options = makeOptions();
+  it("keeps reviews local and starts them once the thread is idle", async () => {
+    const options = makeOptions({ isProcessing: true });
     const { result, rerender } = renderHook(
       (props) => useQueuedSend(props),
       { initialProps: options },
     );
+    await flush();
 
     await act(async () => {
       await result.current.queueMessage("/review check this", ["img-1"]);
-      await result.current.queueMessage("After review");
     });
 
+    expect(updateMessageQueue).not.toHaveBeenCalled();
+    expect(result.current.activeQueue[0]?.images).toEqual([]);
+    expect(options.startReview).not.toHaveBeenCalled();
+
     await act(async () => {
-      await Promise.resolve();
+      rerender({ ...options, isProcessing: false });
     });
+    await flush();
 
     expect(options.startReview).toHaveBeenCalledTimes(1);
     expect(options.startReview).toHaveBeenCalledWith("/review check this");
-    expect(options.sendUserMessage).not.toHaveBeenCalled();
+  });
 
-    await act(async () => {
-      rerender({ ...options, isReviewing: true });
+  it("holds reviews until the backend queue has drained", async () => {
+    vi.mocked(getMessageQueue).mockResolvedValue(
+      backendQueue("thread-1", ["Queued turn"]),
+    );
+    const options = makeOptions();
+    const { result } = renderHook((props) => useQueuedSend(props), {
+      initialProps: options,
     });
+    await flush();
+
     await act(async () => {
-      await Promise.resolve();
+      await result.current.queueMessage("/review later");
     });
+    await flush();
 
-    expect(options.sendUserMessage).not.toHaveBeenCalled();
+    expect(options.startReview).not.toHaveBeenCalled();
 
-    await act(async () => {
-      rerender({ ...options, isReviewing: false });
-    });
-    await act(async () => {
-      await Promise.resolve();
+    act(() => {
+      emitEvent?.({
+        workspace_id: "workspace-1",
+        message: {
+          method: "openvibe/queue/changed",
+          params: backendQueue("thread-1", []),
+        },
+      });
     });
+    await flush();
 
-    expect(options.sendUserMessage).toHaveBeenCalledTimes(1);
-    expect(options.sendUserMessage).toHaveBeenCalledWith("After review", []);
+    expect(options.startReview).toHaveBeenCalledWith("/review later");
   });
 
   it("does not send when reviewing even if steer is enabled", async () => {
@@ -242,27 +320,7 @@ describe("useQueuedSend", () => {
     });
 
     expect(options.sendUserMessage).not.toHaveBeenCalled();
+    expect(updateMessageQueue).not.toHaveBeenCalled();
     expect(result.current.activeQueue).toHaveLength(0);
   });
-
-  it("preserves images for queued messages", async () => {
-    const options = makeOptions();
-    const { result } = renderHook((props) => useQueuedSend(props), {
-      initialProps: options,
-    });
-
-    await act(async () => {
-      await result.current.queueMessage("Images", ["img-1", "img-2"]);
-    });
-
-    await act(async () => {
-      await Promise.resolve();
-    });
-
-    expect(options.sendUserMessage).toHaveBeenCalledTimes(1);
-    expect(options.sendUserMessage).toHaveBeenCalledWith("Images", [
-      "img-1",
-      "img-2",
-    ]);
-  });
 });
@@ -1,5 +1,14 @@
 import { useCallback, useEffect, useMemo, useState } from "react";
-import type { QueuedMessage, WorkspaceInfo } from "../../../types";
+import type {
+  OutgoingMessage,
+  QueuedMessage,
+  ThreadMessageQueue,
+  WorkspaceInfo,
+} from "../../../types";
+import { subscribeAppServerEvents } from "../../../services/events";
+import { getMessageQueue, updateMessageQueue } from "../../../services/tauri";
+
+const QUEUE_CHANGED_METHOD = "openvibe/queue/changed";
 
 type UseQueuedSendOptions = {
   activeThreadId: string | null;
@@ -10,6 +19,11 @@ type UseQueuedSendOptions = {
   connectWorkspace: (workspace: WorkspaceInfo) => Promise<void>;
   sendUserMessage: (text: string, images?: string[]) => Promise<void>;
   startReview: (text: string) => Promise<void>;
+  resolveQueuedMessage: (
+    threadId: string,
+    text: string,
+    images?: string[],
+  ) => OutgoingMessage | null;
   clearActiveImages: () => void;
 };
 
@@ -21,6 +35,12 @@ type UseQueuedSendResult = {
   removeQueuedMessage: (threadId: string, messageId: string) => void;
 };
 
+const isReviewCommand = (text: string) => text.trim().startsWith("/review");
+
+/**
+ * Queued user messages live in the backend queue, which sends them as turns finish even
+ * when the thread is not open. Only `/review` commands wait in a local queue.
+ */
 export function useQueuedSend({
   activeThreadId,
   isProcessing,
@@ -30,9 +50,13 @@ export function useQueuedSend({
   connectWorkspace,
   sendUserMessage,
   startReview,
+  resolveQueuedMessage,
   clearActiveImages,
 }: UseQueuedSendOptions): UseQueuedSendResult {
-  const [queuedByThread, setQueuedByThread] = useState<
+  const [backendQueueByThread, setBackendQueueByThread] = useState<
+    Record<string, ThreadMessageQueue>
+  >({});
+  const [reviewsByThread, setReviewsByThread] = useState<
     Record<string, QueuedMessage[]>
   >({});
   const [inFlightByThread, setInFlightByThread] = useState<
@@ -41,33 +65,137 @@ export function useQueuedSend({
   const [hasStartedByThread, setHasStartedByThread] = useState<
     Record<string, boolean>
   >({});
+  const activeWorkspaceId = activeWorkspace?.id ?? null;
+
+  const storeBackendQueue = useCallback((queue: ThreadMessageQueue) => {
+    setBackendQueueByThread((prev) => ({ ...prev, [queue.threadId]: queue }));
+  }, []);
+
+  useEffect(() => {
+    if (!activeWorkspaceId || !activeThreadId) {
+      return;
+    }
+    let canceled = false;
+    getMessageQueue(activeWorkspaceId, activeThreadId)
+      .then((queue) => {
+        if (!canceled) {
+          storeBackendQueue(queue);
+        }
+      })
+      .catch(() => {});
+    return () => {
+      canceled = true;
+    };
+  }, [activeThreadId, activeWorkspaceId, storeBackendQueue]);
+
+  useEffect(() => {
+    return subscribeAppServerEvents(({ message }) => {
+      if (message.method !== QUEUE_CHANGED_METHOD) {
+        return;
+      }
+      const queue = message.params as ThreadMessageQueue | undefined;
+      if (queue?.threadId) {
+        storeBackendQueue(queue);
+      }
+    });
+  }, [storeBackendQueue]);
+
+  const queuedByThread = useMemo(() => {
+    const merged: Record<string, QueuedMessage[]> = {};
+    Object.values(backendQueueByThread).forEach((queue) => {
+      merged[queue.threadId] = queue.items.map((item) => ({
+        id: item.id,
+        text: item.text,
+        createdAt: item.createdAt,
+        images: item.images ?? [],
+      }));
+    });
+    Object.entries(reviewsByThread).forEach(([threadId, reviews]) => {
+      merged[threadId] = [...(merged[threadId] ?? []), ...reviews];
+    });
+    return merged;
+  }, [backendQueueByThread, reviewsByThread]);
 
   const activeQueue = useMemo(
     () => (activeThreadId ? queuedByThread[activeThreadId] ?? [] : []),
     [activeThreadId, queuedByThread],
   );
 
-  const enqueueMessage = useCallback((threadId: string, item: QueuedMessage) => {
-    setQueuedByThread((prev) => ({
-      ...prev,
-      [threadId]: [...(prev[threadId] ?? []), item],
-    }));
-  }, []);
+  const enqueueMessage = useCallback(
+    async (threadId: string, text: string, images: string[]) => {
+      if (isReviewCommand(text)) {
+        const item: QueuedMessage = {
+          id: `${Date.now()}-${Math.random().toString(36).slice(2, 8)}`,
+          text,
+          createdAt: Date.now(),
+          images: [],
+        };
+        setReviewsByThread((prev) => ({
+          ...prev,
+          [threadId]: [...(prev[threadId] ?? []), item],
+        }));
+        clearActiveImages();
+        return;
+      }
+      if (!activeWorkspaceId) {
+        return;
+      }
+      const message = resolveQueuedMessage(threadId, text, images);
+      if (!message) {
+        return;
+      }
+      try {
+        storeBackendQueue(
+          await updateMessageQueue(activeWorkspaceId, threadId, {
+            type: "enqueue",
+            message,
+          }),
+        );
+        clearActiveImages();
+      } catch (error) {
+        setBackendQueueByThread((prev) => ({
+          ...prev,
+          [threadId]: {
+            workspaceId: activeWorkspaceId,
+            threadId,
+            paused: prev[threadId]?.paused ?? false,
+            items: prev[threadId]?.items ?? [],
+            lastError: error instanceof Error ? error.message : String(error),
+          },
+        }));
+      }
+    },
+    [activeWorkspaceId, clearActiveImages, resolveQueuedMessage, storeBackendQueue],
+  );
 
   const removeQueuedMessage = useCallback(
     (threadId: string, messageId: string) => {
-      setQueuedByThread((prev) => ({
-        ...prev,
-        [threadId]: (prev[threadId] ?? []).filter(
-          (entry) => entry.id !== messageId,
-        ),
-      }));
+      const reviews = reviewsByThread[threadId] ?? [];
+      if (reviews.some((entry) => entry.id === messageId)) {
+        setReviewsByThread((prev) => ({
+          ...prev,
+          [threadId]: (prev[threadId] ?? []).filter(
+            (entry) => entry.id !== messageId,
+          ),
+        }));
+        return;
+      }
+      const queue = backendQueueByThread[threadId];
+      if (!queue) {
+        return;
+      }
+      void updateMessageQueue(queue.workspaceId, threadId, {
+        type: "remove",
+        id: messageId,
+      })
+        .then(storeBackendQueue)
+        .catch(() => {});
     },
-    [],
+    [backendQueueByThread, reviewsByThread, storeBackendQueue],
   );
 
-  const prependQueuedMessage = useCallback((threadId: string, item: QueuedMessage) => {
-    setQueuedByThread((prev) => ({
+  const prependReview = useCallback((threadId: string, item: QueuedMessage) => {
+    setReviewsByThread((prev) => ({
       ...prev,
       [threadId]: [item, ...(prev[threadId] ?? [])],
     }));
@@ -76,8 +204,7 @@ export function useQueuedSend({
   const handleSend = useCallback(
     async (text: string, images: string[] = []) => {
       const trimmed = text.trim();
-      const shouldIgnoreImages = trimmed.startsWith("/review");
-      const nextImages = shouldIgnoreImages ? [] : images;
+      const nextImages = isReviewCommand(trimmed) ? [] : images;
       if (!trimmed && nextImages.length === 0) {
         return;
       }
@@ -85,20 +212,13 @@ export function useQueuedSend({
         return;
       }
       if (isProcessing && activeThreadId && !steerEnabled) {
-        const item: QueuedMessage = {
-          id: `${Date.now()}-${Math.random().toString(36).slice(2, 8)}`,
-          text: trimmed,
-          createdAt: Date.now(),
-          images: nextImages,
-        };
-        enqueueMessage(activeThreadId, item);
-        clearActiveImages();
+        await enqueueMessage(activeThreadId, trimmed, nextImages);
         return;
       }
       if (activeWorkspace && !activeWorkspace.connected) {
         await connectWorkspace(activeWorkspace);
       }
-      if (trimmed.startsWith("/review")) {
+      if (isReviewCommand(trimmed)) {
         await startReview(trimmed);
         clearActiveImages();
         return;
@@ -123,27 +243,16 @@ export function useQueuedSend({
   const queueMessage = useCallback(
     async (text: string, images: string[] = []) => {
       const trimmed = text.trim();
-      const shouldIgnoreImages = trimmed.startsWith("/review");
-      const nextImages = shouldIgnoreImages ? [] : images;
+      const nextImages = isReviewCommand(trimmed) ? [] : images;
       if (!trimmed && nextImages.length === 0) {
         return;
       }
-      if (activeThreadId && isReviewing) {
+      if (!activeThreadId || isReviewing) {
         return;
       }
-      if (!activeThreadId) {
-        return;
-      }
-      const item: QueuedMessage = {
-        id: `${Date.now()}-${Math.random().toString(36).slice(2, 8)}`,
-        text: trimmed,
-        createdAt: Date.now(),
-        images: nextImages,
-      };
-      enqueueMessage(activeThreadId, item);
-      clearActiveImages();
+      await enqueueMessage(activeThreadId, trimmed, nextImages);
     },
-    [activeThreadId, clearActiveImages, enqueueMessage, isReviewing],
+    [activeThreadId, enqueueMessage, isReviewing],
   );
 
   useEffect(() => {
@@ -175,6 +284,7 @@ export function useQueuedSend({
     isReviewing,
   ]);
 
+  // Reviews wait until the backend queue has drained so they never race a queued turn.
   useEffect(() => {
     if (!activeThreadId || isProcessing || isReviewing) {
       return;
@@ -182,39 +292,38 @@ export function useQueuedSend({
     if (inFlightByThread[activeThreadId]) {
       return;
     }
-    const queue = queuedByThread[activeThreadId] ?? [];
-    if (queue.length === 0) {
+    if ((backendQueueByThread[activeThreadId]?.items.length ?? 0) > 0) {
+      return;
+    }
+    const reviews = reviewsByThread[activeThreadId] ?? [];
+    if (reviews.length === 0) {
       return;
     }
     const threadId = activeThreadId;
-    const nextItem = queue[0];
+    const nextItem = reviews[0];
     setInFlightByThread((prev) => ({ ...prev, [threadId]: nextItem }));
     setHasStartedByThread((prev) => ({ ...prev, [threadId]: false }));
-    setQueuedByThread((prev) => ({
+    setReviewsByThread((prev) => ({
       ...prev,
       [threadId]: (prev[threadId] ?? []).slice(1),
     }));
     (async () => {
       try {
-        if (nextItem.text.trim().startsWith("/review")) {
-          await startReview(nextItem.text);
-        } else {
-          await sendUserMessage(nextItem.text, nextItem.images ?? []);
-        }
+        await startReview(nextItem.text);
       } catch {
         setInFlightByThread((prev) => ({ ...prev, [threadId]: null }));
         setHasStartedByThread((prev) => ({ ...prev, [threadId]: false }));
-        prependQueuedMessage(threadId, nextItem);
+        prependReview(threadId, nextItem);
       }
     })();
   }, [
     activeThreadId,
+    backendQueueByThread,
     inFlightByThread,
     isProcessing,
     isReviewing,
-    prependQueuedMessage,
-    queuedByThread,
-    sendUserMessage,
+    prependReview,
+    reviewsByThread,
     startReview,
   ]);
 
@@ -29,6 +29,7 @@ type ThreadEventHandlersOptions = {
   pushThreadErrorMessage: (threadId: string, message: string) => void;
   onDebug?: (entry: DebugEntry) => void;
   onWorkspaceConnected: (workspaceId: string) => void;
+  onWorkspaceDisconnected?: (workspaceId: string) => void;
   getWorkspacePath?: (workspaceId: string) => string | null;
   onHappyBridgeCommand?: (command: HappyBridgeCommand) => void;
   onNanobotBridgeCommand?: (command: NanobotBridgeCommand) => void;
@@ -60,6 +61,7 @@ export function useThreadEventHandlers({
   pushThreadErrorMessage,
   onDebug,
   onWorkspaceConnected,
+  onWorkspaceDisconnected,
   getWorkspacePath,
   onHappyBridgeCommand,
   onNanobotBridgeCommand,
@@ -140,6 +142,7 @@ export function useThreadEventHandlers({
   const handlers = useMemo(
     () => ({
       onWorkspaceConnected,
+      onWorkspaceDisconnected,
       onApprovalRequest,
       onRequestUserInput,
       onAppServerEvent,
@@ -163,6 +166,7 @@ export function useThreadEventHandlers({
     }),
     [
       onWorkspaceConnected,
+      onWorkspaceDisconnected,
       onApprovalRequest,
       onRequestUserInput,
       onAppServerEvent,
@@ -6,6 +6,7 @@ import type {
   CustomPromptOption,
   DebugEntry,
   HappyBridgeCommand,
+  OutgoingMessage,
   WorkspaceInfo,
 } from "../../../types";
 import {
@@ -28,12 +29,14 @@ type SendMessageOptions = {
   effort?: string | null;
   collaborationMode?: Record<string, unknown> | null;
   accessMode?: AccessMode;
+  sandboxProfile?: string | null;
 };
 
 type UseThreadMessagingOptions = {
   activeWorkspace: WorkspaceInfo | null;
   activeThreadId: string | null;
   accessMode?: "read-only" | "current" | "full-access";
+  sandboxProfile?: string | null;
   model?: string | null;
   effort?: string | null;
   collaborationMode?: Record<string, unknown> | null;
@@ -63,6 +66,7 @@ export function useThreadMessaging({
   activeWorkspace,
   activeThreadId,
   accessMode,
+  sandboxProfile,
   model,
   effort,
   collaborationMode,
@@ -121,6 +125,10 @@ export function useThreadMessaging({
           : null;
       const resolvedAccessMode =
         options?.accessMode !== undefined ? options.accessMode : accessMode;
+      const resolvedSandboxProfile =
+        options?.sandboxProfile !== undefined
+          ? options.sandboxProfile
+          : sandboxProfile;
 
       const wasProcessing =
         (threadStatusById[threadId]?.isProcessing ?? false) && steerEnabled;
@@ -207,6 +215,7 @@ export function useThreadMessaging({
               effort: resolvedEffort,
               collaborationMode: sanitizedCollaborationMode,
               accessMode: resolvedAccessMode,
+              sandboxProfile: resolvedSandboxProfile,
               images,
             },
           )) as Record<string, unknown>;
@@ -268,6 +277,7 @@ export function useThreadMessaging({
       onHappyBridgeCommand,
       pushThreadErrorMessage,
       recordThreadActivity,
+      sandboxProfile,
       safeMessageActivity,
       setActiveTurnId,
       steerEnabled,
@@ -472,8 +482,53 @@ export function useThreadMessaging({
     ],
   );
 
+  const resolveQueuedMessage = useCallback(
+    (
+      threadId: string,
+      text: string,
+      images: string[] = [],
+    ): OutgoingMessage | null => {
+      const messageText = text.trim();
+      if (!messageText && images.length === 0) {
+        return null;
+      }
+      const promptExpansion = expandCustomPromptText(messageText, customPrompts);
+      if (promptExpansion && "error" in promptExpansion) {
+        pushThreadErrorMessage(threadId, promptExpansion.error);
+        safeMessageActivity();
+        return null;
+      }
+      const sanitizedCollaborationMode =
+        collaborationMode &&
+        typeof collaborationMode === "object" &&
+        "settings" in collaborationMode
+          ? collaborationMode
+          : null;
+      return {
+        text: promptExpansion?.expanded ?? messageText,
+        model: model ?? null,
+        effort: effort ?? null,
+        accessMode: accessMode ?? null,
+        sandboxProfile: sandboxProfile ?? null,
+        images,
+        collaborationMode: sanitizedCollaborationMode,
+      };
+    },
+    [
+      accessMode,
+      collaborationMode,
+      customPrompts,
+      effort,
+      model,
+      pushThreadErrorMessage,
+      safeMessageActivity,
+      sandboxProfile,
+    ],
+  );
+
   return {
     interruptTurn,
+    resolveQueuedMessage,
     sendUserMessage,
     sendUserMessageToThread,
     startReview,
@@ -87,11 +87,13 @@ function hasPersistedNanobotRoute(workspaceId: string, threadId: string) {
 type UseThreadsOptions = {
   activeWorkspace: WorkspaceInfo | null;
   onWorkspaceConnected: (id: string) => void;
+  onWorkspaceDisconnected?: (id: string) => void;
   onDebug?: (entry: DebugEntry) => void;
   model?: string | null;
   effort?: string | null;
   collaborationMode?: Record<string, unknown> | null;
   accessMode?: "read-only" | "current" | "full-access";
+  sandboxProfile?: string | null;
   steerEnabled?: boolean;
   resumeStreamingEnabled?: boolean;
   customPrompts?: CustomPromptOption[];
@@ -105,11 +107,13 @@ type UseThreadsOptions = {
 export function useThreads({
   activeWorkspace,
   onWorkspaceConnected,
+  onWorkspaceDisconnected,
   onDebug,
   model,
   effort,
   collaborationMode,
   accessMode,
+  sandboxProfile = null,
   steerEnabled = false,
   resumeStreamingEnabled = false,
   customPrompts = [],
@@ -439,6 +443,7 @@ export function useThreads({
     pushThreadErrorMessage,
     onDebug,
     onWorkspaceConnected: handleWorkspaceConnected,
+    onWorkspaceDisconnected,
     getWorkspacePath,
     onHappyBridgeCommand: queueHappyBridgeCommand,
     onNanobotBridgeCommand: queueNanobotBridgeCommand,
@@ -501,6 +506,7 @@ export function useThreads({
 
   const {
     interruptTurn,
+    resolveQueuedMessage,
     sendUserMessage,
     sendUserMessageToThread,
     startReview,
@@ -508,6 +514,7 @@ export function useThreads({
     activeWorkspace,
     activeThreadId,
     accessMode,
+    sandboxProfile,
     model,
     effort,
     collaborationMode,
@@ -657,6 +664,7 @@ export function useThreads({
     refreshThread,
     resetWorkspaceThreads,
     loadOlderThreadsForWorkspace,
+    resolveQueuedMessage,
     sendUserMessage,
     sendUserMessageToThread,
     retryHappyMessage,
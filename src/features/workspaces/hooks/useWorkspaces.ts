@@ -457,6 +457,12 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     );
   }
 
+  function markWorkspaceDisconnected(id: string) {
+    setWorkspaces((prev) =>
+      prev.map((entry) => (entry.id === id ? { ...entry, connected: false } : entry)),
+    );
+  }
+
   const updateWorkspaceSettings = useCallback(
     async (workspaceId: string, patch: Partial<WorkspaceSettings>) => {
       onDebug?.({
@@ -927,6 +933,7 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     connectWorkspace,
     reconnectWorkspace,
     markWorkspaceConnected,
+    markWorkspaceDisconnected,
     updateWorkspaceSettings,
     updateWorkspaceCodexBin,
     createWorkspaceGroup,
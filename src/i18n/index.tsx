@@ -46,6 +46,17 @@ const en = {
   "settings.projects.project.moveDown": "Move project down",
   "settings.projects.project.delete": "Delete project",
   "settings.projects.project.empty": "No projects yet.",
+  "settings.projects.orphans.title": "Sessions outside projects",
+  "settings.projects.orphans.subtitle":
+    "Find CLI sessions that no project shows and open them here.",
+  "settings.projects.orphans.scan": "Scan",
+  "settings.projects.orphans.empty": "No stray sessions found.",
+  "settings.projects.orphans.addProject": "Add project and open",
+  "settings.projects.orphans.open": "Open",
+  "settings.projects.orphans.kind.unregistered": "Not inside any project",
+  "settings.projects.orphans.kind.unlisted": "Missing from the project's thread list",
+  "settings.projects.orphans.kind.unknown":
+    "The project's threads could not be listed",
   "settings.display.title": "Display & Sound",
   "settings.display.subtitle": "Tune visuals and audio alerts to your preferences.",
   "settings.display.section.title": "Display",
@@ -755,6 +766,10 @@ const en = {
   "settings.codex.remote.hostAria": "Remote backend host",
   "settings.codex.remote.tokenAria": "Remote backend token",
   "settings.codex.remote.tokenPlaceholder": "Token (optional)",
+  "settings.codex.remote.fingerprintAria": "Remote backend TLS certificate fingerprint",
+  "settings.codex.remote.fingerprintPlaceholder":
+    "TLS certificate SHA-256 fingerprint (optional, enables TLS)",
+  "settings.codex.remote.retryReads": "Retry read-only requests after reconnecting",
   "settings.codex.remote.help":
     "Start the daemon separately and point OpenVibe to it (host:port + token).",
   "settings.codex.fileAgents.title": "Global AGENTS.md",
@@ -762,12 +777,29 @@ const en = {
   "settings.codex.fileConfig.title": "Global config.toml",
   "settings.codex.fileConfig.placeholder": "Edit the global Codex config.toml…",
   "settings.codex.fileLocation": "Stored at",
+  "settings.codex.sandboxProfiles.title": "Sandbox profiles",
+  "settings.codex.sandboxProfiles.subtitle":
+    "Named sandbox and approval presets offered in the composer for every workspace.",
+  "settings.codex.sandboxProfiles.empty": "No sandbox profiles yet.",
+  "settings.codex.sandboxProfiles.id": "Id",
+  "settings.codex.sandboxProfiles.name": "Name",
+  "settings.codex.sandboxProfiles.sandbox": "Sandbox",
+  "settings.codex.sandboxProfiles.approvalPolicy": "Approval policy",
+  "settings.codex.sandboxProfiles.writableRoots": "Extra writable roots",
+  "settings.codex.sandboxProfiles.writableRootsPlaceholder": "One path per line",
+  "settings.codex.sandboxProfiles.writableRootsHelp":
+    "Relative paths are resolved inside the workspace and may not leave it; absolute paths are used as given.",
+  "settings.codex.sandboxProfiles.networkAccess": "Network access",
+  "settings.codex.sandboxProfiles.add": "Add profile",
+  "settings.codex.sandboxProfiles.remove": "Remove profile",
+  "settings.codex.sandboxProfiles.save": "Save profiles",
   "settings.codex.workspaceOverrides.title": "Workspace overrides",
   "settings.codex.workspaceOverrides.subtitle":
     "Override Codex paths and args for specific workspaces.",
   "settings.codex.workspaceOverrides.binLabel": "Codex binary",
   "settings.codex.workspaceOverrides.homeLabel": "CODEX_HOME",
   "settings.codex.workspaceOverrides.argsLabel": "Codex args",
+  "settings.codex.workspaceOverrides.checkpointsLabel": "Git checkpoint before each turn",
   "settings.codex.workspaceOverrides.binPlaceholder": "Codex binary override",
   "settings.codex.workspaceOverrides.homePlaceholder": "CODEX_HOME override",
   "settings.codex.workspaceOverrides.argsPlaceholder": "Codex args override",
@@ -859,6 +891,7 @@ const en = {
   "composer.default": "Default",
   "composer.access.readOnly": "Read only",
   "composer.access.onRequest": "On-Request",
+  "composer.access.profiles": "Sandbox profiles",
     "composer.access.full": "Full access",
     "composer.contextFree": "Context free {percent}%",
     "composer.contextFreeUnknown": "Context free --",
@@ -1000,6 +1033,15 @@ const zhCN: Record<TranslationKey, string> = {
   "settings.projects.project.moveDown": "项目下移",
   "settings.projects.project.delete": "删除项目",
   "settings.projects.project.empty": "暂无项目。",
+  "settings.projects.orphans.title": "项目之外的会话",
+  "settings.projects.orphans.subtitle": "查找未在任何项目中显示的 CLI 会话，并在此打开。",
+  "settings.projects.orphans.scan": "扫描",
+  "settings.projects.orphans.empty": "未发现游离会话。",
+  "settings.projects.orphans.addProject": "添加项目并打开",
+  "settings.projects.orphans.open": "打开",
+  "settings.projects.orphans.kind.unregistered": "不在任何项目中",
+  "settings.projects.orphans.kind.unlisted": "未出现在项目的线程列表中",
+  "settings.projects.orphans.kind.unknown": "无法列出该项目的线程",
   "settings.display.title": "显示与声音",
   "settings.display.subtitle": "根据偏好调整视觉效果和音频提醒。",
   "settings.display.section.title": "显示",
@@ -1661,18 +1703,38 @@ const zhCN: Record<TranslationKey, string> = {
   "settings.codex.remote.hostAria": "远程后端地址",
   "settings.codex.remote.tokenAria": "远程后端令牌",
   "settings.codex.remote.tokenPlaceholder": "令牌（可选）",
+  "settings.codex.remote.fingerprintAria": "远程后端 TLS 证书指纹",
+  "settings.codex.remote.fingerprintPlaceholder":
+    "TLS 证书 SHA-256 指纹（可选，填写后启用 TLS）",
+  "settings.codex.remote.retryReads": "重新连接后重试只读请求",
   "settings.codex.remote.help": "单独启动守护进程，并指向 OpenVibe（host:port + token）。",
   "settings.codex.fileAgents.title": "全局 AGENTS.md",
   "settings.codex.fileAgents.placeholder": "添加全局 Codex 代理指令…",
   "settings.codex.fileConfig.title": "全局 config.toml",
   "settings.codex.fileConfig.placeholder": "编辑全局 Codex config.toml…",
   "settings.codex.fileLocation": "存放于",
+  "settings.codex.sandboxProfiles.title": "沙箱配置",
+  "settings.codex.sandboxProfiles.subtitle": "在编辑器中为所有工作区提供的沙箱与审批预设。",
+  "settings.codex.sandboxProfiles.empty": "暂无沙箱配置。",
+  "settings.codex.sandboxProfiles.id": "ID",
+  "settings.codex.sandboxProfiles.name": "名称",
+  "settings.codex.sandboxProfiles.sandbox": "沙箱",
+  "settings.codex.sandboxProfiles.approvalPolicy": "审批策略",
+  "settings.codex.sandboxProfiles.writableRoots": "额外可写目录",
+  "settings.codex.sandboxProfiles.writableRootsPlaceholder": "每行一个路径",
+  "settings.codex.sandboxProfiles.writableRootsHelp":
+    "相对路径在工作区内解析，且不能超出工作区；绝对路径按原样使用。",
+  "settings.codex.sandboxProfiles.networkAccess": "网络访问",
+  "settings.codex.sandboxProfiles.add": "添加配置",
+  "settings.codex.sandboxProfiles.remove": "删除配置",
+  "settings.codex.sandboxProfiles.save": "保存配置",
   "settings.codex.workspaceOverrides.title": "工作区覆盖设置",
   "settings.codex.workspaceOverrides.subtitle":
     "为特定工作区覆盖 Codex 路径与参数。",
   "settings.codex.workspaceOverrides.binLabel": "Codex 二进制",
   "settings.codex.workspaceOverrides.homeLabel": "CODEX_HOME",
   "settings.codex.workspaceOverrides.argsLabel": "Codex 参数",
+  "settings.codex.workspaceOverrides.checkpointsLabel": "每轮开始前创建 Git 检查点",
   "settings.codex.workspaceOverrides.binPlaceholder": "Codex 二进制覆盖",
   "settings.codex.workspaceOverrides.homePlaceholder": "CODEX_HOME 覆盖",
   "settings.codex.workspaceOverrides.argsPlaceholder": "Codex 参数覆盖",
@@ -1753,6 +1815,7 @@ const zhCN: Record<TranslationKey, string> = {
   "composer.default": "默认",
   "composer.access.readOnly": "只读",
   "composer.access.onRequest": "按需请求",
+  "composer.access.profiles": "沙箱配置",
   "composer.access.full": "完全访问",
   "composer.contextFree": "上下文剩余 {percent}%",
   "composer.contextFreeUnknown": "上下文剩余 --",
@@ -7,7 +7,9 @@ import {
   subscribeMenuCycleCollaborationMode,
   subscribeMenuCycleModel,
   subscribeMenuNewAgent,
+  subscribeRemoteBackendConnection,
   subscribeTerminalOutput,
+  type RemoteBackendConnectionEvent,
 } from "./events";
 
 vi.mock("@tauri-apps/api/event", () => ({
@@ -113,6 +115,32 @@ describe("events subscriptions", () => {
     cleanup();
   });
 
+  it("delivers remote backend connection states", async () => {
+    let listener: EventCallback<RemoteBackendConnectionEvent> = () => {};
+    const unlisten = vi.fn();
+
+    vi.mocked(listen).mockImplementation((eventName, handler) => {
+      expect(eventName).toBe("remote-backend-connection");
+      listener = handler as EventCallback<RemoteBackendConnectionEvent>;
+      return Promise.resolve(unlisten);
+    });
+
+    const onEvent = vi.fn();
+    const cleanup = subscribeRemoteBackendConnection(onEvent);
+    const payload: RemoteBackendConnectionEvent = {
+      state: "reconnecting",
+      host: "dev-box:4732",
+      attempt: 2,
+      retryInMs: 1000,
+      error: "Failed to connect to remote backend at dev-box:4732",
+      missedEvents: false,
+    };
+    listener({ event: "remote-backend-connection", id: 1, payload });
+    expect(onEvent).toHaveBeenCalledWith(payload);
+
+    cleanup();
+  });
+
   it("reports listen errors through options", async () => {
     const error = new Error("nope");
     vi.mocked(listen).mockRejectedValueOnce(error);
@@ -20,6 +20,15 @@ export type TerminalExitEvent = {
   terminalId: string;
 };
 
+export type RemoteBackendConnectionEvent = {
+  state: "connected" | "disconnected" | "reconnecting";
+  host: string;
+  attempt: number;
+  retryInMs: number | null;
+  error: string | null;
+  missedEvents: boolean;
+};
+
 type SubscriptionOptions = {
   onError?: (error: unknown) => void;
 };
@@ -93,6 +102,9 @@ const terminalOutputHub = createEventHub<TerminalOutputEvent>("terminal-output")
 const happyBridgeHub = createEventHub<HappyBridgeEvent>("happy-bridge-event");
 const nanobotBridgeHub = createEventHub<NanobotBridgeEvent>("nanobot-bridge-event");
 const terminalExitHub = createEventHub<TerminalExitEvent>("terminal-exit");
+const remoteBackendConnectionHub = createEventHub<RemoteBackendConnectionEvent>(
+  "remote-backend-connection",
+);
 const updaterCheckHub = createEventHub<void>("updater-check");
 const menuNewAgentHub = createEventHub<void>("menu-new-agent");
 const menuNewWorktreeAgentHub = createEventHub<void>("menu-new-worktree-agent");
@@ -167,6 +179,13 @@ export function subscribeTerminalExit(
   return terminalExitHub.subscribe(onEvent, options);
 }
 
+export function subscribeRemoteBackendConnection(
+  onEvent: (event: RemoteBackendConnectionEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return remoteBackendConnectionHub.subscribe(onEvent, options);
+}
+
 export function subscribeUpdaterCheck(
   onEvent: () => void,
   options?: SubscriptionOptions,
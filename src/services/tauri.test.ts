@@ -2,20 +2,36 @@ import { beforeEach, describe, expect, it, vi } from "vitest";
 import { invoke } from "@tauri-apps/api/core";
 import {
   addWorkspace,
+  cancelAppServerRequests,
+  exportThread,
+  fanOutPrompt,
+  forkThread,
+  getApprovalAuditLog,
   getGitHubIssues,
   getGitLog,
   getGitStatus,
+  getWorkspaceLogs,
   getOpenAppIcon,
   readGlobalAgentsMd,
   readGlobalCodexConfigToml,
+  listGitCheckpoints,
+  listThreads,
   listWorkspaces,
   openWorkspaceIn,
+  queryThreadMetadata,
   readAgentMd,
   stageGitAll,
   respondToServerRequest,
   respondToUserInputRequest,
+  restoreGitCheckpoint,
+  runHeadlessPrompt,
+  scanOrphanedSessions,
+  searchThreads,
+  updateMessageQueue,
   sendUserMessage,
+  setThreadMetadata,
   startReview,
+  startThreadHistoryStream,
   writeGlobalAgentsMd,
   writeGlobalCodexConfigToml,
   writeAgentMd,
@@ -42,6 +58,32 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("passes a timeout override to listThreads", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ data: [] });
+
+    await listThreads("ws-1", null, 50, 5000);
+
+    expect(invokeMock).toHaveBeenCalledWith("list_threads", {
+      workspaceId: "ws-1",
+      cursor: null,
+      limit: 50,
+      timeoutMs: 5000,
+    });
+  });
+
+  it("cancels in-flight app-server requests by method", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce(1);
+
+    await expect(cancelAppServerRequests("ws-1", "thread/list")).resolves.toBe(1);
+
+    expect(invokeMock).toHaveBeenCalledWith("cancel_app_server_requests", {
+      workspaceId: "ws-1",
+      method: "thread/list",
+    });
+  });
+
   it("maps workspace_id to workspaceId for git status", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({
@@ -241,6 +283,27 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("passes the selected sandbox profile with a message", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await sendUserMessage("ws-4", "thread-1", "hello", {
+      accessMode: "current",
+      sandboxProfile: "offline-write",
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("send_user_message", {
+      workspaceId: "ws-4",
+      threadId: "thread-1",
+      text: "hello",
+      model: null,
+      effort: null,
+      accessMode: "current",
+      images: null,
+      sandboxProfile: "offline-write",
+    });
+  });
+
   it("omits delivery when starting reviews without override", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
@@ -305,4 +368,215 @@ describe("tauri invoke wrappers", () => {
       },
     });
   });
+
+  it("passes log filters through for getWorkspaceLogs", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await getWorkspaceLogs("ws-1", { kinds: ["stderr"], query: "error" });
+
+    expect(invokeMock).toHaveBeenCalledWith("get_workspace_logs", {
+      workspaceId: "ws-1",
+      filter: { kinds: ["stderr"], query: "error" },
+    });
+  });
+
+  it("fills unset headless prompt options with null", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      threadId: "thread-1",
+      text: "ok",
+      output: null,
+      durationMs: 10,
+    });
+
+    await runHeadlessPrompt("ws-1", "Summarize", { timeoutSecs: 30, runId: "run-1" });
+
+    expect(invokeMock).toHaveBeenCalledWith("run_headless_prompt", {
+      workspaceId: "ws-1",
+      prompt: "Summarize",
+      outputSchema: null,
+      timeoutSecs: 30,
+      model: null,
+      effort: null,
+      runId: "run-1",
+    });
+  });
+
+  it("requests the approval audit log with an optional limit", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await getApprovalAuditLog("ws-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("get_approval_audit_log", {
+      workspaceId: "ws-1",
+      limit: null,
+    });
+  });
+
+  it("exports a thread rollout in the requested format", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce("# Thread thread-1");
+
+    await exportThread("thread-1", "/tmp/rollout.jsonl", "markdown");
+
+    expect(invokeMock).toHaveBeenCalledWith("export_thread", {
+      threadId: "thread-1",
+      path: "/tmp/rollout.jsonl",
+      format: "markdown",
+      outputPath: null,
+    });
+  });
+
+  it("searches thread rollouts with filters", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await searchThreads({ query: "auth redirect", workspaceId: "ws-1", limit: 20 });
+
+    expect(invokeMock).toHaveBeenCalledWith("search_threads", {
+      query: { query: "auth redirect", workspaceId: "ws-1", limit: 20 },
+    });
+  });
+
+  it("forks a thread into a new worktree", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await forkThread("ws-1", "thread-1", "/tmp/rollout.jsonl", 4, "fork/try-b");
+
+    expect(invokeMock).toHaveBeenCalledWith("fork_thread", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      path: "/tmp/rollout.jsonl",
+      messageIndex: 4,
+      worktreeBranch: "fork/try-b",
+    });
+  });
+
+  it("updates a thread's backend message queue", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await updateMessageQueue("ws-1", "thread-1", {
+      type: "enqueue",
+      message: { text: "next step", accessMode: "current" },
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("update_message_queue", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      action: {
+        type: "enqueue",
+        message: { text: "next step", accessMode: "current" },
+      },
+    });
+  });
+
+  it("fans a prompt out to new worktrees", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      fanOutId: "fan-out-1",
+      prompt: "Fix the flaky test",
+      workspaceIds: ["ws-2", "ws-3"],
+    });
+
+    await fanOutPrompt({
+      prompt: "Fix the flaky test",
+      count: 2,
+      parentId: "ws-1",
+      lanes: [{ model: "gpt-5" }, { model: "gpt-5", effort: "high" }],
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("fan_out_prompt", {
+      request: {
+        prompt: "Fix the flaky test",
+        count: 2,
+        parentId: "ws-1",
+        lanes: [{ model: "gpt-5" }, { model: "gpt-5", effort: "high" }],
+      },
+    });
+  });
+
+  it("stores thread metadata and queries it by tag", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue(null);
+
+    await setThreadMetadata({
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      tags: ["auth"],
+      pinned: true,
+      linkedPullRequest: 42,
+    });
+    await queryThreadMetadata({ tags: ["auth"] });
+
+    expect(invokeMock).toHaveBeenCalledWith("set_thread_metadata", {
+      metadata: {
+        workspaceId: "ws-1",
+        threadId: "thread-1",
+        tags: ["auth"],
+        pinned: true,
+        linkedPullRequest: 42,
+      },
+    });
+    expect(invokeMock).toHaveBeenCalledWith("query_thread_metadata", {
+      query: { tags: ["auth"] },
+    });
+  });
+
+  it("starts a following thread history stream", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce("stream-1");
+
+    await expect(
+      startThreadHistoryStream("ws-1", "thread-1", "/tmp/rollout.jsonl", true),
+    ).resolves.toBe("stream-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("stream_thread_history", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      path: "/tmp/rollout.jsonl",
+      follow: true,
+    });
+  });
+
+  it("scans for orphaned CLI sessions", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([
+      {
+        threadId: "thread-1",
+        path: "/home/me/.codex/sessions/rollout.jsonl",
+        cwd: "/home/me/project",
+        kind: "unregistered",
+        workspaceId: null,
+        startedAt: null,
+        updatedAt: 1,
+      },
+    ]);
+
+    const sessions = await scanOrphanedSessions();
+
+    expect(invokeMock).toHaveBeenCalledWith("scan_orphaned_sessions");
+    expect(sessions[0].kind).toBe("unregistered");
+  });
+
+  it("restores a git checkpoint and lists the thread's checkpoints", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue([]);
+
+    await listGitCheckpoints("ws-1", "thread-1");
+    await restoreGitCheckpoint("ws-1", "thread-1", "1700000000000");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_git_checkpoints", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("restore_git_checkpoint", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      checkpointId: "1700000000000",
+    });
+  });
 });
@@ -2,20 +2,38 @@ import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import type {
   AppSettings,
+  ApprovalAuditEntry,
   CodexDoctorResult,
   DictationModelStatus,
   DictationSessionState,
+  EventSinkStats,
+  FanOutRequest,
+  FanOutStarted,
+  ForkedThread,
   HappyBridgeCommand,
   HappyBridgeStatus,
+  HeadlessPromptOptions,
+  HeadlessPromptResult,
   LocalUsageSnapshot,
+  MessageQueueAction,
   MoltisTelegramMenuSyncResult,
   NanobotBridgeCommand,
   NanobotBridgeStatus,
   NanobotDingTalkTestResult,
+  OrphanedSession,
+  SessionLogEntry,
+  SessionLogFilter,
+  ThreadExportFormat,
+  ThreadMessageQueue,
+  ThreadMetadata,
+  ThreadMetadataQuery,
+  ThreadSearchHit,
+  ThreadSearchQuery,
   WorkspaceInfo,
   WorkspaceSettings,
 } from "../types";
 import type {
+  GitCheckpoint,
   GitFileDiff,
   GitFileStatus,
   GitCommitDiff,
@@ -59,6 +77,10 @@ export async function pickImageFiles(): Promise<string[]> {
   return Array.isArray(selection) ? selection : [selection];
 }
 
+export async function setRemoteEventWorkspaces(workspaceIds: string[]): Promise<void> {
+  return invoke("set_remote_event_workspaces", { workspaceIds });
+}
+
 export async function listWorkspaces(): Promise<WorkspaceInfo[]> {
   try {
     return await invoke<WorkspaceInfo[]>("list_workspaces");
@@ -281,6 +303,7 @@ export async function sendUserMessage(
     model?: string | null;
     effort?: string | null;
     accessMode?: "read-only" | "current" | "full-access";
+    sandboxProfile?: string | null;
     images?: string[];
     collaborationMode?: Record<string, unknown> | null;
   },
@@ -294,12 +317,31 @@ export async function sendUserMessage(
     accessMode: options?.accessMode ?? null,
     images: options?.images ?? null,
   };
+  if (options?.sandboxProfile) {
+    payload.sandboxProfile = options.sandboxProfile;
+  }
   if (options?.collaborationMode) {
     payload.collaborationMode = options.collaborationMode;
   }
   return invoke("send_user_message", payload);
 }
 
+export async function getMessageQueue(workspaceId: string, threadId: string) {
+  return invoke<ThreadMessageQueue>("get_message_queue", { workspaceId, threadId });
+}
+
+export async function updateMessageQueue(
+  workspaceId: string,
+  threadId: string,
+  action: MessageQueueAction,
+) {
+  return invoke<ThreadMessageQueue>("update_message_queue", {
+    workspaceId,
+    threadId,
+    action,
+  });
+}
+
 export async function interruptTurn(
   workspaceId: string,
   threadId: string,
@@ -345,6 +387,13 @@ export async function respondToUserInputRequest(
   });
 }
 
+export async function getApprovalAuditLog(workspaceId: string, limit?: number) {
+  return invoke<ApprovalAuditEntry[]>("get_approval_audit_log", {
+    workspaceId,
+    limit: limit ?? null,
+  });
+}
+
 export async function rememberApprovalRule(
   workspaceId: string,
   command: string[],
@@ -414,6 +463,40 @@ export async function revertGitAll(workspaceId: string) {
   return invoke("revert_git_all", { workspaceId });
 }
 
+export async function listGitCheckpoints(
+  workspaceId: string,
+  threadId: string,
+) {
+  return invoke<GitCheckpoint[]>("list_git_checkpoints", {
+    workspaceId,
+    threadId,
+  });
+}
+
+export async function diffGitCheckpoint(
+  workspaceId: string,
+  threadId: string,
+  checkpointId: string,
+) {
+  return invoke<string>("diff_git_checkpoint", {
+    workspaceId,
+    threadId,
+    checkpointId,
+  });
+}
+
+export async function restoreGitCheckpoint(
+  workspaceId: string,
+  threadId: string,
+  checkpointId: string,
+) {
+  return invoke<GitCheckpoint>("restore_git_checkpoint", {
+    workspaceId,
+    threadId,
+    checkpointId,
+  });
+}
+
 export async function commitGit(
   workspaceId: string,
   message: string,
@@ -486,6 +569,36 @@ export async function getThreadTokenUsage(
   });
 }
 
+export async function searchThreads(query: ThreadSearchQuery) {
+  return invoke<ThreadSearchHit[]>("search_threads", { query });
+}
+
+export async function getWorkspaceLogs(
+  workspaceId: string,
+  filter?: SessionLogFilter,
+): Promise<SessionLogEntry[]> {
+  return invoke<SessionLogEntry[]>("get_workspace_logs", {
+    workspaceId,
+    filter: filter ?? null,
+  });
+}
+
+export async function exportWorkspaceLogs(
+  workspaceId: string,
+  path: string,
+  filter?: SessionLogFilter,
+): Promise<number> {
+  return invoke<number>("export_workspace_logs", {
+    workspaceId,
+    path,
+    filter: filter ?? null,
+  });
+}
+
+export async function getEventSinkStats() {
+  return invoke<EventSinkStats>("get_event_sink_stats");
+}
+
 export async function getModelList(workspaceId: string) {
   return invoke<any>("model_list", { workspaceId });
 }
@@ -497,6 +610,22 @@ export async function generateRunMetadata(workspaceId: string, prompt: string) {
   });
 }
 
+export async function runHeadlessPrompt(
+  workspaceId: string,
+  prompt: string,
+  options: HeadlessPromptOptions = {},
+) {
+  return invoke<HeadlessPromptResult>("run_headless_prompt", {
+    workspaceId,
+    prompt,
+    outputSchema: options.outputSchema ?? null,
+    timeoutSecs: options.timeoutSecs ?? null,
+    model: options.model ?? null,
+    effort: options.effort ?? null,
+    runId: options.runId ?? null,
+  });
+}
+
 export async function getCollaborationModes(workspaceId: string) {
   return invoke<any>("collaboration_mode_list", { workspaceId });
 }
@@ -774,14 +903,26 @@ export async function listThreads(
   workspaceId: string,
   cursor?: string | null,
   limit?: number | null,
+  timeoutMs?: number | null,
 ) {
-  return invoke<any>("list_threads", { workspaceId, cursor, limit });
+  return invoke<any>("list_threads", { workspaceId, cursor, limit, timeoutMs });
+}
+
+export async function cancelAppServerRequests(
+  workspaceId: string,
+  method: string,
+): Promise<number> {
+  return invoke<number>("cancel_app_server_requests", { workspaceId, method });
 }
 
 export async function listThreadsGlobal() {
   return invoke<any>("list_threads_global");
 }
 
+export async function scanOrphanedSessions() {
+  return invoke<OrphanedSession[]>("scan_orphaned_sessions");
+}
+
 export async function resumeThread(workspaceId: string, threadId: string) {
   return invoke<any>("resume_thread", { workspaceId, threadId });
 }
@@ -790,8 +931,67 @@ export async function startThreadHistoryStream(
   workspaceId: string,
   threadId: string,
   path: string,
+  follow = false,
+) {
+  return invoke<string>("stream_thread_history", {
+    workspaceId,
+    threadId,
+    path,
+    follow,
+  });
+}
+
+export async function exportThread(
+  threadId: string,
+  path: string,
+  format: ThreadExportFormat,
+  outputPath?: string | null,
+) {
+  return invoke<string>("export_thread", {
+    threadId,
+    path,
+    format,
+    outputPath: outputPath ?? null,
+  });
+}
+
+export async function forkThread(
+  workspaceId: string,
+  threadId: string,
+  path: string,
+  messageIndex: number,
+  worktreeBranch?: string | null,
 ) {
-  return invoke<string>("stream_thread_history", { workspaceId, threadId, path });
+  return invoke<ForkedThread>("fork_thread", {
+    workspaceId,
+    threadId,
+    path,
+    messageIndex,
+    worktreeBranch: worktreeBranch ?? null,
+  });
+}
+
+export async function fanOutPrompt(request: FanOutRequest) {
+  return invoke<FanOutStarted>("fan_out_prompt", { request });
+}
+
+export async function getThreadMetadata(workspaceId: string, threadId: string) {
+  return invoke<ThreadMetadata | null>("get_thread_metadata", {
+    workspaceId,
+    threadId,
+  });
+}
+
+export async function setThreadMetadata(metadata: ThreadMetadata) {
+  return invoke<ThreadMetadata | null>("set_thread_metadata", { metadata });
+}
+
+export async function deleteThreadMetadata(workspaceId: string, threadId: string) {
+  return invoke<boolean>("delete_thread_metadata", { workspaceId, threadId });
+}
+
+export async function queryThreadMetadata(query: ThreadMetadataQuery = {}) {
+  return invoke<ThreadMetadata[]>("query_thread_metadata", { query });
 }
 
 export async function stopThreadHistoryStream(
@@ -10,6 +10,46 @@ export type WorkspaceSettings = {
   workspaceIcon?: string | null;
   workspaceEmoji?: string | null;
   workspaceColor?: string | null;
+  approvalRules?: ApprovalRule[];
+  sandboxProfiles?: SandboxProfile[];
+  disableCheckpoints?: boolean;
+};
+
+export type ApprovalAction = "approve" | "deny" | "escalate";
+
+export type ApprovalRule = {
+  id: string;
+  action: ApprovalAction;
+  commandPrefix?: string[] | null;
+  pathGlobs?: string[] | null;
+  sandboxModes?: string[] | null;
+  minIdleSecs?: number | null;
+  maxIdleSecs?: number | null;
+};
+
+export type SandboxProfileMode = "read-only" | "workspace-write" | "full-access";
+
+export type SandboxProfile = {
+  id: string;
+  name: string;
+  sandbox: SandboxProfileMode;
+  writableRoots: string[];
+  networkAccess: boolean;
+  approvalPolicy: "untrusted" | "on-failure" | "on-request" | "never";
+};
+
+export type ApprovalAuditEntry = {
+  ts: number;
+  workspaceId: string;
+  threadId: string | null;
+  requestId: number | string;
+  method: string;
+  command: string[] | null;
+  paths: string[];
+  sandboxMode: string | null;
+  idleSecs: number;
+  action: ApprovalAction;
+  ruleId: string | null;
 };
 
 export type WorkspaceGroup = {
@@ -192,6 +232,8 @@ export type AppSettings = {
   backendMode: BackendMode;
   remoteBackendHost: string;
   remoteBackendToken: string | null;
+  remoteBackendTlsFingerprint: string | null;
+  remoteBackendRetryReads: boolean;
   happyEnabled: boolean;
   happyServerUrl: string;
   happyToken: string | null;
@@ -280,6 +322,9 @@ export type AppSettings = {
   experimentalThreadResumeStreamingEnabled: boolean;
   experimentalYunyiEnabled: boolean;
   experimentalYunyiToken: string;
+  recordAppServerTranscripts: boolean;
+  idleSessionTimeoutMinutes: number;
+  deltaCoalesceWindowMs: number;
   dictationEnabled: boolean;
   dictationModelId: string;
   dictationPreferredLanguage: string | null;
@@ -298,6 +343,7 @@ export type AppSettings = {
   workspaceGroups: WorkspaceGroup[];
   openAppTargets: OpenAppTarget[];
   selectedOpenAppId: string;
+  sandboxProfiles: SandboxProfile[];
 };
 
 export type CodexDoctorResult = {
@@ -473,6 +519,15 @@ export type GitFileDiff = {
   newImageMime?: string | null;
 };
 
+export type GitCheckpoint = {
+  id: string;
+  threadId: string;
+  commit: string;
+  head: string | null;
+  createdAt: number;
+  summary: string;
+};
+
 export type GitCommitDiff = {
   path: string;
   status: string;
@@ -596,6 +651,184 @@ export type LocalUsageSnapshot = {
   topModels: LocalUsageModel[];
 };
 
+export type OrphanedSessionKind = "unregistered" | "unlisted" | "unknown";
+
+export type OrphanedSession = {
+  threadId: string;
+  path: string;
+  cwd: string;
+  kind: OrphanedSessionKind;
+  workspaceId: string | null;
+  startedAt: string | null;
+  updatedAt: number;
+};
+
+export type ThreadSearchQuery = {
+  query: string;
+  workspaceId?: string;
+  since?: number;
+  until?: number;
+  model?: string;
+  limit?: number;
+};
+
+export type ThreadSearchHit = {
+  threadId: string;
+  path: string;
+  itemIndex: number;
+  kind: "user" | "agent" | "command";
+  snippet: string;
+  timestamp: number | null;
+  cwd: string | null;
+  model: string | null;
+  score: number;
+};
+
+export type EventSinkStats = {
+  received: number;
+  emitted: number;
+  coalesced: number;
+};
+
+export type OutgoingMessage = {
+  text: string;
+  model?: string | null;
+  effort?: string | null;
+  accessMode?: "read-only" | "current" | "full-access" | null;
+  sandboxProfile?: string | null;
+  images?: string[] | null;
+  collaborationMode?: Record<string, unknown> | null;
+};
+
+export type BackendQueuedMessage = OutgoingMessage & {
+  id: string;
+  createdAt: number;
+};
+
+export type ThreadMessageQueue = {
+  workspaceId: string;
+  threadId: string;
+  paused: boolean;
+  items: BackendQueuedMessage[];
+  lastError: string | null;
+};
+
+export type MessageQueueAction =
+  | { type: "enqueue"; message: OutgoingMessage }
+  | { type: "edit"; id: string; text: string }
+  | { type: "remove"; id: string }
+  | { type: "reorder"; ids: string[] }
+  | { type: "pause" }
+  | { type: "resume" }
+  | { type: "clear" };
+
+export type ThreadMetadata = {
+  workspaceId: string;
+  threadId: string;
+  tags: string[];
+  notes?: string | null;
+  customTitle?: string | null;
+  pinned: boolean;
+  linkedIssue?: number | null;
+  linkedPullRequest?: number | null;
+  linkedWorktreeId?: string | null;
+  updatedAt?: number;
+};
+
+export type ThreadMetadataQuery = {
+  workspaceId?: string | null;
+  tags?: string[];
+  pinned?: boolean | null;
+};
+
+export type ForkedThread = {
+  workspaceId: string;
+  threadId: string;
+  rolledBackTurns: number;
+  draft: string | null;
+  worktree: WorkspaceInfo | null;
+};
+
+export type FanOutLane = {
+  model?: string | null;
+  effort?: string | null;
+};
+
+export type FanOutRequest = {
+  prompt: string;
+  workspaceIds?: string[];
+  count?: number | null;
+  parentId?: string | null;
+  branchPrefix?: string | null;
+  lanes?: FanOutLane[];
+  accessMode?: "read-only" | "current" | "full-access" | null;
+  sandboxProfile?: string | null;
+  timeoutSecs?: number | null;
+};
+
+export type FanOutLaneReport = {
+  workspaceId: string;
+  threadId: string | null;
+  model: string | null;
+  effort: string | null;
+  status: "completed" | "failed" | "timedOut";
+  finalMessage: string | null;
+  error: string | null;
+  diffs: GitFileDiff[];
+  tokenUsage: Record<string, unknown> | null;
+  durationMs: number;
+};
+
+export type FanOutReport = {
+  fanOutId: string;
+  prompt: string;
+  lanes: FanOutLaneReport[];
+};
+
+export type FanOutStarted = {
+  fanOutId: string;
+  prompt: string;
+  workspaceIds: string[];
+};
+
+export type ThreadExportFormat = "markdown" | "html" | "json";
+
+export type SessionLogKind = "stderr" | "parseError" | "lifecycle";
+
+export type SessionLogEntry = {
+  ts: number;
+  kind: SessionLogKind;
+  message: string;
+};
+
+export type SessionLogFilter = {
+  kinds?: SessionLogKind[];
+  query?: string;
+  since?: number;
+  limit?: number;
+};
+
+export type HeadlessPromptOptions = {
+  outputSchema?: Record<string, unknown>;
+  timeoutSecs?: number;
+  model?: string;
+  effort?: string;
+  runId?: string;
+};
+
+export type HeadlessPromptResult = {
+  threadId: string;
+  text: string;
+  output: unknown | null;
+  durationMs: number;
+};
+
+export type HeadlessProgress =
+  | { stage: "threadStarted"; runId: string; threadId: string }
+  | { stage: "turnStarted"; runId: string; turnId: string }
+  | { stage: "delta"; runId: string; delta: string }
+  | { stage: "completed"; runId: string };
+
 export type TurnPlanStepStatus = "pending" | "inProgress" | "completed";
 
 export type TurnPlanStep = {